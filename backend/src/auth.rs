@@ -0,0 +1,53 @@
+//! API key authentication for protected routes.
+//!
+//! Requests must carry `x-wyat-api-key` matching the `WYAT_API_KEY` env var.
+//! An unset or empty `WYAT_API_KEY` rejects every request.
+
+use axum::{
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+pub const API_KEY_HEADER: &str = "x-wyat-api-key";
+
+fn api_key_matches(expected: &str, provided: Option<&str>) -> bool {
+    !expected.is_empty() && provided == Some(expected)
+}
+
+/// True when `x-wyat-api-key` matches a configured, non-empty `WYAT_API_KEY`.
+pub fn has_valid_api_key(headers: &HeaderMap) -> bool {
+    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
+    let provided_key = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok());
+    api_key_matches(&expected_key, provided_key)
+}
+
+/// Middleware for `axum::middleware::from_fn`: 401 unless the API key is valid.
+pub async fn require_api_key<B>(req: Request<B>, next: Next<B>) -> Response {
+    if !has_valid_api_key(req.headers()) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized: missing or invalid API key",
+        )
+            .into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_key_must_match_configured_key() {
+        assert!(api_key_matches("secret", Some("secret")));
+        assert!(!api_key_matches("secret", Some("other")));
+        assert!(!api_key_matches("secret", None));
+    }
+
+    #[test]
+    fn empty_configured_key_rejects_everything() {
+        assert!(!api_key_matches("", Some("")));
+        assert!(!api_key_matches("", None));
+    }
+}
@@ -15,11 +15,7 @@ struct CurrencyWrapper(Currency);
 
 impl std::hash::Hash for CurrencyWrapper {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        match self.0 {
-            Currency::USD => 0u8.hash(state),
-            Currency::HKD => 1u8.hash(state),
-            Currency::BTC => 2u8.hash(state),
-        }
+        self.0.code().hash(state)
     }
 }
 
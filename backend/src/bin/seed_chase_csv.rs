@@ -102,6 +102,10 @@ async fn main() -> Result<()> {
             external_refs: vec![("statement".into(), "Chase6886_20250908_20251007".into())],
             legs: vec![account_leg, pnl_leg],
             balance_state: BalanceState::Unknown,
+            tags: Vec::new(),
+            reimbursement: None,
+            deleted: false,
+            deleted_at: None,
         };
 
         txn.recompute_balance_state();
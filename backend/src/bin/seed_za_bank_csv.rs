@@ -117,6 +117,10 @@ async fn main() -> Result<()> {
             external_refs: vec![("statement".into(), "ZA_Bank_Sep_2025".into())],
             legs: vec![account_leg, pnl_leg],
             balance_state: BalanceState::Unknown,
+            tags: Vec::new(),
+            reimbursement: None,
+            deleted: false,
+            deleted_at: None,
         };
 
         txn.recompute_balance_state();
@@ -16,24 +16,31 @@
 //!   serde = { version = "1", features = ["derive"] }
 //!   thiserror = "1"
 
-use axum::http::StatusCode;
+use axum::body::Bytes;
+use axum::http::{StatusCode, header};
 use axum::{
     Json,
     extract::{Path, Query, State},
+    response::{IntoResponse, Response},
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use futures::stream::TryStreamExt;
 use mongodb::bson::{Bson, Document as BsonDocument, doc, oid::ObjectId};
-use mongodb::options::{FindOptions, UpdateOptions};
-use mongodb::{Database, bson};
+use mongodb::options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument, UpdateOptions};
+use mongodb::{ClientSession, Database, bson};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use thiserror::Error;
 use utoipa::ToSchema;
+use uuid::Uuid;
 
-use crate::services::data_feeds::{DataFeed, DataFeedProvider, DataFeedService, DataSnapshot};
+use crate::services::data_feeds::{
+    DataFeed, DataFeedError, DataFeedProvider, DataFeedService, DataSnapshot, FeedRefresh,
+};
 
 // Import storage functions and types
 // TODO: Re-enable when implementing bank statement import
@@ -46,11 +53,46 @@ pub const DEFAULT_ENVELOPE_ID: &str = "env_uncategorized";
 
 // ------------------------- Money -------------------------
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 pub enum Currency {
     USD,
     HKD,
     BTC,
+    EUR,
+    GBP,
+}
+
+impl Currency {
+    /// Currency code as stored in the ledger (e.g. "USD").
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::USD => "USD",
+            Currency::HKD => "HKD",
+            Currency::BTC => "BTC",
+            Currency::EUR => "EUR",
+            Currency::GBP => "GBP",
+        }
+    }
+
+    /// Decimal places of the currency's smallest unit (cents, satoshis).
+    pub fn decimal_places(&self) -> u32 {
+        match self {
+            Currency::USD | Currency::HKD | Currency::EUR | Currency::GBP => 2,
+            Currency::BTC => 8,
+        }
+    }
+
+    /// Parse a ledger currency code (e.g. "USD"); `None` for unknown codes.
+    pub fn from_code(code: &str) -> Option<Currency> {
+        match code {
+            "USD" => Some(Currency::USD),
+            "HKD" => Some(Currency::HKD),
+            "BTC" => Some(Currency::BTC),
+            "EUR" => Some(Currency::EUR),
+            "GBP" => Some(Currency::GBP),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
@@ -114,6 +156,59 @@ pub enum CapitalError {
     MinBalanceExceeded(String),
 }
 
+/// Error returned by capital HTTP handlers. Renders as `{ "error": ..., "code": ... }`
+/// with a status matching the variant.
+#[derive(Debug, Error)]
+pub enum CapitalApiError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Validation(String),
+    #[error("{0}")]
+    Conflict(String),
+    /// A price feed or other external service failed.
+    #[error("{0}")]
+    Upstream(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl CapitalApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            CapitalApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            CapitalApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            CapitalApiError::Conflict(_) => StatusCode::CONFLICT,
+            CapitalApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            CapitalApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable machine-readable code for clients.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CapitalApiError::NotFound(_) => "not_found",
+            CapitalApiError::Validation(_) => "validation",
+            CapitalApiError::Conflict(_) => "conflict",
+            CapitalApiError::Upstream(_) => "upstream",
+            CapitalApiError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl From<mongodb::error::Error> for CapitalApiError {
+    fn from(e: mongodb::error::Error) -> Self {
+        CapitalApiError::Internal(format!("Database error: {}", e))
+    }
+}
+
+impl IntoResponse for CapitalApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.to_string(), "code": self.code() });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
 // ========================== //
 // * * * * ENVELOPES. * * * * //
 // ========================== //
@@ -195,6 +290,83 @@ impl Envelope {
         current
     }
 
+    /// Check configuration invariants without touching balances.
+    /// Returns every violated constraint so the UI can show them together.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let ccy = self.balance.ccy;
+
+        if self.id.trim().is_empty() {
+            errors.push("id cannot be empty".to_string());
+        }
+        if self.name.trim().is_empty() {
+            errors.push("name cannot be empty".to_string());
+        }
+
+        if let Some(rule) = &self.funding {
+            if rule.amount.ccy != ccy {
+                errors.push(format!(
+                    "funding currency {:?} must match balance currency {:?}",
+                    rule.amount.ccy, ccy
+                ));
+            }
+            if rule.amount.amount.is_sign_negative() {
+                errors.push("funding amount cannot be negative".to_string());
+            }
+        }
+
+        if let Some(limit) = &self.period_limit {
+            if limit.ccy != ccy {
+                errors.push(format!(
+                    "period_limit currency {:?} must match balance currency {:?}",
+                    limit.ccy, ccy
+                ));
+            }
+            if limit.amount.is_sign_negative() {
+                errors.push("period_limit cannot be negative".to_string());
+            }
+        }
+
+        if let Some(min_balance) = self.min_balance {
+            if !self.allow_negative {
+                errors.push("min_balance is only allowed when allow_negative is true".to_string());
+            } else if min_balance.is_sign_positive() && !min_balance.is_zero() {
+                errors.push("min_balance must be zero or negative".to_string());
+            }
+        }
+
+        let cap = match &self.rollover {
+            RolloverPolicy::ResetToZero => None,
+            RolloverPolicy::CarryOver { cap } | RolloverPolicy::SinkingFund { cap } => cap.as_ref(),
+            RolloverPolicy::Decay { keep_ratio, cap } => {
+                if *keep_ratio <= Decimal::ZERO || *keep_ratio > Decimal::ONE {
+                    errors.push(format!(
+                        "decay keep_ratio must be in (0, 1], got {}",
+                        keep_ratio
+                    ));
+                }
+                cap.as_ref()
+            }
+        };
+        if let Some(cap) = cap {
+            if cap.ccy != ccy {
+                errors.push(format!(
+                    "rollover cap currency {:?} must match balance currency {:?}",
+                    cap.ccy, ccy
+                ));
+            }
+            if cap.amount.is_sign_negative() {
+                errors.push("rollover cap cannot be negative".to_string());
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Transition the envelope to a new month period and apply rollover + funding.
     pub fn start_new_period(&mut self, year: i32, month: u32) {
         let period = format!("{year}-{month:02}");
@@ -398,8 +570,45 @@ pub enum AccountMetadata {
     },
 }
 
+/// Mask an account number down to its last four characters (e.g. `****1234`).
+pub fn mask_account_number(account_number: &str) -> String {
+    let chars: Vec<char> = account_number.trim().chars().collect();
+    if chars.len() <= 4 {
+        return "****".to_string();
+    }
+    let last4: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{}", last4)
+}
+
+impl AccountMetadata {
+    /// Replace every `account_number` carried by this metadata with its masked form.
+    pub fn mask_account_numbers(&mut self) {
+        match self {
+            AccountMetadata::Checking { account_number, .. }
+            | AccountMetadata::Savings { account_number, .. }
+            | AccountMetadata::Credit { account_number, .. }
+            | AccountMetadata::BrokerageAccount { account_number, .. } => {
+                *account_number = mask_account_number(account_number);
+            }
+            AccountMetadata::Tagged { data, .. } => {
+                if let Some(serde_json::Value::String(number)) = data.get_mut("account_number") {
+                    *number = mask_account_number(number);
+                }
+            }
+            AccountMetadata::CryptoWallet { .. }
+            | AccountMetadata::Cex { .. }
+            | AccountMetadata::Trust { .. } => {}
+        }
+    }
+}
+
 impl Account {
-    #[allow(dead_code)]
+    /// Copy of this account safe to return to clients by default.
+    pub fn masked(mut self) -> Self {
+        self.metadata.mask_account_numbers();
+        self
+    }
+
     pub fn kind(&self) -> &'static str {
         match &self.metadata {
             AccountMetadata::Tagged { account_type, .. } => match account_type.as_str() {
@@ -421,6 +630,16 @@ impl Account {
             AccountMetadata::BrokerageAccount { .. } => "BrokerageAccount",
         }
     }
+
+    /// Wallets and exchanges hold assets, so their balances are per-asset quantities.
+    pub fn holds_assets(&self) -> bool {
+        matches!(self.kind(), "CryptoWallet" | "Cex")
+    }
+
+    /// Trust assets can't be drawn on freely, so they don't count toward liquid net worth.
+    pub fn is_liquid(&self) -> bool {
+        self.kind() != "Trust"
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -450,6 +669,169 @@ pub struct AccountRangeBalance {
     label: Option<String>,
 }
 
+/// Sum signed fiat legs (debits positive) for an account up to and including `as_of`.
+async fn sum_as_of(
+    db: &mongodb::Database,
+    account_id: &str,
+    ccy_str: &str,
+    as_of: i64,
+) -> Result<Decimal, String> {
+    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
+    // Match by account, currency, and time <= as_of (posted_ts or ts)
+    let mut leg_match = doc! {
+      "legs.account_id": account_id,
+      "legs.amount.kind": "Fiat",
+      "legs.amount.data.ccy": ccy_str,
+    };
+    leg_match.extend(cycle_time_match(i64::MIN, as_of));
+    leg_match.extend(live_transactions());
+    let pipeline = vec![
+        doc! { "$unwind": "$legs" },
+        doc! { "$match": leg_match },
+        doc! { "$project": {
+          "signed": {
+            "$cond": [
+              { "$eq": [ "$legs.direction", "Debit" ] },
+              { "$toDecimal": "$legs.amount.data.amount" },
+              { "$multiply": [ { "$toDecimal": "$legs.amount.data.amount" }, -1 ] }
+            ]
+          }
+        }},
+        doc! { "$group": { "_id": null, "sum": { "$sum": "$signed" } } },
+    ];
+
+    let mut cursor = ledger
+        .aggregate(pipeline, None)
+        .await
+        .map_err(|e| format!("agg error: {e}"))?;
+    let mut amt = Decimal::ZERO;
+    if let Some(doc) = cursor
+        .try_next()
+        .await
+        .map_err(|e| format!("cursor error: {e}"))?
+    {
+        if let Some(sum) = doc.get("sum") {
+            amt = match sum {
+                Bson::Decimal128(d) => {
+                    Decimal::from_str_exact(&d.to_string()).unwrap_or(Decimal::ZERO)
+                }
+                Bson::Double(f) => Decimal::try_from(*f).unwrap_or(Decimal::ZERO),
+                Bson::Int32(i) => Decimal::from(*i),
+                Bson::Int64(i) => Decimal::from(*i),
+                _ => Decimal::ZERO,
+            };
+        }
+    }
+    Ok(amt)
+}
+
+/// Sum signed crypto legs (debits positive) per asset for an account up to and
+/// including `as_of`.
+async fn sum_crypto_as_of(
+    db: &mongodb::Database,
+    account_id: &str,
+    as_of: i64,
+) -> Result<BTreeMap<String, Decimal>, String> {
+    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
+    let mut leg_match = doc! {
+      "legs.account_id": account_id,
+      "legs.amount.kind": "Crypto",
+    };
+    leg_match.extend(cycle_time_match(i64::MIN, as_of));
+    leg_match.extend(live_transactions());
+    let pipeline = vec![
+        doc! { "$unwind": "$legs" },
+        doc! { "$match": leg_match },
+        doc! { "$project": {
+          "asset": "$legs.amount.data.asset",
+          "signed": {
+            "$cond": [
+              { "$eq": [ "$legs.direction", "Debit" ] },
+              { "$toDecimal": "$legs.amount.data.qty" },
+              { "$multiply": [ { "$toDecimal": "$legs.amount.data.qty" }, -1 ] }
+            ]
+          }
+        }},
+        doc! { "$group": { "_id": "$asset", "sum": { "$sum": "$signed" } } },
+    ];
+
+    let mut cursor = ledger
+        .aggregate(pipeline, None)
+        .await
+        .map_err(|e| format!("agg error: {e}"))?;
+    let mut totals = BTreeMap::new();
+    while let Some(doc) = cursor
+        .try_next()
+        .await
+        .map_err(|e| format!("cursor error: {e}"))?
+    {
+        if let (Ok(asset), Some(sum)) = (doc.get_str("_id"), doc.get("sum")) {
+            totals.insert(asset.to_string(), decimal_from_bson(sum));
+        }
+    }
+    Ok(totals)
+}
+
+/// Per-asset `closing - opening`, covering assets present on either side.
+fn crypto_delta(
+    opening: &BTreeMap<String, Decimal>,
+    closing: &BTreeMap<String, Decimal>,
+) -> BTreeMap<String, Decimal> {
+    opening
+        .keys()
+        .chain(closing.keys())
+        .map(|asset| {
+            let open = opening.get(asset).copied().unwrap_or(Decimal::ZERO);
+            let close = closing.get(asset).copied().unwrap_or(Decimal::ZERO);
+            (asset.clone(), close - open)
+        })
+        .collect()
+}
+
+/// Crypto-account variant of `get_account_balance`: asset -> qty maps instead of `Money`.
+async fn crypto_account_balance(
+    db: &mongodb::Database,
+    account_id: &str,
+    q: &AccountBalanceQuery,
+) -> Result<serde_json::Value, String> {
+    let range = match (&q.label, q.from, q.to) {
+        (Some(label), _, _) => {
+            let settings = load_capital_settings(db).await;
+            let (start_ts, end_ts) = cycle_bounds_for_label(label, settings.cycle_start_day)
+                .ok_or_else(|| format!("Invalid cycle label: {label}"))?;
+            Some((start_ts, end_ts))
+        }
+        (None, Some(from), Some(to)) => Some((from, to)),
+        _ => None,
+    };
+
+    if let Some((start_ts, end_ts)) = range {
+        let opening = sum_crypto_as_of(db, account_id, start_ts - 1).await?;
+        let closing = sum_crypto_as_of(db, account_id, end_ts).await?;
+        let delta = crypto_delta(&opening, &closing);
+        let mut body = serde_json::json!({
+          "account_id": account_id,
+          "opening": opening,
+          "closing": closing,
+          "delta": delta,
+          "start_ts": start_ts,
+          "end_ts": end_ts
+        });
+        if let Some(label) = &q.label {
+            body["label"] = serde_json::json!(label);
+        }
+        return Ok(body);
+    }
+
+    let as_of = q.as_of.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let balances = sum_crypto_as_of(db, account_id, as_of).await?;
+    Ok(serde_json::json!({
+      "account_id": account_id,
+      "balances": balances,
+      "as_of": as_of
+    }))
+}
+
 pub async fn get_account_balance(
     State(state): State<Arc<AppState>>,
     Path(account_id): Path<String>,
@@ -465,70 +847,18 @@ pub async fn get_account_balance(
         .map_err(|e| format!("db error: {e}"))?
         .ok_or_else(|| "account not found".to_string())?;
 
-    let ccy_str = match account.currency {
-        Currency::USD => "USD",
-        Currency::HKD => "HKD",
-        Currency::BTC => "BTC",
-    };
-
-    // Helper: sum signed fiat legs for this account up to and including `as_of`
-    async fn sum_as_of(
-        db: &mongodb::Database,
-        account_id: &str,
-        ccy_str: &str,
-        as_of: i64,
-    ) -> Result<Decimal, String> {
-        let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
-        // Match by account, currency, and time <= as_of (posted_ts or ts)
-        let pipeline = vec![
-            doc! { "$unwind": "$legs" },
-            doc! { "$match": {
-              "legs.account_id": account_id,
-              "legs.amount.kind": "Fiat",
-              "legs.amount.data.ccy": ccy_str,
-              "$expr": { "$lte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, as_of ] }
-            }},
-            doc! { "$project": {
-              "signed": {
-                "$cond": [
-                  { "$eq": [ "$legs.direction", "Debit" ] },
-                  { "$toDecimal": "$legs.amount.data.amount" },
-                  { "$multiply": [ { "$toDecimal": "$legs.amount.data.amount" }, -1 ] }
-                ]
-              }
-            }},
-            doc! { "$group": { "_id": null, "sum": { "$sum": "$signed" } } },
-        ];
-
-        let mut cursor = ledger
-            .aggregate(pipeline, None)
-            .await
-            .map_err(|e| format!("agg error: {e}"))?;
-        let mut amt = Decimal::ZERO;
-        if let Some(doc) = cursor
-            .try_next()
-            .await
-            .map_err(|e| format!("cursor error: {e}"))?
-        {
-            if let Some(sum) = doc.get("sum") {
-                amt = match sum {
-                    Bson::Decimal128(d) => {
-                        Decimal::from_str_exact(&d.to_string()).unwrap_or(Decimal::ZERO)
-                    }
-                    Bson::Double(f) => Decimal::try_from(*f).unwrap_or(Decimal::ZERO),
-                    Bson::Int32(i) => Decimal::from(*i),
-                    Bson::Int64(i) => Decimal::from(*i),
-                    _ => Decimal::ZERO,
-                };
-            }
-        }
-        Ok(amt)
+    // Wallets and exchanges hold assets, so report per-asset quantities
+    if account.holds_assets() {
+        return crypto_account_balance(&db, &account_id, &q).await.map(Json);
     }
 
+    let ccy_str = account.currency.code();
+
     // 2) Resolve query intent: point vs range
     if let Some(label) = q.label.clone() {
         // Use cycle boundaries for this label
-        let (start_ts, end_ts) = crate::capital::cycle_bounds_for_label(&label)
+        let settings = load_capital_settings(&db).await;
+        let (start_ts, end_ts) = cycle_bounds_for_label(&label, settings.cycle_start_day)
             .ok_or_else(|| format!("Invalid cycle label: {label}"))?;
         let opening_as_of = start_ts - 1;
         let closing_as_of = end_ts;
@@ -673,16 +1003,43 @@ pub struct Transaction {
     pub tx_type: Option<String>,
     #[serde(default)]
     pub balance_state: BalanceState,
+    #[serde(default)]
+    pub tags: Vec<String>, // free-form labels, e.g. "reimbursable"
+    #[serde(default)]
+    pub reimbursement: Option<ReimbursementSettlement>,
+    /// Soft-deleted rows stay in the ledger but are excluded from queries and sums.
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub deleted_at: Option<i64>, // unix seconds when soft-deleted
+}
+
+/// Records how a reimbursable transaction was paid back.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReimbursementSettlement {
+    pub settled_by_tx: String, // id of the transaction carrying the repayment
+    pub settled_at: i64,       // unix seconds when it was marked settled
 }
 
 impl Transaction {
     /// Check zero-sum integrity by valuing each leg in the requested reporting currency.
     /// Returns (is_balanced, net_amount) — where net_amount should be 0 when balanced.
     pub fn is_balanced_in(&self, report_ccy: Currency) -> (bool, Money) {
+        self.is_balanced_with_rates(report_ccy, &HashMap::new())
+    }
+
+    /// Like [`Transaction::is_balanced_in`], but legs without a usable `fx` snapshot
+    /// fall back to `rates` (price of one unit of the key currency in `report_ccy`).
+    /// Crypto legs use the table when their asset code is a known currency (e.g. BTC).
+    pub fn is_balanced_with_rates(
+        &self,
+        report_ccy: Currency,
+        rates: &HashMap<Currency, Decimal>,
+    ) -> (bool, Money) {
         let mut net = Decimal::ZERO;
         for leg in &self.legs {
             // Try to value leg in report_ccy; if not possible, skip valuation (treat as 0)
-            let valued = match (&leg.amount, leg.fx) {
+            let snapshot = match (&leg.amount, leg.fx) {
                 (LegAmount::Fiat(m), fx) => {
                     if m.ccy == report_ccy {
                         Some(*m)
@@ -704,6 +1061,18 @@ impl Transaction {
                     }
                 }),
             };
+            let valued = snapshot.or_else(|| {
+                let (ccy, amount) = match &leg.amount {
+                    LegAmount::Fiat(m) => (m.ccy, m.amount),
+                    LegAmount::Crypto { asset, qty } => (Currency::from_code(asset)?, *qty),
+                };
+                if ccy == report_ccy {
+                    return Some(Money::new(amount, report_ccy));
+                }
+                rates
+                    .get(&ccy)
+                    .map(|rate| Money::new(amount * rate, report_ccy))
+            });
 
             if let Some(v) = valued {
                 let signed = match leg.direction {
@@ -714,14 +1083,18 @@ impl Transaction {
             }
         }
 
-        // Use a tolerance of 0.01 (1 cent) to account for floating-point precision errors
-        // Common in FX conversions and decimal arithmetic
-        let tolerance = Decimal::new(1, 2); // 0.01
+        // Allow up to one smallest unit of report_ccy (a cent, a satoshi) for the
+        // rounding common in FX conversions and decimal arithmetic
+        let tolerance = Decimal::new(1, report_ccy.decimal_places());
         let is_balanced = net.abs() < tolerance;
 
         (is_balanced, Money::new(net, report_ccy))
     }
 
+    /// Balance a single fiat leg against P&L, tagged with the leg's envelope. The P&L
+    /// leg mirrors the account leg, so money out (Credit) reads as spend (P&L Debit).
+    /// A `refund` is money in (Debit), so its P&L leg is a Credit that lowers the
+    /// envelope's usage; `refund_direction_error` rejects refunds booked the other way.
     fn apply_spending_autobalance(&mut self) {
         if self.legs.len() != 1 {
             return;
@@ -729,7 +1102,6 @@ impl Transaction {
         if self.is_transfer_type() {
             return;
         }
-
         let primary_leg = self.legs.first_mut().expect("checked len");
         let amount = match &primary_leg.amount {
             LegAmount::Fiat(m) => *m,
@@ -753,6 +1125,26 @@ impl Transaction {
         self.legs.push(balancing_leg);
     }
 
+    fn is_refund_type(&self) -> bool {
+        self.tx_type
+            .as_deref()
+            .is_some_and(|t| t.eq_ignore_ascii_case("refund"))
+    }
+
+    /// A single-leg refund credited to its account would auto-balance as more spend,
+    /// so it is rejected rather than stored with the wrong sign.
+    fn refund_direction_error(&self) -> Option<String> {
+        let [leg] = self.legs.as_slice() else {
+            return None;
+        };
+        (self.is_refund_type() && leg.direction == LegDirection::Credit).then(|| {
+            format!(
+                "refund '{}' must be money in (Debit on '{}'), got Credit",
+                self.id, leg.account_id
+            )
+        })
+    }
+
     fn is_transfer_type(&self) -> bool {
         if let Some(tx_type) = &self.tx_type {
             if tx_type.eq_ignore_ascii_case("transfer")
@@ -798,6 +1190,30 @@ impl Transaction {
     }
 }
 
+/// Create indexes backing capital ledger lookups.
+pub async fn init_indexes(db: &Database) -> mongodb::error::Result<()> {
+    let ledger = db.collection::<Transaction>("capital_ledger");
+    ledger
+        .create_index(
+            mongodb::IndexModel::builder()
+                .keys(doc! { "external_refs": 1 })
+                .build(),
+            None,
+        )
+        .await?;
+
+    // Price history lookups by symbol over a time range
+    db.collection::<DataSnapshot>("capital_data_snapshots")
+        .create_index(
+            mongodb::IndexModel::builder()
+                .keys(doc! { "feed_symbol": 1, "fetch_time": 1 })
+                .build(),
+            None,
+        )
+        .await?;
+    Ok(())
+}
+
 /// Bank/credit statement header for reconciliation.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Statement {
@@ -874,6 +1290,115 @@ pub struct PublicFund {
     pub updated_at: i64,
 }
 
+impl From<Fund> for PublicFund {
+    fn from(f: Fund) -> Self {
+        PublicFund {
+            id: f.id.to_hex(),
+            fund_id: f.fund_id,
+            name: f.name,
+            symbol: f.symbol,
+            assets: f.assets,
+            purpose: f.purpose,
+            horizon_years: f.horizon_years,
+            discretionary_sales: f.discretionary_sales,
+            acquisition_policy: f.acquisition_policy,
+            yield_policy: f.yield_policy,
+            denominated_in: f.denominated_in,
+            balancing_policy: f.balancing_policy,
+            multiplier_rules: f.multiplier_rules,
+            max_pct_networth: f.max_pct_networth,
+            max_pct_liquid: f.max_pct_liquid,
+            liquid: f.liquid,
+            review_cadence: f.review_cadence,
+            status: f.status,
+            created_at: f
+                .created_at
+                .map(|d| d.timestamp_millis() / 1000)
+                .unwrap_or(0),
+            updated_at: f
+                .updated_at
+                .map(|d| d.timestamp_millis() / 1000)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Body for `POST /capital/funds`: a `PublicFund` without `id` or timestamps.
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct NewFund {
+    pub fund_id: String,
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub assets: Vec<String>,
+    pub purpose: String,
+    pub horizon_years: i32,
+    pub discretionary_sales: bool,
+    #[serde(default)]
+    pub acquisition_policy: Option<String>,
+    #[serde(default)]
+    pub yield_policy: Option<String>,
+    pub denominated_in: Currency,
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub balancing_policy: Option<mongodb::bson::Document>,
+    #[serde(default)]
+    pub multiplier_rules: Option<Vec<String>>,
+    pub max_pct_networth: f64,
+    pub max_pct_liquid: f64,
+    pub liquid: bool,
+    pub review_cadence: String,
+    pub status: String,
+}
+
+impl NewFund {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.fund_id.trim().is_empty() {
+            return Err("fund_id cannot be empty".to_string());
+        }
+        if self.name.trim().is_empty() {
+            return Err("name cannot be empty".to_string());
+        }
+        for (field, value) in [
+            ("max_pct_networth", self.max_pct_networth),
+            ("max_pct_liquid", self.max_pct_liquid),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!(
+                    "{} must be between 0.0 and 1.0, got {}",
+                    field, value
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn into_fund(self, now: mongodb::bson::DateTime) -> Fund {
+        Fund {
+            id: mongodb::bson::oid::ObjectId::new(),
+            name: self.name,
+            symbol: self.symbol,
+            assets: self.assets,
+            purpose: self.purpose,
+            horizon_years: self.horizon_years,
+            discretionary_sales: self.discretionary_sales,
+            acquisition_policy: self.acquisition_policy,
+            yield_policy: self.yield_policy,
+            denominated_in: self.denominated_in,
+            balancing_policy: self.balancing_policy,
+            multiplier_rules: self.multiplier_rules,
+            max_pct_networth: self.max_pct_networth,
+            max_pct_liquid: self.max_pct_liquid,
+            liquid: self.liquid,
+            review_cadence: self.review_cadence,
+            status: self.status,
+            created_at: Some(now),
+            updated_at: Some(now),
+            fund_id: self.fund_id,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct Position {
     pub fund_id: String,
@@ -892,14 +1417,94 @@ pub struct Position {
 // * * * * API HANDLERS. * * * * //
 // ============================= //
 use crate::AppState;
+use crate::mongo::supports_transactions;
 
 // ------------------------- Helper Functions -------------------------
 
-/// Calculate the active budget cycle window (10th of month → 9th of next month) in UTC.
+/// Filter excluding soft-deleted transactions; rows without the field are live.
+pub fn live_transactions() -> BsonDocument {
+    doc! { "deleted": { "$ne": true } }
+}
+
+/// `$match` clause keeping transactions whose effective time (posted_ts, falling back
+/// to ts) lies in `[start_ts, end_ts]`. Merge extra field filters into the result.
+pub fn cycle_time_match(start_ts: i64, end_ts: i64) -> BsonDocument {
+    doc! {
+        "$expr": {
+            "$and": [
+                { "$gte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, start_ts ] },
+                { "$lte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, end_ts ] }
+            ]
+        }
+    }
+}
+
+/// Cycle start day used when `capital_settings` has none.
+pub const DEFAULT_CYCLE_START_DAY: u32 = 10;
+
+/// Capital-wide settings, stored as a single document in `capital_settings`.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct CapitalSettings {
+    /// Day of month (1–31) each budget cycle starts on. Months shorter than this
+    /// start on their last day instead.
+    #[serde(default = "default_cycle_start_day")]
+    pub cycle_start_day: u32,
+}
+
+fn default_cycle_start_day() -> u32 {
+    DEFAULT_CYCLE_START_DAY
+}
+
+impl Default for CapitalSettings {
+    fn default() -> Self {
+        CapitalSettings {
+            cycle_start_day: DEFAULT_CYCLE_START_DAY,
+        }
+    }
+}
+
+/// Load settings at request time, falling back to defaults when the document is
+/// missing, unreadable or out of range.
+pub async fn load_capital_settings(db: &Database) -> CapitalSettings {
+    match db
+        .collection::<CapitalSettings>("capital_settings")
+        .find_one(None, None)
+        .await
+    {
+        Ok(Some(settings)) if (1..=31).contains(&settings.cycle_start_day) => settings,
+        Ok(Some(settings)) => {
+            eprintln!(
+                "Ignoring invalid cycle_start_day {}; using {}",
+                settings.cycle_start_day, DEFAULT_CYCLE_START_DAY
+            );
+            CapitalSettings::default()
+        }
+        Ok(None) => CapitalSettings::default(),
+        Err(e) => {
+            eprintln!("Error loading capital settings: {}", e);
+            CapitalSettings::default()
+        }
+    }
+}
+
+/// First day of the cycle labelled `year-month`: `start_day`, clamped to the
+/// month's last day (start day 31 begins February's cycle on the 28th/29th).
+fn cycle_start_date(year: i32, month: u32, start_day: u32) -> Option<chrono::NaiveDate> {
+    use chrono::Datelike;
+
+    let first = chrono::NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last_day = first
+        .checked_add_months(chrono::Months::new(1))?
+        .pred_opt()?
+        .day();
+    chrono::NaiveDate::from_ymd_opt(year, month, start_day.clamp(1, last_day))
+}
+
+/// Calculate the active budget cycle window in UTC for cycles starting on `start_day`.
 /// Returns (start_timestamp, end_timestamp, label) where label is "YYYY-MM" format.
-/// Cycle convention: Settlement window uses UTC. Active cycle = 10th 00:00:00 UTC of month → 9th 23:59:59 UTC of next month. Filtering uses posted_ts if present, otherwise ts.
-fn active_cycle_bounds(now_utc: i64) -> (i64, i64, String) {
-    use chrono::{Datelike, NaiveDateTime, TimeZone, Utc};
+/// Cycle convention: Settlement window uses UTC. Active cycle = start day 00:00:00 UTC of month → the second before the next month's start day. Filtering uses posted_ts if present, otherwise ts.
+fn active_cycle_bounds(now_utc: i64, start_day: u32) -> (i64, i64, String) {
+    use chrono::Datelike;
 
     let dt = match Utc.timestamp_opt(now_utc, 0) {
         chrono::LocalResult::Single(d) => d,
@@ -909,52 +1514,28 @@ fn active_cycle_bounds(now_utc: i64) -> (i64, i64, String) {
             Utc::now()
         }
     };
-    let (y, m, d) = (dt.year(), dt.month(), dt.day());
+    let (y, m) = (dt.year(), dt.month());
+
+    // Before this month's start day we're still in last month's cycle
+    let label = format!("{y:04}-{m:02}");
+    let label = match cycle_bounds_for_label(&label, start_day) {
+        Some((start, _)) if now_utc < start => {
+            if m == 1 {
+                format!("{:04}-12", y - 1)
+            } else {
+                format!("{y:04}-{:02}", m - 1)
+            }
+        }
+        _ => label,
+    };
 
-    // Determine cycle start month
-    let (start_y, start_m) = if d >= 10 {
-        (y, m)
-    } else {
-        if m == 1 { (y - 1, 12) } else { (y, m - 1) }
-    };
-
-    // Start: 10th at 00:00:00
-    let start = match (
-        chrono::NaiveDate::from_ymd_opt(start_y, start_m, 10),
-        chrono::NaiveTime::from_hms_opt(0, 0, 0),
-    ) {
-        (Some(date), Some(time)) => Utc
-            .from_utc_datetime(&NaiveDateTime::new(date, time))
-            .timestamp(),
-        _ => {
-            eprintln!(
-                "Failed to create start date for cycle: {}-{}",
-                start_y, start_m
-            );
-            now_utc
-        }
-    };
-
-    // End: 9th at 23:59:59 of next month
-    let (end_y, end_m) = if start_m == 12 {
-        (start_y + 1, 1)
-    } else {
-        (start_y, start_m + 1)
-    };
-    let end = match (
-        chrono::NaiveDate::from_ymd_opt(end_y, end_m, 9),
-        chrono::NaiveTime::from_hms_opt(23, 59, 59),
-    ) {
-        (Some(date), Some(time)) => Utc
-            .from_utc_datetime(&NaiveDateTime::new(date, time))
-            .timestamp(),
-        _ => {
-            eprintln!("Failed to create end date for cycle: {}-{}", end_y, end_m);
-            now_utc + 2_592_000 // fallback: ~30 days later
+    match cycle_bounds_for_label(&label, start_day) {
+        Some((start, end)) => (start, end, label),
+        None => {
+            eprintln!("Failed to compute cycle bounds for {}", label);
+            (now_utc, now_utc + 2_592_000, label) // fallback: ~30 days later
         }
-    };
-
-    (start, end, format!("{start_y:04}-{start_m:02}"))
+    }
 }
 
 // ------------------------- Response Types -------------------------
@@ -1001,6 +1582,35 @@ pub async fn get_all_envelopes(State(state): State<Arc<AppState>>) -> Json<Vec<E
     }
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnvelopeValidation {
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+/// POST /capital/envelopes/validate - Check an envelope payload without persisting it
+#[utoipa::path(
+    post,
+    path = "/capital/envelopes/validate",
+    request_body = Envelope,
+    responses(
+        (status = 200, description = "Validation result", body = EnvelopeValidation)
+    ),
+    tag = "capital"
+)]
+pub async fn validate_envelope(Json(envelope): Json<Envelope>) -> Json<EnvelopeValidation> {
+    match envelope.validate() {
+        Ok(()) => Json(EnvelopeValidation {
+            valid: true,
+            errors: Vec::new(),
+        }),
+        Err(errors) => Json(EnvelopeValidation {
+            valid: false,
+            errors,
+        }),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UsageQuery {
     pub label: Option<String>,
@@ -1009,7 +1619,8 @@ pub struct UsageQuery {
 /// GET /capital/envelopes/{envelope_id}/usage - Get envelope usage for a cycle
 ///
 /// Returns budget, spent, remaining, and percent for a single envelope ID.
-/// The active cycle runs from the 10th of one month to the 9th of the next month (UTC).
+/// The active cycle starts on the configured `cycle_start_day` (default the 10th) and
+/// runs to the day before the next month's start (UTC).
 ///
 /// Query parameters:
 /// - label: Optional cycle label (e.g., "2025-10") to query historical usage.
@@ -1023,25 +1634,15 @@ pub async fn get_envelope_usage(
     axum::extract::Path(envelope_id): axum::extract::Path<String>,
     Query(q): Query<UsageQuery>,
 ) -> Result<Json<EnvelopeUsage>, String> {
-    use futures::stream::TryStreamExt;
-    use mongodb::bson::{Bson, doc};
+    use mongodb::bson::doc;
+
+    let db = state.mongo_client.database("wyat");
+    let settings = load_capital_settings(&db).await;
 
     // Determine cycle bounds based on optional label query parameter
-    let (start_ts, end_ts, label) = match q.label.as_deref() {
-        Some(l) => {
-            let (s, e) =
-                cycle_bounds_for_label(l).ok_or_else(|| format!("Invalid cycle label: {}", l))?;
-            (s, e, l.to_string())
-        }
-        None => {
-            let now = chrono::Utc::now().timestamp();
-            active_cycle_bounds(now)
-        }
-    };
+    let (start_ts, end_ts, label) = resolve_cycle(q.label.as_deref(), settings.cycle_start_day)?;
 
-    let db = state.mongo_client.database("wyat");
     let envs = db.collection::<Envelope>("capital_envelopes");
-    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
 
     // Fetch envelope (for budget + currency)
     let env = match envs.find_one(doc! {"id": &envelope_id}, None).await {
@@ -1056,39 +1657,100 @@ pub async fn get_envelope_usage(
         }
     };
 
-    let budget_money = env
-        .funding
+    let spent_amount = envelope_spent(
+        &db,
+        &envelope_id,
+        envelope_budget(&env).ccy,
+        start_ts,
+        end_ts,
+        &label,
+    )
+    .await;
+
+    Ok(Json(envelope_usage(&env, label, spent_amount)))
+}
+
+/// Usage for `env` given the cycle's spend in its budget currency.
+fn envelope_usage(env: &Envelope, label: String, spent_amount: Decimal) -> EnvelopeUsage {
+    let budget_money = envelope_budget(env);
+    let spent = Money {
+        amount: spent_amount,
+        ccy: budget_money.ccy,
+    };
+    let remaining = Money {
+        amount: budget_money.amount - spent.amount,
+        ccy: budget_money.ccy,
+    };
+    let percent = spend_ratio(spent.amount, budget_money.amount);
+
+    EnvelopeUsage {
+        envelope_id: env.id.clone(),
+        label,
+        budget: budget_money,
+        spent,
+        remaining,
+        percent,
+    }
+}
+
+/// Cycle window for an optional label; the active cycle when omitted.
+fn resolve_cycle(label: Option<&str>, start_day: u32) -> Result<(i64, i64, String), String> {
+    match label {
+        Some(l) => {
+            let (s, e) = cycle_bounds_for_label(l, start_day)
+                .ok_or_else(|| format!("Invalid cycle label: {}", l))?;
+            Ok((s, e, l.to_string()))
+        }
+        None => {
+            let now = chrono::Utc::now().timestamp();
+            Ok(active_cycle_bounds(now, start_day))
+        }
+    }
+}
+
+/// Envelope budget from `funding.amount`, zero when unfunded.
+fn envelope_budget(env: &Envelope) -> Money {
+    env.funding
         .as_ref()
         .map(|f| f.amount)
-        .unwrap_or_else(|| Money::zero(env.balance.ccy));
+        .unwrap_or_else(|| Money::zero(env.balance.ccy))
+}
+
+/// `spent / budget`, floored at 0; 0 for an empty budget.
+fn spend_ratio(spent: Decimal, budget: Decimal) -> f64 {
+    if budget.is_zero() {
+        0.0
+    } else {
+        (spent / budget).to_f64().unwrap_or(0.0).max(0.0)
+    }
+}
 
-    // Aggregate spent from P&L legs (legs with category_id == envelope_id)
-    // - Uses posted_ts when available, falls back to ts
-    // - Applies proper sign: Debit = positive spend, Credit = negative (refund)
+/// Sum P&L legs tagged with `envelope_id` in `[start_ts, end_ts]`.
+/// - Uses posted_ts when available, falls back to ts
+/// - Applies proper sign: Debit = positive spend, Credit = negative (refund)
+///
+/// Aggregation failures are logged and count as zero spend.
+async fn envelope_spent(
+    db: &mongodb::Database,
+    envelope_id: &str,
+    ccy: Currency,
+    start_ts: i64,
+    end_ts: i64,
+    label: &str,
+) -> Decimal {
+    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
+    let ccy_str = ccy.code();
 
-    // Currency string representation for MongoDB (e.g., "USD")
-    let ccy_str = match budget_money.ccy {
-        Currency::USD => "USD",
-        Currency::HKD => "HKD",
-        Currency::BTC => "BTC",
-    };
+    let mut window_match = cycle_time_match(start_ts, end_ts);
+    window_match.extend(live_transactions());
+    window_match.insert("legs.category_id", envelope_id);
 
     let pipeline = vec![
-        doc! {
-            "$match": {
-                "$expr": {
-                    "$and": [
-                        { "$gte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, start_ts ] },
-                        { "$lte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, end_ts ] }
-                    ]
-                },
-                "legs.category_id": &envelope_id
-            }
-        },
+        doc! { "$match": window_match },
         doc! { "$unwind": "$legs" },
         doc! {
             "$match": {
-                "legs.category_id": &envelope_id,
+                "legs.category_id": envelope_id,
                 "legs.amount.kind": "Fiat",
                 "legs.amount.data.ccy": ccy_str
             }
@@ -1161,69 +1823,246 @@ pub async fn get_envelope_usage(
             // Continue with zero spent rather than failing
         }
     }
+    spent_amount
+}
 
-    let spent = Money {
-        amount: spent_amount,
-        ccy: budget_money.ccy,
-    };
-    let remaining = Money {
-        amount: budget_money.amount - spent.amount,
-        ccy: budget_money.ccy,
-    };
-    let percent = if budget_money.amount.is_zero() {
-        0.0
-    } else {
-        (spent.amount / budget_money.amount)
-            .to_f64()
-            .unwrap_or(0.0)
-            .max(0.0)
-    };
+/// Spend for many envelopes in one aggregation, keyed by `(category_id, ccy)`.
+/// Same signs and window as [`envelope_spent`].
+async fn envelope_spent_by_category(
+    db: &mongodb::Database,
+    envelope_ids: &[String],
+    start_ts: i64,
+    end_ts: i64,
+) -> mongodb::error::Result<std::collections::HashMap<(String, String), Decimal>> {
+    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
 
-    Ok(Json(EnvelopeUsage {
-        envelope_id,
-        label,
-        budget: budget_money,
-        spent,
-        remaining,
-        percent,
-    }))
+    let mut window_match = cycle_time_match(start_ts, end_ts);
+    window_match.extend(live_transactions());
+    window_match.insert("legs.category_id", doc! { "$in": envelope_ids });
+
+    let pipeline = vec![
+        doc! { "$match": window_match },
+        doc! { "$unwind": "$legs" },
+        doc! {
+            "$match": {
+                "legs.category_id": { "$in": envelope_ids },
+                "legs.amount.kind": "Fiat",
+            }
+        },
+        doc! {
+            "$group": {
+                "_id": { "category_id": "$legs.category_id", "ccy": "$legs.amount.data.ccy" },
+                "sum": { "$sum": {
+                    "$cond": [
+                        { "$eq": [ "$legs.direction", "Debit" ] },
+                        { "$toDecimal": "$legs.amount.data.amount" },
+                        { "$multiply": [ { "$toDecimal": "$legs.amount.data.amount" }, -1 ] }
+                    ]
+                }}
+            }
+        },
+    ];
+
+    let mut spent = std::collections::HashMap::new();
+    let mut cursor = ledger.aggregate(pipeline, None).await?;
+    while let Some(row) = cursor.try_next().await? {
+        let Ok(key) = row.get_document("_id") else {
+            continue;
+        };
+        let (Ok(category_id), Ok(ccy)) = (key.get_str("category_id"), key.get_str("ccy")) else {
+            continue;
+        };
+        let sum = row
+            .get("sum")
+            .map(decimal_from_bson)
+            .unwrap_or(Decimal::ZERO);
+        spent.insert((category_id.to_string(), ccy.to_string()), sum);
+    }
+    Ok(spent)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnvelopesUsageQuery {
+    pub label: Option<String>,
+    /// Comma-separated envelope ids; all envelopes when omitted.
+    pub ids: Option<String>,
+}
+
+/// GET /capital/envelopes/usage - Usage for several envelopes in one call
+///
+/// Runs a single aggregation over the cycle window instead of one per envelope.
+#[utoipa::path(
+    get,
+    path = "/capital/envelopes/usage",
+    params(
+        ("label" = Option<String>, Query, description = "Cycle label (e.g., '2025-10'); defaults to the active cycle"),
+        ("ids" = Option<String>, Query, description = "Comma-separated envelope ids; all envelopes when omitted")
+    ),
+    responses(
+        (status = 200, description = "Usage per envelope", body = Vec<EnvelopeUsage>),
+        (status = 400, description = "Invalid cycle label")
+    ),
+    tag = "capital"
+)]
+pub async fn get_envelopes_usage(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<EnvelopesUsageQuery>,
+) -> Result<Json<Vec<EnvelopeUsage>>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    let settings = load_capital_settings(&db).await;
+    let (start_ts, end_ts, label) = resolve_cycle(q.label.as_deref(), settings.cycle_start_day)
+        .map_err(CapitalApiError::Validation)?;
+
+    let filter = q.ids.as_deref().map(|ids| {
+        let ids: Vec<&str> = ids
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .collect();
+        doc! { "id": { "$in": ids } }
+    });
+
+    let envelopes: Vec<Envelope> = db
+        .collection::<Envelope>("capital_envelopes")
+        .find(filter, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let ids: Vec<String> = envelopes.iter().map(|env| env.id.clone()).collect();
+    let spent = envelope_spent_by_category(&db, &ids, start_ts, end_ts).await?;
+
+    let usage = envelopes
+        .iter()
+        .map(|env| {
+            let ccy = envelope_budget(env).ccy;
+            let amount = spent
+                .get(&(env.id.clone(), ccy.code().to_string()))
+                .copied()
+                .unwrap_or(Decimal::ZERO);
+            envelope_usage(env, label.clone(), amount)
+        })
+        .collect();
+    Ok(Json(usage))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnvelopeAlert {
+    pub envelope_id: String,
+    pub name: String,
+    pub label: String,
+    /// `period_limit`, or the funding budget when no limit is set.
+    pub limit: Money,
+    pub spent: Money,
+    pub over_by: Money,
+    /// Spend as a fraction of `limit` (1.25 = 25% over).
+    pub percent: f64,
+}
+
+/// Alert when `spent` exceeds the envelope's `period_limit` (falling back to its
+/// funding budget). Inactive envelopes and those with nothing to compare against
+/// never alert.
+pub fn envelope_limit_breach(env: &Envelope, label: &str, spent: Decimal) -> Option<EnvelopeAlert> {
+    if matches!(env.status, EnvelopeStatus::Inactive) {
+        return None;
+    }
+    let limit = env
+        .period_limit
+        .or_else(|| env.funding.as_ref().map(|f| f.amount))?;
+    if spent <= limit.amount {
+        return None;
+    }
+
+    Some(EnvelopeAlert {
+        envelope_id: env.id.clone(),
+        name: env.name.clone(),
+        label: label.to_string(),
+        limit,
+        spent: Money::new(spent, limit.ccy),
+        over_by: Money::new(spent - limit.amount, limit.ccy),
+        percent: spend_ratio(spent, limit.amount),
+    })
+}
+
+/// GET /capital/envelopes/alerts - Envelopes whose cycle spend exceeds their limit
+#[utoipa::path(
+    get,
+    path = "/capital/envelopes/alerts",
+    params(
+        ("label" = Option<String>, Query, description = "Cycle label (e.g., '2025-10'); defaults to the active cycle")
+    ),
+    responses(
+        (status = 200, description = "Envelopes over their limit", body = Vec<EnvelopeAlert>),
+        (status = 400, description = "Invalid cycle label")
+    ),
+    tag = "capital"
+)]
+pub async fn get_envelope_alerts(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<UsageQuery>,
+) -> Result<Json<Vec<EnvelopeAlert>>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    let settings = load_capital_settings(&db).await;
+    let (start_ts, end_ts, label) = resolve_cycle(q.label.as_deref(), settings.cycle_start_day)
+        .map_err(CapitalApiError::Validation)?;
+
+    let envelopes: Vec<Envelope> = db
+        .collection::<Envelope>("capital_envelopes")
+        .find(doc! { "status": "Active" }, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let ids: Vec<String> = envelopes.iter().map(|env| env.id.clone()).collect();
+    let spent = envelope_spent_by_category(&db, &ids, start_ts, end_ts).await?;
+
+    let alerts = envelopes
+        .iter()
+        .filter_map(|env| {
+            let ccy = env
+                .period_limit
+                .map(|l| l.ccy)
+                .unwrap_or_else(|| envelope_budget(env).ccy);
+            let amount = spent
+                .get(&(env.id.clone(), ccy.code().to_string()))
+                .copied()
+                .unwrap_or(Decimal::ZERO);
+            envelope_limit_breach(env, &label, amount)
+        })
+        .collect();
+
+    Ok(Json(alerts))
 }
 
 // Put near other helpers/constants
 const FIRST_CYCLE_START_UTC: i64 = 1_754_784_000;
 // = 2025-08-10 00:00:00 UTC  (update if your first cycle is a different year)
 
-// Given a cycle label "YYYY-MM", return (start_ts,end_ts)
-fn cycle_bounds_for_label(label: &str) -> Option<(i64, i64)> {
-    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+// Given a cycle label "YYYY-MM", return (start_ts,end_ts) for cycles starting on `start_day`.
+// The cycle ends one second before the next month's start.
+fn cycle_bounds_for_label(label: &str, start_day: u32) -> Option<(i64, i64)> {
     let (yyyy, mm) = label.split_once('-')?;
     let y: i32 = yyyy.parse().ok()?;
     let m: u32 = mm.parse().ok()?;
 
-    let start_date = NaiveDate::from_ymd_opt(y, m, 10)?;
-    let start = Utc
-        .from_utc_datetime(&NaiveDateTime::new(
-            start_date,
-            NaiveTime::from_hms_opt(0, 0, 0)?,
-        ))
+    let start = cycle_start_date(y, m, start_day)?
+        .and_hms_opt(0, 0, 0)?
+        .and_utc()
         .timestamp();
 
-    let (ey, em) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
-    let end_date = NaiveDate::from_ymd_opt(ey, em, 9)?;
-    let end = Utc
-        .from_utc_datetime(&NaiveDateTime::new(
-            end_date,
-            NaiveTime::from_hms_opt(23, 59, 59)?,
-        ))
+    let (ny, nm) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+    let next_start = cycle_start_date(ny, nm, start_day)?
+        .and_hms_opt(0, 0, 0)?
+        .and_utc()
         .timestamp();
 
-    Some((start, end))
+    Some((start, next_start - 1))
 }
 
 // Return all cycle labels from FIRST_CYCLE_START_UTC to "now" inclusive.
-// Labels are "YYYY-MM" for the month that STARTS on the 10th.
-fn list_cycle_labels(now_utc: i64) -> Vec<String> {
-    use chrono::{Datelike, TimeZone, Utc};
+// Labels are "YYYY-MM" for the month the cycle STARTS in.
+fn list_cycle_labels(now_utc: i64, start_day: u32) -> Vec<String> {
+    use chrono::Datelike;
     let mut out = Vec::new();
 
     // first cycle label
@@ -1234,16 +2073,13 @@ fn list_cycle_labels(now_utc: i64) -> Vec<String> {
     let mut y = start_dt.year();
     let mut m = start_dt.month();
 
-    // compute active cycle's label (same rule as active_cycle_bounds)
-    let active = Utc.timestamp_opt(now_utc, 0).single().unwrap();
-    let (ay, am) = if active.day() >= 10 {
-        (active.year(), active.month())
-    } else {
-        if active.month() == 1 {
-            (active.year() - 1, 12)
-        } else {
-            (active.year(), active.month() - 1)
-        }
+    // active cycle's label (same rule as active_cycle_bounds)
+    let (_, _, active) = active_cycle_bounds(now_utc, start_day);
+    let Some((ay, am)) = active
+        .split_once('-')
+        .and_then(|(ay, am)| Some((ay.parse::<i32>().ok()?, am.parse::<u32>().ok()?)))
+    else {
+        return out;
     };
 
     while y < ay || (y == ay && m <= am) {
@@ -1265,91 +2101,381 @@ pub struct CycleList {
 }
 
 /// GET /capital/cycles  → { labels: [...], active: "YYYY-MM" }
-pub async fn get_cycles(State(_state): State<Arc<AppState>>) -> Json<CycleList> {
+pub async fn get_cycles(State(state): State<Arc<AppState>>) -> Json<CycleList> {
+    let settings = load_capital_settings(&state.mongo_client.database("wyat")).await;
     let now = chrono::Utc::now().timestamp();
-    let labels = list_cycle_labels(now);
-    let (_, _, active) = active_cycle_bounds(now);
+    let labels = list_cycle_labels(now, settings.cycle_start_day);
+    let (_, _, active) = active_cycle_bounds(now, settings.cycle_start_day);
     Json(CycleList { labels, active })
 }
 
-/// GET /capital/funds - Fetch all funds from MongoDB (capital_funds collection)
-#[utoipa::path(
-    get,
-    path = "/capital/funds",
-    responses(
-        (status = 200, description = "List of all funds", body = Vec<PublicFund>)
-    ),
-    tag = "capital"
-)]
-pub async fn get_all_funds(State(state): State<Arc<AppState>>) -> Json<Vec<PublicFund>> {
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<Fund>("capital_funds");
+// ------------------------- Cycle Summaries -------------------------
 
-    use futures::stream::TryStreamExt;
+/// Convert an aggregation `$sum` result into a Decimal (zero for unexpected types).
+fn decimal_from_bson(value: &Bson) -> Decimal {
+    match value {
+        Bson::Decimal128(d) => Decimal::from_str_exact(&d.to_string()).unwrap_or(Decimal::ZERO),
+        Bson::Double(f) => Decimal::try_from(*f).unwrap_or(Decimal::ZERO),
+        Bson::Int32(i) => Decimal::from(*i),
+        Bson::Int64(i) => Decimal::from(*i),
+        _ => Decimal::ZERO,
+    }
+}
 
-    match collection.find(None, None).await {
-        Ok(cursor) => match cursor.try_collect::<Vec<Fund>>().await {
-            Ok(funds) => Json(
-                funds
-                    .into_iter()
-                    .map(|f| PublicFund {
-                        id: f.id.to_hex(),
-                        fund_id: f.fund_id,
-                        name: f.name,
-                        symbol: f.symbol,
-                        assets: f.assets,
-                        purpose: f.purpose,
-                        horizon_years: f.horizon_years,
-                        discretionary_sales: f.discretionary_sales,
-                        acquisition_policy: f.acquisition_policy,
-                        yield_policy: f.yield_policy,
-                        denominated_in: f.denominated_in,
-                        balancing_policy: f.balancing_policy,
-                        multiplier_rules: f.multiplier_rules,
-                        max_pct_networth: f.max_pct_networth,
-                        max_pct_liquid: f.max_pct_liquid,
-                        liquid: f.liquid,
-                        review_cadence: f.review_cadence,
-                        status: f.status,
-                        created_at: f
-                            .created_at
-                            .map(|d| d.timestamp_millis() / 1000)
-                            .unwrap_or(0),
-                        updated_at: f
-                            .updated_at
-                            .map(|d| d.timestamp_millis() / 1000)
-                            .unwrap_or(0),
-                    })
-                    .collect(),
-            ),
-            Err(e) => {
-                eprintln!("Error collecting funds: {}", e);
-                Json(Vec::new())
+/// P&L totals for a single cycle window in one currency.
+/// `income` sums Credit legs on the P&L account, `spending` sums Debit legs.
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleSummary {
+    pub label: String,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub ccy: Currency,
+    pub income: Decimal,
+    pub spending: Decimal,
+    pub net: Decimal,
+    pub savings_rate: Option<Decimal>,
+}
+
+/// Aggregate P&L legs for a cycle window, grouped by leg direction.
+/// Uses posted_ts when available, falls back to ts.
+async fn summarize_cycle(
+    db: &Database,
+    label: &str,
+    start_ts: i64,
+    end_ts: i64,
+    ccy: Currency,
+) -> Result<CycleSummary, String> {
+    let ledger = db.collection::<BsonDocument>("capital_ledger");
+
+    let mut window_match = cycle_time_match(start_ts, end_ts);
+    window_match.extend(live_transactions());
+    window_match.insert("legs.account_id", PNL_ACCOUNT_ID);
+
+    let pipeline = vec![
+        doc! { "$match": window_match },
+        doc! { "$unwind": "$legs" },
+        doc! {
+            "$match": {
+                "legs.account_id": PNL_ACCOUNT_ID,
+                "legs.amount.kind": "Fiat",
+                "legs.amount.data.ccy": ccy.code()
             }
         },
-        Err(e) => {
-            eprintln!("Error fetching funds: {}", e);
-            Json(Vec::new())
+        doc! {
+            "$group": {
+                "_id": "$legs.direction",
+                "sum": { "$sum": { "$toDecimal": "$legs.amount.data.amount" } }
+            }
+        },
+    ];
+
+    let mut cursor = ledger
+        .aggregate(pipeline, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut income = Decimal::ZERO;
+    let mut spending = Decimal::ZERO;
+    while let Some(row) = cursor
+        .try_next()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+    {
+        let sum = row
+            .get("sum")
+            .map(decimal_from_bson)
+            .unwrap_or(Decimal::ZERO);
+        match row.get_str("_id") {
+            Ok("Credit") => income += sum,
+            Ok("Debit") => spending += sum,
+            _ => {}
         }
     }
+
+    Ok(CycleSummary {
+        label: label.to_string(),
+        start_ts,
+        end_ts,
+        ccy,
+        income,
+        spending,
+        net: income - spending,
+        savings_rate: savings_rate(income, spending),
+    })
 }
 
-/// Helper function to get positions for a single fund with cost basis calculation.
-///
-/// Trading Rule: All crypto trades MUST be against USDT or USDC pairs for proper accounting.
-/// This function extracts paired stablecoin legs to compute USD cost basis and average entry price.
-async fn get_positions_for_fund(state: &Arc<AppState>, fund_id: &str) -> Vec<Position> {
-    use futures::stream::TryStreamExt;
-    use mongodb::bson::{Bson, doc};
-    use std::collections::HashMap;
+/// `(income - spending) / income` rounded to three places; `None` when there is no income.
+fn savings_rate(income: Decimal, spending: Decimal) -> Option<Decimal> {
+    if income.is_zero() {
+        return None;
+    }
+    Some(((income - spending) / income).round_dp(3))
+}
 
-    let db = state.mongo_client.database("wyat");
-    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
+#[derive(Debug, Deserialize)]
+pub struct CycleSummaryQuery {
+    pub ccy: Option<Currency>,
+}
 
-    // Step 1: Find all transactions that touch this fund
-    let pipeline = vec![
-        doc! { "$match": {
-            "legs.category_id": &fund_id
+#[derive(Debug, Serialize)]
+pub struct SavingsRateResponse {
+    pub label: String,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub ccy: Currency,
+    pub income: Decimal,
+    pub spending: Decimal,
+    pub savings_rate: Option<Decimal>,
+}
+
+/// GET /capital/cycles/{label}/savings-rate?ccy=USD
+///
+/// Income is the sum of Credit P&L legs, spending the sum of Debit P&L legs.
+/// `savings_rate` is null when the cycle has no income.
+pub async fn get_cycle_savings_rate(
+    State(state): State<Arc<AppState>>,
+    Path(label): Path<String>,
+    Query(q): Query<CycleSummaryQuery>,
+) -> Result<Json<SavingsRateResponse>, String> {
+    let db = state.mongo_client.database("wyat");
+    let settings = load_capital_settings(&db).await;
+    let (start_ts, end_ts) = cycle_bounds_for_label(&label, settings.cycle_start_day)
+        .ok_or_else(|| format!("Invalid cycle label: {}", label))?;
+    let ccy = q.ccy.unwrap_or(Currency::USD);
+
+    let summary = summarize_cycle(&db, &label, start_ts, end_ts, ccy).await?;
+
+    Ok(Json(SavingsRateResponse {
+        savings_rate: summary.savings_rate,
+        label: summary.label,
+        start_ts: summary.start_ts,
+        end_ts: summary.end_ts,
+        ccy: summary.ccy,
+        income: summary.income,
+        spending: summary.spending,
+    }))
+}
+
+/// Reconciliation progress for one cycle's live transactions.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct CycleReconciliationSummary {
+    pub label: String,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub total_count: u64,
+    pub reconciled_count: u64,
+    pub unreconciled_count: u64,
+    pub balanced_count: u64,
+    pub needs_offset_count: u64,
+    pub awaiting_match_count: u64,
+}
+
+/// Count reconciled and balance states over `[start_ts, end_ts]` in one `$group`.
+async fn cycle_reconciliation_summary(
+    db: &mongodb::Database,
+    label: &str,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<CycleReconciliationSummary, CapitalApiError> {
+    let mut filter = cycle_time_match(start_ts, end_ts);
+    filter.extend(live_transactions());
+    let count_if = |cond: BsonDocument| doc! { "$sum": { "$cond": [cond, 1, 0] } };
+    let pipeline = vec![
+        doc! { "$match": filter },
+        doc! { "$group": {
+            "_id": null,
+            "total": { "$sum": 1 },
+            "reconciled": count_if(doc! { "$eq": ["$reconciled", true] }),
+            "balanced": count_if(doc! { "$eq": ["$balance_state", "Balanced"] }),
+            "needs_offset": count_if(doc! { "$eq": ["$balance_state", "NeedsEnvelopeOffset"] }),
+            "awaiting_match": count_if(doc! { "$eq": ["$balance_state", "AwaitingTransferMatch"] }),
+        }},
+    ];
+
+    let mut cursor = db
+        .collection::<BsonDocument>("capital_ledger")
+        .aggregate(pipeline, None)
+        .await?;
+    let mut summary = CycleReconciliationSummary {
+        label: label.to_string(),
+        start_ts,
+        end_ts,
+        ..Default::default()
+    };
+    if let Some(doc) = cursor.try_next().await? {
+        let count = |key: &str| match doc.get(key) {
+            Some(Bson::Int32(n)) => *n as u64,
+            Some(Bson::Int64(n)) => *n as u64,
+            _ => 0,
+        };
+        summary.total_count = count("total");
+        summary.reconciled_count = count("reconciled");
+        summary.unreconciled_count = summary.total_count - summary.reconciled_count;
+        summary.balanced_count = count("balanced");
+        summary.needs_offset_count = count("needs_offset");
+        summary.awaiting_match_count = count("awaiting_match");
+    }
+    Ok(summary)
+}
+
+/// GET /capital/cycles/{label}/summary
+///
+/// Transaction counts by reconciliation and balance state for the cycle.
+pub async fn get_cycle_reconciliation_summary(
+    State(state): State<Arc<AppState>>,
+    Path(label): Path<String>,
+) -> Result<Json<CycleReconciliationSummary>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    let settings = load_capital_settings(&db).await;
+    let (start_ts, end_ts) = cycle_bounds_for_label(&label, settings.cycle_start_day)
+        .ok_or_else(|| CapitalApiError::Validation(format!("Invalid cycle label: {}", label)))?;
+
+    cycle_reconciliation_summary(&db, &label, start_ts, end_ts)
+        .await
+        .map(Json)
+}
+
+const DEFAULT_CYCLE_SUMMARY_LIMIT: usize = 12;
+const MAX_CYCLE_SUMMARY_LIMIT: usize = 60;
+
+#[derive(Debug, Deserialize)]
+pub struct CycleSummariesQuery {
+    pub limit: Option<usize>,
+    pub ccy: Option<Currency>,
+}
+
+/// GET /capital/cycles/summaries?limit=12&ccy=USD
+///
+/// Income/spending/net/savings rate for the most recent `limit` cycles (newest first),
+/// aggregated concurrently. `limit` defaults to 12 and is capped at 60.
+pub async fn get_cycle_summaries(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<CycleSummariesQuery>,
+) -> Result<Json<Vec<CycleSummary>>, String> {
+    let limit = q
+        .limit
+        .unwrap_or(DEFAULT_CYCLE_SUMMARY_LIMIT)
+        .clamp(1, MAX_CYCLE_SUMMARY_LIMIT);
+    let ccy = q.ccy.unwrap_or(Currency::USD);
+
+    let db = state.mongo_client.database("wyat");
+    let start_day = load_capital_settings(&db).await.cycle_start_day;
+    let now = chrono::Utc::now().timestamp();
+    let labels: Vec<String> = list_cycle_labels(now, start_day)
+        .into_iter()
+        .rev()
+        .take(limit)
+        .collect();
+
+    let summaries = futures::future::try_join_all(labels.iter().map(|label| {
+        let db = &db;
+        async move {
+            let (start_ts, end_ts) = cycle_bounds_for_label(label, start_day)
+                .ok_or_else(|| format!("Invalid cycle label: {}", label))?;
+            summarize_cycle(db, label, start_ts, end_ts, ccy).await
+        }
+    }))
+    .await?;
+
+    Ok(Json(summaries))
+}
+
+/// GET /capital/funds - Fetch all funds from MongoDB (capital_funds collection)
+#[utoipa::path(
+    get,
+    path = "/capital/funds",
+    responses(
+        (status = 200, description = "List of all funds", body = Vec<PublicFund>)
+    ),
+    tag = "capital"
+)]
+pub async fn get_all_funds(State(state): State<Arc<AppState>>) -> Json<Vec<PublicFund>> {
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Fund>("capital_funds");
+
+    use futures::stream::TryStreamExt;
+
+    match collection.find(None, None).await {
+        Ok(cursor) => match cursor.try_collect::<Vec<Fund>>().await {
+            Ok(funds) => Json(funds.into_iter().map(PublicFund::from).collect()),
+            Err(e) => {
+                eprintln!("Error collecting funds: {}", e);
+                Json(Vec::new())
+            }
+        },
+        Err(e) => {
+            eprintln!("Error fetching funds: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+/// Validate and insert a new fund, rejecting a `fund_id` that is already taken.
+async fn insert_fund(
+    db: &mongodb::Database,
+    new_fund: NewFund,
+) -> Result<PublicFund, CapitalApiError> {
+    new_fund.validate().map_err(CapitalApiError::Validation)?;
+
+    let collection = db.collection::<Fund>("capital_funds");
+    let fund_id = new_fund.fund_id.trim().to_string();
+    if collection
+        .find_one(doc! { "fund_id": &fund_id }, None)
+        .await?
+        .is_some()
+    {
+        return Err(CapitalApiError::Conflict(format!(
+            "Fund with fund_id '{}' already exists",
+            fund_id
+        )));
+    }
+
+    let fund = NewFund {
+        fund_id,
+        ..new_fund
+    }
+    .into_fund(mongodb::bson::DateTime::now());
+    collection.insert_one(&fund, None).await?;
+    Ok(fund.into())
+}
+
+/// POST /capital/funds - Create a fund in the capital_funds collection
+#[utoipa::path(
+    post,
+    path = "/capital/funds",
+    request_body = NewFund,
+    responses(
+        (status = 200, description = "Fund created", body = PublicFund),
+        (status = 400, description = "Invalid fund"),
+        (status = 409, description = "fund_id already exists")
+    ),
+    tag = "capital"
+)]
+pub async fn create_fund(
+    State(state): State<Arc<AppState>>,
+    Json(new_fund): Json<NewFund>,
+) -> Result<Json<PublicFund>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    insert_fund(&db, new_fund).await.map(Json)
+}
+
+/// Helper function to get positions for a single fund with cost basis calculation.
+///
+/// Trading Rule: All crypto trades MUST be against USDT or USDC pairs for proper accounting.
+/// This function extracts paired stablecoin legs to compute USD cost basis and average entry price.
+async fn get_positions_for_fund(state: &Arc<AppState>, fund_id: &str) -> Vec<Position> {
+    use futures::stream::TryStreamExt;
+    use mongodb::bson::{Bson, doc};
+    use std::collections::HashMap;
+
+    let db = state.mongo_client.database("wyat");
+    let ledger = db.collection::<mongodb::bson::Document>("capital_ledger");
+
+    // Step 1: Find all transactions that touch this fund
+    let pipeline = vec![
+        doc! { "$match": {
+            "legs.category_id": &fund_id,
+            "deleted": { "$ne": true }
         }},
         doc! { "$project": {
             "id": 1,
@@ -1441,16 +2567,10 @@ async fn get_positions_for_fund(state: &Arc<AppState>, fund_id: &str) -> Vec<Pos
                                         };
 
                                         // For fiat, cost basis equals the amount in USD (or converted to USD)
-                                        let usd_cost = if ccy == "USD" {
-                                            qty_change
-                                        } else if ccy == "HKD" {
-                                            // Convert HKD to USD (approximate peg: 1 USD = 7.8 HKD)
-                                            qty_change
-                                                / Decimal::from_str_exact("7.8")
-                                                    .unwrap_or(Decimal::ONE)
-                                        } else {
-                                            qty_change // Default to same value
-                                        };
+                                        let usd_cost = Currency::from_code(&ccy)
+                                            .and_then(|c| usd_per_unit(c, None))
+                                            .map(|rate| qty_change * rate)
+                                            .unwrap_or(qty_change); // Default to same value
 
                                         if !ccy.is_empty() && qty_change != Decimal::ZERO {
                                             trades.push(TradeData {
@@ -1669,152 +2789,744 @@ pub async fn get_fund_positions(
     Json(get_positions_for_fund(&state, &fund_id).await)
 }
 
-/// GET /capital/funds/:fund_id/positions - Compute positions for a specific fund
-///
-/// Positions are derived from capital_ledger transactions filtered by transaction-level `fund_id`.
-/// For v1, this endpoint returns net quantities per asset (fiat currencies and crypto assets).
-/// Valuation and pricing are intentionally omitted for now.
-// (duplicate get_fund_positions removed in favor of category-based aggregator above)
-
-/// GET /capital/accounts - Fetch all accounts from MongoDB
-#[utoipa::path(
-    get,
-    path = "/capital/accounts",
-    responses(
-        (status = 200, description = "List of all accounts", body = Vec<Account>)
-    ),
-    tag = "capital"
-)]
-pub async fn get_all_accounts(State(state): State<Arc<AppState>>) -> Json<Vec<Account>> {
-    println!("=== GET_ALL_ACCOUNTS START ===");
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<Account>("capital_accounts");
+// ------------------------- Fund Compliance -------------------------
 
-    use futures::stream::TryStreamExt;
+/// HKD per USD under the peg, used wherever HKD is valued in USD.
+const HKD_PER_USD: Decimal = Decimal::from_parts(78, 0, 0, false, 1);
 
-    match collection.find(None, None).await {
-        Ok(cursor) => match cursor.try_collect::<Vec<Account>>().await {
-            Ok(accounts) => {
-                println!("Successfully fetched {} accounts", accounts.len());
-                for account in &accounts {
-                    println!("  Account: id={}, name={}", account.id, account.name);
-                }
-                println!("=== GET_ALL_ACCOUNTS END ===");
-                Json(accounts)
-            }
-            Err(e) => {
-                eprintln!("Error collecting accounts: {}", e);
-                println!("=== GET_ALL_ACCOUNTS ERROR ===");
-                Json(Vec::new())
-            }
-        },
-        Err(e) => {
-            eprintln!("Error fetching accounts: {}", e);
-            println!("=== GET_ALL_ACCOUNTS ERROR ===");
-            Json(Vec::new())
-        }
+/// Value of one unit of `ccy` in USD; BTC needs a quoted `btc_usd`. EUR and GBP
+/// float, so they have no fixed rate here.
+fn usd_per_unit(ccy: Currency, btc_usd: Option<Decimal>) -> Option<Decimal> {
+    match ccy {
+        Currency::USD => Some(Decimal::ONE),
+        Currency::HKD => Some(Decimal::ONE / HKD_PER_USD),
+        Currency::BTC => btc_usd,
+        Currency::EUR | Currency::GBP => None,
     }
 }
 
-/// POST /capital/accounts - Create a new account
-#[utoipa::path(
-    post,
-    path = "/capital/accounts",
-    request_body = Account,
-    responses(
-        (status = 200, description = "Account created successfully", body = Account)
-    ),
-    tag = "capital"
-)]
-pub async fn create_account(
-    State(state): State<Arc<AppState>>,
-    Json(account): Json<Account>,
-) -> Result<Json<Account>, String> {
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<Account>("capital_accounts");
-
-    use mongodb::bson::doc;
+/// Convert `amount` between reporting currencies via USD.
+fn convert_ccy(
+    amount: Decimal,
+    from: Currency,
+    to: Currency,
+    btc_usd: Option<Decimal>,
+) -> Option<Decimal> {
+    if from == to {
+        return Some(amount);
+    }
+    let to_rate = usd_per_unit(to, btc_usd)?;
+    if to_rate.is_zero() {
+        return None;
+    }
+    Some(amount * usd_per_unit(from, btc_usd)? / to_rate)
+}
 
-    // Validate account ID is not empty
-    if account.id.trim().is_empty() {
-        return Err("Account ID cannot be empty".to_string());
+/// Reject reporting currencies that have no USD rate; valuing in them would leave
+/// every holding unpriced.
+fn check_reporting_ccy(ccy: Currency) -> Result<(), CapitalApiError> {
+    match usd_per_unit(ccy, Some(Decimal::ONE)) {
+        Some(_) => Ok(()),
+        None => Err(CapitalApiError::Validation(format!(
+            "Unsupported reporting currency '{}': expected USD, HKD or BTC",
+            ccy.code()
+        ))),
     }
+}
 
-    // Validate account name is not empty
-    if account.name.trim().is_empty() {
-        return Err("Account name cannot be empty".to_string());
+/// Latest stored quote for `asset` and the currency it is quoted in. Stablecoins are
+/// taken at 1 USD; anything else comes from its watchlist feed's latest snapshot.
+async fn latest_asset_quote(
+    db: &mongodb::Database,
+    service: &DataFeedService,
+    asset: &str,
+) -> Result<Option<(Decimal, Currency)>, CapitalApiError> {
+    if matches!(asset.to_uppercase().as_str(), "USDT" | "USDC") {
+        return Ok(Some((Decimal::ONE, Currency::USD)));
     }
 
-    // Check if account already exists
-    let existing = collection
-        .find_one(doc! { "id": &account.id }, None)
+    let watchlist = db.collection::<WatchlistEntry>("capital_watchlist");
+    let Some(entry) = find_watchlist_entry(&watchlist, asset).await? else {
+        return Ok(None);
+    };
+    let Some(snapshot) = service
+        .get_latest_snapshot(db, &entry.feed_symbol)
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(|e| CapitalApiError::Internal(format!("Database error: {e}")))?
+    else {
+        return Ok(None);
+    };
+    let Some(data) = snapshot.data.first() else {
+        return Ok(None);
+    };
+    let unit = data
+        .unit
+        .as_deref()
+        .or(entry.unit.as_deref())
+        .unwrap_or("USD");
+    Ok(Currency::from_code(&unit.to_uppercase()).map(|ccy| (data.value, ccy)))
+}
 
-    if existing.is_some() {
-        return Err(format!("Account with ID '{}' already exists", account.id));
+/// Unit prices in one reporting currency, looked up once per asset.
+struct ReportingPrices<'a> {
+    db: &'a mongodb::Database,
+    service: &'a DataFeedService,
+    ccy: Currency,
+    btc_usd: Option<Decimal>,
+    prices: HashMap<String, Decimal>,
+    missing: std::collections::HashSet<String>,
+}
+
+impl<'a> ReportingPrices<'a> {
+    async fn new(
+        db: &'a mongodb::Database,
+        service: &'a DataFeedService,
+        ccy: Currency,
+    ) -> Result<Self, CapitalApiError> {
+        check_reporting_ccy(ccy)?;
+        let btc_usd = match latest_asset_quote(db, service, "BTC").await? {
+            Some((price, unit)) => convert_ccy(price, unit, Currency::USD, None),
+            None => None,
+        };
+        Ok(Self {
+            db,
+            service,
+            ccy,
+            btc_usd,
+            prices: HashMap::new(),
+            missing: Default::default(),
+        })
     }
 
-    // Insert the new account
-    collection
-        .insert_one(&account, None)
-        .await
-        .map_err(|e| format!("Failed to create account: {}", e))?;
+    /// Look up `asset` unless already seen; currencies convert directly, anything
+    /// else goes through its latest quote.
+    async fn load(&mut self, asset: &str) -> Result<(), CapitalApiError> {
+        if self.prices.contains_key(asset) || self.missing.contains(asset) {
+            return Ok(());
+        }
+        let price = match Currency::from_code(asset) {
+            Some(held) => convert_ccy(Decimal::ONE, held, self.ccy, self.btc_usd),
+            None => match latest_asset_quote(self.db, self.service, asset).await? {
+                Some((price, unit)) => convert_ccy(price, unit, self.ccy, self.btc_usd),
+                None => None,
+            },
+        };
+        match price {
+            Some(price) => {
+                self.prices.insert(asset.to_string(), price);
+            }
+            None => {
+                self.missing.insert(asset.to_string());
+            }
+        }
+        Ok(())
+    }
+}
 
-    Ok(Json(account))
+/// Sum of holding values, given per-asset unit prices in the reporting currency.
+/// Non-zero holdings without a price are skipped and returned so callers can surface them.
+pub fn value_holdings<'h>(
+    holdings: impl IntoIterator<Item = (&'h str, Decimal)>,
+    prices: &HashMap<String, Decimal>,
+) -> (Decimal, Vec<String>) {
+    let mut total = Decimal::ZERO;
+    let mut unpriced = Vec::new();
+    for (asset, qty) in holdings {
+        match prices.get(asset) {
+            Some(price) => total += qty * price,
+            None if !qty.is_zero() => unpriced.push(asset.to_string()),
+            None => {}
+        }
+    }
+    (total, unpriced)
 }
 
-// ------------------------- Query Parameters -------------------------
+/// Current holdings of one account: per-asset quantities for wallets and exchanges,
+/// otherwise the balance in the account's currency.
+async fn account_holdings(
+    db: &mongodb::Database,
+    account: &Account,
+    as_of: i64,
+) -> Result<BTreeMap<String, Decimal>, CapitalApiError> {
+    if account.holds_assets() {
+        return sum_crypto_as_of(db, &account.id, as_of)
+            .await
+            .map_err(CapitalApiError::Internal);
+    }
+    let code = account.currency.code();
+    let balance = sum_as_of(db, &account.id, code, as_of)
+        .await
+        .map_err(CapitalApiError::Internal)?;
+    Ok(BTreeMap::from([(code.to_string(), balance)]))
+}
 
 #[derive(Debug, Deserialize)]
-pub struct TransactionQuery {
-    pub account_id: Option<String>,
-    pub envelope_id: Option<String>,
-    pub from: Option<i64>, // Unix timestamp
-    pub to: Option<i64>,   // Unix timestamp
-    pub label: Option<String>,
-    pub tx_type: Option<String>,
+pub struct FundComplianceQuery {
+    /// Reporting currency shared by the fund valuation and the net-worth totals;
+    /// USD, HKD or BTC.
+    pub ccy: Currency,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ReclassifyTransactionRequest {
-    pub transaction_id: String,
-    pub leg_index: usize,
-    pub category_id: Option<String>, // envelope ID or category
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct FundCompliance {
+    pub fund_id: String,
+    pub ccy: Currency,
+    #[schema(value_type = String)]
+    pub fund_value: Decimal,
+    #[schema(value_type = String)]
+    pub networth: Decimal,
+    #[schema(value_type = String)]
+    pub liquid_networth: Decimal,
+    pub pct_networth: f64,
+    pub pct_liquid: f64,
+    pub over_networth_cap: bool,
+    pub over_liquid_cap: bool,
+    /// Assets held by the fund or any account that were left out of the totals
+    /// because no price was available.
+    pub unpriced_assets: Vec<String>,
+}
+
+/// Compare a fund's value against net worth and liquid net worth and its policy caps.
+/// An empty denominator yields a share of 0.
+pub fn fund_compliance(
+    fund: &Fund,
+    ccy: Currency,
+    fund_value: Decimal,
+    networth: Decimal,
+    liquid_networth: Decimal,
+) -> FundCompliance {
+    let share = |total: Decimal| {
+        if total.is_zero() {
+            0.0
+        } else {
+            (fund_value / total).to_f64().unwrap_or(0.0)
+        }
+    };
+    let pct_networth = share(networth);
+    let pct_liquid = share(liquid_networth);
+
+    FundCompliance {
+        fund_id: fund.fund_id.clone(),
+        ccy,
+        fund_value,
+        networth,
+        liquid_networth,
+        pct_networth,
+        pct_liquid,
+        over_networth_cap: pct_networth > fund.max_pct_networth,
+        over_liquid_cap: pct_liquid > fund.max_pct_liquid,
+        unpriced_assets: Vec::new(),
+    }
 }
 
+/// GET /capital/funds/:fund_id/compliance - Check a fund against its allocation caps
+///
+/// Net worth is the combined balance of every account and liquid net worth that of
+/// accounts that are not trusts, all valued in `ccy` from the latest stored price
+/// snapshots. Assets that can't be priced are listed in `unpriced_assets`.
 #[utoipa::path(
     get,
-    path = "/capital/transactions",
+    path = "/capital/funds/{fund_id}/compliance",
     params(
-        ("account_id" = Option<String>, Query, description = "Filter by account ID"),
-        ("envelope_id" = Option<String>, Query, description = "Filter by envelope/category ID"),
-        ("label" = Option<String>, Query, description = "Filter by cycle label (e.g., '2025-10')"),
-        ("from" = Option<i64>, Query, description = "Unix timestamp for start of time range"),
-        ("to" = Option<i64>, Query, description = "Unix timestamp for end of time range"),
-        ("tx_type" = Option<String>, Query, description = "Filter by transaction type")
+        ("fund_id" = String, Path, description = "Fund ID"),
+        ("ccy" = String, Query, description = "Reporting currency (USD, HKD or BTC)")
     ),
     responses(
-        (status = 200, description = "List of transactions", body = Vec<Transaction>)
+        (status = 200, description = "Fund allocation against its caps", body = FundCompliance),
+        (status = 400, description = "Reporting currency has no conversion rate"),
+        (status = 404, description = "Fund not found")
     ),
     tag = "capital"
 )]
-pub async fn get_transactions(
+pub async fn get_fund_compliance(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<TransactionQuery>,
-) -> Result<Json<Vec<Transaction>>, String> {
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<Transaction>("capital_ledger");
-
-    use futures::stream::TryStreamExt;
-    use mongodb::bson::doc;
+    Path(fund_id): Path<String>,
+    Query(q): Query<FundComplianceQuery>,
+) -> Result<Json<FundCompliance>, CapitalApiError> {
+    use std::collections::BTreeSet;
 
-    // Build MongoDB query filter
-    let mut filter = doc! {};
-
-    // Filter by account_id if provided
-    if let Some(account_id) = &params.account_id {
-        filter.insert("legs.account_id", account_id);
+    let db = state.mongo_client.database("wyat");
+    let service = DataFeedService::new().map_err(|e| CapitalApiError::Internal(e.to_string()))?;
+    let fund = db
+        .collection::<Fund>("capital_funds")
+        .find_one(doc! { "fund_id": &fund_id }, None)
+        .await?
+        .ok_or_else(|| CapitalApiError::NotFound(format!("Fund '{}' not found", fund_id)))?;
+
+    let mut prices = ReportingPrices::new(&db, &service, q.ccy).await?;
+    let mut unpriced_assets = BTreeSet::new();
+
+    let positions = get_positions_for_fund(&state, &fund.fund_id).await;
+    for position in &positions {
+        prices.load(&position.asset).await?;
+    }
+    let (fund_value, unpriced) = value_holdings(
+        positions.iter().map(|p| (p.asset.as_str(), p.qty)),
+        &prices.prices,
+    );
+    unpriced_assets.extend(unpriced);
+
+    let accounts: Vec<Account> = db
+        .collection::<Account>("capital_accounts")
+        .find(None, None)
+        .await?
+        .try_collect()
+        .await?;
+    let now = Utc::now().timestamp();
+    let mut networth = Decimal::ZERO;
+    let mut liquid_networth = Decimal::ZERO;
+    for account in &accounts {
+        let holdings = account_holdings(&db, account, now).await?;
+        for asset in holdings.keys() {
+            prices.load(asset).await?;
+        }
+        let (value, unpriced) = value_holdings(
+            holdings.iter().map(|(asset, qty)| (asset.as_str(), *qty)),
+            &prices.prices,
+        );
+        unpriced_assets.extend(unpriced);
+        networth += value;
+        if account.is_liquid() {
+            liquid_networth += value;
+        }
+    }
+
+    let mut compliance = fund_compliance(&fund, q.ccy, fund_value, networth, liquid_networth);
+    compliance.unpriced_assets = unpriced_assets.into_iter().collect();
+    Ok(Json(compliance))
+}
+
+/// GET /capital/funds/:fund_id/positions - Compute positions for a specific fund
+///
+/// Positions are derived from capital_ledger transactions filtered by transaction-level `fund_id`.
+/// For v1, this endpoint returns net quantities per asset (fiat currencies and crypto assets).
+/// Valuation and pricing are intentionally omitted for now.
+// (duplicate get_fund_positions removed in favor of category-based aggregator above)
+
+#[derive(Debug, Deserialize)]
+pub struct AccountLedgerQuery {
+    /// Cycle label "YYYY-MM"; omit for the account's whole history.
+    pub label: Option<String>,
+}
+
+/// Per-asset quantities at either edge of a window (wallet and exchange accounts).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct AssetBalances {
+    #[schema(value_type = Object)]
+    pub opening: BTreeMap<String, Decimal>,
+    #[schema(value_type = Object)]
+    pub closing: BTreeMap<String, Decimal>,
+}
+
+/// One account with its ledger rows and balances for a window.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountLedger {
+    pub account: Account,
+    /// Balance just before the window (zero for the whole history).
+    pub opening: Money,
+    /// Balance at the end of the window.
+    pub closing: Money,
+    /// Every asset's quantities, for wallet and exchange accounts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets: Option<AssetBalances>,
+    pub start_ts: Option<i64>,
+    pub end_ts: i64,
+    pub label: Option<String>,
+    pub transactions: Vec<Transaction>,
+}
+
+/// An account's live transactions in a window, oldest first, with balances at
+/// either edge. Shared by the ledger and generated-statement endpoints.
+struct AccountWindow {
+    account: Account,
+    start_ts: Option<i64>,
+    end_ts: i64,
+    opening: Decimal,
+    closing: Decimal,
+    assets: Option<AssetBalances>,
+    transactions: Vec<Transaction>,
+}
+
+/// Load the window for cycle `label` (or the whole history up to `now`).
+///
+/// Fiat accounts sum fiat legs in the account currency with `sum_as_of`. Wallet and
+/// exchange accounts sum crypto legs per asset with `sum_crypto_as_of`; their `Money`
+/// balances are the quantity of the account's own currency (e.g. BTC for a BTC wallet).
+async fn account_window(
+    db: &mongodb::Database,
+    account_id: &str,
+    label: Option<&str>,
+    now: i64,
+) -> Result<AccountWindow, CapitalApiError> {
+    let account = db
+        .collection::<Account>("capital_accounts")
+        .find_one(doc! { "id": account_id }, None)
+        .await?
+        .ok_or_else(|| CapitalApiError::NotFound(format!("Account not found: {}", account_id)))?;
+
+    let (start_ts, end_ts) = match label {
+        Some(label) => {
+            let settings = load_capital_settings(db).await;
+            let (start_ts, end_ts) = cycle_bounds_for_label(label, settings.cycle_start_day)
+                .ok_or_else(|| {
+                    CapitalApiError::Validation(format!("Invalid cycle label: {}", label))
+                })?;
+            (Some(start_ts), end_ts)
+        }
+        None => (None, now),
+    };
+
+    let ccy_str = account.currency.code();
+    let (opening, closing, assets) = if account.holds_assets() {
+        let opening = match start_ts {
+            Some(start_ts) => sum_crypto_as_of(db, &account.id, start_ts - 1)
+                .await
+                .map_err(CapitalApiError::Internal)?,
+            None => BTreeMap::new(),
+        };
+        let closing = sum_crypto_as_of(db, &account.id, end_ts)
+            .await
+            .map_err(CapitalApiError::Internal)?;
+        let own = |totals: &BTreeMap<String, Decimal>| {
+            totals.get(ccy_str).copied().unwrap_or(Decimal::ZERO)
+        };
+        (
+            own(&opening),
+            own(&closing),
+            Some(AssetBalances { opening, closing }),
+        )
+    } else {
+        let opening = match start_ts {
+            Some(start_ts) => sum_as_of(db, &account.id, ccy_str, start_ts - 1)
+                .await
+                .map_err(CapitalApiError::Internal)?,
+            None => Decimal::ZERO,
+        };
+        let closing = sum_as_of(db, &account.id, ccy_str, end_ts)
+            .await
+            .map_err(CapitalApiError::Internal)?;
+        (opening, closing, None)
+    };
+
+    let mut filter = doc! { "legs.account_id": &account.id };
+    if let Some(start_ts) = start_ts {
+        filter.extend(cycle_time_match(start_ts, end_ts));
+    }
+    filter.extend(live_transactions());
+    let options = FindOptions::builder().sort(doc! { "ts": 1 }).build();
+    let transactions: Vec<Transaction> = db
+        .collection::<Transaction>("capital_ledger")
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(AccountWindow {
+        account,
+        start_ts,
+        end_ts,
+        opening,
+        closing,
+        assets,
+        transactions,
+    })
+}
+
+/// Load an account's live transactions in the cycle `label` (or all of them), oldest
+/// first, with opening and closing balances from `account_window`.
+pub async fn account_ledger(
+    db: &mongodb::Database,
+    account_id: &str,
+    label: Option<&str>,
+    now: i64,
+) -> Result<AccountLedger, CapitalApiError> {
+    let window = account_window(db, account_id, label, now).await?;
+    Ok(AccountLedger {
+        opening: Money::new(window.opening, window.account.currency),
+        closing: Money::new(window.closing, window.account.currency),
+        account: window.account.masked(),
+        assets: window.assets,
+        start_ts: window.start_ts,
+        end_ts: window.end_ts,
+        label: label.map(str::to_string),
+        transactions: window.transactions,
+    })
+}
+
+/// GET /capital/accounts/:account_id/ledger - An account with its transactions
+///
+/// Account numbers are masked as in `/capital/accounts`. Balances use the ledger
+/// sign convention (debits positive).
+#[utoipa::path(
+    get,
+    path = "/capital/accounts/{account_id}/ledger",
+    params(
+        ("account_id" = String, Path, description = "Account id"),
+        ("label" = Option<String>, Query, description = "Cycle label YYYY-MM; omit for all history")
+    ),
+    responses(
+        (status = 200, description = "Account, balances and transactions", body = AccountLedger),
+        (status = 400, description = "Invalid cycle label"),
+        (status = 404, description = "Account not found")
+    ),
+    tag = "capital"
+)]
+pub async fn get_account_ledger(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<String>,
+    Query(q): Query<AccountLedgerQuery>,
+) -> Result<Json<AccountLedger>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    account_ledger(&db, &account_id, q.label.as_deref(), Utc::now().timestamp())
+        .await
+        .map(Json)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountsQuery {
+    /// Return full account numbers (requires `x-wyat-api-key`).
+    #[serde(default)]
+    pub reveal: bool,
+}
+
+/// GET /capital/accounts - Fetch all accounts from MongoDB
+///
+/// Account numbers are masked to their last four digits unless `reveal=true`
+/// is passed.
+#[utoipa::path(
+    get,
+    path = "/capital/accounts",
+    params(
+        ("reveal" = Option<bool>, Query, description = "Return full account numbers")
+    ),
+    responses(
+        (status = 200, description = "List of all accounts", body = Vec<Account>),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 500, description = "Database error")
+    ),
+    tag = "capital"
+)]
+pub async fn get_all_accounts(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<AccountsQuery>,
+) -> Result<Json<Vec<Account>>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    let accounts: Vec<Account> = db
+        .collection::<Account>("capital_accounts")
+        .find(None, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    tracing::info!(
+        count = accounts.len(),
+        reveal = q.reveal,
+        "fetched accounts"
+    );
+    if q.reveal {
+        Ok(Json(accounts))
+    } else {
+        Ok(Json(accounts.into_iter().map(Account::masked).collect()))
+    }
+}
+
+/// Accounts sharing a `group_id`; `group_id` is null for ungrouped accounts.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountGroup {
+    pub group_id: Option<String>,
+    pub accounts: Vec<Account>,
+}
+
+/// Nest accounts by `group_id`. Groups are ordered by id with the ungrouped
+/// bucket last; accounts by `group_order` (unset last), then name, then id.
+pub fn group_accounts(accounts: Vec<Account>) -> Vec<AccountGroup> {
+    let mut by_group: BTreeMap<String, Vec<Account>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+    for account in accounts {
+        match account.group_id.clone().filter(|g| !g.trim().is_empty()) {
+            Some(group_id) => by_group.entry(group_id).or_default().push(account),
+            None => ungrouped.push(account),
+        }
+    }
+
+    let sort = |accounts: &mut Vec<Account>| {
+        accounts.sort_by(|a, b| {
+            (a.group_order.is_none(), a.group_order, &a.name, &a.id).cmp(&(
+                b.group_order.is_none(),
+                b.group_order,
+                &b.name,
+                &b.id,
+            ))
+        })
+    };
+
+    let mut groups: Vec<AccountGroup> = by_group
+        .into_iter()
+        .map(|(group_id, mut accounts)| {
+            sort(&mut accounts);
+            AccountGroup {
+                group_id: Some(group_id),
+                accounts,
+            }
+        })
+        .collect();
+    if !ungrouped.is_empty() {
+        sort(&mut ungrouped);
+        groups.push(AccountGroup {
+            group_id: None,
+            accounts: ungrouped,
+        });
+    }
+    groups
+}
+
+/// GET /capital/accounts/grouped - Accounts nested under their group
+///
+/// Account numbers are masked unless `reveal=true` is passed.
+#[utoipa::path(
+    get,
+    path = "/capital/accounts/grouped",
+    params(
+        ("reveal" = Option<bool>, Query, description = "Return full account numbers")
+    ),
+    responses(
+        (status = 200, description = "Accounts grouped by group_id", body = Vec<AccountGroup>),
+        (status = 401, description = "Missing or invalid API key")
+    ),
+    tag = "capital"
+)]
+pub async fn get_grouped_accounts(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<AccountsQuery>,
+) -> Result<Json<Vec<AccountGroup>>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    let accounts: Vec<Account> = db
+        .collection::<Account>("capital_accounts")
+        .find(None, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let accounts = if q.reveal {
+        accounts
+    } else {
+        accounts.into_iter().map(Account::masked).collect()
+    };
+    Ok(Json(group_accounts(accounts)))
+}
+
+/// Validate and insert a new account. Returns `Ok(false)` without writing when an
+/// account with the same id already exists.
+pub async fn insert_account(db: &mongodb::Database, account: &Account) -> Result<bool, String> {
+    let collection = db.collection::<Account>("capital_accounts");
+
+    // Validate account ID is not empty
+    if account.id.trim().is_empty() {
+        return Err("Account ID cannot be empty".to_string());
+    }
+
+    // Validate account name is not empty
+    if account.name.trim().is_empty() {
+        return Err("Account name cannot be empty".to_string());
+    }
+
+    // Check if account already exists
+    let existing = collection
+        .find_one(doc! { "id": &account.id }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if existing.is_some() {
+        return Ok(false);
+    }
+
+    // Insert the new account
+    collection
+        .insert_one(account, None)
+        .await
+        .map_err(|e| format!("Failed to create account: {}", e))?;
+
+    Ok(true)
+}
+
+/// POST /capital/accounts - Create a new account
+#[utoipa::path(
+    post,
+    path = "/capital/accounts",
+    request_body = Account,
+    responses(
+        (status = 200, description = "Account created successfully", body = Account)
+    ),
+    tag = "capital"
+)]
+pub async fn create_account(
+    State(state): State<Arc<AppState>>,
+    Json(account): Json<Account>,
+) -> Result<Json<Account>, String> {
+    let db = state.mongo_client.database("wyat");
+
+    if !insert_account(&db, &account).await? {
+        return Err(format!("Account with ID '{}' already exists", account.id));
+    }
+
+    Ok(Json(account.masked()))
+}
+
+// ------------------------- Query Parameters -------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionQuery {
+    pub account_id: Option<String>,
+    pub envelope_id: Option<String>,
+    pub from: Option<i64>, // Unix timestamp
+    pub to: Option<i64>,   // Unix timestamp
+    pub label: Option<String>,
+    pub tx_type: Option<String>,
+    /// Include soft-deleted transactions.
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReclassifyTransactionRequest {
+    pub transaction_id: String,
+    pub leg_index: usize,
+    pub category_id: Option<String>, // envelope ID or category
+}
+
+#[utoipa::path(
+    get,
+    path = "/capital/transactions",
+    params(
+        ("account_id" = Option<String>, Query, description = "Filter by account ID"),
+        ("envelope_id" = Option<String>, Query, description = "Filter by envelope/category ID"),
+        ("label" = Option<String>, Query, description = "Filter by cycle label (e.g., '2025-10')"),
+        ("from" = Option<i64>, Query, description = "Unix timestamp for start of time range"),
+        ("to" = Option<i64>, Query, description = "Unix timestamp for end of time range"),
+        ("tx_type" = Option<String>, Query, description = "Filter by transaction type"),
+        ("include_deleted" = Option<bool>, Query, description = "Include soft-deleted transactions")
+    ),
+    responses(
+        (status = 200, description = "List of transactions", body = Vec<Transaction>)
+    ),
+    tag = "capital"
+)]
+pub async fn get_transactions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TransactionQuery>,
+) -> Result<Json<Vec<Transaction>>, String> {
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    use futures::stream::TryStreamExt;
+    use mongodb::bson::doc;
+
+    // Build MongoDB query filter
+    let mut filter = doc! {};
+
+    // Filter by account_id if provided
+    if let Some(account_id) = &params.account_id {
+        filter.insert("legs.account_id", account_id);
     }
 
     // Filter by envelope_id/category_id if provided
@@ -1829,7 +3541,9 @@ pub async fn get_transactions(
 
     // Determine time range: prefer cycle label, fall back to from/to params
     let (from, to) = if let Some(label) = &params.label {
-        cycle_bounds_for_label(label).ok_or_else(|| format!("Invalid cycle label: {}", label))?
+        let settings = load_capital_settings(&db).await;
+        cycle_bounds_for_label(label, settings.cycle_start_day)
+            .ok_or_else(|| format!("Invalid cycle label: {}", label))?
     } else if params.from.is_some() || params.to.is_some() {
         (
             params.from.unwrap_or(i64::MIN),
@@ -1842,15 +3556,11 @@ pub async fn get_transactions(
 
     // Apply time filter using posted_ts with fallback to ts
     if from != i64::MIN || to != i64::MAX {
-        filter.insert(
-            "$expr",
-            doc! {
-                "$and": [
-                    { "$gte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, from ] },
-                    { "$lte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, to ] }
-                ]
-            },
-        );
+        filter.extend(cycle_time_match(from, to));
+    }
+
+    if !params.include_deleted {
+        filter.extend(live_transactions());
     }
 
     match collection.find(filter, None).await {
@@ -1868,49 +3578,524 @@ pub async fn get_transactions(
     }
 }
 
-/// GET /capital/transactions/:transaction_id - Get a single transaction by ID
+#[derive(Debug, Deserialize)]
+pub struct ExternalRefQuery {
+    pub kind: String,
+    pub value: String,
+}
+
+/// `external_refs` is stored as `[kind, value]` pairs, so match the pair as one element.
+fn external_ref_filter(kind: &str, value: &str) -> BsonDocument {
+    doc! { "external_refs": [kind, value] }
+}
+
+/// GET /capital/transactions/by-ref - Find transactions carrying an external ref pair
 ///
+/// e.g. `?kind=tx_hash&value=0xabc` or `?kind=extraction_run&value=run_123`.
 #[utoipa::path(
     get,
-    path = "/capital/transactions/{transaction_id}",
+    path = "/capital/transactions/by-ref",
     params(
-        ("transaction_id" = String, Path, description = "Transaction ID")
+        ("kind" = String, Query, description = "External ref kind (e.g., 'tx_hash', 'transfer_group')"),
+        ("value" = String, Query, description = "External ref value")
     ),
     responses(
-        (status = 200, description = "Transaction details", body = Transaction),
-        (status = 404, description = "Transaction not found")
+        (status = 200, description = "Transactions with the external ref, oldest first", body = Vec<Transaction>)
     ),
     tag = "capital"
 )]
-pub async fn get_transaction_by_id(
+pub async fn get_transactions_by_ref(
     State(state): State<Arc<AppState>>,
-    axum::extract::Path(transaction_id): axum::extract::Path<String>,
-) -> Result<Json<Transaction>, String> {
+    Query(params): Query<ExternalRefQuery>,
+) -> Result<Json<Vec<Transaction>>, String> {
+    if params.kind.trim().is_empty() || params.value.trim().is_empty() {
+        return Err("Both 'kind' and 'value' are required".to_string());
+    }
+
     let db = state.mongo_client.database("wyat");
     let collection = db.collection::<Transaction>("capital_ledger");
 
-    use mongodb::bson::doc;
+    let options = FindOptions::builder().sort(doc! { "ts": 1 }).build();
+    let cursor = collection
+        .find(
+            external_ref_filter(params.kind.trim(), params.value.trim()),
+            options,
+        )
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let transactions: Vec<Transaction> = cursor
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
 
-    let filter = doc! { "id": &transaction_id };
+    Ok(Json(transactions))
+}
 
-    match collection.find_one(filter, None).await {
-        Ok(Some(transaction)) => Ok(Json(transaction)),
-        Ok(None) => Err(format!("Transaction not found: {}", transaction_id)),
-        Err(e) => {
-            eprintln!("Error fetching transaction {}: {}", transaction_id, e);
-            Err(format!("Database error: {}", e))
-        }
-    }
+// ------------------------- Ledger export -------------------------
+
+/// Export columns, in order; names match the flat batch-import format.
+pub const LEDGER_EXPORT_COLUMNS: [&str; 11] = [
+    "date",
+    "txid",
+    "account_id",
+    "direction",
+    "kind",
+    "ccy_or_asset",
+    "amount_or_qty",
+    "category_id",
+    "payee",
+    "memo",
+    "tx_type",
+];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerExportFormat {
+    #[default]
+    Csv,
+    Json,
 }
 
-/// PUT /capital/transactions/reclassify - Update transaction leg category
-///
-/// Body: ReclassifyTransactionRequest
-/// - transaction_id: ID of the transaction to update
-/// - leg_index: Index of the leg to update (usually 0)
-/// - category_id: New envelope/category ID (or null to clear)
-///
-/// Example:
+#[derive(Debug, Deserialize)]
+pub struct LedgerExportQuery {
+    pub label: String,
+    #[serde(default)]
+    pub format: LedgerExportFormat,
+}
+
+/// One leg of a ledger transaction, flattened for export.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct LedgerExportRow {
+    pub date: String, // YYYY-MM-DD of `ts` in `CAPITAL_TIMEZONE`
+    pub txid: String,
+    pub account_id: String,
+    pub direction: LegDirection,
+    pub kind: String, // "fiat" | "crypto"
+    pub ccy_or_asset: String,
+    #[schema(value_type = String)]
+    pub amount_or_qty: Decimal,
+    pub category_id: Option<String>,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub tx_type: Option<String>,
+}
+
+/// One export row per leg of `tx`.
+fn ledger_export_rows(tx: &Transaction, tz: Tz) -> Vec<LedgerExportRow> {
+    let date = DateTime::from_timestamp(tx.ts, 0)
+        .map(|dt| dt.with_timezone(&tz).format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    tx.legs
+        .iter()
+        .map(|leg| {
+            let (kind, ccy_or_asset, amount_or_qty) = match &leg.amount {
+                LegAmount::Fiat(m) => ("fiat", m.ccy.code().to_string(), m.amount),
+                LegAmount::Crypto { asset, qty } => ("crypto", asset.clone(), *qty),
+            };
+            LedgerExportRow {
+                date: date.clone(),
+                txid: tx.id.clone(),
+                account_id: leg.account_id.clone(),
+                direction: leg.direction,
+                kind: kind.to_string(),
+                ccy_or_asset,
+                amount_or_qty,
+                category_id: leg.category_id.clone(),
+                payee: tx.payee.clone(),
+                memo: tx.memo.clone(),
+                tx_type: tx.tx_type.clone(),
+            }
+        })
+        .collect()
+}
+
+/// The CSV header line for a ledger export.
+fn ledger_csv_header() -> std::io::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(LEDGER_EXPORT_COLUMNS)?;
+    writer.into_inner().map_err(|e| e.into_error())
+}
+
+/// CSV lines (no header) for every leg of `tx`.
+fn ledger_csv_rows(tx: &Transaction, tz: Tz) -> std::io::Result<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    for row in ledger_export_rows(tx, tz) {
+        writer.serialize(row)?;
+    }
+    writer.into_inner().map_err(|e| e.into_error())
+}
+
+/// GET /capital/transactions/export - Dump a cycle's ledger, one row per leg
+///
+/// CSV is written one transaction at a time as the cursor is read, so large
+/// cycles are never held in memory; JSON returns the rows as an array.
+#[utoipa::path(
+    get,
+    path = "/capital/transactions/export",
+    params(
+        ("label" = String, Query, description = "Cycle label (e.g., '2025-10')"),
+        ("format" = Option<String>, Query, description = "'csv' (default) or 'json'")
+    ),
+    responses(
+        (status = 200, description = "Ledger rows for the cycle, oldest first", body = Vec<LedgerExportRow>),
+        (status = 400, description = "Invalid cycle label or format"),
+        (status = 500, description = "Database error")
+    ),
+    tag = "capital"
+)]
+pub async fn export_transactions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LedgerExportQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let db = state.mongo_client.database("wyat");
+    let settings = load_capital_settings(&db).await;
+    let (start_ts, end_ts) = cycle_bounds_for_label(&params.label, settings.cycle_start_day)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid cycle label: {}", params.label),
+            )
+        })?;
+
+    let mut filter = cycle_time_match(start_ts, end_ts);
+    filter.extend(live_transactions());
+    let options = FindOptions::builder()
+        .sort(doc! { "ts": 1, "id": 1 })
+        .build();
+    let cursor = db
+        .collection::<Transaction>("capital_ledger")
+        .find(filter, options)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Database error: {}", e),
+            )
+        })?;
+
+    let tz = capital_timezone();
+    let extension = match params.format {
+        LedgerExportFormat::Csv => "csv",
+        LedgerExportFormat::Json => "json",
+    };
+    let disposition = format!(
+        "attachment; filename=\"ledger-{}.{}\"",
+        params.label, extension
+    );
+
+    match params.format {
+        LedgerExportFormat::Csv => {
+            use futures::StreamExt;
+
+            let header_line = ledger_csv_header().map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("CSV error: {}", e),
+                )
+            })?;
+            let rows = cursor.map(move |tx| {
+                tx.map_err(std::io::Error::other)
+                    .and_then(|tx| ledger_csv_rows(&tx, tz))
+                    .map(Bytes::from)
+            });
+            let body = futures::stream::once(async { Ok(Bytes::from(header_line)) }).chain(rows);
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                axum::body::StreamBody::new(body),
+            )
+                .into_response())
+        }
+        LedgerExportFormat::Json => {
+            let transactions: Vec<Transaction> = cursor.try_collect().await.map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Database error: {}", e),
+                )
+            })?;
+            let rows: Vec<LedgerExportRow> = transactions
+                .iter()
+                .flat_map(|tx| ledger_export_rows(tx, tz))
+                .collect();
+            Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(rows)).into_response())
+        }
+    }
+}
+
+// ------------------------- Reimbursements -------------------------
+
+/// Tag marking a transaction as money someone else owes back.
+pub const REIMBURSABLE_TAG: &str = "reimbursable";
+
+#[derive(Debug, Deserialize)]
+pub struct ReimbursableQuery {
+    pub settled: Option<bool>, // defaults to false (outstanding only)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReimbursableList {
+    pub transactions: Vec<Transaction>,
+    pub total_outstanding: Vec<Money>, // one entry per currency, unsettled only
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SettleReimbursementRequest {
+    pub settled_by_tx: String,
+}
+
+/// Amount owed back on a reimbursable transaction, per currency.
+/// Sums its fiat P&L legs: Debit (spend) adds, Credit (refund) subtracts.
+fn reimbursable_amounts(tx: &Transaction) -> Vec<Money> {
+    let mut totals: Vec<Money> = Vec::new();
+    for leg in tx.legs.iter().filter(|l| l.account_id == PNL_ACCOUNT_ID) {
+        let LegAmount::Fiat(m) = &leg.amount else {
+            continue;
+        };
+        let signed = match leg.direction {
+            LegDirection::Debit => m.amount,
+            LegDirection::Credit => -m.amount,
+        };
+        match totals.iter_mut().find(|t| t.ccy == m.ccy) {
+            Some(total) => total.amount += signed,
+            None => totals.push(Money::new(signed, m.ccy)),
+        }
+    }
+    totals
+}
+
+/// Total still owed across the unsettled transactions in `txs`, per currency.
+fn total_outstanding(txs: &[Transaction]) -> Vec<Money> {
+    let mut totals: Vec<Money> = Vec::new();
+    for tx in txs.iter().filter(|tx| tx.reimbursement.is_none()) {
+        for owed in reimbursable_amounts(tx) {
+            match totals.iter_mut().find(|t| t.ccy == owed.ccy) {
+                Some(total) => total.amount += owed.amount,
+                None => totals.push(owed),
+            }
+        }
+    }
+    totals
+}
+
+/// GET /capital/transactions/reimbursable - Transactions others owe you for
+///
+/// `?settled=false` (default) lists outstanding ones, `?settled=true` settled ones.
+#[utoipa::path(
+    get,
+    path = "/capital/transactions/reimbursable",
+    params(
+        ("settled" = Option<bool>, Query, description = "List settled instead of outstanding (default false)")
+    ),
+    responses(
+        (status = 200, description = "Reimbursable transactions and total outstanding", body = ReimbursableList)
+    ),
+    tag = "capital"
+)]
+pub async fn get_reimbursable_transactions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReimbursableQuery>,
+) -> Result<Json<ReimbursableList>, String> {
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    // Missing and null `reimbursement` both mean outstanding
+    let settled_clause = if params.settled.unwrap_or(false) {
+        doc! { "$ne": null }
+    } else {
+        doc! { "$eq": null }
+    };
+    let mut filter = doc! { "tags": REIMBURSABLE_TAG, "reimbursement": settled_clause };
+    filter.extend(live_transactions());
+    let options = FindOptions::builder().sort(doc! { "ts": 1 }).build();
+    let cursor = collection
+        .find(filter, options)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let transactions: Vec<Transaction> = cursor
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(Json(ReimbursableList {
+        total_outstanding: total_outstanding(&transactions),
+        transactions,
+    }))
+}
+
+/// POST /capital/transactions/{transaction_id}/reimbursable - Tag a transaction as reimbursable
+pub async fn mark_transaction_reimbursable(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(transaction_id): axum::extract::Path<String>,
+) -> Result<Json<serde_json::Value>, String> {
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    let result = collection
+        .update_one(
+            doc! { "id": &transaction_id },
+            doc! { "$addToSet": { "tags": REIMBURSABLE_TAG } },
+            None,
+        )
+        .await
+        .map_err(|e| format!("Database update error: {}", e))?;
+    if result.matched_count == 0 {
+        return Err(format!("Transaction not found: {}", transaction_id));
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "transaction_id": transaction_id,
+        "tag": REIMBURSABLE_TAG
+    })))
+}
+
+/// POST /capital/transactions/{transaction_id}/settle-reimbursement - Mark a reimbursable
+/// transaction as paid back by `settled_by_tx`
+pub async fn settle_reimbursement(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(transaction_id): axum::extract::Path<String>,
+    Json(request): Json<SettleReimbursementRequest>,
+) -> Result<Json<Transaction>, String> {
+    let settled_by_tx = request.settled_by_tx.trim().to_string();
+    if settled_by_tx.is_empty() {
+        return Err("settled_by_tx is required".to_string());
+    }
+    if settled_by_tx == transaction_id {
+        return Err("A transaction cannot settle itself".to_string());
+    }
+
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    let tx = collection
+        .find_one(doc! { "id": &transaction_id }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Transaction not found: {}", transaction_id))?;
+    if !tx.tags.iter().any(|t| t == REIMBURSABLE_TAG) {
+        return Err(format!(
+            "Transaction {} is not tagged '{}'",
+            transaction_id, REIMBURSABLE_TAG
+        ));
+    }
+    if let Some(existing) = &tx.reimbursement {
+        return Err(format!(
+            "Transaction {} was already settled by {}",
+            transaction_id, existing.settled_by_tx
+        ));
+    }
+
+    let settling_exists = collection
+        .find_one(doc! { "id": &settled_by_tx }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .is_some();
+    if !settling_exists {
+        return Err(format!("Settling transaction not found: {}", settled_by_tx));
+    }
+
+    let settlement = ReimbursementSettlement {
+        settled_by_tx,
+        settled_at: chrono::Utc::now().timestamp(),
+    };
+    let settlement_bson =
+        bson::to_bson(&settlement).map_err(|e| format!("Failed to serialize settlement: {}", e))?;
+    let updated = collection
+        .find_one_and_update(
+            doc! { "id": &transaction_id, "reimbursement": null },
+            doc! { "$set": { "reimbursement": settlement_bson } },
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await
+        .map_err(|e| format!("Database update error: {}", e))?
+        .ok_or_else(|| format!("Transaction {} was settled concurrently", transaction_id))?;
+
+    Ok(Json(updated))
+}
+
+/// Match transactions carrying `value` as the value of any external ref pair.
+fn external_ref_value_filter(value: &str) -> BsonDocument {
+    doc! { "external_refs": { "$elemMatch": { "1": value } } }
+}
+
+/// Resolve a transaction by internal `id`, falling back to an external ref value
+/// (e.g. a bank reference or tx hash). Ambiguous ref matches are a conflict, and
+/// soft-deleted transactions are not found either way.
+async fn find_transaction(db: &Database, id_or_ref: &str) -> Result<Transaction, CapitalApiError> {
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    let mut by_id = doc! { "id": id_or_ref };
+    by_id.extend(live_transactions());
+    if let Some(transaction) = collection.find_one(by_id, None).await? {
+        return Ok(transaction);
+    }
+
+    let mut filter = external_ref_value_filter(id_or_ref);
+    filter.extend(live_transactions());
+    let options = FindOptions::builder().limit(2).build();
+    let mut matches: Vec<Transaction> = collection
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+    match matches.len() {
+        0 => Err(CapitalApiError::NotFound(format!(
+            "Transaction not found: {}",
+            id_or_ref
+        ))),
+        1 => Ok(matches.remove(0)),
+        _ => Err(CapitalApiError::Conflict(format!(
+            "External ref '{}' matches more than one transaction",
+            id_or_ref
+        ))),
+    }
+}
+
+/// GET /capital/transactions/:transaction_id - Get a single transaction by ID
+///
+/// Falls back to matching an `external_refs` value when no transaction has this ID.
+#[utoipa::path(
+    get,
+    path = "/capital/transactions/{transaction_id}",
+    params(
+        ("transaction_id" = String, Path, description = "Transaction ID or external ref value")
+    ),
+    responses(
+        (status = 200, description = "Transaction details", body = Transaction),
+        (status = 404, description = "Transaction not found"),
+        (status = 409, description = "External ref matches several transactions")
+    ),
+    tag = "capital"
+)]
+pub async fn get_transaction_by_id(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(transaction_id): axum::extract::Path<String>,
+) -> Result<Json<Transaction>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    find_transaction(&db, &transaction_id)
+        .await
+        .map(Json)
+        .inspect_err(|e| {
+            if matches!(e, CapitalApiError::Internal(_)) {
+                tracing::error!(%transaction_id, error = %e, "fetching transaction failed");
+            }
+        })
+}
+
+/// PUT /capital/transactions/reclassify - Update transaction leg category
+///
+/// Body: ReclassifyTransactionRequest
+/// - transaction_id: ID of the transaction to update
+/// - leg_index: Index of the leg to update (usually 0)
+/// - category_id: New envelope/category ID (or null to clear)
+///
+/// Example:
 /// PUT /capital/transactions/reclassify
 /// {
 ///   "transaction_id": "123e4567-e89b-12d3-a456-426614174000",
@@ -1920,60 +4105,205 @@ pub async fn get_transaction_by_id(
 pub async fn reclassify_transaction(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ReclassifyTransactionRequest>,
-) -> Result<Json<serde_json::Value>, String> {
+) -> Result<Json<serde_json::Value>, CapitalApiError> {
     let db = state.mongo_client.database("wyat");
     let collection = db.collection::<Transaction>("capital_ledger");
 
-    use mongodb::bson::doc;
-
     // First, find the transaction
     let filter = doc! { "id": &request.transaction_id };
 
-    match collection.find_one(Some(filter.clone()), None).await {
-        Ok(Some(mut transaction)) => {
-            // Validate leg_index
-            if request.leg_index >= transaction.legs.len() {
-                return Err(format!(
-                    "Invalid leg_index: {} (transaction has {} legs)",
-                    request.leg_index,
-                    transaction.legs.len()
-                ));
-            }
+    let mut transaction = collection
+        .find_one(Some(filter.clone()), None)
+        .await?
+        .ok_or_else(|| {
+            CapitalApiError::NotFound(format!("Transaction not found: {}", request.transaction_id))
+        })?;
 
-            // Clone category_id before moving it
-            let new_category_id = request.category_id.clone();
+    // Validate leg_index
+    if request.leg_index >= transaction.legs.len() {
+        return Err(CapitalApiError::Validation(format!(
+            "Invalid leg_index: {} (transaction has {} legs)",
+            request.leg_index,
+            transaction.legs.len()
+        )));
+    }
 
-            // Update the leg's category_id
-            transaction.legs[request.leg_index].category_id = request.category_id;
+    // Clone category_id before moving it
+    let new_category_id = request.category_id.clone();
 
-            // Update the transaction in the database
-            let update = doc! {
-                "$set": {
-                    "legs": bson::to_bson(&transaction.legs)
-                        .map_err(|e| format!("Failed to serialize legs: {}", e))?
-                }
-            };
+    // Update the leg's category_id
+    transaction.legs[request.leg_index].category_id = request.category_id;
 
-            match collection.update_one(filter, update, None).await {
-                Ok(result) => {
-                    if result.modified_count == 1 {
-                        Ok(Json(serde_json::json!({
-                            "success": true,
-                            "message": "Transaction reclassified successfully",
-                            "transaction_id": request.transaction_id,
-                            "leg_index": request.leg_index,
-                            "category_id": new_category_id
-                        })))
-                    } else {
-                        Err("Transaction not found or not modified".to_string())
-                    }
-                }
-                Err(e) => Err(format!("Database update error: {}", e)),
-            }
+    // Update the transaction in the database
+    let legs = bson::to_bson(&transaction.legs)
+        .map_err(|e| CapitalApiError::Internal(format!("Failed to serialize legs: {}", e)))?;
+    let update = doc! { "$set": { "legs": legs } };
+
+    let result = collection.update_one(filter, update, None).await?;
+    if result.modified_count == 1 {
+        Ok(Json(serde_json::json!({
+            "success": true,
+            "message": "Transaction reclassified successfully",
+            "transaction_id": request.transaction_id,
+            "leg_index": request.leg_index,
+            "category_id": new_category_id
+        })))
+    } else {
+        Err(CapitalApiError::NotFound(
+            "Transaction not found or not modified".to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReclassifyRequest {
+    /// Regex matched against `payee` (Rust `regex` syntax).
+    pub payee_regex: String,
+    #[serde(default)]
+    pub account_id: Option<String>,
+    pub category_id: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkReclassifyResponse {
+    pub count: usize,
+    pub transaction_ids: Vec<String>,
+    /// Payee matches left alone because they are transfers.
+    pub skipped_transfers: usize,
+    pub dry_run: bool,
+}
+
+/// Split payee matches into ids to reclassify and the number of transfers skipped.
+pub fn select_bulk_reclassify(
+    transactions: &[Transaction],
+    payee: &regex::Regex,
+) -> (Vec<String>, usize) {
+    let mut ids = Vec::new();
+    let mut skipped_transfers = 0;
+    for tx in transactions {
+        if tx.legs.is_empty() || !tx.payee.as_deref().is_some_and(|p| payee.is_match(p)) {
+            continue;
+        }
+        if tx.is_transfer_type() {
+            skipped_transfers += 1;
+        } else {
+            ids.push(tx.id.clone());
+        }
+    }
+    (ids, skipped_transfers)
+}
+
+/// POST /capital/transactions/reclassify-bulk - Set leg 0's category by payee pattern
+///
+/// Body: { "payee_regex": "(?i)starbucks", "account_id"?: "...", "category_id": "env_dining", "dry_run": true }
+///
+/// Transfers (`tx_type` transfer/transfer_fx or transfer refs) are never touched.
+/// `dry_run` returns the ids that would change without writing.
+pub async fn reclassify_transactions_bulk(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BulkReclassifyRequest>,
+) -> Result<Json<BulkReclassifyResponse>, CapitalApiError> {
+    let payee = regex::Regex::new(&request.payee_regex)
+        .map_err(|e| CapitalApiError::Validation(format!("Invalid payee_regex: {}", e)))?;
+
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    let mut filter = doc! { "payee": { "$type": "string" } };
+    filter.extend(live_transactions());
+    if let Some(account_id) = &request.account_id {
+        filter.insert("legs.account_id", account_id);
+    }
+    let candidates: Vec<Transaction> = collection.find(filter, None).await?.try_collect().await?;
+    let (ids, skipped_transfers) = select_bulk_reclassify(&candidates, &payee);
+
+    if !request.dry_run && !ids.is_empty() {
+        collection
+            .update_many(
+                doc! { "id": { "$in": &ids } },
+                doc! { "$set": { "legs.0.category_id": &request.category_id } },
+                None,
+            )
+            .await?;
+    }
+
+    Ok(Json(BulkReclassifyResponse {
+        count: ids.len(),
+        transaction_ids: ids,
+        skipped_transfers,
+        dry_run: request.dry_run,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RecomputeBalanceStateQuery {
+    pub account_id: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RecomputeBalanceStateResponse {
+    pub scanned: usize,
+    pub changed: usize,
+}
+
+/// Re-derive `balance_state` for every transaction matching `filter`, writing back
+/// only those whose stored state was stale.
+async fn recompute_stored_balance_states(
+    db: &Database,
+    filter: BsonDocument,
+) -> Result<RecomputeBalanceStateResponse, CapitalApiError> {
+    let collection = db.collection::<Transaction>("capital_ledger");
+    let mut cursor = collection.find(filter, None).await?;
+
+    let mut scanned = 0;
+    let mut changed = 0;
+    while let Some(mut tx) = cursor.try_next().await? {
+        scanned += 1;
+        let stored = tx.balance_state;
+        let state = tx.recompute_balance_state();
+        if state == stored {
+            continue;
         }
-        Ok(None) => Err(format!("Transaction not found: {}", request.transaction_id)),
-        Err(e) => Err(format!("Database query error: {}", e)),
+        let state = bson::to_bson(&state).map_err(|e| CapitalApiError::Internal(e.to_string()))?;
+        collection
+            .update_one(
+                doc! { "id": &tx.id },
+                doc! { "$set": { "balance_state": state } },
+                None,
+            )
+            .await?;
+        changed += 1;
+    }
+
+    Ok(RecomputeBalanceStateResponse { scanned, changed })
+}
+
+/// POST /capital/transactions/recompute-balance-state?account_id=&label= - Fix stale balance states
+///
+/// Useful after editing legs directly in Mongo. Soft-deleted rows are skipped.
+pub async fn recompute_balance_states(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<RecomputeBalanceStateQuery>,
+) -> Result<Json<RecomputeBalanceStateResponse>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+
+    let mut filter = live_transactions();
+    if let Some(account_id) = &q.account_id {
+        filter.insert("legs.account_id", account_id);
     }
+    if let Some(label) = &q.label {
+        let settings = load_capital_settings(&db).await;
+        let (start_ts, end_ts) = cycle_bounds_for_label(label, settings.cycle_start_day)
+            .ok_or_else(|| {
+                CapitalApiError::Validation(format!("Invalid cycle label: {}", label))
+            })?;
+        filter.extend(cycle_time_match(start_ts, end_ts));
+    }
+
+    recompute_stored_balance_states(&db, filter).await.map(Json)
 }
 
 /// PATCH /capital/transactions/{transaction_id}/type - Update transaction type
@@ -2111,18 +4441,33 @@ pub async fn update_transaction_legs(
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct BalanceTransactionRequest {
+    /// Currency to check the zero sum in; defaults to USD.
+    #[serde(default)]
+    pub report_ccy: Option<Currency>,
+    /// Fallback rates to `report_ccy` for legs without an fx snapshot.
+    #[serde(default)]
+    pub rates: HashMap<Currency, Decimal>,
+}
+
 /// POST /capital/transactions/{transaction_id}/balance - Balance and reconcile a transaction
 ///
 /// Attempts to balance a transaction by:
-/// 1. Checking that credit and debit legs sum to zero (in USD)
+/// 1. Checking that credit and debit legs sum to zero (in USD, or `report_ccy`)
 /// 2. Verifying that __pnl__ leg (if present) has a category_id
 /// 3. If checks pass, sets balance_state to "Balanced" and reconciled to true
 ///
+/// Optional body: { "report_ccy": "BTC", "rates": { "USD": "0.0000166" } } values legs
+/// lacking an fx snapshot with the given rates to `report_ccy`.
+///
 /// Returns success or error message with details
 pub async fn balance_transaction(
     State(state): State<Arc<AppState>>,
     axum::extract::Path(transaction_id): axum::extract::Path<String>,
+    body: Option<Json<BalanceTransactionRequest>>,
 ) -> Result<Json<serde_json::Value>, String> {
+    let request = body.map(|Json(req)| req).unwrap_or_default();
     let db = state.mongo_client.database("wyat");
     let collection = db.collection::<Transaction>("capital_ledger");
 
@@ -2145,17 +4490,14 @@ pub async fn balance_transaction(
         })));
     }
 
-    // Check 1: Verify legs sum to zero in USD
-    let (is_balanced, net) = transaction.is_balanced_in(Currency::USD);
+    // Check 1: Verify legs sum to zero in the report currency
+    let (is_balanced, net) = transaction
+        .is_balanced_with_rates(request.report_ccy.unwrap_or(Currency::USD), &request.rates);
     if !is_balanced {
         return Err(format!(
             "Transaction does not balance: net amount is {} {}",
             net.amount,
-            match net.ccy {
-                Currency::USD => "USD",
-                Currency::HKD => "HKD",
-                Currency::BTC => "BTC",
-            }
+            net.ccy.code()
         ));
     }
 
@@ -2195,7 +4537,8 @@ pub async fn balance_transaction(
 
 /// DELETE /capital/transactions/{transaction_id} - Delete a transaction
 ///
-/// Deletes a transaction from the database by its ID.
+/// Soft-deletes a transaction by its ID: it is flagged `deleted` and hidden from
+/// listings and sums, and can be undone with `POST .../restore`.
 ///
 /// Example:
 /// DELETE /capital/transactions/123e4567-e89b-12d3-a456-426614174000
@@ -2208,12 +4551,17 @@ pub async fn delete_transaction(
 
     use mongodb::bson::doc;
 
-    // Delete the transaction by ID
-    let filter = doc! { "id": &transaction_id };
+    // Soft-delete: keep the row so it can be restored
+    let mut filter = doc! { "id": &transaction_id };
+    filter.extend(live_transactions());
+    let update = doc! { "$set": {
+        "deleted": true,
+        "deleted_at": chrono::Utc::now().timestamp(),
+    }};
 
-    match collection.delete_one(filter, None).await {
+    match collection.update_one(filter, update, None).await {
         Ok(result) => {
-            if result.deleted_count == 1 {
+            if result.matched_count == 1 {
                 Ok(Json(serde_json::json!({
                     "success": true,
                     "message": "Transaction deleted successfully",
@@ -2227,6 +4575,42 @@ pub async fn delete_transaction(
     }
 }
 
+/// POST /capital/transactions/:id/restore - Undo a soft delete
+#[utoipa::path(
+    post,
+    path = "/capital/transactions/{transaction_id}/restore",
+    params(("transaction_id" = String, Path, description = "Transaction ID")),
+    responses(
+        (status = 200, description = "Restored transaction", body = Transaction),
+        (status = 404, description = "No deleted transaction with this ID")
+    ),
+    tag = "capital"
+)]
+pub async fn restore_transaction(
+    State(state): State<Arc<AppState>>,
+    Path(transaction_id): Path<String>,
+) -> Result<Json<Transaction>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<Transaction>("capital_ledger");
+
+    collection
+        .find_one_and_update(
+            doc! { "id": &transaction_id, "deleted": true },
+            doc! {
+                "$set": { "deleted": false },
+                "$unset": { "deleted_at": "" },
+            },
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await?
+        .map(Json)
+        .ok_or_else(|| {
+            CapitalApiError::NotFound(format!("No deleted transaction found: {}", transaction_id))
+        })
+}
+
 // POST /capital/transactions
 fn default_reconciled() -> bool {
     false
@@ -2234,8 +4618,15 @@ fn default_reconciled() -> bool {
 
 #[derive(Debug, serde::Deserialize)]
 pub struct NewTransaction {
+    /// Optional client-supplied id; a UUIDv4 is generated when omitted or blank.
+    #[serde(default)]
     pub id: String,
-    pub ts: i64,
+    /// Unix timestamp. Either `ts` or `date` is required.
+    #[serde(default)]
+    pub ts: Option<i64>,
+    /// Calendar date (YYYY-MM-DD), stored as start of day in `CAPITAL_TIMEZONE`.
+    #[serde(default)]
+    pub date: Option<String>,
     #[serde(default)]
     pub posted_ts: Option<i64>,
     pub source: String,
@@ -2256,6 +4647,35 @@ pub struct NewTransaction {
 }
 
 impl NewTransaction {
+    /// Fill in a server-generated UUIDv4 when the client did not supply an id.
+    pub fn ensure_id(&mut self) {
+        if self.id.trim().is_empty() {
+            self.id = Uuid::new_v4().to_string();
+        }
+    }
+
+    /// Resolve the transaction timestamp from `ts` or `date`.
+    /// When both are given, `ts` must fall on `date` in `tz`.
+    pub fn resolve_ts(&self, tz: Tz) -> Result<i64, String> {
+        match (self.ts, self.date.as_deref()) {
+            (Some(ts), None) => Ok(ts),
+            (None, Some(date)) => start_of_day_ts(date, tz),
+            (Some(ts), Some(date)) => {
+                let day_start = start_of_day_ts(date, tz)?;
+                let next_day_start = start_of_day_ts(&next_day(date)?, tz)?;
+                if (day_start..next_day_start).contains(&ts) {
+                    Ok(ts)
+                } else {
+                    Err(format!(
+                        "ts {} does not fall on date '{}' ({}); provide only one of ts or date",
+                        ts, date, tz
+                    ))
+                }
+            }
+            (None, None) => Err("either ts or date is required".to_string()),
+        }
+    }
+
     pub fn into_transaction(self) -> Result<Transaction, String> {
         if self.id.trim().is_empty() {
             return Err("transaction id cannot be empty".to_string());
@@ -2266,10 +4686,11 @@ impl NewTransaction {
                 self.id
             ));
         }
+        let ts = self.resolve_ts(capital_timezone())?;
 
         let mut tx = Transaction {
             id: self.id,
-            ts: self.ts,
+            ts,
             posted_ts: self.posted_ts,
             source: self.source,
             payee: self.payee,
@@ -2280,12 +4701,52 @@ impl NewTransaction {
             legs: self.legs,
             tx_type: self.tx_type,
             balance_state: BalanceState::Unknown,
+            tags: Vec::new(),
+            reimbursement: None,
+            deleted: false,
+            deleted_at: None,
         };
+        if let Some(err) = tx.refund_direction_error() {
+            return Err(err);
+        }
         tx.normalize();
         Ok(tx)
     }
 }
 
+/// Timezone used to turn date-only transactions into timestamps.
+/// Reads `CAPITAL_TIMEZONE` (IANA name), defaulting to UTC.
+pub fn capital_timezone() -> Tz {
+    std::env::var("CAPITAL_TIMEZONE")
+        .ok()
+        .and_then(|v| v.trim().parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Unix timestamp of local midnight for a YYYY-MM-DD date in `tz`.
+/// On DST transitions without a midnight, the earliest valid time is used.
+pub fn start_of_day_ts(date: &str, tz: Tz) -> Result<i64, String> {
+    let day = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}', expected YYYY-MM-DD", date))?;
+    let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(midnight + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map(|dt| dt.timestamp())
+        .ok_or_else(|| format!("date '{}' has no valid start of day in {}", date, tz))
+}
+
+fn next_day(date: &str) -> Result<String, String> {
+    chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.succ_opt())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| format!("invalid date '{}', expected YYYY-MM-DD", date))
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct CreateTransactionResp {
     pub success: bool,
@@ -2296,10 +4757,10 @@ pub struct CreateTransactionResp {
 
 pub async fn create_transaction(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<NewTransaction>,
-) -> Result<Json<CreateTransactionResp>, String> {
-    println!("=== create_transaction START ===");
-    println!("Request: {:?}", req);
+    Json(mut req): Json<NewTransaction>,
+) -> Result<Json<CreateTransactionResp>, CapitalApiError> {
+    req.ensure_id();
+    tracing::debug!(request = ?req, "creating transaction");
 
     let db = state.mongo_client.database("wyat");
     let collection = db.collection::<Transaction>("capital_ledger");
@@ -2307,23 +4768,24 @@ pub async fn create_transaction(
     let tx_id = req.id.clone();
 
     // Check if transaction already exists
-    let existing = collection
-        .find_one(doc! { "id": &tx_id }, None)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = collection.find_one(doc! { "id": &tx_id }, None).await?;
     if existing.is_some() {
-        return Err(format!("Transaction with ID '{}' already exists", tx_id));
+        return Err(CapitalApiError::Conflict(format!(
+            "Transaction with ID '{}' already exists",
+            tx_id
+        )));
     }
 
     // Build the normalized transaction
-    let transaction = req
-        .into_transaction()
-        .map_err(|err| format!("Validation error for '{}': {}", tx_id, err))?;
+    let transaction = req.into_transaction().map_err(|err| {
+        CapitalApiError::Validation(format!("Validation error for '{}': {}", tx_id, err))
+    })?;
 
     if transaction.balance_state != BalanceState::Balanced {
-        println!(
-            "Warning: Transaction '{}' stored with balance_state={:?}",
-            transaction.id, transaction.balance_state
+        tracing::warn!(
+            txid = %transaction.id,
+            balance_state = ?transaction.balance_state,
+            "transaction stored unbalanced"
         );
     }
 
@@ -2333,7 +4795,7 @@ pub async fn create_transaction(
     // Insert into database
     match collection.insert_one(&transaction, None).await {
         Ok(_) => {
-            println!("Transaction created successfully: {}", stored_id);
+            tracing::info!(txid = %stored_id, "transaction created");
             Ok(Json(CreateTransactionResp {
                 success: true,
                 transaction_id: stored_id,
@@ -2342,10 +4804,240 @@ pub async fn create_transaction(
             }))
         }
         Err(e) => {
-            println!("Failed to create transaction: {}", e);
-            Err(format!("Database error: {}", e))
+            tracing::error!(txid = %stored_id, error = %e, "creating transaction failed");
+            Err(e.into())
+        }
+    }
+}
+
+// ------------------------- Statement Reconciliation -------------------------
+
+/// Default allowed gap between computed and stated balances: one smallest unit of
+/// `ccy`, so 0.01 for USD but 0.00000001 for BTC.
+pub fn statement_tolerance(ccy: Currency) -> Decimal {
+    Decimal::new(1, ccy.decimal_places())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReconcileStatementRequest {
+    pub statement: Statement,
+    /// Allowed absolute difference; defaults to `statement_tolerance` of the
+    /// statement's currency.
+    #[serde(default)]
+    pub tolerance: Option<Decimal>,
+}
+
+/// Ledger balances for a statement period compared against the statement.
+/// Amounts use the ledger sign convention (debits positive), as
+/// `/capital/accounts/:id/balance` reports them. Deltas are computed minus stated.
+#[derive(Debug, Serialize)]
+pub struct StatementReconciliation {
+    pub statement_id: String,
+    pub account_id: String,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub computed_opening: Money,
+    pub computed_closing: Money,
+    pub opening_delta: Money,
+    pub closing_delta: Money,
+    pub opening_matches: bool,
+    pub matches: bool,
+    pub tolerance: Decimal,
+    pub unreconciled_transactions: Vec<Transaction>,
+}
+
+/// Compare computed opening/closing balances against a statement.
+pub fn compare_statement(
+    statement: &Statement,
+    computed_opening: Decimal,
+    computed_closing: Decimal,
+    tolerance: Decimal,
+) -> StatementReconciliation {
+    let ccy = statement.closing_balance.ccy;
+    let opening_delta = computed_opening - statement.opening_balance.amount;
+    let closing_delta = computed_closing - statement.closing_balance.amount;
+
+    StatementReconciliation {
+        statement_id: statement.id.clone(),
+        account_id: statement.account_id.clone(),
+        period_start: statement.period_start,
+        period_end: statement.period_end,
+        computed_opening: Money::new(computed_opening, ccy),
+        computed_closing: Money::new(computed_closing, ccy),
+        opening_delta: Money::new(opening_delta, ccy),
+        closing_delta: Money::new(closing_delta, ccy),
+        opening_matches: opening_delta.abs() <= tolerance,
+        matches: closing_delta.abs() <= tolerance,
+        tolerance,
+        unreconciled_transactions: Vec::new(),
+    }
+}
+
+/// POST /capital/statements/reconcile - Check a statement against the ledger
+///
+/// Computes the account's opening (as of `period_start - 1`) and closing (as of
+/// `period_end`) balances and reports whether the closing balance matches the
+/// statement within `tolerance`. Also lists transactions in the period that are
+/// still `reconciled = false`.
+pub async fn reconcile_statement(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ReconcileStatementRequest>,
+) -> Result<Json<StatementReconciliation>, CapitalApiError> {
+    let statement = request.statement;
+    let tolerance = request
+        .tolerance
+        .unwrap_or_else(|| statement_tolerance(statement.closing_balance.ccy));
+    if tolerance.is_sign_negative() {
+        return Err(CapitalApiError::Validation(
+            "tolerance must not be negative".to_string(),
+        ));
+    }
+    if statement.period_start > statement.period_end {
+        return Err(CapitalApiError::Validation(format!(
+            "period_start ({}) must not be after period_end ({})",
+            statement.period_start, statement.period_end
+        )));
+    }
+
+    let db = state.mongo_client.database("wyat");
+    let account = db
+        .collection::<Account>("capital_accounts")
+        .find_one(doc! { "id": &statement.account_id }, None)
+        .await?
+        .ok_or_else(|| {
+            CapitalApiError::NotFound(format!("Account not found: {}", statement.account_id))
+        })?;
+
+    for balance in [&statement.opening_balance, &statement.closing_balance] {
+        if balance.ccy != account.currency {
+            return Err(CapitalApiError::Validation(format!(
+                "statement currency {} does not match account currency {}",
+                balance.ccy.code(),
+                account.currency.code()
+            )));
         }
     }
+
+    let ccy_str = account.currency.code();
+    let opening = sum_as_of(&db, &account.id, ccy_str, statement.period_start - 1)
+        .await
+        .map_err(CapitalApiError::Internal)?;
+    let closing = sum_as_of(&db, &account.id, ccy_str, statement.period_end)
+        .await
+        .map_err(CapitalApiError::Internal)?;
+
+    let mut filter = doc! {
+        "legs.account_id": &account.id,
+        "reconciled": false,
+    };
+    filter.extend(cycle_time_match(
+        statement.period_start,
+        statement.period_end,
+    ));
+    filter.extend(live_transactions());
+    let options = FindOptions::builder().sort(doc! { "ts": 1 }).build();
+    let unreconciled: Vec<Transaction> = db
+        .collection::<Transaction>("capital_ledger")
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut reconciliation = compare_statement(&statement, opening, closing, tolerance);
+    reconciliation.unreconciled_transactions = unreconciled;
+    Ok(Json(reconciliation))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeneratedStatementQuery {
+    /// Cycle label "YYYY-MM".
+    pub label: String,
+    /// Also upsert the statement header into `capital_statements`.
+    #[serde(default)]
+    pub save: bool,
+}
+
+/// Statement synthesized from the ledger, with the transactions it covers.
+#[derive(Debug, Serialize)]
+pub struct GeneratedStatement {
+    #[serde(flatten)]
+    pub statement: Statement,
+    /// Every asset's quantities, for wallet and exchange accounts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets: Option<AssetBalances>,
+    pub transactions: Vec<Transaction>,
+}
+
+/// Build a ledger-derived statement header for one account and cycle.
+pub fn ledger_statement(
+    account: &Account,
+    label: &str,
+    period_start: i64,
+    period_end: i64,
+    opening: Decimal,
+    closing: Decimal,
+) -> Statement {
+    Statement {
+        id: format!("stmt.{}.{}", account.id, label),
+        account_id: account.id.clone(),
+        period_start,
+        period_end,
+        opening_balance: Money::new(opening, account.currency),
+        closing_balance: Money::new(closing, account.currency),
+        raw_ref: Some("generated:ledger".to_string()),
+    }
+}
+
+/// GET /capital/accounts/:account_id/statement?label=YYYY-MM - Generate a statement
+///
+/// Opening balance is as of cycle start - 1, closing as of cycle end, using the
+/// ledger sign convention. Lists the account's transactions in the cycle.
+/// `?save=true` upserts the header into `capital_statements` (keyed by id).
+pub async fn get_account_statement(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<String>,
+    Query(q): Query<GeneratedStatementQuery>,
+) -> Result<Json<GeneratedStatement>, CapitalApiError> {
+    let db = state.mongo_client.database("wyat");
+    generated_statement(&db, &account_id, &q).await.map(Json)
+}
+
+/// Statement for one account and cycle; saved when `q.save` is set.
+pub async fn generated_statement(
+    db: &mongodb::Database,
+    account_id: &str,
+    q: &GeneratedStatementQuery,
+) -> Result<GeneratedStatement, CapitalApiError> {
+    let window = account_window(db, account_id, Some(&q.label), Utc::now().timestamp()).await?;
+    let start_ts = window
+        .start_ts
+        .expect("a labelled window always has a start");
+    let statement = ledger_statement(
+        &window.account,
+        &q.label,
+        start_ts,
+        window.end_ts,
+        window.opening,
+        window.closing,
+    );
+
+    if q.save {
+        db.collection::<Statement>("capital_statements")
+            .replace_one(
+                doc! { "id": &statement.id },
+                &statement,
+                mongodb::options::ReplaceOptions::builder()
+                    .upsert(true)
+                    .build(),
+            )
+            .await?;
+    }
+
+    Ok(GeneratedStatement {
+        statement,
+        assets: window.assets,
+        transactions: window.transactions,
+    })
 }
 
 // ------------------------- Batch Import -------------------------
@@ -2418,185 +5110,803 @@ impl FlatTransaction {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct BatchImportRequest {
     pub transactions: Vec<FlatTransaction>,
+    /// Skip (and report) rows that don't normalize to `Balanced` instead of storing them.
+    #[serde(default)]
+    pub require_balanced: bool,
+    /// Amounts carry the direction in their sign (e.g. debits exported as negatives):
+    /// a negative `amount_or_qty` flips `direction` and is stored as its absolute value.
+    #[serde(default)]
+    pub signed_amounts: bool,
+    /// Parse and normalize every row but write nothing; results land in `preview`.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One leg of a multi-leg import row; fields mirror the leg half of `FlatTransaction`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FlatLeg {
+    pub account_id: String,
+    pub direction: String,
+    pub kind: String,
+    pub ccy_or_asset: String,
+    pub amount_or_qty: f64,
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub price_ccy: Option<String>,
+    #[serde(default)]
+    pub category_id: Option<String>,
+}
+
+/// Import row carrying every leg of one transaction (e.g. both sides of a trade).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MultiLegTransaction {
+    pub txid: String,
+    pub date: String,
+    #[serde(default)]
+    pub posted_ts: Option<i64>,
+    #[serde(default = "default_source")]
+    pub source: String,
+    #[serde(default)]
+    pub payee: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default = "default_status")]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub tx_type: Option<String>,
+    #[serde(default)]
+    pub ext1_kind: Option<String>,
+    #[serde(default)]
+    pub ext1_val: Option<String>,
+    pub legs: Vec<FlatLeg>,
+}
+
+/// Options for the multi-leg import. Unlike v1 there is no `signed_amounts`; each
+/// leg's `direction` is taken as given.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BatchImportRequestV2 {
+    pub transactions: Vec<MultiLegTransaction>,
+    /// Skip (and report) transactions that don't normalize to `Balanced`.
+    #[serde(default)]
+    pub require_balanced: bool,
+    /// Parse and normalize every transaction but write nothing; results land in `preview`.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct BatchImportResponse {
     pub imported: usize,
     pub skipped: usize,
     pub errors: Vec<String>,
+    /// txids inserted by this import, in input order.
+    pub imported_ids: Vec<String>,
+    /// txids skipped as duplicates or errors, in input order.
+    pub skipped_ids: Vec<String>,
+    /// Rows whose amount sign disagreed with `direction` (only with `signed_amounts`).
+    pub sign_conflicts: Vec<String>,
+    /// Normalized transactions that would be inserted (only with `dry_run`).
+    pub preview: Vec<Transaction>,
 }
 
-pub async fn process_batch_import(
-    db: &Database,
-    transactions: Vec<FlatTransaction>,
-) -> Result<BatchImportResponse, String> {
-    use mongodb::bson::doc;
-    use rust_decimal::Decimal;
-    use rust_decimal::prelude::FromPrimitive;
-
-    let collection = db.collection::<Transaction>("capital_ledger");
-
-    let mut imported = 0usize;
-    let mut skipped = 0usize;
-    let mut errors: Vec<String> = Vec::new();
+impl BatchImportResponse {
+    fn record_imported(&mut self, txid: &str) {
+        self.imported += 1;
+        self.imported_ids.push(txid.to_string());
+    }
 
-    for itx in transactions {
-        let txid = itx.txid.clone();
-        // Skip if already exists
-        if let Ok(Some(_existing)) = collection.find_one(doc! { "id": &txid }, None).await {
-            skipped += 1;
-            continue;
+    fn record_skipped(&mut self, txid: &str, error: Option<String>) {
+        self.skipped += 1;
+        self.skipped_ids.push(txid.to_string());
+        if let Some(error) = error {
+            self.errors.push(format!("{}: {}", txid, error));
         }
+    }
 
-        // Parse date (YYYY-MM-DD) -> unix ts (00:00:00 UTC)
-        let ts = match chrono::NaiveDate::parse_from_str(&itx.date, "%Y-%m-%d") {
-            Ok(d) => chrono::DateTime::<chrono::Utc>::from_utc(
-                chrono::NaiveDateTime::new(d, chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-                chrono::Utc,
-            )
-            .timestamp(),
-            Err(e) => {
-                errors.push(format!("{}: invalid date '{}': {}", txid, itx.date, e));
-                skipped += 1;
-                continue;
-            }
-        };
+    fn log_outcome(&self) {
+        tracing::info!(
+            imported = self.imported,
+            skipped = self.skipped,
+            errors = self.errors.len(),
+            sign_conflicts = self.sign_conflicts.len(),
+            "batch import finished"
+        );
+    }
+}
 
-        // Build leg amount
-        let amount_dec = match Decimal::from_f64(itx.amount_or_qty) {
-            Some(v) => v,
-            None => {
-                errors.push(format!("{}: invalid amount {}", txid, itx.amount_or_qty));
-                skipped += 1;
-                continue;
-            }
-        };
+/// Convert one flat import row into a normalized single-leg transaction.
+/// The date (YYYY-MM-DD) becomes 00:00:00 UTC.
+fn flat_to_transaction(itx: &FlatTransaction) -> Result<Transaction, String> {
+    let leg = FlatLeg {
+        account_id: itx.account_id.clone(),
+        direction: itx.direction.clone(),
+        kind: itx.kind.clone(),
+        ccy_or_asset: itx.ccy_or_asset.clone(),
+        amount_or_qty: itx.amount_or_qty,
+        price: itx.price,
+        price_ccy: itx.price_ccy.clone(),
+        category_id: itx.category_id.clone(),
+    };
+    let header = ImportHeader {
+        txid: &itx.txid,
+        date: &itx.date,
+        posted_ts: itx.posted_ts,
+        source: &itx.source,
+        payee: &itx.payee,
+        memo: &itx.memo,
+        status: &itx.status,
+        tx_type: &itx.tx_type,
+        ext1: (&itx.ext1_kind, &itx.ext1_val),
+    };
+    header.into_transaction(vec![flat_leg_to_leg(&leg)?])
+}
 
-        let leg_amount = if itx.kind.eq_ignore_ascii_case("fiat") {
-            let ccy = match itx.ccy_or_asset.as_str() {
-                "USD" => Currency::USD,
-                "HKD" => Currency::HKD,
-                "BTC" => Currency::BTC,
-                other => {
-                    errors.push(format!("{}: unsupported fiat ccy '{}'", txid, other));
-                    skipped += 1;
-                    continue;
-                }
-            };
-            LegAmount::Fiat(Money::new(amount_dec, ccy))
-        } else {
-            // Minimal crypto support: qty = amount_or_qty, price ignored for now
-            LegAmount::Crypto {
-                asset: itx.ccy_or_asset.clone(),
-                qty: amount_dec,
-            }
-        };
+/// Convert a multi-leg import row into one normalized transaction (same date rule as v1).
+fn multi_leg_to_transaction(itx: &MultiLegTransaction) -> Result<Transaction, String> {
+    if itx.legs.is_empty() {
+        return Err(format!("{}: at least one leg is required", itx.txid));
+    }
+    let legs = itx
+        .legs
+        .iter()
+        .enumerate()
+        .map(|(idx, leg)| flat_leg_to_leg(leg).map_err(|e| format!("leg {}: {}", idx, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let header = ImportHeader {
+        txid: &itx.txid,
+        date: &itx.date,
+        posted_ts: itx.posted_ts,
+        source: &itx.source,
+        payee: &itx.payee,
+        memo: &itx.memo,
+        status: &itx.status,
+        tx_type: &itx.tx_type,
+        ext1: (&itx.ext1_kind, &itx.ext1_val),
+    };
+    header.into_transaction(legs)
+}
 
-        let direction = match itx.direction.as_str() {
-            "Debit" => LegDirection::Debit,
-            "Credit" => LegDirection::Credit,
-            other => {
-                errors.push(format!("{}: invalid direction '{}'", txid, other));
-                skipped += 1;
-                continue;
-            }
-        };
+/// Transaction-level fields shared by v1 and v2 import rows.
+struct ImportHeader<'a> {
+    txid: &'a str,
+    date: &'a str,
+    posted_ts: Option<i64>,
+    source: &'a str,
+    payee: &'a Option<String>,
+    memo: &'a Option<String>,
+    status: &'a Option<String>,
+    tx_type: &'a Option<String>,
+    ext1: (&'a Option<String>, &'a Option<String>),
+}
 
-        let leg = Leg {
-            account_id: itx.account_id.clone(),
-            direction,
-            amount: leg_amount,
-            fx: None,
-            category_id: itx.category_id.clone(),
-            fee_of_leg_idx: None,
-            notes: None,
-        };
+impl ImportHeader<'_> {
+    fn into_transaction(self, legs: Vec<Leg>) -> Result<Transaction, String> {
+        let date = chrono::NaiveDate::parse_from_str(self.date, "%Y-%m-%d")
+            .map_err(|e| format!("invalid date '{}': {}", self.date, e))?;
+        let ts = date
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is valid")
+            .and_utc()
+            .timestamp();
 
         let mut external_refs: Vec<(String, String)> = Vec::new();
-        if let (Some(k), Some(v)) = (itx.ext1_kind.clone(), itx.ext1_val.clone()) {
-            external_refs.push((k, v));
+        if let (Some(k), Some(v)) = self.ext1 {
+            external_refs.push((k.clone(), v.clone()));
         }
 
-        let source = if itx.source.trim().is_empty() {
+        let source = if self.source.trim().is_empty() {
             default_source()
         } else {
-            itx.source.clone()
+            self.source.to_string()
         };
 
-        let new_tx = NewTransaction {
-            id: txid.clone(),
-            ts,
-            posted_ts: itx.posted_ts,
+        NewTransaction {
+            id: self.txid.to_string(),
+            ts: Some(ts),
+            date: None,
+            posted_ts: self.posted_ts,
             source,
-            payee: itx.payee.clone(),
-            memo: itx.memo.clone(),
-            status: itx.status.clone(),
+            payee: self.payee.clone(),
+            memo: self.memo.clone(),
+            status: self.status.clone(),
             reconciled: false,
             external_refs,
-            legs: vec![leg],
-            tx_type: itx.tx_type.clone(),
-        };
+            legs,
+            tx_type: self.tx_type.clone(),
+        }
+        .into_transaction()
+    }
+}
 
-        let tx = match new_tx.into_transaction() {
-            Ok(tx) => tx,
-            Err(err) => {
-                errors.push(format!("{}: {}", txid, err));
-                skipped += 1;
-                continue;
-            }
-        };
+fn flat_leg_to_leg(itx: &FlatLeg) -> Result<Leg, String> {
+    use rust_decimal::prelude::FromPrimitive;
 
-        if tx.balance_state != BalanceState::Balanced {
-            println!(
-                "Batch import: '{}' stored with balance_state={:?}",
-                tx.id, tx.balance_state
-            );
+    // Build leg amount
+    let amount_dec = Decimal::from_f64(itx.amount_or_qty)
+        .ok_or_else(|| format!("invalid amount {}", itx.amount_or_qty))?;
+
+    let leg_amount = if itx.kind.eq_ignore_ascii_case("fiat") {
+        let ccy = Currency::from_code(&itx.ccy_or_asset)
+            .ok_or_else(|| format!("unsupported fiat ccy '{}'", itx.ccy_or_asset))?;
+        LegAmount::Fiat(Money::new(amount_dec, ccy))
+    } else {
+        LegAmount::Crypto {
+            asset: itx.ccy_or_asset.clone(),
+            qty: amount_dec,
         }
+    };
 
-        match collection.insert_one(&tx, None).await {
-            Ok(_) => imported += 1,
-            Err(e) => {
-                errors.push(format!("{}: insert error: {}", txid, e));
-                skipped += 1;
-            }
+    // Crypto rows priced in a known currency carry that price as the leg's FX snapshot
+    let fx = match (&leg_amount, itx.price, itx.price_ccy.as_deref()) {
+        (LegAmount::Crypto { .. }, Some(price), Some(price_ccy)) => {
+            let to = Currency::from_code(price_ccy)
+                .ok_or_else(|| format!("unsupported price ccy '{}'", price_ccy))?;
+            let rate =
+                Decimal::from_f64(price).ok_or_else(|| format!("invalid price {}", price))?;
+            Some(FxSnapshot { to, rate })
         }
-    }
+        _ => None,
+    };
 
-    Ok(BatchImportResponse {
-        imported,
-        skipped,
-        errors,
+    let direction = match itx.direction.as_str() {
+        "Debit" => LegDirection::Debit,
+        "Credit" => LegDirection::Credit,
+        other => return Err(format!("invalid direction '{}'", other)),
+    };
+
+    Ok(Leg {
+        account_id: itx.account_id.clone(),
+        direction,
+        amount: leg_amount,
+        fx,
+        category_id: itx.category_id.clone(),
+        fee_of_leg_idx: None,
+        notes: None,
     })
 }
 
-/// POST /capital/transactions/batch-import
-/// Accepts extracted flat transactions and inserts Transaction rows with a single P&L leg.
-pub async fn batch_import_transactions(
-    State(state): State<Arc<AppState>>,
-    Json(req): Json<BatchImportRequest>,
-) -> Result<Json<BatchImportResponse>, String> {
-    let db = state.mongo_client.database("wyat");
-    let summary = process_batch_import(&db, req.transactions).await?;
-    Ok(Json(summary))
+/// For signed-amount imports: a negative amount means the opposite of the stated
+/// direction. Flips the row to a positive amount and returns a note describing it.
+fn apply_signed_amount(itx: &mut FlatTransaction) -> Option<String> {
+    if itx.amount_or_qty >= 0.0 {
+        return None;
+    }
+    let flipped = match itx.direction.as_str() {
+        "Debit" => "Credit",
+        "Credit" => "Debit",
+        _ => return None,
+    };
+    let note = format!(
+        "{}: amount {} with direction {}; imported as {} {}",
+        itx.txid, itx.amount_or_qty, itx.direction, flipped, -itx.amount_or_qty
+    );
+    itx.direction = flipped.to_string();
+    itx.amount_or_qty = -itx.amount_or_qty;
+    Some(note)
 }
 
-// ======================= //
-// * * * * DATA FEEDS * * * //
-// ======================= //
+/// In strict mode, reject transactions that did not normalize to `Balanced`.
+fn check_import_balance(tx: &Transaction, require_balanced: bool) -> Result<(), String> {
+    if tx.balance_state == BalanceState::Balanced {
+        return Ok(());
+    }
+    if require_balanced {
+        return Err(format!(
+            "not balanced (balance_state={:?}); skipped because require_balanced is set",
+            tx.balance_state
+        ));
+    }
+    tracing::warn!(
+        txid = %tx.id,
+        balance_state = ?tx.balance_state,
+        "batch import row stored unbalanced"
+    );
+    Ok(())
+}
 
-#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum WatchlistAssetKind {
-    Stock,
-    Crypto,
+#[tracing::instrument(
+    skip_all,
+    fields(count = request.transactions.len(), dry_run = request.dry_run)
+)]
+pub async fn process_batch_import(
+    db: &Database,
+    request: BatchImportRequest,
+) -> Result<BatchImportResponse, String> {
+    let collection = db.collection::<Transaction>("capital_ledger");
+    let mut summary = BatchImportResponse::default();
+
+    for mut itx in request.transactions {
+        let txid = itx.txid.clone();
+        if request.signed_amounts
+            && let Some(note) = apply_signed_amount(&mut itx)
+        {
+            summary.sign_conflicts.push(note);
+        }
+        store_import_row(
+            &collection,
+            &mut summary,
+            &txid,
+            flat_to_transaction(&itx),
+            request.require_balanced,
+            request.dry_run,
+        )
+        .await;
+    }
+
+    summary.log_outcome();
+    Ok(summary)
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
-pub struct WatchlistEntry {
+/// Like `process_batch_import`, but each row already carries all of its legs.
+#[tracing::instrument(
+    skip_all,
+    fields(count = request.transactions.len(), dry_run = request.dry_run)
+)]
+pub async fn process_batch_import_v2(
+    db: &Database,
+    request: BatchImportRequestV2,
+) -> Result<BatchImportResponse, String> {
+    let collection = db.collection::<Transaction>("capital_ledger");
+    let mut summary = BatchImportResponse::default();
+
+    for itx in request.transactions {
+        store_import_row(
+            &collection,
+            &mut summary,
+            &itx.txid,
+            multi_leg_to_transaction(&itx),
+            request.require_balanced,
+            request.dry_run,
+        )
+        .await;
+    }
+
+    summary.log_outcome();
+    Ok(summary)
+}
+
+/// Shared tail of both import formats: skip duplicates and rejected rows,
+/// then insert (or preview) the converted transaction.
+async fn store_import_row(
+    collection: &mongodb::Collection<Transaction>,
+    summary: &mut BatchImportResponse,
+    txid: &str,
+    converted: Result<Transaction, String>,
+    require_balanced: bool,
+    dry_run: bool,
+) {
+    // Skip if already exists
+    if let Ok(Some(_existing)) = collection.find_one(doc! { "id": txid }, None).await {
+        summary.record_skipped(txid, None);
+        return;
+    }
+
+    let tx = match converted.and_then(|tx| check_import_balance(&tx, require_balanced).map(|_| tx))
+    {
+        Ok(tx) => tx,
+        Err(err) => {
+            summary.record_skipped(txid, Some(err));
+            return;
+        }
+    };
+
+    if dry_run {
+        summary.record_imported(txid);
+        summary.preview.push(tx);
+        return;
+    }
+
+    match collection.insert_one(&tx, None).await {
+        Ok(_) => summary.record_imported(txid),
+        Err(e) => summary.record_skipped(txid, Some(format!("insert error: {}", e))),
+    }
+}
+
+/// POST /capital/transactions/batch-import
+/// Accepts extracted flat transactions and inserts Transaction rows with a single P&L leg.
+/// With `dry_run`, nothing is written and `imported` counts rows that would be inserted.
+pub async fn batch_import_transactions(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BatchImportRequest>,
+) -> Result<Json<BatchImportResponse>, String> {
+    let db = state.mongo_client.database("wyat");
+    let summary = process_batch_import(&db, req).await?;
+    Ok(Json(summary))
+}
+
+/// POST /capital/transactions/batch-import-v2
+/// Accepts multi-leg rows (`legs: [...]` per txid), so trades and transfers import
+/// as one transaction. Supports `require_balanced` and `dry_run`; there is no
+/// `signed_amounts`, since every leg states its own direction.
+pub async fn batch_import_transactions_v2(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BatchImportRequestV2>,
+) -> Result<Json<BatchImportResponse>, String> {
+    let db = state.mongo_client.database("wyat");
+    let summary = process_batch_import_v2(&db, req).await?;
+    Ok(Json(summary))
+}
+
+// ------------------------- Config Export / Import -------------------------
+
+/// Bundle format version for `/capital/config/export`.
+pub const CAPITAL_CONFIG_VERSION: u32 = 1;
+
+/// Budget setup snapshot: accounts, envelopes and funds. Ledger transactions
+/// are intentionally excluded.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CapitalConfig {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: Option<i64>,
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    #[serde(default)]
+    pub envelopes: Vec<Envelope>,
+    #[serde(default)]
+    pub funds: Vec<Fund>,
+}
+
+impl CapitalConfig {
+    /// Validate every item and reject duplicate ids within the bundle.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.version != CAPITAL_CONFIG_VERSION {
+            errors.push(format!(
+                "unsupported config version {} (expected {})",
+                self.version, CAPITAL_CONFIG_VERSION
+            ));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for account in &self.accounts {
+            if account.id.trim().is_empty() {
+                errors.push(format!("account '{}': id cannot be empty", account.name));
+            } else if !seen.insert(account.id.as_str()) {
+                errors.push(format!("account '{}': duplicate id", account.id));
+            }
+            if account.name.trim().is_empty() {
+                errors.push(format!("account '{}': name cannot be empty", account.id));
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for envelope in &self.envelopes {
+            if !envelope.id.trim().is_empty() && !seen.insert(envelope.id.as_str()) {
+                errors.push(format!("envelope '{}': duplicate id", envelope.id));
+            }
+            if let Err(envelope_errors) = envelope.validate() {
+                errors.extend(
+                    envelope_errors
+                        .into_iter()
+                        .map(|e| format!("envelope '{}': {}", envelope.id, e)),
+                );
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for fund in &self.funds {
+            if fund.fund_id.trim().is_empty() {
+                errors.push(format!("fund '{}': fund_id cannot be empty", fund.name));
+            } else if !seen.insert(fund.fund_id.as_str()) {
+                errors.push(format!("fund '{}': duplicate fund_id", fund.fund_id));
+            }
+            if fund.name.trim().is_empty() {
+                errors.push(format!("fund '{}': name cannot be empty", fund.fund_id));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ConfigImportCounts {
+    pub created: usize,
+    pub updated: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigImportResponse {
+    pub dry_run: bool,
+    pub valid: bool,
+    /// Whether the writes ran in a single Mongo transaction.
+    pub transactional: bool,
+    pub accounts: ConfigImportCounts,
+    pub envelopes: ConfigImportCounts,
+    pub funds: ConfigImportCounts,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Ids from `candidates` that already exist in `collection` under `field`.
+async fn existing_ids<T>(
+    collection: &mongodb::Collection<T>,
+    field: &str,
+    candidates: Vec<&str>,
+) -> Result<std::collections::HashSet<String>, String> {
+    let docs: Vec<BsonDocument> = collection
+        .clone_with_type::<BsonDocument>()
+        .find(
+            doc! { field: { "$in": candidates } },
+            FindOptions::builder().projection(doc! { field: 1 }).build(),
+        )
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .try_collect()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(docs
+        .iter()
+        .filter_map(|d| d.get_str(field).ok().map(str::to_string))
+        .collect())
+}
+
+/// GET /capital/config/export - Accounts, envelopes and funds as one document
+///
+/// Account numbers are exported unmasked, so a valid API key is required.
+#[utoipa::path(
+    get,
+    path = "/capital/config/export",
+    responses(
+        (status = 200, description = "Budget configuration bundle", body = CapitalConfig),
+        (status = 401, description = "Missing or invalid API key")
+    ),
+    tag = "capital"
+)]
+pub async fn export_capital_config(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CapitalConfig>, (StatusCode, String)> {
+    let db = state.mongo_client.database("wyat");
+    let db_error = |e: mongodb::error::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )
+    };
+
+    let accounts: Vec<Account> = db
+        .collection::<Account>("capital_accounts")
+        .find(None, None)
+        .await
+        .map_err(db_error)?
+        .try_collect()
+        .await
+        .map_err(db_error)?;
+    let envelopes: Vec<Envelope> = db
+        .collection::<Envelope>("capital_envelopes")
+        .find(None, None)
+        .await
+        .map_err(db_error)?
+        .try_collect()
+        .await
+        .map_err(db_error)?;
+    let funds: Vec<Fund> = db
+        .collection::<Fund>("capital_funds")
+        .find(None, None)
+        .await
+        .map_err(db_error)?
+        .try_collect()
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(CapitalConfig {
+        version: CAPITAL_CONFIG_VERSION,
+        exported_at: Some(Utc::now().timestamp()),
+        accounts,
+        envelopes,
+        funds,
+    }))
+}
+
+/// Upsert every item of `config`. Runs inside `session`'s transaction when one is
+/// given, so a failure part-way leaves nothing applied.
+async fn write_capital_config(
+    db: &Database,
+    config: CapitalConfig,
+    mut session: Option<&mut ClientSession>,
+) -> mongodb::error::Result<()> {
+    let accounts_coll = db.collection::<Account>("capital_accounts");
+    let envelopes_coll = db.collection::<Envelope>("capital_envelopes");
+    let funds_coll = db.collection::<Fund>("capital_funds");
+
+    for account in &config.accounts {
+        let filter = doc! { "id": &account.id };
+        upsert_with(&accounts_coll, filter, account, session.as_deref_mut()).await?;
+    }
+    for envelope in &config.envelopes {
+        let filter = doc! { "id": &envelope.id };
+        upsert_with(&envelopes_coll, filter, envelope, session.as_deref_mut()).await?;
+    }
+    for mut fund in config.funds {
+        // `_id` is immutable, so an existing fund keeps its stored ObjectId.
+        let filter = doc! { "fund_id": &fund.fund_id };
+        let current = match session.as_deref_mut() {
+            Some(session) => {
+                funds_coll
+                    .find_one_with_session(filter.clone(), None, session)
+                    .await?
+            }
+            None => funds_coll.find_one(filter.clone(), None).await?,
+        };
+        if let Some(current) = current {
+            fund.id = current.id;
+        }
+        upsert_with(&funds_coll, filter, &fund, session.as_deref_mut()).await?;
+    }
+    Ok(())
+}
+
+async fn upsert_with<T: Serialize>(
+    collection: &mongodb::Collection<T>,
+    filter: BsonDocument,
+    replacement: &T,
+    session: Option<&mut ClientSession>,
+) -> mongodb::error::Result<()> {
+    let upsert = mongodb::options::ReplaceOptions::builder()
+        .upsert(true)
+        .build();
+    match session {
+        Some(session) => {
+            collection
+                .replace_one_with_session(filter, replacement, upsert, session)
+                .await?
+        }
+        None => collection.replace_one(filter, replacement, upsert).await?,
+    };
+    Ok(())
+}
+
+/// POST /capital/config/import - Upsert accounts, envelopes and funds
+///
+/// The whole bundle is validated before anything is written; any error
+/// rejects the import. With `dry_run=true` nothing is written and the
+/// response reports what would be created or updated. On a replica set the
+/// writes run in one transaction (`transactional: true`); a standalone server
+/// applies them one by one.
+#[utoipa::path(
+    post,
+    path = "/capital/config/import",
+    request_body = CapitalConfig,
+    params(
+        ("dry_run" = Option<bool>, Query, description = "Validate and report without writing")
+    ),
+    responses(
+        (status = 200, description = "Import summary", body = ConfigImportResponse),
+        (status = 400, description = "Bundle failed validation"),
+        (status = 401, description = "Missing or invalid API key")
+    ),
+    tag = "capital"
+)]
+pub async fn import_capital_config(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<ConfigImportQuery>,
+    Json(config): Json<CapitalConfig>,
+) -> Result<Json<ConfigImportResponse>, (StatusCode, String)> {
+    let errors = config.validate().err().unwrap_or_default();
+    if !errors.is_empty() && !q.dry_run {
+        return Err((StatusCode::BAD_REQUEST, errors.join("; ")));
+    }
+
+    let db = state.mongo_client.database("wyat");
+    let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    let accounts_coll = db.collection::<Account>("capital_accounts");
+    let envelopes_coll = db.collection::<Envelope>("capital_envelopes");
+    let funds_coll = db.collection::<Fund>("capital_funds");
+
+    let existing_accounts = existing_ids(
+        &accounts_coll,
+        "id",
+        config.accounts.iter().map(|a| a.id.as_str()).collect(),
+    )
+    .await
+    .map_err(internal)?;
+    let existing_envelopes = existing_ids(
+        &envelopes_coll,
+        "id",
+        config.envelopes.iter().map(|e| e.id.as_str()).collect(),
+    )
+    .await
+    .map_err(internal)?;
+    let existing_funds = existing_ids(
+        &funds_coll,
+        "fund_id",
+        config.funds.iter().map(|f| f.fund_id.as_str()).collect(),
+    )
+    .await
+    .map_err(internal)?;
+
+    let counts = |ids: Vec<&str>, existing: &std::collections::HashSet<String>| {
+        let updated = ids.iter().filter(|id| existing.contains(**id)).count();
+        ConfigImportCounts {
+            created: ids.len() - updated,
+            updated,
+        }
+    };
+    let mut response = ConfigImportResponse {
+        dry_run: q.dry_run,
+        valid: errors.is_empty(),
+        transactional: false,
+        accounts: counts(
+            config.accounts.iter().map(|a| a.id.as_str()).collect(),
+            &existing_accounts,
+        ),
+        envelopes: counts(
+            config.envelopes.iter().map(|e| e.id.as_str()).collect(),
+            &existing_envelopes,
+        ),
+        funds: counts(
+            config.funds.iter().map(|f| f.fund_id.as_str()).collect(),
+            &existing_funds,
+        ),
+        errors,
+    };
+    if q.dry_run {
+        return Ok(Json(response));
+    }
+
+    let db_error = |e: mongodb::error::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )
+    };
+    response.transactional = supports_transactions(&db).await;
+    if response.transactional {
+        let mut session = state
+            .mongo_client
+            .start_session(None)
+            .await
+            .map_err(db_error)?;
+        session.start_transaction(None).await.map_err(db_error)?;
+        match write_capital_config(&db, config, Some(&mut session)).await {
+            Ok(()) => session.commit_transaction().await.map_err(db_error)?,
+            Err(e) => {
+                let _ = session.abort_transaction().await;
+                return Err(db_error(e));
+            }
+        }
+    } else {
+        write_capital_config(&db, config, None)
+            .await
+            .map_err(db_error)?;
+    }
+
+    tracing::info!(
+        accounts = ?response.accounts,
+        envelopes = ?response.envelopes,
+        funds = ?response.funds,
+        "config import finished"
+    );
+    Ok(Json(response))
+}
+// ======================= //
+// * * * * DATA FEEDS * * * //
+// ======================= //
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchlistAssetKind {
+    Stock,
+    Crypto,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct WatchlistEntry {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     #[schema(value_type = Option<String>)]
     pub id: Option<ObjectId>,
@@ -2608,6 +5918,18 @@ pub struct WatchlistEntry {
     pub unit: Option<String>,
     #[schema(value_type = i64)]
     pub created_at: DateTime<Utc>,
+    /// Alert when the latest value rises to or above this.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub alert_above: Option<Decimal>,
+    /// Alert when the latest value falls to or below this.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub alert_below: Option<Decimal>,
+    /// Value that last fired an alert; cleared once the price is back between bounds.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub last_alerted_value: Option<Decimal>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -2637,6 +5959,9 @@ pub struct WatchlistAssetResponse {
     pub change_24h_pct: Option<f64>,
     #[schema(value_type = Option<i64>)]
     pub last_updated: Option<DateTime<Utc>>,
+    /// Served from cached snapshots because `WYAT_OFFLINE` is set.
+    #[serde(default)]
+    pub offline: bool,
 }
 
 fn provider_for_kind(kind: &WatchlistAssetKind) -> DataFeedProvider {
@@ -2676,10 +6001,59 @@ fn metadata_from_pair_unit(pair: &Option<String>, unit: &Option<String>) -> Opti
     if doc.is_empty() { None } else { Some(doc) }
 }
 
+/// 24h change reported by the feed itself (e.g. CoinGecko metadata).
+fn reported_change_24h_pct(snapshot: &DataSnapshot) -> Option<f64> {
+    snapshot
+        .data
+        .first()?
+        .metadata
+        .as_ref()?
+        .get("change_24h_pct")
+        .and_then(|v| v.as_f64())
+}
+
+/// Percent change from `previous` to `current`, computed on the Decimal values.
+fn percent_change(current: Decimal, previous: Decimal) -> Option<f64> {
+    if previous.is_zero() {
+        return None;
+    }
+    ((current - previous) / previous * Decimal::ONE_HUNDRED).to_f64()
+}
+
+/// Snapshot from ~24h before `snapshot`, used to derive `change_24h_pct` when the
+/// feed doesn't report it. Lookup failures only cost the derived change.
+async fn snapshot_24h_before(
+    service: &DataFeedService,
+    db: &Database,
+    snapshot: Option<&DataSnapshot>,
+) -> Option<DataSnapshot> {
+    let snapshot = snapshot?;
+    if reported_change_24h_pct(snapshot).is_some() {
+        return None;
+    }
+    let before = snapshot.fetch_time - chrono::Duration::hours(24);
+    match service
+        .get_snapshot_at_or_before(db, &snapshot.feed_symbol, before)
+        .await
+    {
+        Ok(previous) => previous,
+        Err(err) => {
+            tracing::warn!(
+                symbol = %snapshot.feed_symbol,
+                error = %err,
+                "loading previous snapshot failed; skipping derived 24h change"
+            );
+            None
+        }
+    }
+}
+
 fn build_watchlist_response(
     entry: &WatchlistEntry,
     feed: &DataFeed,
     snapshot: Option<&DataSnapshot>,
+    previous: Option<&DataSnapshot>,
+    offline: bool,
 ) -> WatchlistAssetResponse {
     let mut latest_value = None;
     let mut latest_text = None;
@@ -2687,20 +6061,21 @@ fn build_watchlist_response(
     let mut unit = entry.unit.clone();
     let mut change_24h_pct = None;
 
-    if let Some(snapshot) = snapshot {
-        if let Some(data) = snapshot.data.first() {
-            latest_value = data.value.to_f64();
-            latest_text = Some(data.value.normalize().to_string());
-            if let Some(snapshot_unit) = &data.unit {
-                unit = Some(snapshot_unit.clone());
-            }
-            last_updated = snapshot.source_time.or(Some(snapshot.fetch_time));
-
-            // Extract 24h change from metadata
-            if let Some(metadata) = &data.metadata {
-                change_24h_pct = metadata.get("change_24h_pct").and_then(|v| v.as_f64());
-            }
+    if let Some(snapshot) = snapshot
+        && let Some(data) = snapshot.data.first()
+    {
+        // `latest_value_text` is the exact Decimal; `latest_value` is for display only
+        latest_value = data.value.to_f64();
+        latest_text = Some(data.value.normalize().to_string());
+        if let Some(snapshot_unit) = &data.unit {
+            unit = Some(snapshot_unit.clone());
         }
+        last_updated = snapshot.source_time.or(Some(snapshot.fetch_time));
+
+        change_24h_pct = reported_change_24h_pct(snapshot).or_else(|| {
+            let previous = previous?.data.first()?;
+            percent_change(data.value, previous.value)
+        });
     }
 
     WatchlistAssetResponse {
@@ -2714,6 +6089,7 @@ fn build_watchlist_response(
         latest_value_text: latest_text,
         change_24h_pct,
         last_updated,
+        offline,
     }
 }
 
@@ -2741,6 +6117,7 @@ pub async fn get_watchlist_data(
         .map_err(|e| format!("Database error: {e}"))?;
 
     let feeds = db.collection::<DataFeed>("capital_data_feeds");
+    let mut pending: Vec<(WatchlistEntry, DataFeed)> = Vec::new();
     let mut responses = Vec::new();
 
     while let Some(entry) = cursor
@@ -2771,25 +6148,44 @@ pub async fn get_watchlist_data(
         feed.source = service.source_for(&provider, &entry.feed_symbol);
         feed.metadata = metadata.clone();
 
-        let mut snapshot_opt: Option<DataSnapshot> = None;
-        let mut refreshed = false;
+        pending.push((entry, feed));
+    }
 
-        if service.needs_refresh(&feed) {
-            match service
-                .fetch_and_store_snapshot(&db, &mut feed, entry.pair.clone(), entry.unit.clone())
-                .await
-            {
-                Ok(snapshot) => {
-                    refreshed = true;
-                    snapshot_opt = Some(snapshot);
-                }
-                Err(err) => {
-                    eprintln!("Failed to refresh feed {}: {}", feed.symbol, err);
-                }
+    // Refresh stale feeds together so crypto assets share one Coingecko request
+    let stale: Vec<usize> = (0..pending.len())
+        .filter(|&i| service.needs_refresh(&pending[i].1))
+        .collect();
+    let mut refreshes: Vec<FeedRefresh> = stale
+        .iter()
+        .map(|&i| FeedRefresh {
+            feed: pending[i].1.clone(),
+            pair: pending[i].0.pair.clone(),
+            unit: pending[i].0.unit.clone(),
+        })
+        .collect();
+    let results = service.fetch_and_store_snapshots(&db, &mut refreshes).await;
+
+    let mut refreshed: Vec<Option<DataSnapshot>> = vec![None; pending.len()];
+    for ((&i, refresh), result) in stale.iter().zip(refreshes).zip(results) {
+        match result {
+            Ok(snapshot) => {
+                pending[i].1 = refresh.feed;
+                refreshed[i] = Some(snapshot);
+            }
+            Err(err) => {
+                tracing::warn!(
+                    symbol = %refresh.feed.symbol,
+                    error = %err,
+                    "refreshing feed failed; serving the cached snapshot"
+                );
             }
         }
+    }
 
-        if !refreshed {
+    for ((entry, feed), snapshot_opt) in pending.into_iter().zip(refreshed) {
+        let mut snapshot_opt = snapshot_opt;
+
+        if snapshot_opt.is_none() {
             let feed_doc =
                 bson::to_document(&feed).map_err(|e| format!("Serialization error: {e}"))?;
             feeds
@@ -2800,19 +6196,20 @@ pub async fn get_watchlist_data(
                 )
                 .await
                 .map_err(|e| format!("Database error: {e}"))?;
-        }
 
-        if snapshot_opt.is_none() {
             snapshot_opt = service
                 .get_latest_snapshot(&db, &feed.symbol)
                 .await
                 .map_err(|e| format!("Database error: {e}"))?;
         }
 
+        let previous = snapshot_24h_before(&service, &db, snapshot_opt.as_ref()).await;
         responses.push(build_watchlist_response(
             &entry,
             &feed,
             snapshot_opt.as_ref(),
+            previous.as_ref(),
+            service.is_offline(),
         ));
     }
 
@@ -2830,24 +6227,24 @@ pub async fn add_watchlist_asset(
     State(state): State<Arc<AppState>>,
     Json(req): Json<AddWatchlistAssetRequest>,
 ) -> Result<Json<WatchlistAssetResponse>, String> {
-    println!("=== ADD WATCHLIST ASSET START ===");
-    println!(
-        "Request: symbol={}, name={}, kind={:?}, pair={:?}, unit={:?}",
-        req.symbol, req.name, req.kind, req.pair, req.unit
+    tracing::debug!(
+        symbol = %req.symbol,
+        name = %req.name,
+        kind = ?req.kind,
+        pair = ?req.pair,
+        unit = ?req.unit,
+        "adding watchlist asset"
     );
 
     if req.symbol.trim().is_empty() {
-        eprintln!("❌ Symbol is empty");
         return Err("Symbol is required".to_string());
     }
     if req.name.trim().is_empty() {
-        eprintln!("❌ Name is empty");
         return Err("Name is required".to_string());
     }
 
-    println!("Creating DataFeedService...");
     let service = DataFeedService::new().map_err(|e| {
-        eprintln!("❌ Failed to create DataFeedService: {}", e);
+        tracing::error!(error = %e, "creating DataFeedService failed");
         e.to_string()
     })?;
 
@@ -2856,28 +6253,23 @@ pub async fn add_watchlist_asset(
     let feeds = db.collection::<DataFeed>("capital_data_feeds");
 
     let normalized_symbol = normalize_symbol(&req.kind, &req.symbol);
-    println!("Normalized symbol: {} -> {}", req.symbol, normalized_symbol);
-
-    println!("Checking if asset already exists in watchlist...");
     if watchlist
         .find_one(doc! { "symbol": &normalized_symbol }, None)
         .await
         .map_err(|e| {
-            eprintln!("❌ Database error checking watchlist: {}", e);
+            tracing::error!(error = %e, "checking watchlist failed");
             format!("Database error: {e}")
         })?
         .is_some()
     {
-        eprintln!("❌ Asset '{}' already on watchlist", normalized_symbol);
+        tracing::warn!(symbol = %normalized_symbol, "asset already on watchlist");
         return Err(format!(
             "Asset '{}' is already on the watchlist",
             normalized_symbol
         ));
     }
-    println!("✅ Asset not in watchlist, proceeding...");
 
     let provider = provider_for_kind(&req.kind);
-    println!("Provider: {:?}", provider);
 
     let pair = req.pair.as_ref().and_then(|p| {
         let trimmed = p.trim();
@@ -2895,52 +6287,54 @@ pub async fn add_watchlist_asset(
             Some(trimmed.to_uppercase())
         }
     });
-    println!("Processed pair: {:?}, unit: {:?}", pair, unit);
 
     let metadata = metadata_from_pair_unit(&pair, &unit);
 
-    println!("Checking for existing feed...");
     let mut feed = match feeds
         .find_one(doc! { "symbol": &normalized_symbol }, None)
         .await
         .map_err(|e| {
-            eprintln!("❌ Database error checking feeds: {}", e);
+            tracing::error!(error = %e, "checking data feeds failed");
             format!("Database error: {e}")
         })? {
-        Some(existing) => {
-            println!("Found existing feed for {}", normalized_symbol);
-            existing
-        }
-        None => {
-            println!("Creating new feed for {}", normalized_symbol);
-            DataFeed {
-                name: req.name.trim().to_string(),
-                symbol: normalized_symbol.clone(),
-                categories: categories_for_kind(&req.kind),
-                source: service.source_for(&provider, &normalized_symbol),
-                last_fetch: None,
-                metadata: metadata.clone(),
-            }
-        }
+        Some(existing) => existing,
+        None => DataFeed {
+            name: req.name.trim().to_string(),
+            symbol: normalized_symbol.clone(),
+            categories: categories_for_kind(&req.kind),
+            source: service.source_for(&provider, &normalized_symbol),
+            last_fetch: None,
+            metadata: metadata.clone(),
+        },
     };
 
-    println!("Updating feed metadata...");
     feed.name = req.name.trim().to_string();
     feed.categories = categories_for_kind(&req.kind);
     feed.source = service.source_for(&provider, &normalized_symbol);
     feed.metadata = metadata.clone();
 
-    println!("Fetching latest price snapshot...");
-    let snapshot = service
-        .fetch_and_store_snapshot(&db, &mut feed, pair.clone(), unit.clone())
-        .await
-        .map_err(|e| {
-            eprintln!("❌ Failed to fetch snapshot: {}", e);
-            format!("Failed to fetch latest data: {e}")
-        })?;
-    println!("✅ Successfully fetched snapshot");
+    let snapshot = if service.is_offline() {
+        // Offline: still record the feed, and use whatever snapshot is cached
+        tracing::info!(symbol = %feed.symbol, "offline mode: skipping price fetch");
+        service
+            .save_feed(&db, &feed)
+            .await
+            .map_err(|e| format!("Database error: {e}"))?;
+        service
+            .get_latest_snapshot(&db, &feed.symbol)
+            .await
+            .map_err(|e| format!("Database error: {e}"))?
+    } else {
+        let snapshot = service
+            .fetch_and_store_snapshot(&db, &mut feed, pair.clone(), unit.clone())
+            .await
+            .map_err(|e| {
+                tracing::error!(symbol = %feed.symbol, error = %e, "fetching snapshot failed");
+                format!("Failed to fetch latest data: {e}")
+            })?;
+        Some(snapshot)
+    };
 
-    println!("Creating watchlist entry...");
     let entry = WatchlistEntry {
         id: None,
         symbol: normalized_symbol.clone(),
@@ -2950,19 +6344,25 @@ pub async fn add_watchlist_asset(
         pair: pair.clone(),
         unit: unit.clone(),
         created_at: Utc::now(),
+        alert_above: None,
+        alert_below: None,
+        last_alerted_value: None,
     };
 
-    println!("Inserting entry into watchlist collection...");
     watchlist.insert_one(&entry, None).await.map_err(|e| {
-        eprintln!("❌ Failed to insert watchlist entry: {}", e);
+        tracing::error!(error = %e, "inserting watchlist entry failed");
         format!("Database error: {e}")
     })?;
-    println!("✅ Successfully inserted watchlist entry");
 
-    println!("Building response...");
-    let response = build_watchlist_response(&entry, &feed, Some(&snapshot));
-    println!("✅ Successfully added {} to watchlist", normalized_symbol);
-    println!("=== ADD WATCHLIST ASSET END ===");
+    let previous = snapshot_24h_before(&service, &db, snapshot.as_ref()).await;
+    let response = build_watchlist_response(
+        &entry,
+        &feed,
+        snapshot.as_ref(),
+        previous.as_ref(),
+        service.is_offline(),
+    );
+    tracing::info!(symbol = %normalized_symbol, "added asset to watchlist");
 
     Ok(Json(response))
 }
@@ -3009,65 +6409,198 @@ pub async fn remove_watchlist_asset(
     Ok(StatusCode::NOT_FOUND)
 }
 
+#[derive(Debug, Deserialize)]
+pub struct WatchlistHistoryQuery {
+    /// Unix seconds, inclusive. Defaults to 30 days before `to`.
+    pub from: Option<i64>,
+    /// Unix seconds, inclusive. Defaults to now.
+    pub to: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct WatchlistHistoryPoint {
+    #[schema(value_type = i64)]
+    pub fetch_time: DateTime<Utc>,
+    #[schema(value_type = Option<i64>)]
+    pub source_time: Option<DateTime<Utc>>,
+    pub value: Option<f64>,
+    pub value_text: String,
+    pub unit: Option<String>,
+}
+
+fn history_point(snapshot: &DataSnapshot) -> Option<WatchlistHistoryPoint> {
+    let data = snapshot.data.first()?;
+    Some(WatchlistHistoryPoint {
+        fetch_time: snapshot.fetch_time,
+        source_time: snapshot.source_time,
+        value: data.value.to_f64(),
+        value_text: data.value.normalize().to_string(),
+        unit: data.unit.clone(),
+    })
+}
+
 #[utoipa::path(
-    patch,
-    path = "/capital/data/watchlist/{symbol}",
-    params(("symbol" = String, Path, description = "Symbol to update")),
-    request_body = UpdateWatchlistAssetRequest,
+    get,
+    path = "/capital/data/watchlist/{symbol}/history",
+    params(
+        ("symbol" = String, Path, description = "Watchlist symbol"),
+        ("from" = Option<i64>, Query, description = "Start (unix seconds); defaults to 30 days before `to`"),
+        ("to" = Option<i64>, Query, description = "End (unix seconds); defaults to now")
+    ),
     responses(
-        (status = 200, description = "Asset name updated", body = WatchlistAssetResponse),
+        (status = 200, description = "Stored price points, oldest first", body = [WatchlistHistoryPoint]),
+        (status = 400, description = "Invalid range"),
         (status = 404, description = "Asset not found")
     ),
     tag = "capital"
 )]
-pub async fn update_watchlist_asset(
+pub async fn get_watchlist_history(
     State(state): State<Arc<AppState>>,
     Path(symbol): Path<String>,
-    Json(req): Json<UpdateWatchlistAssetRequest>,
-) -> Result<Json<WatchlistAssetResponse>, String> {
-    println!("=== UPDATE WATCHLIST ASSET START ===");
-    println!("Symbol: {}, New name: {}", symbol, req.name);
-
-    if req.name.trim().is_empty() {
-        eprintln!("❌ Name is empty");
-        return Err("Name is required".to_string());
+    Query(q): Query<WatchlistHistoryQuery>,
+) -> Result<Json<Vec<WatchlistHistoryPoint>>, CapitalApiError> {
+    let to = match q.to {
+        Some(ts) => DateTime::from_timestamp(ts, 0)
+            .ok_or_else(|| CapitalApiError::Validation(format!("Invalid 'to': {}", ts)))?,
+        None => Utc::now(),
+    };
+    let from = match q.from {
+        Some(ts) => DateTime::from_timestamp(ts, 0)
+            .ok_or_else(|| CapitalApiError::Validation(format!("Invalid 'from': {}", ts)))?,
+        None => to - chrono::Duration::days(30),
+    };
+    if from > to {
+        return Err(CapitalApiError::Validation(
+            "'from' must not be after 'to'".to_string(),
+        ));
     }
 
     let db = state.mongo_client.database("wyat");
     let watchlist = db.collection::<WatchlistEntry>("capital_watchlist");
-    let feeds = db.collection::<DataFeed>("capital_data_feeds");
-    let trimmed = symbol.trim();
+    let entry = find_watchlist_entry(&watchlist, &symbol)
+        .await?
+        .ok_or_else(|| CapitalApiError::NotFound(format!("Asset {} not found", symbol)))?;
 
-    // Try different case variations
-    let mut candidates = vec![trimmed.to_string()];
-    let upper = trimmed.to_uppercase();
-    if !candidates.contains(&upper) {
-        candidates.push(upper);
-    }
-    let lower = trimmed.to_lowercase();
-    if !candidates.contains(&lower) {
-        candidates.push(lower);
+    let service = DataFeedService::new().map_err(|e| CapitalApiError::Internal(e.to_string()))?;
+    let snapshots = service
+        .get_snapshot_history(&db, &entry.feed_symbol, from, to)
+        .await
+        .map_err(|e| CapitalApiError::Internal(format!("Database error: {e}")))?;
+
+    Ok(Json(snapshots.iter().filter_map(history_point).collect()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WatchlistBackfillQuery {
+    /// Days of history to fetch. Defaults to 90.
+    pub days: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct WatchlistBackfillResponse {
+    pub symbol: String,
+    /// Points newly stored; timestamps already in history are skipped.
+    pub inserted: usize,
+}
+
+const MAX_BACKFILL_DAYS: u32 = 365;
+
+#[utoipa::path(
+    post,
+    path = "/capital/data/watchlist/{symbol}/backfill",
+    params(
+        ("symbol" = String, Path, description = "Watchlist symbol"),
+        ("days" = Option<u32>, Query, description = "Days of history to fetch (1-365); defaults to 90")
+    ),
+    responses(
+        (status = 200, description = "History stored", body = WatchlistBackfillResponse),
+        (status = 400, description = "Invalid range or offline mode"),
+        (status = 404, description = "Asset not found"),
+        (status = 502, description = "Price feed failed")
+    ),
+    tag = "capital"
+)]
+pub async fn backfill_watchlist_history(
+    State(state): State<Arc<AppState>>,
+    Path(symbol): Path<String>,
+    Query(q): Query<WatchlistBackfillQuery>,
+) -> Result<Json<WatchlistBackfillResponse>, CapitalApiError> {
+    let days = q.days.unwrap_or(90);
+    if !(1..=MAX_BACKFILL_DAYS).contains(&days) {
+        return Err(CapitalApiError::Validation(format!(
+            "'days' must be between 1 and {}",
+            MAX_BACKFILL_DAYS
+        )));
     }
 
-    println!("Searching for symbol in: {:?}", candidates);
+    let db = state.mongo_client.database("wyat");
+    let watchlist = db.collection::<WatchlistEntry>("capital_watchlist");
+    let entry = find_watchlist_entry(&watchlist, &symbol)
+        .await?
+        .ok_or_else(|| CapitalApiError::NotFound(format!("Asset {} not found", symbol)))?;
 
-    // Find the watchlist entry
-    let mut found_entry: Option<WatchlistEntry> = None;
-    for candidate in &candidates {
-        if let Some(entry) = watchlist
-            .find_one(doc! { "symbol": candidate }, None)
-            .await
-            .map_err(|e| format!("Database error: {e}"))?
-        {
-            found_entry = Some(entry);
-            break;
-        }
+    let service = DataFeedService::new().map_err(|e| CapitalApiError::Internal(e.to_string()))?;
+    let provider = provider_for_kind(&entry.kind);
+    let feed = DataFeed {
+        name: entry.name.clone(),
+        symbol: entry.feed_symbol.clone(),
+        categories: categories_for_kind(&entry.kind),
+        source: service.source_for(&provider, &entry.feed_symbol),
+        last_fetch: None,
+        metadata: metadata_from_pair_unit(&entry.pair, &entry.unit),
+    };
+
+    let inserted = service
+        .backfill_history(&db, &feed, entry.unit.clone(), days)
+        .await
+        .map_err(|e| match e {
+            DataFeedError::Offline => CapitalApiError::Validation(e.to_string()),
+            DataFeedError::Database(e) => CapitalApiError::from(e),
+            e => CapitalApiError::Upstream(e.to_string()),
+        })?;
+
+    Ok(Json(WatchlistBackfillResponse {
+        symbol: entry.symbol,
+        inserted,
+    }))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/capital/data/watchlist/{symbol}",
+    params(("symbol" = String, Path, description = "Symbol to update")),
+    request_body = UpdateWatchlistAssetRequest,
+    responses(
+        (status = 200, description = "Asset name updated", body = WatchlistAssetResponse),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "capital"
+)]
+pub async fn update_watchlist_asset(
+    State(state): State<Arc<AppState>>,
+    Path(symbol): Path<String>,
+    Json(req): Json<UpdateWatchlistAssetRequest>,
+) -> Result<Json<WatchlistAssetResponse>, String> {
+    println!("=== UPDATE WATCHLIST ASSET START ===");
+    println!("Symbol: {}, New name: {}", symbol, req.name);
+
+    if req.name.trim().is_empty() {
+        eprintln!("❌ Name is empty");
+        return Err("Name is required".to_string());
     }
 
-    let entry = found_entry.ok_or_else(|| {
-        eprintln!("❌ Asset '{}' not found in watchlist", trimmed);
-        format!("Asset '{}' not found in watchlist", trimmed)
-    })?;
+    let db = state.mongo_client.database("wyat");
+    let watchlist = db.collection::<WatchlistEntry>("capital_watchlist");
+    let feeds = db.collection::<DataFeed>("capital_data_feeds");
+    let trimmed = symbol.trim();
+
+    let entry = find_watchlist_entry(&watchlist, trimmed)
+        .await
+        .map_err(|e| format!("Database error: {e}"))?
+        .ok_or_else(|| {
+            eprintln!("❌ Asset '{}' not found in watchlist", trimmed);
+            format!("Asset '{}' not found in watchlist", trimmed)
+        })?;
 
     println!("Found entry with symbol: {}", entry.symbol);
 
@@ -3121,10 +6654,1881 @@ pub async fn update_watchlist_asset(
         .await
         .map_err(|e| format!("Database error: {e}"))?;
 
-    let response = build_watchlist_response(&updated_entry, &feed, snapshot_opt.as_ref());
+    let previous = snapshot_24h_before(&service, &db, snapshot_opt.as_ref()).await;
+    let response = build_watchlist_response(
+        &updated_entry,
+        &feed,
+        snapshot_opt.as_ref(),
+        previous.as_ref(),
+        service.is_offline(),
+    );
 
     println!("✅ Successfully updated {} to '{}'", entry.symbol, req.name);
     println!("=== UPDATE WATCHLIST ASSET END ===");
 
     Ok(Json(response))
 }
+
+// ------------------------- Price Alerts -------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertBound {
+    Above,
+    Below,
+}
+
+/// Result of checking one entry's thresholds against its latest value.
+#[derive(Debug, PartialEq)]
+pub struct AlertCheck {
+    pub fired: Option<AlertBound>,
+    pub last_alerted_value: Option<Decimal>,
+}
+
+/// Decide whether `latest` fires an alert. A bound fires once when crossed and
+/// stays quiet while the price remains beyond it; moving back between the
+/// bounds clears `last_alerted_value` and re-arms both.
+pub fn check_price_alert(
+    latest: Decimal,
+    alert_above: Option<Decimal>,
+    alert_below: Option<Decimal>,
+    last_alerted_value: Option<Decimal>,
+) -> AlertCheck {
+    let crossed = if alert_above.is_some_and(|above| latest >= above) {
+        Some(AlertBound::Above)
+    } else if alert_below.is_some_and(|below| latest <= below) {
+        Some(AlertBound::Below)
+    } else {
+        None
+    };
+
+    let Some(bound) = crossed else {
+        return AlertCheck {
+            fired: None,
+            last_alerted_value: None,
+        };
+    };
+
+    let already_alerted = last_alerted_value.is_some_and(|value| match bound {
+        AlertBound::Above => alert_above.is_some_and(|above| value >= above),
+        AlertBound::Below => alert_below.is_some_and(|below| value <= below),
+    });
+    if already_alerted {
+        AlertCheck {
+            fired: None,
+            last_alerted_value,
+        }
+    } else {
+        AlertCheck {
+            fired: Some(bound),
+            last_alerted_value: Some(latest),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct WatchlistAlert {
+    pub symbol: String,
+    pub name: String,
+    pub bound: AlertBound,
+    #[schema(value_type = String)]
+    pub threshold: Decimal,
+    #[schema(value_type = String)]
+    pub latest_value: Decimal,
+    pub unit: Option<String>,
+    #[schema(value_type = Option<i64>)]
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWatchlistAlertsRequest {
+    /// `null` clears the bound.
+    #[schema(value_type = Option<String>)]
+    pub alert_above: Option<Decimal>,
+    #[schema(value_type = Option<String>)]
+    pub alert_below: Option<Decimal>,
+}
+
+/// Watchlist entry by symbol, trying it as given, upper- and lower-cased.
+async fn find_watchlist_entry(
+    watchlist: &mongodb::Collection<WatchlistEntry>,
+    symbol: &str,
+) -> mongodb::error::Result<Option<WatchlistEntry>> {
+    let trimmed = symbol.trim();
+    for candidate in [
+        trimmed.to_string(),
+        trimmed.to_uppercase(),
+        trimmed.to_lowercase(),
+    ] {
+        if let Some(entry) = watchlist
+            .find_one(doc! { "symbol": &candidate }, None)
+            .await?
+        {
+            return Ok(Some(entry));
+        }
+    }
+    Ok(None)
+}
+
+#[utoipa::path(
+    patch,
+    path = "/capital/data/watchlist/{symbol}/alerts",
+    params(("symbol" = String, Path, description = "Watchlist symbol")),
+    request_body = UpdateWatchlistAlertsRequest,
+    responses(
+        (status = 200, description = "Thresholds updated", body = WatchlistEntry),
+        (status = 400, description = "Invalid thresholds"),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "capital"
+)]
+pub async fn update_watchlist_alerts(
+    State(state): State<Arc<AppState>>,
+    Path(symbol): Path<String>,
+    Json(req): Json<UpdateWatchlistAlertsRequest>,
+) -> Result<Json<WatchlistEntry>, CapitalApiError> {
+    if let (Some(above), Some(below)) = (req.alert_above, req.alert_below)
+        && above <= below
+    {
+        return Err(CapitalApiError::Validation(format!(
+            "alert_above ({}) must be greater than alert_below ({})",
+            above, below
+        )));
+    }
+
+    let db = state.mongo_client.database("wyat");
+    let watchlist = db.collection::<WatchlistEntry>("capital_watchlist");
+    let entry = find_watchlist_entry(&watchlist, &symbol)
+        .await?
+        .ok_or_else(|| {
+            CapitalApiError::NotFound(format!("Asset '{}' not found in watchlist", symbol.trim()))
+        })?;
+
+    let to_bson = |value: Option<Decimal>| value.map(|v| v.to_string());
+    // New thresholds re-arm the alert
+    let updated = watchlist
+        .find_one_and_update(
+            doc! { "symbol": &entry.symbol },
+            doc! { "$set": {
+                "alert_above": to_bson(req.alert_above),
+                "alert_below": to_bson(req.alert_below),
+                "last_alerted_value": Bson::Null,
+            }},
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await?
+        .ok_or_else(|| CapitalApiError::NotFound(format!("Asset '{}' not found", entry.symbol)))?;
+
+    Ok(Json(updated))
+}
+
+/// GET /capital/data/watchlist/alerts - Entries whose latest value crossed a threshold
+///
+/// Refreshes stale feeds for entries with thresholds, then reports each bound that
+/// newly fired. Firing records `last_alerted_value` so later polls stay quiet until
+/// the price moves back between the bounds. A failed refresh returns 502 rather
+/// than checking against stale prices.
+#[utoipa::path(
+    get,
+    path = "/capital/data/watchlist/alerts",
+    responses(
+        (status = 200, description = "Alerts that fired on this check", body = [WatchlistAlert]),
+        (status = 502, description = "A stale feed could not be refreshed")
+    ),
+    tag = "capital"
+)]
+pub async fn get_watchlist_alerts(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<WatchlistAlert>>, CapitalApiError> {
+    let service = DataFeedService::new().map_err(|e| CapitalApiError::Internal(e.to_string()))?;
+    let db = state.mongo_client.database("wyat");
+    let watchlist = db.collection::<WatchlistEntry>("capital_watchlist");
+    let feeds = db.collection::<DataFeed>("capital_data_feeds");
+
+    let filter = doc! { "$or": [
+        { "alert_above": { "$ne": null } },
+        { "alert_below": { "$ne": null } },
+    ]};
+    let entries: Vec<WatchlistEntry> = watchlist.find(filter, None).await?.try_collect().await?;
+
+    // Refresh stale feeds first (crypto shares one request)
+    let mut refreshes = Vec::new();
+    for entry in &entries {
+        if let Some(feed) = feeds
+            .find_one(doc! { "symbol": &entry.feed_symbol }, None)
+            .await?
+            && service.needs_refresh(&feed)
+        {
+            refreshes.push(FeedRefresh {
+                feed,
+                pair: entry.pair.clone(),
+                unit: entry.unit.clone(),
+            });
+        }
+    }
+    let results = service.fetch_and_store_snapshots(&db, &mut refreshes).await;
+    let mut failed = Vec::new();
+    for (refresh, result) in refreshes.iter().zip(results) {
+        if let Err(err) = result {
+            tracing::warn!(symbol = %refresh.feed.symbol, error = %err, "refreshing feed failed");
+            failed.push(format!("{}: {}", refresh.feed.symbol, err));
+        }
+    }
+    // Checking thresholds against stale prices could miss or misreport alerts
+    if !failed.is_empty() {
+        return Err(CapitalApiError::Upstream(format!(
+            "Failed to refresh feeds: {}",
+            failed.join("; ")
+        )));
+    }
+
+    let mut alerts = Vec::new();
+    for entry in entries {
+        let Some(snapshot) = service
+            .get_latest_snapshot(&db, &entry.feed_symbol)
+            .await
+            .map_err(|e| CapitalApiError::Internal(format!("Database error: {e}")))?
+        else {
+            continue;
+        };
+        let Some(data) = snapshot.data.first() else {
+            continue;
+        };
+
+        let check = check_price_alert(
+            data.value,
+            entry.alert_above,
+            entry.alert_below,
+            entry.last_alerted_value,
+        );
+        if check.last_alerted_value != entry.last_alerted_value {
+            watchlist
+                .update_one(
+                    doc! { "symbol": &entry.symbol },
+                    doc! { "$set": {
+                        "last_alerted_value": check.last_alerted_value.map(|v| v.to_string()),
+                    }},
+                    None,
+                )
+                .await?;
+        }
+
+        if let Some(bound) = check.fired {
+            let threshold = match bound {
+                AlertBound::Above => entry.alert_above,
+                AlertBound::Below => entry.alert_below,
+            }
+            .expect("fired bound is configured");
+            alerts.push(WatchlistAlert {
+                symbol: entry.symbol,
+                name: entry.name,
+                bound,
+                threshold,
+                latest_value: data.value,
+                unit: data.unit.clone(),
+                last_updated: snapshot.source_time.or(Some(snapshot.fetch_time)),
+            });
+        }
+    }
+
+    Ok(Json(alerts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn envelope(rollover: RolloverPolicy) -> Envelope {
+        Envelope {
+            id: "env_groceries".to_string(),
+            name: "Groceries".to_string(),
+            kind: EnvelopeKind::Variable,
+            status: EnvelopeStatus::Active,
+            funding: Some(FundingRule {
+                amount: Money::new(dec("500"), Currency::USD),
+                freq: FundingFreq::Monthly,
+            }),
+            rollover,
+            balance: Money::zero(Currency::USD),
+            period_limit: None,
+            last_period: None,
+            allow_negative: false,
+            min_balance: None,
+            deficit_policy: None,
+        }
+    }
+
+    #[test]
+    fn validate_accepts_well_formed_envelope() {
+        let env = envelope(RolloverPolicy::CarryOver {
+            cap: Some(Money::new(dec("1000"), Currency::USD)),
+        });
+        assert!(env.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_currency_mismatch() {
+        let mut env = envelope(RolloverPolicy::ResetToZero);
+        env.funding = Some(FundingRule {
+            amount: Money::new(dec("500"), Currency::HKD),
+            freq: FundingFreq::Monthly,
+        });
+        let errors = env.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("funding currency")));
+    }
+
+    #[test]
+    fn validate_rejects_negative_cap() {
+        let env = envelope(RolloverPolicy::SinkingFund {
+            cap: Some(Money::new(dec("-10"), Currency::USD)),
+        });
+        let errors = env.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("cap cannot be negative")));
+    }
+
+    #[test]
+    fn validate_rejects_decay_ratio_outside_unit_interval() {
+        for ratio in [dec("0"), dec("-0.5"), dec("1.01")] {
+            let env = envelope(RolloverPolicy::Decay {
+                keep_ratio: ratio,
+                cap: None,
+            });
+            assert!(
+                env.validate().is_err(),
+                "ratio {} should be rejected",
+                ratio
+            );
+        }
+        let env = envelope(RolloverPolicy::Decay {
+            keep_ratio: dec("1"),
+            cap: None,
+        });
+        assert!(env.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_min_balance_without_allow_negative() {
+        let mut env = envelope(RolloverPolicy::ResetToZero);
+        env.min_balance = Some(dec("-100"));
+        assert!(env.validate().is_err());
+        env.allow_negative = true;
+        assert!(env.validate().is_ok());
+    }
+
+    fn new_tx(ts: Option<i64>, date: Option<&str>) -> NewTransaction {
+        NewTransaction {
+            id: "tx_1".to_string(),
+            ts,
+            date: date.map(str::to_string),
+            posted_ts: None,
+            source: "manual".to_string(),
+            payee: None,
+            memo: None,
+            status: None,
+            reconciled: false,
+            external_refs: Vec::new(),
+            legs: Vec::new(),
+            tx_type: None,
+        }
+    }
+
+    #[test]
+    fn new_transaction_date_resolves_to_local_start_of_day() {
+        let tx = new_tx(None, Some("2025-03-15"));
+        // 2025-03-15 00:00 UTC
+        assert_eq!(tx.resolve_ts(Tz::UTC), Ok(1741996800));
+        // 2025-03-15 00:00 EDT (UTC-4)
+        let ny: Tz = "America/New_York".parse().unwrap();
+        assert_eq!(tx.resolve_ts(ny), Ok(1742011200));
+    }
+
+    #[test]
+    fn new_transaction_rejects_conflicting_ts_and_date() {
+        let day_start = 1741996800; // 2025-03-15 00:00 UTC
+        assert_eq!(
+            new_tx(Some(day_start + 3600), Some("2025-03-15")).resolve_ts(Tz::UTC),
+            Ok(day_start + 3600)
+        );
+        assert!(
+            new_tx(Some(day_start - 1), Some("2025-03-15"))
+                .resolve_ts(Tz::UTC)
+                .is_err()
+        );
+        assert!(new_tx(None, None).resolve_ts(Tz::UTC).is_err());
+    }
+
+    fn account(id: &str) -> Account {
+        Account {
+            id: id.to_string(),
+            name: format!("Account {}", id),
+            currency: Currency::USD,
+            metadata: AccountMetadata::Tagged {
+                account_type: "checking".to_string(),
+                color: String::new(),
+                data: serde_json::json!({ "account_number": "123456789" }),
+            },
+            group_id: None,
+            group_order: None,
+        }
+    }
+
+    #[test]
+    fn config_validate_reports_duplicates_and_invalid_envelopes() {
+        let mut bad_envelope = envelope(RolloverPolicy::ResetToZero);
+        bad_envelope.id = "env_bad".to_string();
+        bad_envelope.name = String::new();
+        let config = CapitalConfig {
+            version: CAPITAL_CONFIG_VERSION,
+            exported_at: None,
+            accounts: vec![account("acct_1"), account("acct_1")],
+            envelopes: vec![envelope(RolloverPolicy::ResetToZero), bad_envelope],
+            funds: Vec::new(),
+        };
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("acct_1") && errors[0].contains("duplicate"));
+        assert!(errors[1].starts_with("envelope 'env_bad'"));
+    }
+
+    fn config_fund(oid: &str) -> Fund {
+        serde_json::from_value(serde_json::json!({
+            "_id": { "$oid": oid },
+            "fund_id": "fund_btc",
+            "name": "Bitcoin",
+            "symbol": "BTC",
+            "assets": ["BTC"],
+            "purpose": "Long-term store of value",
+            "horizon_years": 10,
+            "discretionary_sales": false,
+            "denominated_in": "USD",
+            "max_pct_networth": 0.2,
+            "max_pct_liquid": 0.1,
+            "liquid": true,
+            "review_cadence": "quarterly",
+            "status": "active",
+            "created_at": { "$date": { "$numberLong": "1700000000000" } }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn config_round_trips_through_json() {
+        let fund = config_fund("65a000000000000000000001");
+        let config = CapitalConfig {
+            version: CAPITAL_CONFIG_VERSION,
+            exported_at: Some(1_700_000_000),
+            accounts: vec![account("acct_1")],
+            envelopes: vec![envelope(RolloverPolicy::ResetToZero)],
+            funds: vec![fund.clone()],
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: CapitalConfig = serde_json::from_str(&json).unwrap();
+
+        assert!(restored.validate().is_ok());
+        assert_eq!(restored.funds[0].id, fund.id);
+        assert_eq!(restored.funds[0].created_at, fund.created_at);
+        assert_eq!(restored.envelopes[0].id, "env_groceries");
+        assert!(json.contains("123456789"));
+    }
+
+    #[tokio::test]
+    async fn config_import_upserts_and_keeps_fund_object_id() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+        let config = |account_name: &str, fund_oid: &str| {
+            let mut acct = account("acct_1");
+            acct.name = account_name.to_string();
+            CapitalConfig {
+                version: CAPITAL_CONFIG_VERSION,
+                exported_at: None,
+                accounts: vec![acct],
+                envelopes: vec![envelope(RolloverPolicy::ResetToZero)],
+                funds: vec![config_fund(fund_oid)],
+            }
+        };
+
+        write_capital_config(&db, config("Checking", "65a000000000000000000001"), None)
+            .await
+            .unwrap();
+        write_capital_config(&db, config("Renamed", "65a000000000000000000002"), None)
+            .await
+            .unwrap();
+
+        let accounts: Vec<Account> = db
+            .collection::<Account>("capital_accounts")
+            .find(None, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].name, "Renamed");
+        let fund = db
+            .collection::<Fund>("capital_funds")
+            .find_one(doc! { "fund_id": "fund_btc" }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fund.id.to_hex(), "65a000000000000000000001");
+
+        db.drop(None).await.unwrap();
+    }
+
+    fn flat_row(txid: &str, kind: &str, asset: &str, tx_type: Option<&str>) -> FlatTransaction {
+        FlatTransaction {
+            txid: txid.to_string(),
+            date: "2025-03-15".to_string(),
+            posted_ts: None,
+            source: "test".to_string(),
+            payee: None,
+            memo: None,
+            account_id: "acct.test".to_string(),
+            direction: "Debit".to_string(),
+            kind: kind.to_string(),
+            ccy_or_asset: asset.to_string(),
+            amount_or_qty: 1.5,
+            price: None,
+            price_ccy: None,
+            category_id: None,
+            status: None,
+            tx_type: tx_type.map(str::to_string),
+            ext1_kind: None,
+            ext1_val: None,
+        }
+    }
+
+    /// Transaction built through the import path, so a single fiat row gets its
+    /// auto-balanced P&L leg. Starts from `flat_row`: 1.5 USD debited to `acct.test`.
+    struct TestTx(FlatTransaction);
+
+    fn test_tx(txid: &str) -> TestTx {
+        TestTx(flat_row(txid, "fiat", "USD", None))
+    }
+
+    impl TestTx {
+        fn crypto(mut self, asset: &str) -> Self {
+            self.0.kind = "crypto".to_string();
+            self.0.ccy_or_asset = asset.to_string();
+            self
+        }
+
+        fn tx_type(mut self, tx_type: &str) -> Self {
+            self.0.tx_type = Some(tx_type.to_string());
+            self
+        }
+
+        fn account(mut self, account_id: &str) -> Self {
+            self.0.account_id = account_id.to_string();
+            self
+        }
+
+        fn date(mut self, date: &str) -> Self {
+            self.0.date = date.to_string();
+            self
+        }
+
+        fn direction(mut self, direction: &str) -> Self {
+            self.0.direction = direction.to_string();
+            self
+        }
+
+        fn amount(mut self, amount_or_qty: f64) -> Self {
+            self.0.amount_or_qty = amount_or_qty;
+            self
+        }
+
+        /// Money out of the account, which the P&L leg books as envelope spend.
+        fn spend(self, amount: f64) -> Self {
+            self.direction("Credit").amount(amount)
+        }
+
+        fn envelope(mut self, category_id: &str) -> Self {
+            self.0.category_id = Some(category_id.to_string());
+            self
+        }
+
+        fn payee(mut self, payee: &str) -> Self {
+            self.0.payee = Some(payee.to_string());
+            self
+        }
+
+        fn try_build(self) -> Result<Transaction, String> {
+            flat_to_transaction(&self.0)
+        }
+
+        fn build(self) -> Transaction {
+            self.try_build().unwrap()
+        }
+
+        /// The row's header with `legs` in place of the imported ones.
+        fn with_legs(self, legs: Vec<Leg>) -> Transaction {
+            let mut tx = self.build();
+            tx.legs = legs;
+            tx.recompute_balance_state();
+            tx
+        }
+    }
+
+    #[test]
+    fn strict_import_rejects_unbalanced_rows() {
+        // Unpriced crypto legs are not valued, so the row counts as balanced.
+        let crypto = test_tx("tx_eth").crypto("ETH").build();
+        assert_eq!(crypto.ts, 1741996800);
+        assert_eq!(crypto.balance_state, BalanceState::Balanced);
+        assert!(check_import_balance(&crypto, true).is_ok());
+
+        // A single-leg transfer gets no P&L offset and awaits its counterpart.
+        let transfer = test_tx("tx_xfer").tx_type("transfer").build();
+        assert_eq!(transfer.balance_state, BalanceState::AwaitingTransferMatch);
+        assert!(check_import_balance(&transfer, false).is_ok());
+        let err = check_import_balance(&transfer, true).unwrap_err();
+        assert!(err.contains("AwaitingTransferMatch"));
+    }
+
+    #[test]
+    fn priced_crypto_import_values_leg_in_price_ccy() {
+        let mut row = flat_row("tx_eth_buy", "crypto", "ETH", None);
+        row.price = Some(2000.5);
+        row.price_ccy = Some("USD".to_string());
+
+        let tx = flat_to_transaction(&row).unwrap();
+        let leg = &tx.legs[0];
+        let fx = leg.fx.expect("priced crypto leg carries fx");
+        assert_eq!(fx.to, Currency::USD);
+        assert_eq!(fx.rate, dec("2000.5"));
+        assert_eq!(
+            leg.amount.valued_in(leg.fx),
+            Some(Money::new(dec("3000.75"), Currency::USD))
+        );
+
+        // Once valued, a lone crypto leg has no automatic offset.
+        assert_eq!(tx.balance_state, BalanceState::NeedsEnvelopeOffset);
+        assert!(check_import_balance(&tx, true).is_err());
+
+        row.price_ccy = Some("JPY".to_string());
+        let err = flat_to_transaction(&row).unwrap_err();
+        assert!(err.contains("unsupported price ccy 'JPY'"));
+    }
+
+    #[test]
+    fn multi_leg_fx_trade_imports_as_one_balanced_transaction() {
+        let leg = |account: &str, direction: &str, kind: &str, asset: &str, qty: f64| FlatLeg {
+            account_id: account.to_string(),
+            direction: direction.to_string(),
+            kind: kind.to_string(),
+            ccy_or_asset: asset.to_string(),
+            amount_or_qty: qty,
+            price: None,
+            price_ccy: None,
+            category_id: None,
+        };
+        let mut buy_btc = leg("acct.exchange_btc", "Debit", "crypto", "BTC", 0.02);
+        buy_btc.price = Some(50000.0);
+        buy_btc.price_ccy = Some("USD".to_string());
+        let row = MultiLegTransaction {
+            txid: "tx_trade".to_string(),
+            date: "2025-03-15".to_string(),
+            posted_ts: None,
+            source: "test".to_string(),
+            payee: None,
+            memo: None,
+            status: None,
+            tx_type: Some("trade".to_string()),
+            ext1_kind: None,
+            ext1_val: None,
+            legs: vec![
+                leg("acct.exchange_usd", "Credit", "fiat", "USD", 1000.0),
+                buy_btc,
+            ],
+        };
+
+        let tx = multi_leg_to_transaction(&row).unwrap();
+        assert_eq!(tx.id, "tx_trade");
+        assert_eq!(tx.legs.len(), 2);
+        assert_eq!(tx.balance_state, BalanceState::Balanced);
+        assert!(check_import_balance(&tx, true).is_ok());
+
+        let empty = MultiLegTransaction {
+            legs: Vec::new(),
+            ..row
+        };
+        assert!(multi_leg_to_transaction(&empty).is_err());
+    }
+
+    #[test]
+    fn ledger_csv_has_one_row_per_leg_under_the_header() {
+        let tx = test_tx("tx_lunch")
+            .tx_type("spending")
+            .spend(12.5)
+            .payee("Cafe, Ltd")
+            .envelope("env_dining")
+            .build();
+
+        let header = String::from_utf8(ledger_csv_header().unwrap()).unwrap();
+        assert_eq!(
+            header,
+            "date,txid,account_id,direction,kind,ccy_or_asset,amount_or_qty,category_id,payee,memo,tx_type\n"
+        );
+
+        let csv = String::from_utf8(ledger_csv_rows(&tx, Tz::UTC).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), tx.legs.len());
+        assert_eq!(
+            lines[0],
+            "2025-03-15,tx_lunch,acct.test,Credit,fiat,USD,12.5,,\"Cafe, Ltd\",,spending"
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "2025-03-15,tx_lunch,{},Debit,fiat,USD,12.5,env_dining,\"Cafe, Ltd\",,spending",
+                PNL_ACCOUNT_ID
+            )
+        );
+        // The date is the local calendar day of `ts`
+        let ny: Tz = "America/New_York".parse().unwrap();
+        assert_eq!(ledger_export_rows(&tx, ny)[0].date, "2025-03-14");
+    }
+
+    #[test]
+    fn outstanding_reimbursements_skip_settled_and_net_refunds() {
+        // Paying from the account credits it and debits P&L (spend).
+        let dinner = test_tx("tx_dinner").spend(80.0).build();
+        // Money back into the account credits P&L.
+        let refund = test_tx("tx_refund").direction("Debit").amount(20.0).build();
+        let mut settled = dinner.clone();
+        settled.id = "tx_settled".to_string();
+        settled.reimbursement = Some(ReimbursementSettlement {
+            settled_by_tx: "tx_repaid".to_string(),
+            settled_at: 0,
+        });
+
+        assert_eq!(dinner.legs[1].account_id, PNL_ACCOUNT_ID);
+        assert_eq!(
+            reimbursable_amounts(&dinner),
+            vec![Money::new(dec("80"), Currency::USD)]
+        );
+        assert_eq!(
+            total_outstanding(&[dinner, refund, settled]),
+            vec![Money::new(dec("60"), Currency::USD)]
+        );
+    }
+
+    #[test]
+    fn external_ref_filter_matches_stored_pair() {
+        let refs = vec![
+            ("tx_hash".to_string(), "0xabc".to_string()),
+            ("extraction_run".to_string(), "run_1".to_string()),
+        ];
+        let stored = bson::to_bson(&refs).unwrap();
+        let filter = external_ref_filter("tx_hash", "0xabc");
+        let wanted = filter.get("external_refs").unwrap();
+
+        assert!(stored.as_array().unwrap().contains(wanted));
+        assert!(
+            !stored.as_array().unwrap().contains(
+                external_ref_filter("tx_hash", "run_1")
+                    .get("external_refs")
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn batch_import_summary_ids_match_counts() {
+        let mut summary = BatchImportResponse::default();
+        summary.record_imported("tx_1");
+        summary.record_skipped("tx_2", None);
+        summary.record_imported("tx_3");
+        summary.record_skipped("tx_4", Some("invalid direction 'Up'".to_string()));
+
+        assert_eq!(summary.imported, summary.imported_ids.len());
+        assert_eq!(summary.skipped, summary.skipped_ids.len());
+        assert_eq!(summary.imported_ids, vec!["tx_1", "tx_3"]);
+        assert_eq!(summary.skipped_ids, vec!["tx_2", "tx_4"]);
+        assert_eq!(summary.errors, vec!["tx_4: invalid direction 'Up'"]);
+    }
+
+    #[test]
+    fn signed_amounts_flip_direction_of_negative_rows() {
+        let mut debit = flat_row("tx_neg", "fiat", "USD", None);
+        debit.amount_or_qty = -42.5;
+        let note = apply_signed_amount(&mut debit).unwrap();
+        assert_eq!(debit.direction, "Credit");
+        assert_eq!(debit.amount_or_qty, 42.5);
+        assert!(note.starts_with("tx_neg: amount -42.5 with direction Debit"));
+
+        let mut positive = flat_row("tx_pos", "fiat", "USD", None);
+        assert!(apply_signed_amount(&mut positive).is_none());
+        assert_eq!(positive.direction, "Debit");
+        assert_eq!(positive.amount_or_qty, 1.5);
+    }
+
+    #[tokio::test]
+    async fn dry_run_batch_import_writes_nothing() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let request = BatchImportRequest {
+            transactions: vec![
+                flat_row("tx_a", "fiat", "USD", None),
+                flat_row("tx_b", "crypto", "ETH", None),
+            ],
+            dry_run: true,
+            ..Default::default()
+        };
+        let summary = process_batch_import(&db, request).await.unwrap();
+
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.preview.len(), 2);
+        assert_eq!(summary.preview[0].balance_state, BalanceState::Balanced);
+        let stored = db
+            .collection::<Transaction>("capital_ledger")
+            .count_documents(None, None)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn strict_batch_import_reports_unbalanced_crypto_row() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        // Pricing the second row values its lone leg, which leaves it unbalanced.
+        let mut priced = flat_row("tx_eth_priced", "crypto", "ETH", None);
+        priced.price = Some(2000.0);
+        priced.price_ccy = Some("USD".to_string());
+        let request = BatchImportRequest {
+            transactions: vec![flat_row("tx_eth", "crypto", "ETH", None), priced],
+            require_balanced: true,
+            ..Default::default()
+        };
+        let summary = process_batch_import(&db, request).await.unwrap();
+
+        assert_eq!(summary.imported_ids, vec!["tx_eth"]);
+        assert_eq!(summary.skipped_ids, vec!["tx_eth_priced"]);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0].starts_with("tx_eth_priced: not balanced"));
+        assert!(summary.errors[0].contains("NeedsEnvelopeOffset"));
+        let stored: Vec<Transaction> = db
+            .collection::<Transaction>("capital_ledger")
+            .find(None, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, "tx_eth");
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn percent_change_is_computed_on_decimals() {
+        // f64 can't tell these apart at this magnitude
+        let previous = dec("98765432109876.54321");
+        let current = dec("98765432109876.54322");
+        assert!(percent_change(current, previous).unwrap() > 0.0);
+
+        assert_eq!(percent_change(dec("110"), dec("100")), Some(10.0));
+        assert_eq!(percent_change(dec("90"), dec("100")), Some(-10.0));
+        assert_eq!(percent_change(dec("1"), Decimal::ZERO), None);
+    }
+
+    #[test]
+    fn cycle_time_match_uses_posted_ts_with_ts_fallback() {
+        let stage = cycle_time_match(100, 200);
+        let expected = doc! {
+            "$expr": {
+                "$and": [
+                    { "$gte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, 100_i64 ] },
+                    { "$lte": [ { "$ifNull": [ "$posted_ts", "$ts" ] }, 200_i64 ] }
+                ]
+            }
+        };
+        assert_eq!(stage, expected);
+        assert_eq!(stage.keys().collect::<Vec<_>>(), vec!["$expr"]);
+    }
+
+    #[tokio::test]
+    async fn api_errors_render_status_and_json_body() {
+        let cases = [
+            (
+                CapitalApiError::NotFound("missing".into()),
+                404,
+                "not_found",
+            ),
+            (CapitalApiError::Validation("bad".into()), 400, "validation"),
+            (CapitalApiError::Conflict("dup".into()), 409, "conflict"),
+            (CapitalApiError::Upstream("feed".into()), 502, "upstream"),
+            (CapitalApiError::Internal("boom".into()), 500, "internal"),
+        ];
+        for (err, status, code) in cases {
+            let message = err.to_string();
+            let response = err.into_response();
+            assert_eq!(response.status().as_u16(), status);
+
+            let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(body, serde_json::json!({ "error": message, "code": code }));
+        }
+    }
+
+    fn statement(opening: &str, closing: &str) -> Statement {
+        Statement {
+            id: "stmt_2025_01".to_string(),
+            account_id: "acct.chase_checking".to_string(),
+            period_start: 1_735_689_600,
+            period_end: 1_738_367_999,
+            opening_balance: Money::new(dec(opening), Currency::USD),
+            closing_balance: Money::new(dec(closing), Currency::USD),
+            raw_ref: None,
+        }
+    }
+
+    #[test]
+    fn statement_reconciles_within_tolerance() {
+        let stmt = statement("1000.00", "1250.50");
+        let result = compare_statement(
+            &stmt,
+            dec("1000"),
+            dec("1250.505"),
+            statement_tolerance(Currency::USD),
+        );
+
+        assert!(result.opening_matches);
+        assert!(result.matches);
+        assert_eq!(
+            result.closing_delta,
+            Money::new(dec("0.005"), Currency::USD)
+        );
+    }
+
+    #[test]
+    fn statement_mismatch_reports_delta() {
+        let stmt = statement("1000.00", "1250.50");
+        let result = compare_statement(
+            &stmt,
+            dec("1000"),
+            dec("1200.50"),
+            statement_tolerance(Currency::USD),
+        );
+
+        assert!(result.opening_matches);
+        assert!(!result.matches);
+        assert_eq!(
+            result.closing_delta,
+            Money::new(dec("-50.00"), Currency::USD)
+        );
+        assert_eq!(result.computed_closing.amount, dec("1200.50"));
+    }
+
+    #[test]
+    fn btc_statement_tolerance_is_one_satoshi() {
+        let mut stmt = statement("0.5", "0.75");
+        stmt.opening_balance.ccy = Currency::BTC;
+        stmt.closing_balance.ccy = Currency::BTC;
+        let tolerance = statement_tolerance(Currency::BTC);
+        assert_eq!(tolerance, dec("0.00000001"));
+
+        // Off by 0.005 BTC would pass a cent-sized tolerance
+        let result = compare_statement(&stmt, dec("0.5"), dec("0.745"), tolerance);
+        assert!(!result.matches);
+        let result = compare_statement(&stmt, dec("0.5"), dec("0.75000001"), tolerance);
+        assert!(result.matches);
+    }
+
+    #[test]
+    fn ledger_statement_covers_the_cycle_in_account_currency() {
+        let account = Account {
+            id: "acct.cold_wallet".to_string(),
+            name: "Cold Wallet".to_string(),
+            currency: Currency::BTC,
+            metadata: AccountMetadata::CryptoWallet {
+                address: "bc1q".to_string(),
+                network: AccountNetwork::Bitcoin,
+                is_ledger: true,
+                color: None,
+                txid_prefix: None,
+            },
+            group_id: None,
+            group_order: None,
+        };
+        let (start, end) = cycle_bounds_for_label("2025-03", DEFAULT_CYCLE_START_DAY).unwrap();
+
+        let stmt = ledger_statement(&account, "2025-03", start, end, dec("0.5"), dec("0.75"));
+
+        assert_eq!(stmt.id, "stmt.acct.cold_wallet.2025-03");
+        assert_eq!((stmt.period_start, stmt.period_end), (start, end));
+        assert_eq!(stmt.opening_balance, Money::new(dec("0.5"), Currency::BTC));
+        assert_eq!(stmt.closing_balance, Money::new(dec("0.75"), Currency::BTC));
+    }
+
+    #[test]
+    fn bulk_reclassify_matches_payee_and_skips_transfers() {
+        let coffee = test_tx("tx_coffee").payee("STARBUCKS #1234").build();
+        let lunch = test_tx("tx_lunch").payee("Chipotle").build();
+        let transfer = test_tx("tx_xfer")
+            .tx_type("transfer")
+            .payee("Starbucks card reload")
+            .build();
+        let no_payee = test_tx("tx_none").build();
+
+        let pattern = regex::Regex::new("(?i)starbucks").unwrap();
+        let (ids, skipped) = select_bulk_reclassify(&[coffee, lunch, transfer, no_payee], &pattern);
+
+        assert_eq!(ids, vec!["tx_coffee".to_string()]);
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn price_alert_fires_once_per_crossing() {
+        let above = Some(dec("70000"));
+        let below = Some(dec("50000"));
+
+        // Inside the band: nothing fires
+        let quiet = check_price_alert(dec("60000"), above, below, None);
+        assert_eq!(quiet.fired, None);
+        assert_eq!(quiet.last_alerted_value, None);
+
+        // Crossing up fires and records the value
+        let up = check_price_alert(dec("71000"), above, below, None);
+        assert_eq!(up.fired, Some(AlertBound::Above));
+        assert_eq!(up.last_alerted_value, Some(dec("71000")));
+
+        // Staying above does not re-fire
+        let still_up = check_price_alert(dec("72000"), above, below, up.last_alerted_value);
+        assert_eq!(still_up.fired, None);
+        assert_eq!(still_up.last_alerted_value, Some(dec("71000")));
+
+        // Dropping straight through the lower bound fires the other side
+        let down = check_price_alert(dec("49000"), above, below, still_up.last_alerted_value);
+        assert_eq!(down.fired, Some(AlertBound::Below));
+
+        // Back inside re-arms; the next crossing fires again
+        let rearmed = check_price_alert(dec("60000"), above, below, down.last_alerted_value);
+        assert_eq!(rearmed.last_alerted_value, None);
+        let again = check_price_alert(dec("48000"), above, below, rearmed.last_alerted_value);
+        assert_eq!(again.fired, Some(AlertBound::Below));
+    }
+
+    fn new_fund(fund_id: &str) -> NewFund {
+        NewFund {
+            fund_id: fund_id.to_string(),
+            name: "Long-term BTC".to_string(),
+            symbol: "LTB".to_string(),
+            assets: vec!["BTC".to_string()],
+            purpose: "Store of value".to_string(),
+            horizon_years: 10,
+            discretionary_sales: false,
+            acquisition_policy: None,
+            yield_policy: None,
+            denominated_in: Currency::USD,
+            balancing_policy: None,
+            multiplier_rules: None,
+            max_pct_networth: 0.3,
+            max_pct_liquid: 0.5,
+            liquid: true,
+            review_cadence: "quarterly".to_string(),
+            status: "active".to_string(),
+        }
+    }
+
+    #[test]
+    fn new_fund_rejects_caps_outside_unit_range() {
+        assert!(new_fund("fund.btc").validate().is_ok());
+
+        let mut over = new_fund("fund.btc");
+        over.max_pct_networth = 1.5;
+        assert!(over.validate().is_err());
+
+        let mut negative = new_fund("fund.btc");
+        negative.max_pct_liquid = -0.1;
+        assert!(negative.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn create_fund_rejects_duplicate_fund_id() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let created = insert_fund(&db, new_fund("fund.btc")).await.unwrap();
+        assert_eq!(created.fund_id, "fund.btc");
+        assert!(created.created_at > 0);
+        assert_eq!(created.created_at, created.updated_at);
+
+        let err = insert_fund(&db, new_fund("fund.btc")).await.unwrap_err();
+        assert!(matches!(err, CapitalApiError::Conflict(_)));
+        let stored = db
+            .collection::<Fund>("capital_funds")
+            .count_documents(None, None)
+            .await
+            .unwrap();
+        assert_eq!(stored, 1);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn fund_over_networth_cap_is_flagged() {
+        let prices = std::collections::HashMap::from([
+            ("BTC".to_string(), dec("60000")),
+            ("USD".to_string(), Decimal::ONE),
+        ]);
+        // An emptied holding with no price isn't worth reporting
+        let (fund_value, unpriced) = value_holdings(
+            [
+                ("BTC", dec("0.5")),
+                ("USD", dec("10000")),
+                ("PEPE", dec("1000000")),
+                ("DOGE", Decimal::ZERO),
+            ],
+            &prices,
+        );
+        assert_eq!(fund_value, dec("40000"));
+        assert_eq!(unpriced, vec!["PEPE".to_string()]);
+
+        // 40k of 100k net worth breaches a 30% cap; 40k of 160k liquid stays under 50%
+        let fund = new_fund("fund.btc").into_fund(mongodb::bson::DateTime::now());
+        let check = fund_compliance(
+            &fund,
+            Currency::USD,
+            fund_value,
+            dec("100000"),
+            dec("160000"),
+        );
+        assert!((check.pct_networth - 0.4).abs() < 1e-9);
+        assert!((check.pct_liquid - 0.25).abs() < 1e-9);
+        assert!(check.over_networth_cap);
+        assert!(!check.over_liquid_cap);
+    }
+
+    #[test]
+    fn convert_ccy_routes_through_usd() {
+        let btc_usd = Some(dec("60000"));
+        assert_eq!(
+            convert_ccy(dec("78"), Currency::HKD, Currency::USD, None),
+            Some(dec("10"))
+        );
+        assert_eq!(
+            convert_ccy(dec("30000"), Currency::USD, Currency::BTC, btc_usd),
+            Some(dec("0.5"))
+        );
+        assert_eq!(
+            convert_ccy(Decimal::ONE, Currency::BTC, Currency::USD, None),
+            None
+        );
+    }
+
+    #[test]
+    fn reporting_ccy_needs_a_usd_rate() {
+        for ccy in [Currency::USD, Currency::HKD, Currency::BTC] {
+            assert!(check_reporting_ccy(ccy).is_ok(), "{:?}", ccy);
+        }
+        for ccy in [Currency::EUR, Currency::GBP] {
+            assert!(matches!(
+                check_reporting_ccy(ccy),
+                Err(CapitalApiError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn currency_codes_round_trip() {
+        for ccy in [
+            Currency::USD,
+            Currency::HKD,
+            Currency::BTC,
+            Currency::EUR,
+            Currency::GBP,
+        ] {
+            assert_eq!(Currency::from_code(ccy.code()), Some(ccy));
+            let json = serde_json::to_string(&ccy).unwrap();
+            assert_eq!(json, format!("\"{}\"", ccy.code()));
+        }
+        assert_eq!(Currency::from_code("eur"), None);
+        assert_eq!(
+            convert_ccy(Decimal::ONE, Currency::EUR, Currency::USD, None),
+            None
+        );
+    }
+
+    #[test]
+    fn envelope_alerts_only_for_spend_over_limit() {
+        let mut over = envelope(RolloverPolicy::ResetToZero);
+        over.period_limit = Some(Money::new(dec("400"), Currency::USD));
+        let alert = envelope_limit_breach(&over, "2025-10", dec("500")).unwrap();
+        assert_eq!(alert.limit.amount, dec("400"));
+        assert_eq!(alert.over_by.amount, dec("100"));
+        assert!((alert.percent - 1.25).abs() < 1e-9);
+
+        // No explicit limit: the 500 funding budget applies
+        let under = envelope(RolloverPolicy::ResetToZero);
+        assert!(envelope_limit_breach(&under, "2025-10", dec("450")).is_none());
+        assert!(envelope_limit_breach(&under, "2025-10", dec("500")).is_none());
+
+        let mut inactive = over.clone();
+        inactive.status = EnvelopeStatus::Inactive;
+        assert!(envelope_limit_breach(&inactive, "2025-10", dec("500")).is_none());
+    }
+
+    #[tokio::test]
+    async fn batched_envelope_usage_matches_per_envelope_sums() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let spend = |txid: &str, envelope_id: &str, direction: &str, amount: f64| {
+            test_tx(txid)
+                .envelope(envelope_id)
+                .direction(direction)
+                .amount(amount)
+                .build()
+        };
+        let ledger = db.collection::<Transaction>("capital_ledger");
+        ledger
+            .insert_many(
+                vec![
+                    spend("tx_1", "env_groceries", "Credit", 120.0),
+                    spend("tx_2", "env_groceries", "Debit", 30.0),
+                    spend("tx_3", "env_transport", "Credit", 45.5),
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let (start_ts, end_ts) =
+            cycle_bounds_for_label("2025-03", DEFAULT_CYCLE_START_DAY).unwrap();
+        let ids = vec![
+            "env_groceries".to_string(),
+            "env_transport".to_string(),
+            "env_empty".to_string(),
+        ];
+        let batched = envelope_spent_by_category(&db, &ids, start_ts, end_ts)
+            .await
+            .unwrap();
+
+        for id in &ids {
+            let single = envelope_spent(&db, id, Currency::USD, start_ts, end_ts, "2025-03").await;
+            let from_batch = batched
+                .get(&(id.clone(), "USD".to_string()))
+                .copied()
+                .unwrap_or(Decimal::ZERO);
+            assert_eq!(from_batch, single, "{id}");
+        }
+        assert_eq!(
+            batched[&("env_groceries".to_string(), "USD".to_string())],
+            dec("90")
+        );
+
+        db.drop(None).await.unwrap();
+    }
+
+    fn utc(date: &str, time: &str) -> i64 {
+        chrono::NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn cycle_start_day_one_covers_the_calendar_month() {
+        assert_eq!(
+            cycle_bounds_for_label("2025-02", 1),
+            Some((utc("2025-02-01", "00:00:00"), utc("2025-02-28", "23:59:59")))
+        );
+        assert_eq!(
+            cycle_bounds_for_label("2025-12", 1),
+            Some((utc("2025-12-01", "00:00:00"), utc("2025-12-31", "23:59:59")))
+        );
+
+        let (_, _, label) = active_cycle_bounds(utc("2025-03-01", "00:00:00"), 1);
+        assert_eq!(label, "2025-03");
+    }
+
+    #[test]
+    fn cycle_start_day_fifteen_spans_two_months() {
+        assert_eq!(
+            cycle_bounds_for_label("2025-03", 15),
+            Some((utc("2025-03-15", "00:00:00"), utc("2025-04-14", "23:59:59")))
+        );
+
+        // Before the 15th we're still in the previous month's cycle, across year ends too
+        let (_, _, label) = active_cycle_bounds(utc("2026-01-14", "12:00:00"), 15);
+        assert_eq!(label, "2025-12");
+        let (_, _, label) = active_cycle_bounds(utc("2026-01-15", "00:00:00"), 15);
+        assert_eq!(label, "2026-01");
+    }
+
+    #[test]
+    fn cycle_start_day_thirty_one_clamps_to_short_months() {
+        // January's cycle ends just before February's clamped start on the 28th
+        assert_eq!(
+            cycle_bounds_for_label("2025-01", 31),
+            Some((utc("2025-01-31", "00:00:00"), utc("2025-02-27", "23:59:59")))
+        );
+        assert_eq!(
+            cycle_bounds_for_label("2025-02", 31),
+            Some((utc("2025-02-28", "00:00:00"), utc("2025-03-30", "23:59:59")))
+        );
+        // Leap year
+        assert_eq!(
+            cycle_bounds_for_label("2024-02", 31).map(|(start, _)| start),
+            Some(utc("2024-02-29", "00:00:00"))
+        );
+
+        let (_, _, label) = active_cycle_bounds(utc("2025-03-30", "23:59:59"), 31);
+        assert_eq!(label, "2025-02");
+    }
+
+    #[test]
+    fn default_cycle_start_day_keeps_tenth_to_ninth() {
+        assert_eq!(
+            cycle_bounds_for_label("2025-10", DEFAULT_CYCLE_START_DAY),
+            Some((utc("2025-10-10", "00:00:00"), utc("2025-11-09", "23:59:59")))
+        );
+    }
+
+    #[test]
+    fn refund_is_money_in_and_credits_pnl() {
+        let tx = test_tx("tx_refund")
+            .tx_type("refund")
+            .direction("Debit")
+            .envelope("env_groceries")
+            .build();
+        assert_eq!(tx.legs.len(), 2);
+        assert_eq!(tx.legs[0].direction, LegDirection::Debit);
+        assert_eq!(tx.legs[1].account_id, PNL_ACCOUNT_ID);
+        assert_eq!(tx.legs[1].direction, LegDirection::Credit);
+        assert_eq!(tx.legs[1].category_id.as_deref(), Some("env_groceries"));
+        assert_eq!(tx.balance_state, BalanceState::Balanced);
+
+        // Credited to the account it would read as spend, so it is rejected as sent
+        let err = test_tx("tx_refund")
+            .tx_type("refund")
+            .direction("Credit")
+            .try_build()
+            .unwrap_err();
+        assert!(err.contains("must be money in"), "{err}");
+
+        // Non-refunds keep the reported direction
+        let tx = test_tx("tx_spend")
+            .tx_type("spending")
+            .direction("Credit")
+            .build();
+        assert_eq!(tx.legs[0].direction, LegDirection::Credit);
+        assert_eq!(tx.legs[1].direction, LegDirection::Debit);
+    }
+
+    #[tokio::test]
+    async fn imported_refund_lowers_envelope_spent_and_raises_balance() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let row = |txid: &str, direction: &str, amount: f64, tx_type: &str| {
+            test_tx(txid)
+                .tx_type(tx_type)
+                .direction(direction)
+                .amount(amount)
+                .envelope("env_groceries")
+        };
+        let (start_ts, end_ts) =
+            cycle_bounds_for_label("2025-03", DEFAULT_CYCLE_START_DAY).unwrap();
+        let totals = || async {
+            let spent = envelope_spent(
+                &db,
+                "env_groceries",
+                Currency::USD,
+                start_ts,
+                end_ts,
+                "2025-03",
+            )
+            .await;
+            let balance = sum_as_of(&db, "acct.test", "USD", end_ts).await.unwrap();
+            (spent, balance)
+        };
+
+        let ledger = db.collection::<Transaction>("capital_ledger");
+        ledger
+            .insert_one(
+                row("tx_groceries", "Credit", 120.0, "spending").build(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(totals().await, (dec("120"), dec("-120")));
+
+        ledger
+            .insert_one(row("tx_refund", "Debit", 30.0, "refund").build(), None)
+            .await
+            .unwrap();
+        assert_eq!(totals().await, (dec("90"), dec("-90")));
+
+        // A refund credited to the account never reaches the ledger
+        assert!(
+            row("tx_bad_refund", "Credit", 30.0, "refund")
+                .try_build()
+                .is_err()
+        );
+        assert_eq!(totals().await, (dec("90"), dec("-90")));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn deleted_transactions_are_left_out_of_envelope_usage() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let spend = |txid: &str, amount: f64| {
+            test_tx(txid)
+                .spend(amount)
+                .envelope("env_groceries")
+                .build()
+        };
+        let mut deleted = spend("tx_deleted", 500.0);
+        deleted.deleted = true;
+        deleted.deleted_at = Some(1_742_000_000);
+        db.collection::<Transaction>("capital_ledger")
+            .insert_many(vec![spend("tx_live", 40.0), deleted], None)
+            .await
+            .unwrap();
+
+        let (start_ts, end_ts) =
+            cycle_bounds_for_label("2025-03", DEFAULT_CYCLE_START_DAY).unwrap();
+        let single = envelope_spent(
+            &db,
+            "env_groceries",
+            Currency::USD,
+            start_ts,
+            end_ts,
+            "2025-03",
+        )
+        .await;
+        assert_eq!(single, dec("40"));
+
+        let batched =
+            envelope_spent_by_category(&db, &["env_groceries".to_string()], start_ts, end_ts)
+                .await
+                .unwrap();
+        assert_eq!(
+            batched[&("env_groceries".to_string(), "USD".to_string())],
+            dec("40")
+        );
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn recompute_corrects_externally_mutated_balance_state() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+        let ledger = db.collection::<Transaction>("capital_ledger");
+
+        let tx = test_tx("tx_coffee").build();
+        assert_eq!(tx.balance_state, BalanceState::Balanced);
+        ledger.insert_one(&tx, None).await.unwrap();
+
+        // Someone drops the offsetting leg by hand; the stored state is now stale
+        ledger
+            .update_one(
+                doc! { "id": "tx_coffee" },
+                doc! { "$pop": { "legs": 1 } },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let summary = recompute_stored_balance_states(&db, live_transactions())
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            RecomputeBalanceStateResponse {
+                scanned: 1,
+                changed: 1
+            }
+        );
+        let stored = ledger
+            .find_one(doc! { "id": "tx_coffee" }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.balance_state, BalanceState::NeedsEnvelopeOffset);
+
+        // A second pass has nothing left to fix
+        let again = recompute_stored_balance_states(&db, live_transactions())
+            .await
+            .unwrap();
+        assert_eq!(again.changed, 0);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn usd_for_btc_trade_balances_in_btc_with_rates() {
+        let leg = |direction, amount| Leg {
+            account_id: "acct.exchange".to_string(),
+            direction,
+            amount,
+            fx: None,
+            category_id: None,
+            fee_of_leg_idx: None,
+            notes: None,
+        };
+        let mut trade = test_tx("tx_buy_btc").tx_type("trade").with_legs(vec![
+            leg(
+                LegDirection::Credit,
+                LegAmount::Fiat(Money::new(dec("30000"), Currency::USD)),
+            ),
+            leg(
+                LegDirection::Debit,
+                LegAmount::Crypto {
+                    asset: "BTC".to_string(),
+                    qty: dec("0.5"),
+                },
+            ),
+        ]);
+
+        // Without a USD rate the USD leg can't be valued in BTC
+        let (balanced, _) = trade.is_balanced_in(Currency::BTC);
+        assert!(!balanced);
+
+        let rates = HashMap::from([(Currency::USD, dec("1") / dec("60000"))]);
+        let (balanced, net) = trade.is_balanced_with_rates(Currency::BTC, &rates);
+        assert!(balanced, "net {:?}", net);
+        assert_eq!(net.ccy, Currency::BTC);
+
+        // 0.005 BTC short is far more than a cent's worth of slack
+        let mut short = trade.clone();
+        short.legs[1].amount = LegAmount::Crypto {
+            asset: "BTC".to_string(),
+            qty: dec("0.495"),
+        };
+        let (balanced, net) = short.is_balanced_with_rates(Currency::BTC, &rates);
+        assert!(!balanced, "net {:?}", net);
+
+        // A per-leg snapshot still wins over the table
+        trade.legs[0].fx = Some(FxSnapshot {
+            to: Currency::BTC,
+            rate: dec("1") / dec("50000"),
+        });
+        let (balanced, _) = trade.is_balanced_with_rates(Currency::BTC, &rates);
+        assert!(!balanced);
+    }
+
+    #[tokio::test]
+    async fn transaction_resolves_by_external_ref_value() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let mut tx = test_tx("tx_onchain").build();
+        tx.external_refs = vec![
+            ("statement".to_string(), "stmt_2025_03".to_string()),
+            ("tx_hash".to_string(), "0xabc123".to_string()),
+        ];
+        db.collection::<Transaction>("capital_ledger")
+            .insert_one(&tx, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find_transaction(&db, "tx_onchain").await.unwrap().id,
+            "tx_onchain"
+        );
+        assert_eq!(
+            find_transaction(&db, "0xabc123").await.unwrap().id,
+            "tx_onchain"
+        );
+        // Ref kinds are not values
+        assert!(matches!(
+            find_transaction(&db, "tx_hash").await,
+            Err(CapitalApiError::NotFound(_))
+        ));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn deleted_transaction_is_not_found_by_id_or_ref() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let mut tx = test_tx("tx_deleted").build();
+        tx.external_refs = vec![("tx_hash".to_string(), "0xdead".to_string())];
+        tx.deleted = true;
+        tx.deleted_at = Some(1_700_000_000);
+        db.collection::<Transaction>("capital_ledger")
+            .insert_one(&tx, None)
+            .await
+            .unwrap();
+
+        for lookup in ["tx_deleted", "0xdead"] {
+            assert!(
+                matches!(
+                    find_transaction(&db, lookup).await,
+                    Err(CapitalApiError::NotFound(_))
+                ),
+                "{} resolved to a deleted transaction",
+                lookup
+            );
+        }
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn account_ledger_for_unknown_account_is_not_found() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let result = account_ledger(&db, "acct.missing", Some("2025-03"), 0).await;
+        assert!(matches!(result, Err(CapitalApiError::NotFound(_))));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn crypto_account_statement_and_ledger_sum_asset_legs() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let wallet = Account {
+            currency: Currency::BTC,
+            metadata: AccountMetadata::CryptoWallet {
+                address: "bc1q".to_string(),
+                network: AccountNetwork::Bitcoin,
+                is_ledger: true,
+                color: None,
+                txid_prefix: None,
+            },
+            ..account("acct.cold_wallet")
+        };
+        db.collection::<Account>("capital_accounts")
+            .insert_one(&wallet, None)
+            .await
+            .unwrap();
+
+        let deposit = |txid: &str, date: &str, asset: &str, qty: f64| {
+            test_tx(txid)
+                .crypto(asset)
+                .account("acct.cold_wallet")
+                .date(date)
+                .amount(qty)
+                .build()
+        };
+        db.collection::<Transaction>("capital_ledger")
+            .insert_many(
+                vec![
+                    deposit("tx_before", "2025-02-01", "BTC", 0.5),
+                    deposit("tx_btc", "2025-03-15", "BTC", 0.25),
+                    deposit("tx_eth", "2025-03-20", "ETH", 2.0),
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let query = GeneratedStatementQuery {
+            label: "2025-03".to_string(),
+            save: false,
+        };
+        let generated = generated_statement(&db, "acct.cold_wallet", &query)
+            .await
+            .unwrap();
+        assert_eq!(
+            generated.statement.opening_balance,
+            Money::new(dec("0.5"), Currency::BTC)
+        );
+        assert_eq!(
+            generated.statement.closing_balance,
+            Money::new(dec("0.75"), Currency::BTC)
+        );
+        let assets = generated.assets.unwrap();
+        assert_eq!(
+            assets.opening,
+            BTreeMap::from([("BTC".to_string(), dec("0.5"))])
+        );
+        assert_eq!(
+            assets.closing,
+            BTreeMap::from([
+                ("BTC".to_string(), dec("0.75")),
+                ("ETH".to_string(), dec("2")),
+            ])
+        );
+        assert_eq!(generated.transactions.len(), 2);
+
+        // The ledger endpoint shares the same window
+        let ledger = account_ledger(&db, "acct.cold_wallet", Some("2025-03"), 0)
+            .await
+            .unwrap();
+        assert_eq!(ledger.opening, generated.statement.opening_balance);
+        assert_eq!(ledger.closing, generated.statement.closing_balance);
+        assert_eq!(ledger.assets.unwrap().closing, assets.closing);
+        assert_eq!(ledger.transactions.len(), 2);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn accounts_are_nested_by_group_and_ordered() {
+        let grouped = |id: &str, group: &str, order: Option<u32>| Account {
+            group_id: Some(group.to_string()),
+            group_order: order,
+            ..account(id)
+        };
+        let accounts = vec![
+            account("cash"),
+            grouped("acct.savings", "banks", Some(2)),
+            grouped("acct.wallet", "crypto", None),
+            grouped("acct.checking", "banks", Some(1)),
+            grouped("acct.card", "banks", None),
+            grouped("acct.exchange", "crypto", Some(0)),
+        ];
+
+        let groups = group_accounts(accounts);
+        let nested: Vec<(Option<&str>, Vec<&str>)> = groups
+            .iter()
+            .map(|g| {
+                (
+                    g.group_id.as_deref(),
+                    g.accounts.iter().map(|a| a.id.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            nested,
+            vec![
+                (
+                    Some("banks"),
+                    vec!["acct.checking", "acct.savings", "acct.card"]
+                ),
+                (Some("crypto"), vec!["acct.exchange", "acct.wallet"]),
+                (None, vec!["cash"]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn crypto_wallet_balance_sums_qty_per_asset() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let eth_leg = |txid: &str, direction: &str, qty: f64| {
+            test_tx(txid)
+                .crypto("ETH")
+                .account("acct.wallet")
+                .direction(direction)
+                .amount(qty)
+                .build()
+        };
+        db.collection::<Transaction>("capital_ledger")
+            .insert_many(
+                vec![
+                    eth_leg("tx_buy1", "Debit", 1.5),
+                    eth_leg("tx_buy2", "Debit", 0.5),
+                    eth_leg("tx_send", "Credit", 0.25),
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let balances = sum_crypto_as_of(&db, "acct.wallet", i64::MAX)
+            .await
+            .unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances["ETH"], dec("1.75"));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn cycle_summary_counts_reconciliation_states() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_capital_{}", ObjectId::new().to_hex()));
+
+        let tx = |txid: &str, reconciled: bool, state: BalanceState| {
+            let mut tx = test_tx(txid).build();
+            tx.reconciled = reconciled;
+            tx.balance_state = state;
+            tx
+        };
+        let mut deleted = tx("tx_deleted", false, BalanceState::Balanced);
+        deleted.deleted = true;
+        let mut outside = tx("tx_outside", false, BalanceState::Balanced);
+        outside.ts -= 90 * 86_400;
+        outside.posted_ts = None;
+        let rows = vec![
+            tx("tx_a", true, BalanceState::Balanced),
+            tx("tx_b", false, BalanceState::Balanced),
+            tx("tx_c", false, BalanceState::NeedsEnvelopeOffset),
+            tx("tx_d", false, BalanceState::AwaitingTransferMatch),
+            tx("tx_e", false, BalanceState::Unknown),
+            deleted,
+            outside,
+        ];
+        db.collection::<Transaction>("capital_ledger")
+            .insert_many(rows, None)
+            .await
+            .unwrap();
+
+        let (start_ts, end_ts) = cycle_bounds_for_label("2025-03", 1).unwrap();
+        let summary = cycle_reconciliation_summary(&db, "2025-03", start_ts, end_ts)
+            .await
+            .unwrap();
+        assert_eq!(
+            (
+                summary.total_count,
+                summary.reconciled_count,
+                summary.unreconciled_count,
+                summary.balanced_count,
+                summary.needs_offset_count,
+                summary.awaiting_match_count,
+            ),
+            (5, 1, 4, 2, 1, 1)
+        );
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -1,23 +1,26 @@
 // backend/src/journal.rs
 use crate::AppState;
 use crate::services::openai::generate_tags_and_keywords;
+use crate::timezone::resolve_tz;
 use axum::{
     Json,
     extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use futures::stream::TryStreamExt;
 use mongodb::bson::oid::ObjectId;
 use mongodb::{
-    Client as MongoClient, Collection,
-    bson::{doc, to_bson},
+    Collection, Database, IndexModel,
+    bson::{Document, doc, to_bson},
+    options::{FindOptions, IndexOptions},
 };
 use regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -43,6 +46,17 @@ pub struct JournalEntry {
     pub tags: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keywords: Option<Vec<String>>,
+    /// Set when the entry is soft-deleted; cleared by restore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Text-search relevance; only present on search results, never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// Filter matching entries that have not been soft-deleted.
+fn active_entries() -> Document {
+    doc! { "deleted_at": null }
 }
 
 #[derive(Serialize)]
@@ -50,6 +64,132 @@ pub struct JournalResponse {
     pub message: String,
 }
 
+// ========================================= //
+// * * * PERSON & PLACE TAG VALIDATION * * * //
+// ========================================= //
+/// `?strict_tags=true` rejects saves that reference unknown persons/places.
+#[derive(Debug, Default, Deserialize)]
+pub struct TagValidationQuery {
+    #[serde(default)]
+    pub strict_tags: bool,
+}
+
+/// Tags from the person and place registries (`meta` documents).
+#[derive(Debug, Default)]
+pub struct RegistryTags {
+    pub persons: std::collections::HashSet<String>,
+    pub places: std::collections::HashSet<String>,
+}
+
+impl RegistryTags {
+    /// `@person:alice` matches a registry tag of `alice` or `person_alice`.
+    fn contains(&self, kind: &str, tag: &str) -> bool {
+        let registry = match kind {
+            "person" => &self.persons,
+            _ => &self.places,
+        };
+        registry.contains(tag) || registry.contains(&format!("{}_{}", kind, tag))
+    }
+}
+
+async fn registry_tags(
+    db: &Database,
+    doc_type: &str,
+    field: &str,
+) -> mongodb::error::Result<Vec<String>> {
+    let registry = db
+        .collection::<Document>("meta")
+        .find_one(doc! { "type": doc_type }, None)
+        .await?;
+    Ok(registry
+        .as_ref()
+        .and_then(|registry| registry.get_array(field).ok())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| entry.as_document()?.get_str("tag").ok())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+pub async fn load_registry_tags(db: &Database) -> mongodb::error::Result<RegistryTags> {
+    Ok(RegistryTags {
+        persons: registry_tags(db, "person_registry", "persons")
+            .await?
+            .into_iter()
+            .collect(),
+        places: registry_tags(db, "place_registry", "places")
+            .await?
+            .into_iter()
+            .collect(),
+    })
+}
+
+/// `@person:<tag>` / `@place:<tag>` mentions in entry text; captures the kind and the tag.
+pub(crate) static REGISTRY_REFERENCE: std::sync::LazyLock<regex::Regex> =
+    std::sync::LazyLock::new(|| regex::Regex::new(r"@(person|place):([A-Za-z0-9_\-]+)").unwrap());
+
+/// `@person:<tag>` / `@place:<tag>` references in `text` that are not in the registries,
+/// in order of first appearance.
+fn unknown_registry_refs(text: &str, registries: &RegistryTags) -> Vec<String> {
+    let mut unknown: Vec<String> = Vec::new();
+    for capture in REGISTRY_REFERENCE.captures_iter(text) {
+        let (kind, tag) = (&capture[1], &capture[2]);
+        let reference = format!("@{}:{}", kind, tag);
+        if !registries.contains(kind, tag) && !unknown.contains(&reference) {
+            unknown.push(reference);
+        }
+    }
+    unknown
+}
+
+/// Check `text` against the registries: `Ok(warnings)` when the save may go ahead,
+/// `Err(unknown)` when strict mode should reject it.
+fn check_registry_refs(
+    text: &str,
+    registries: &RegistryTags,
+    strict: bool,
+) -> Result<Vec<String>, Vec<String>> {
+    let unknown = unknown_registry_refs(text, registries);
+    if strict && !unknown.is_empty() {
+        Err(unknown)
+    } else {
+        Ok(unknown)
+    }
+}
+
+/// Run the registry check for a save, mapping strict failures to 422. When the
+/// registries can't be read, strict saves fail closed with 500 and non-strict saves
+/// fail open: they go ahead unchecked, with no warnings.
+async fn validate_registry_refs(
+    db: &Database,
+    text: &str,
+    strict: bool,
+) -> Result<Vec<String>, axum::response::Response> {
+    let registries = match load_registry_tags(db).await {
+        Ok(registries) => registries,
+        Err(e) if strict => {
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response());
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "registry read failed; saving without tag validation");
+            return Ok(Vec::new());
+        }
+    };
+    check_registry_refs(text, &registries, strict).map_err(|unknown| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "error": "Unknown person/place tags",
+                "unknown_tags": unknown,
+            })),
+        )
+            .into_response()
+    })
+}
+
 // ================================ //
 // * * * CREATE JOURNAL ENTRY * * * //
 // ================================ //
@@ -59,24 +199,20 @@ pub struct NewJournalEntry {
     pub date: Option<String>,
 }
 
+/// POST /journal/mongo[?strict_tags=true] - Unknown `@person:`/`@place:` references are
+/// returned as `warnings`, or rejected with 422 in strict mode.
 pub async fn create_journal_entry_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
+    Query(validation): Query<TagValidationQuery>,
     Json(payload): Json<NewJournalEntry>,
 ) -> impl axum::response::IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
+
+    let warnings = match validate_registry_refs(&db, &payload.text, validation.strict_tags).await {
+        Ok(warnings) => warnings,
+        Err(response) => return response,
+    };
     let version = JournalVersion {
         text: payload.text.clone(),
         timestamp: Utc::now(),
@@ -94,10 +230,16 @@ pub async fn create_journal_entry_mongo(
         preview_text,
         tags: None,
         keywords: None,
+        deleted_at: None,
+        score: None,
     };
     match collection.insert_one(new_entry, None).await {
-        Ok(_) => Json(serde_json::json!({"status": "success", "message": "Saved to MongoDB"}))
-            .into_response(),
+        Ok(_) => Json(serde_json::json!({
+            "status": "success",
+            "message": "Saved to MongoDB",
+            "warnings": warnings
+        }))
+        .into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
@@ -110,22 +252,13 @@ pub struct EditJournalEntry {
     pub text: String,
 }
 
+/// PATCH /journal/mongo/:id[?strict_tags=true] - Adds a version; tag validation as in create.
 pub async fn edit_journal_entry_mongo(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
+    Query(validation): Query<TagValidationQuery>,
     Json(payload): Json<EditJournalEntry>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
@@ -134,6 +267,11 @@ pub async fn edit_journal_entry_mongo(
         Err(_) => return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response(),
     };
 
+    let warnings = match validate_registry_refs(&db, &payload.text, validation.strict_tags).await {
+        Ok(warnings) => warnings,
+        Err(response) => return response,
+    };
+
     let filter = doc! { "_id": object_id };
     let new_version = JournalVersion {
         text: payload.text.clone(),
@@ -148,9 +286,10 @@ pub async fn edit_journal_entry_mongo(
     match collection.update_one(filter, update, None).await {
         Ok(update_result) => {
             if update_result.matched_count == 1 {
-                Json(JournalResponse {
-                    message: format!("Journal entry {} updated in MongoDB.", id),
-                })
+                Json(json!({
+                    "message": format!("Journal entry {} updated in MongoDB.", id),
+                    "warnings": warnings
+                }))
                 .into_response()
             } else {
                 (StatusCode::NOT_FOUND, "Entry not found").into_response()
@@ -163,21 +302,15 @@ pub async fn edit_journal_entry_mongo(
 // =============================== //
 // * * * DELETE JOURNAL ENTRY * * * //
 // =============================== //
+/// Default days a soft-deleted entry is kept before `purge` may remove it.
+const DEFAULT_DELETE_RETENTION_DAYS: i64 = 30;
+
+/// Soft-delete: stamps `deleted_at` so the entry drops out of lists and search
+/// but can still be restored.
 pub async fn delete_journal_entry_mongo(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
@@ -186,13 +319,18 @@ pub async fn delete_journal_entry_mongo(
         Err(_) => return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response(),
     };
 
-    let filter = doc! { "_id": object_id };
+    let deleted_at = match to_bson(&Utc::now()) {
+        Ok(value) => value,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let filter = doc! { "_id": object_id, "deleted_at": null };
+    let update = doc! { "$set": { "deleted_at": deleted_at } };
 
-    match collection.delete_one(filter, None).await {
-        Ok(delete_result) => {
-            if delete_result.deleted_count == 1 {
+    match collection.update_one(filter, update, None).await {
+        Ok(result) => {
+            if result.matched_count == 1 {
                 Json(JournalResponse {
-                    message: format!("Journal entry {} deleted from MongoDB.", id),
+                    message: format!("Journal entry {} moved to trash.", id),
                 })
                 .into_response()
             } else {
@@ -203,72 +341,282 @@ pub async fn delete_journal_entry_mongo(
     }
 }
 
-// =============================== //
-// * * * GET JOURNAL ENTRIES * * * //
-// =============================== //
+/// Outcome of resolving the single active entry for a date and trashing it.
+#[derive(Debug, PartialEq)]
+pub enum DeleteByDateOutcome {
+    Deleted(ObjectId),
+    NotFound,
+    /// More than one active entry has the date; nothing was deleted.
+    Ambiguous(Vec<ObjectId>),
+}
 
-pub async fn get_journal_entries_mongo(
+/// Soft-delete the active entry for `date`, refusing when the date is ambiguous.
+pub async fn soft_delete_journal_entry_by_date(
+    collection: &Collection<JournalEntry>,
+    date: &str,
+) -> mongodb::error::Result<DeleteByDateOutcome> {
+    let mut filter = active_entries();
+    filter.insert("date", date);
+
+    let entries: Vec<JournalEntry> = collection.find(filter, None).await?.try_collect().await?;
+    let ids: Vec<ObjectId> = entries.iter().filter_map(|entry| entry.id).collect();
+    let id = match ids.as_slice() {
+        [] => return Ok(DeleteByDateOutcome::NotFound),
+        [id] => *id,
+        _ => return Ok(DeleteByDateOutcome::Ambiguous(ids)),
+    };
+
+    let deleted_at = to_bson(&Utc::now())?;
+    let result = collection
+        .update_one(
+            doc! { "_id": id, "deleted_at": null },
+            doc! { "$set": { "deleted_at": deleted_at } },
+            None,
+        )
+        .await?;
+    if result.matched_count == 1 {
+        Ok(DeleteByDateOutcome::Deleted(id))
+    } else {
+        // Trashed concurrently between the lookup and the update
+        Ok(DeleteByDateOutcome::NotFound)
+    }
+}
+
+/// DELETE /journal/mongo/date/:date - Soft-delete the entry for a date (YYYY-MM-DD).
+/// 404 when there is none, 409 (with the candidate ids) when several share the date.
+pub async fn delete_journal_entry_by_date_mongo(
+    Path(date): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
+    let db = state.mongo_client.database("wyat");
+    let collection: Collection<JournalEntry> = db.collection("journal");
 
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
+    match soft_delete_journal_entry_by_date(&collection, &date).await {
+        Ok(DeleteByDateOutcome::Deleted(id)) => Json(JournalResponse {
+            message: format!("Journal entry {} for {} moved to trash.", id.to_hex(), date),
+        })
+        .into_response(),
+        Ok(DeleteByDateOutcome::NotFound) => {
+            (StatusCode::NOT_FOUND, "Entry not found").into_response()
+        }
+        Ok(DeleteByDateOutcome::Ambiguous(ids)) => (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": format!("{} entries exist for {}; delete one by id", ids.len(), date),
+                "ids": ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>(),
+            })),
         )
-            .into_response();
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
+}
 
+/// POST /journal/mongo/:id/restore - Undo a soft delete.
+pub async fn restore_journal_entry_mongo(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
-    let mut cursor = match collection.find(None, None).await {
-        Ok(cursor) => cursor,
-        Err(e) => {
-            println!("MongoDB find error: {}", e);
+    let object_id = match mongodb::bson::oid::ObjectId::parse_str(&id) {
+        Ok(oid) => oid,
+        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid ID format").into_response(),
+    };
+
+    let filter = doc! { "_id": object_id, "deleted_at": { "$ne": null } };
+    let update = doc! { "$unset": { "deleted_at": "" } };
+
+    match collection.update_one(filter, update, None).await {
+        Ok(result) => {
+            if result.matched_count == 1 {
+                Json(JournalResponse {
+                    message: format!("Journal entry {} restored.", id),
+                })
+                .into_response()
+            } else {
+                (StatusCode::NOT_FOUND, "Deleted entry not found").into_response()
+            }
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// True when the entry was soft-deleted at or before `cutoff`.
+fn is_purgeable(entry: &JournalEntry, cutoff: DateTime<Utc>) -> bool {
+    entry
+        .deleted_at
+        .is_some_and(|deleted_at| deleted_at <= cutoff)
+}
+
+/// POST /journal/mongo/purge?retention_days=30 - Permanently remove entries that
+/// have been soft-deleted for longer than the retention window.
+pub async fn purge_deleted_journal_entries(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let retention_days = match params.get("retention_days").map(|v| v.parse::<i64>()) {
+        None => DEFAULT_DELETE_RETENTION_DAYS,
+        Some(Ok(n)) if n >= 0 => n,
+        Some(_) => {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
+                StatusCode::BAD_REQUEST,
+                "retention_days must be a non-negative integer",
             )
                 .into_response();
         }
     };
+    let cutoff = Utc::now() - chrono::Duration::days(retention_days);
 
-    let mut entries = Vec::new();
-    while let Some(doc) = match cursor.try_next().await {
-        Ok(doc) => doc,
+    let db = state.mongo_client.database("wyat");
+    let collection: Collection<JournalEntry> = db.collection("journal");
+
+    // deleted_at is stored as an RFC 3339 string, so compare after deserializing
+    let trashed: Vec<JournalEntry> = match collection
+        .find(doc! { "deleted_at": { "$ne": null } }, None)
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(entries) => entries,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let ids: Vec<ObjectId> = trashed
+        .iter()
+        .filter(|entry| is_purgeable(entry, cutoff))
+        .filter_map(|entry| entry.id)
+        .collect();
+
+    if ids.is_empty() {
+        return Json(json!({ "purged": 0, "retention_days": retention_days })).into_response();
+    }
+
+    match collection
+        .delete_many(doc! { "_id": { "$in": &ids } }, None)
+        .await
+    {
+        Ok(result) => Json(json!({
+            "purged": result.deleted_count,
+            "retention_days": retention_days
+        }))
+        .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// =============================== //
+// * * * GET JOURNAL ENTRIES * * * //
+// =============================== //
+
+/// Filters and paging for `GET /journal/mongo/all`.
+#[derive(Debug, Default, PartialEq)]
+pub struct JournalListOptions {
+    pub include_deleted: bool,
+    /// Entries must carry every one of these tags
+    pub tags: Vec<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<u64>,
+}
+
+impl JournalListOptions {
+    /// Build from raw query pairs so `tag` can repeat (`?tag=travel&tag=family`).
+    pub fn from_query_pairs(pairs: &[(String, String)]) -> Result<Self, String> {
+        let mut options = Self::default();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "include_deleted" => options.include_deleted = value == "true",
+                "tag" => {
+                    let tag = value.trim();
+                    if !tag.is_empty() && !options.tags.iter().any(|t| t == tag) {
+                        options.tags.push(tag.to_string());
+                    }
+                }
+                "limit" => {
+                    options.limit = Some(
+                        value
+                            .parse::<i64>()
+                            .ok()
+                            .filter(|l| *l > 0)
+                            .ok_or("limit must be a positive integer")?,
+                    )
+                }
+                "offset" => {
+                    options.offset = Some(
+                        value
+                            .parse::<u64>()
+                            .map_err(|_| "offset must be a non-negative integer")?,
+                    )
+                }
+                _ => {}
+            }
+        }
+        Ok(options)
+    }
+
+    fn filter(&self) -> Document {
+        let mut filter = if self.include_deleted {
+            doc! {}
+        } else {
+            active_entries()
+        };
+        if !self.tags.is_empty() {
+            filter.insert("tags", doc! { "$all": &self.tags });
+        }
+        filter
+    }
+}
+
+/// Entries matching `options`, newest date first, paged.
+pub async fn list_journal_entries(
+    collection: &Collection<JournalEntry>,
+    options: &JournalListOptions,
+) -> mongodb::error::Result<Vec<JournalEntry>> {
+    let find_options = FindOptions::builder()
+        .sort(doc! { "date": -1, "_id": -1 })
+        .skip(options.offset.filter(|o| *o > 0))
+        .limit(options.limit)
+        .build();
+    collection
+        .find(options.filter(), find_options)
+        .await?
+        .try_collect()
+        .await
+}
+
+/// GET /journal/mongo/all?tag=travel&tag=family&limit=20&offset=0
+///
+/// Newest first. Repeated `tag` params must all be present on an entry. Soft-deleted
+/// entries are left out unless `?include_deleted=true`.
+pub async fn get_journal_entries_mongo(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> impl IntoResponse {
+    let db = state.mongo_client.database("wyat");
+    let collection: Collection<JournalEntry> = db.collection("journal");
+
+    let options = match JournalListOptions::from_query_pairs(&params) {
+        Ok(options) => options,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    match list_journal_entries(&collection, &options).await {
+        Ok(entries) => Json(entries).into_response(),
         Err(e) => {
-            println!("MongoDB cursor error: {}", e);
-            return (
+            println!("MongoDB find error: {}", e);
+            (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Database error: {}", e),
             )
-                .into_response();
+                .into_response()
         }
-    } {
-        entries.push(doc);
     }
-    Json(entries).into_response()
 }
 
 pub async fn get_journal_entry_by_id_mongo(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
     use mongodb::bson::doc;
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
@@ -289,24 +637,13 @@ pub async fn get_journal_entry_by_id_mongo(
 pub async fn get_journal_entry_by_date_mongo(
     Path(date): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
     // Query for entries with the specified date (YYYY-MM-DD format)
-    let filter = doc! { "date": &date };
+    let mut filter = active_entries();
+    filter.insert("date", &date);
 
     let mut cursor = match collection.find(filter, None).await {
         Ok(cursor) => cursor,
@@ -341,22 +678,48 @@ pub async fn get_journal_entry_by_date_mongo(
 // =========================== //
 // * * * TAGS & KEYWORDS * * * //
 // =========================== //
+/// How generated tags/keywords combine with what the entry already has.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagGenerationMode {
+    /// Union with the existing values, keeping manual ones
+    #[default]
+    Merge,
+    /// Discard the existing values
+    Replace,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GenerateTagsPayload {
+    #[serde(default)]
+    pub mode: TagGenerationMode,
+}
+
+/// Combine existing and generated values, deduping case-insensitively. Existing values
+/// keep their spelling and order (merge); new ones are appended in generated order.
+fn combine_tags(existing: &[String], generated: &[String], mode: TagGenerationMode) -> Vec<String> {
+    let base: &[String] = match mode {
+        TagGenerationMode::Merge => existing,
+        TagGenerationMode::Replace => &[],
+    };
+    let mut seen = std::collections::HashSet::new();
+    base.iter()
+        .chain(generated)
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// POST /journal/mongo/:id/generate-tags - Generate tags and keywords from the latest
+/// version. Body `{ "mode": "merge" | "replace" }` (default merge) decides whether manual
+/// tags survive; the response carries `previous_tags` and `tags` for diffing.
 pub async fn patch_journal_entry_tags_and_keywords(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
+    payload: Option<Json<GenerateTagsPayload>>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
+    let mode = payload.map(|Json(p)| p.mode).unwrap_or_default();
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
@@ -388,15 +751,15 @@ pub async fn patch_journal_entry_tags_and_keywords(
     println!("Generated tags: {:?}", tags);
     println!("Generated keywords: {:?}", keywords);
 
-    // Clone tags and keywords for the response
-    let tags_clone = tags.clone();
-    let keywords_clone = keywords.clone();
+    let previous_tags = entry.tags.clone().unwrap_or_default();
+    let previous_keywords = entry.keywords.clone().unwrap_or_default();
+    let new_tags = combine_tags(&previous_tags, &tags, mode);
+    let new_keywords = combine_tags(&previous_keywords, &keywords, mode);
 
-    // Use $addToSet to add tags and keywords without replacing existing ones
     let update = doc! {
-        "$addToSet": {
-            "tags": { "$each": tags },
-            "keywords": { "$each": keywords }
+        "$set": {
+            "tags": &new_tags,
+            "keywords": &new_keywords
         }
     };
 
@@ -412,8 +775,16 @@ pub async fn patch_journal_entry_tags_and_keywords(
             Json(json!({
                 "status": "success",
                 "message": format!("Added tags and keywords to entry: {}", entry.date),
-                "generated_tags": tags_clone,
-                "generated_keywords": keywords_clone,
+                "mode": match mode {
+                    TagGenerationMode::Merge => "merge",
+                    TagGenerationMode::Replace => "replace",
+                },
+                "generated_tags": tags,
+                "generated_keywords": keywords,
+                "previous_tags": previous_tags,
+                "tags": new_tags,
+                "previous_keywords": previous_keywords,
+                "keywords": new_keywords,
                 "modified_count": result.modified_count
             }))
             .into_response()
@@ -444,19 +815,8 @@ pub struct JournalEntryId {
 pub async fn edit_journal_entry_tags(
     Path(id): Path<String>,
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Json(payload): Json<EditTagsPayload>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
@@ -519,56 +879,89 @@ pub struct SearchResult {
     pub date: String,
 }
 
+/// Name of the text index backing `search_journal_entries`.
+const JOURNAL_TEXT_INDEX: &str = "journal_text_search";
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Create the journal text index used for relevance-ranked search.
+pub async fn init_indexes(db: &Database) -> mongodb::error::Result<()> {
+    let collection: Collection<JournalEntry> = db.collection("journal");
+    let text_index = IndexModel::builder()
+        .keys(doc! { "versions.text": "text", "tags": "text", "keywords": "text" })
+        .options(
+            IndexOptions::builder()
+                .name(Some(JOURNAL_TEXT_INDEX.to_string()))
+                .build(),
+        )
+        .build();
+    collection.create_index(text_index, None).await?;
+    Ok(())
+}
+
+/// `$text` search over entry text, tags and keywords, best matches first.
+/// Each hit carries its relevance in `score`.
+pub async fn search_journal_text(
+    collection: &Collection<JournalEntry>,
+    query: &str,
+    limit: i64,
+) -> mongodb::error::Result<Vec<JournalEntry>> {
+    let options = FindOptions::builder()
+        .projection(doc! { "score": { "$meta": "textScore" } })
+        .sort(doc! { "score": { "$meta": "textScore" } })
+        .limit(limit)
+        .build();
+    collection
+        .find(
+            doc! { "$text": { "$search": query }, "deleted_at": null },
+            options,
+        )
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Relevance-ranked search: `?q=history,ceremonial&limit=20`.
+/// Terms are matched against entry text, tags and keywords via the text index
+/// and each hit carries a `score`. Without `q`, returns every entry.
 pub async fn search_journal_entries(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
-    // Accept search terms like: ?q=history,ceremonial,mystery
-    let search_terms = params.get("q").map(|s| {
-        s.split(',')
-            .map(|term| term.trim())
-            .filter(|term| !term.is_empty())
-            .collect::<Vec<_>>()
-    });
-
-    let filter = if let Some(terms) = search_terms {
-        // Build regex patterns for each term
-        let mut or_conditions = Vec::new();
-
-        for term in terms {
-            let regex_pattern = format!(".*{}.*", regex::escape(term));
-            let regex_pattern_clone = regex_pattern.clone();
-            or_conditions.push(doc! {
-                "tags": { "$elemMatch": { "$regex": regex_pattern, "$options": "i" } }
-            });
-            or_conditions.push(doc! {
-                "keywords": { "$elemMatch": { "$regex": regex_pattern_clone, "$options": "i" } }
-            });
-        }
-
-        doc! {
-            "$or": or_conditions
+    let limit = match params.get("limit").map(|v| v.parse::<i64>()) {
+        None => None,
+        Some(Ok(n)) if n > 0 => Some(n),
+        Some(_) => {
+            return (StatusCode::BAD_REQUEST, "limit must be a positive integer").into_response();
         }
-    } else {
-        doc! {} // return all if no query
     };
 
-    let mut cursor = match collection.find(filter, None).await {
+    // Accept search terms like: ?q=history,ceremonial,mystery
+    let search_query = params
+        .get("q")
+        .map(|s| {
+            s.split(',')
+                .map(|term| term.trim())
+                .filter(|term| !term.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|q| !q.is_empty());
+
+    if let Some(query) = search_query {
+        return match search_journal_text(&collection, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+            .await
+        {
+            Ok(hits) => Json(hits).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    }
+
+    // No query: return all entries
+    let options = FindOptions::builder().limit(limit).build();
+    let mut cursor = match collection.find(active_entries(), options).await {
         Ok(c) => c,
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     };
@@ -586,20 +979,8 @@ pub async fn search_journal_entries(
 
 pub async fn search_journal_entries_return_ids(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<JournalEntry> = db.collection("journal");
 
@@ -611,7 +992,7 @@ pub async fn search_journal_entries_return_ids(
             .collect::<Vec<_>>()
     });
 
-    let filter = if let Some(terms) = &search_terms {
+    let mut filter = if let Some(terms) = &search_terms {
         // Build regex patterns for each term
         let mut or_conditions = Vec::new();
 
@@ -632,6 +1013,7 @@ pub async fn search_journal_entries_return_ids(
     } else {
         doc! {} // return all if no query
     };
+    filter.extend(active_entries());
 
     let mut cursor = match collection.find(filter, None).await {
         Ok(c) => c,
@@ -690,3 +1072,479 @@ pub async fn search_journal_entries_return_ids(
 
     Json(results).into_response()
 }
+
+// ==================================== //
+// * * * STREAK & ENTRY FREQUENCY * * * //
+// ==================================== //
+#[derive(Debug, Serialize, PartialEq)]
+pub struct JournalStreak {
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub last_7: u32,
+    pub last_30: u32,
+}
+
+/// Calendar day an entry belongs to: its `date` field, falling back to the
+/// first version's timestamp in `tz` for entries without a valid date.
+fn entry_local_date(entry: &JournalEntry, tz: &Tz) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(entry.date.trim(), "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            entry
+                .versions
+                .first()
+                .map(|v| v.timestamp.with_timezone(tz).date_naive())
+        })
+}
+
+/// Streak stats over the set of days with at least one entry. The current
+/// streak still counts if today has no entry yet but yesterday does.
+fn compute_journal_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> JournalStreak {
+    let one_day = chrono::Duration::days(1);
+
+    let mut cursor = if days.contains(&today) {
+        today
+    } else {
+        today - one_day
+    };
+    let mut current_streak = 0;
+    while days.contains(&cursor) {
+        current_streak += 1;
+        cursor -= one_day;
+    }
+
+    let mut longest_streak = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for &day in days.range(..=today) {
+        run = match prev {
+            Some(p) if day - p == one_day => run + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(run);
+        prev = Some(day);
+    }
+
+    let active_since = |n: i64| {
+        days.range(today - chrono::Duration::days(n - 1)..=today)
+            .count() as u32
+    };
+
+    JournalStreak {
+        current_streak,
+        longest_streak,
+        last_7: active_since(7),
+        last_30: active_since(30),
+    }
+}
+
+pub async fn get_journal_streak(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let tz: Tz = match resolve_tz(params.get("tz").map(|s| s.as_str())) {
+        Ok(tz) => tz,
+        Err(tz_str) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid timezone: {}", tz_str),
+            )
+                .into_response();
+        }
+    };
+
+    let db = state.mongo_client.database("wyat");
+    let collection: Collection<JournalEntry> = db.collection("journal");
+
+    let entries: Vec<JournalEntry> = match collection.find(active_entries(), None).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(entries) => entries,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let days: BTreeSet<NaiveDate> = entries
+        .iter()
+        .filter_map(|entry| entry_local_date(entry, &tz))
+        .collect();
+    let today = Utc::now().with_timezone(&tz).date_naive();
+
+    Json(compute_journal_streak(&days, today)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::Client as MongoClient;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn days(list: &[&str]) -> BTreeSet<NaiveDate> {
+        list.iter().map(|s| day(s)).collect()
+    }
+
+    #[test]
+    fn test_streak_counts_back_from_today() {
+        let set = days(&[
+            "2025-01-01",
+            "2025-01-02",
+            "2025-01-03",
+            "2025-01-04",
+            "2025-01-08",
+            "2025-01-09",
+            "2025-01-10",
+        ]);
+        let streak = compute_journal_streak(&set, day("2025-01-10"));
+        assert_eq!(
+            streak,
+            JournalStreak {
+                current_streak: 3,
+                longest_streak: 4,
+                last_7: 4,
+                last_30: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_streak_survives_until_today_is_written() {
+        let set = days(&["2025-01-08", "2025-01-09"]);
+        assert_eq!(
+            compute_journal_streak(&set, day("2025-01-10")).current_streak,
+            2
+        );
+        assert_eq!(
+            compute_journal_streak(&set, day("2025-01-11")).current_streak,
+            0
+        );
+    }
+
+    #[test]
+    fn test_purge_only_takes_entries_deleted_before_cutoff() {
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        let entry = |deleted_at: Option<DateTime<Utc>>| JournalEntry {
+            id: Some(ObjectId::new()),
+            title: None,
+            date_unix: None,
+            date: "2025-01-01".to_string(),
+            versions: Vec::new(),
+            preview_text: String::new(),
+            tags: None,
+            keywords: None,
+            deleted_at,
+            score: None,
+        };
+
+        assert!(!is_purgeable(&entry(None), cutoff));
+        assert!(!is_purgeable(
+            &entry(Some(cutoff + chrono::Duration::days(1))),
+            cutoff
+        ));
+        assert!(is_purgeable(&entry(Some(cutoff)), cutoff));
+        assert!(is_purgeable(
+            &entry(Some(cutoff - chrono::Duration::days(1))),
+            cutoff
+        ));
+    }
+
+    #[test]
+    fn test_deleted_at_round_trips_and_is_omitted_when_unset() {
+        let entry: JournalEntry = serde_json::from_value(json!({
+            "date": "2025-01-01",
+            "versions": [],
+            "preview_text": ""
+        }))
+        .unwrap();
+        assert!(entry.deleted_at.is_none());
+        assert!(
+            serde_json::to_value(&entry)
+                .unwrap()
+                .get("deleted_at")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_text_search_ranks_repeated_term_higher() {
+        let client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_journal_{}", ObjectId::new().to_hex()));
+        init_indexes(&db).await.unwrap();
+        let collection: Collection<JournalEntry> = db.collection("journal");
+
+        let term = format!("zq{}", ObjectId::new().to_hex());
+        let entry = |text: String| JournalEntry {
+            id: None,
+            title: None,
+            date_unix: None,
+            date: "2025-01-01".to_string(),
+            versions: vec![JournalVersion {
+                text: text.clone(),
+                timestamp: Utc::now(),
+            }],
+            preview_text: text,
+            tags: None,
+            keywords: None,
+            deleted_at: None,
+            score: None,
+        };
+        let once = collection
+            .insert_one(
+                entry(format!("walked by the river, thought about {}", term)),
+                None,
+            )
+            .await
+            .unwrap();
+        let twice = collection
+            .insert_one(
+                entry(format!(
+                    "{} again today, and {} kept coming back",
+                    term, term
+                )),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let hits = search_journal_text(&collection, &term, 10).await.unwrap();
+        let ids: Vec<_> = hits.iter().map(|h| h.id).collect();
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(ids[0], twice.inserted_id.as_object_id());
+        assert_eq!(ids[1], once.inserted_id.as_object_id());
+        assert!(hits[0].score.unwrap() > hits[1].score.unwrap());
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_date_handles_none_one_and_many() {
+        let client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_journal_{}", ObjectId::new().to_hex()));
+        let collection: Collection<JournalEntry> = db.collection("journal");
+
+        let entry = |date: &str| JournalEntry {
+            id: None,
+            title: None,
+            date_unix: None,
+            date: date.to_string(),
+            versions: Vec::new(),
+            preview_text: String::new(),
+            tags: None,
+            keywords: None,
+            deleted_at: None,
+            score: None,
+        };
+        let single = collection
+            .insert_one(entry("2025-01-01"), None)
+            .await
+            .unwrap()
+            .inserted_id
+            .as_object_id()
+            .unwrap();
+        collection
+            .insert_many([entry("2025-01-02"), entry("2025-01-02")], None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            soft_delete_journal_entry_by_date(&collection, "2025-01-03")
+                .await
+                .unwrap(),
+            DeleteByDateOutcome::NotFound
+        );
+
+        assert_eq!(
+            soft_delete_journal_entry_by_date(&collection, "2025-01-01")
+                .await
+                .unwrap(),
+            DeleteByDateOutcome::Deleted(single)
+        );
+        let trashed = collection
+            .find_one(doc! { "_id": single }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(trashed.deleted_at.is_some());
+        // Already trashed, so the date no longer resolves
+        assert_eq!(
+            soft_delete_journal_entry_by_date(&collection, "2025-01-01")
+                .await
+                .unwrap(),
+            DeleteByDateOutcome::NotFound
+        );
+
+        match soft_delete_journal_entry_by_date(&collection, "2025-01-02")
+            .await
+            .unwrap()
+        {
+            DeleteByDateOutcome::Ambiguous(ids) => assert_eq!(ids.len(), 2),
+            other => panic!("expected Ambiguous, got {:?}", other),
+        }
+        let still_active = collection
+            .count_documents(doc! { "date": "2025-01-02", "deleted_at": null }, None)
+            .await
+            .unwrap();
+        assert_eq!(still_active, 2);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_merge_keeps_manual_tags_and_dedupes_case_insensitively() {
+        let strings =
+            |list: &[&str]| -> Vec<String> { list.iter().map(|s| s.to_string()).collect() };
+        let existing = strings(&["person/alice", "Theme/Work"]);
+        let generated = strings(&["theme/work", "emotion/calm", "emotion/calm"]);
+
+        assert_eq!(
+            combine_tags(&existing, &generated, TagGenerationMode::Merge),
+            strings(&["person/alice", "Theme/Work", "emotion/calm"])
+        );
+        // Regenerating is a no-op once merged
+        let merged = combine_tags(&existing, &generated, TagGenerationMode::Merge);
+        assert_eq!(
+            combine_tags(&merged, &generated, TagGenerationMode::Merge),
+            merged
+        );
+        assert_eq!(
+            combine_tags(&existing, &generated, TagGenerationMode::Replace),
+            strings(&["theme/work", "emotion/calm"])
+        );
+    }
+
+    fn registries() -> RegistryTags {
+        RegistryTags {
+            persons: ["alice", "person_bob"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            places: ["place_hong_kong"].iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_non_strict_tag_check_warns_but_allows_save() {
+        let text = "Lunch with @person:alice and @person:bob in @place:hong_kong, \
+                    then @person:alcie and @place:paris. @person:alcie again.";
+        assert_eq!(
+            check_registry_refs(text, &registries(), false),
+            Ok(vec![
+                "@person:alcie".to_string(),
+                "@place:paris".to_string()
+            ])
+        );
+        assert_eq!(
+            check_registry_refs("nothing tagged", &registries(), false),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_strict_tag_check_rejects_unknown_tags() {
+        assert_eq!(
+            check_registry_refs("Met @person:carol", &registries(), true),
+            Err(vec!["@person:carol".to_string()])
+        );
+        assert_eq!(
+            check_registry_refs("Met @person:alice at @place:hong_kong", &registries(), true),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_list_options_collect_repeated_tags() {
+        let pairs: Vec<(String, String)> = [
+            ("tag", "travel"),
+            ("tag", " family "),
+            ("tag", "travel"),
+            ("limit", "5"),
+            ("offset", "10"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let options = JournalListOptions::from_query_pairs(&pairs).unwrap();
+        assert_eq!(options.tags, vec!["travel", "family"]);
+        assert_eq!(options.limit, Some(5));
+        assert_eq!(options.offset, Some(10));
+        assert!(!options.include_deleted);
+
+        let bad = [("limit".to_string(), "0".to_string())];
+        assert!(JournalListOptions::from_query_pairs(&bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tag_filter_requires_every_tag_and_pages_newest_first() {
+        let client = MongoClient::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_journal_{}", ObjectId::new().to_hex()));
+        let collection: Collection<JournalEntry> = db.collection("journal");
+
+        let entry = |date: &str, tags: &[&str]| JournalEntry {
+            id: None,
+            title: None,
+            date_unix: None,
+            date: date.to_string(),
+            versions: Vec::new(),
+            preview_text: String::new(),
+            tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+            keywords: None,
+            deleted_at: None,
+            score: None,
+        };
+        collection
+            .insert_many(
+                [
+                    entry("2025-01-01", &["travel"]),
+                    entry("2025-01-02", &["travel", "family"]),
+                    entry("2025-01-03", &["family"]),
+                    entry("2025-01-04", &["family", "travel", "food"]),
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        let dates = |entries: Vec<JournalEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.date).collect()
+        };
+
+        let travel = JournalListOptions {
+            tags: vec!["travel".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            dates(list_journal_entries(&collection, &travel).await.unwrap()),
+            vec!["2025-01-04", "2025-01-02", "2025-01-01"]
+        );
+
+        let both = JournalListOptions {
+            tags: vec!["travel".to_string(), "family".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            dates(list_journal_entries(&collection, &both).await.unwrap()),
+            vec!["2025-01-04", "2025-01-02"]
+        );
+
+        let paged = JournalListOptions {
+            tags: vec!["family".to_string()],
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            dates(list_journal_entries(&collection, &paged).await.unwrap()),
+            vec!["2025-01-03"]
+        );
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -4,10 +4,16 @@
 
 use mongodb::Client as MongoClient;
 
+pub mod auth;
 pub mod capital;
 pub mod journal;
+pub mod mongo;
+pub mod request_id;
 pub mod services;
+pub mod shutdown;
 pub mod storage;
+pub mod telemetry;
+pub mod timezone;
 
 /// Shared application state
 #[derive(Clone)]
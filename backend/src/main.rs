@@ -1,14 +1,20 @@
+mod auth;
 mod capital;
 mod journal;
 use axum::http::{HeaderName, HeaderValue, Method};
 use dotenvy::dotenv;
 mod meta;
+mod mongo;
 mod projects;
+mod request_id;
+mod shutdown;
 mod storage;
+mod telemetry;
+mod timezone;
 mod vitals;
 mod workout;
 use crate::services::storage::Document;
-use capital::{BatchImportResponse, FlatTransaction, process_batch_import};
+use capital::{BatchImportRequest, BatchImportResponse, FlatTransaction, process_batch_import};
 
 // AppState is now defined in the root module
 pub struct AppState {
@@ -16,31 +22,31 @@ pub struct AppState {
 }
 
 use journal::{
-    create_journal_entry_mongo, delete_journal_entry_mongo, edit_journal_entry_mongo,
-    edit_journal_entry_tags, get_journal_entries_mongo, get_journal_entry_by_date_mongo,
-    get_journal_entry_by_id_mongo, patch_journal_entry_tags_and_keywords, search_journal_entries,
-    search_journal_entries_return_ids,
+    create_journal_entry_mongo, delete_journal_entry_by_date_mongo, delete_journal_entry_mongo,
+    edit_journal_entry_mongo, edit_journal_entry_tags, get_journal_entries_mongo,
+    get_journal_entry_by_date_mongo, get_journal_entry_by_id_mongo, get_journal_streak,
+    patch_journal_entry_tags_and_keywords, purge_deleted_journal_entries,
+    restore_journal_entry_mongo, search_journal_entries, search_journal_entries_return_ids,
 };
 use meta::{
     add_person, add_place, delete_person, delete_place, get_capital_readme,
     get_keywording_best_practices, get_person_registry, get_place_registry, get_tag_taxonomy,
-    update_capital_readme, update_keywording_best_practices, update_person, update_place,
-    update_tag_taxonomy,
+    merge_persons_handler, update_capital_readme, update_keywording_best_practices, update_person,
+    update_place, update_tag_taxonomy,
 };
 use tower_http::cors::CorsLayer;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use axum::{
-    Json, Router,
+    Json, Router, middleware,
     response::IntoResponse,
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
     routing::{delete, get, patch, post, put},
 };
-use futures::stream::TryStreamExt;
-use hyper;
 use mongodb::bson::doc;
 use mongodb::bson::oid::ObjectId;
-use mongodb::options::{FindOneOptions, FindOptions};
+use mongodb::options::FindOneOptions;
 use mongodb::{Client as MongoClient, options::ClientOptions};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -52,17 +58,21 @@ use services::oura::{
     handle_oura_daily_cardiovascular_age_sync, handle_oura_daily_readiness_sync,
     handle_oura_daily_resilience_sync, handle_oura_daily_sleep_sync, handle_oura_daily_spo2_sync,
     handle_oura_daily_stress_sync, handle_oura_heartrate_sync, handle_oura_historical_sync,
-    handle_oura_sleep_sync, handle_oura_vo2_max_sync,
+    handle_oura_sleep_sync, handle_oura_sync_all, handle_oura_vo2_max_sync,
+};
+use services::plaid::{
+    PLAID_USER_ID, get_plaid_base_url, handle_import_plaid_accounts, handle_plaid_webhook,
+    handle_public_token_exchange, plaid_client,
 };
 use services::storage_http;
 use vitals::{
-    get_daily_activity, get_daily_cardiovascular_age, get_daily_readiness, get_daily_resilience,
-    get_daily_spo2, get_daily_stress, get_vo2_max,
+    get_daily_activity, get_daily_activity_range, get_daily_cardiovascular_age,
+    get_daily_readiness, get_daily_resilience, get_daily_sleep_range, get_daily_spo2,
+    get_daily_stress, get_daily_summary, get_daily_summary_range, get_vo2_max,
 };
 use workout::init_indexes;
 
 use axum::Json as AxumJson;
-use reqwest::Client;
 use std::env;
 use std::sync::Arc;
 
@@ -70,32 +80,66 @@ async fn test_mongo() -> impl IntoResponse {
     Json(json!({"status": "MongoDB endpoint ready"}))
 }
 
+const HEALTH_PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// GET /healthz - Readiness probe; pings MongoDB and returns 503 if it is unreachable.
+async fn healthz(AxumState(state): AxumState<Arc<AppState>>) -> impl IntoResponse {
+    let db = state.mongo_client.database("wyat");
+    let ping = tokio::time::timeout(
+        HEALTH_PING_TIMEOUT,
+        db.run_command(doc! { "ping": 1 }, None),
+    );
+
+    match ping.await {
+        Ok(Ok(_)) => (
+            axum::http::StatusCode::OK,
+            Json(json!({"status": "ok", "mongo": "up"})),
+        ),
+        Ok(Err(e)) => {
+            tracing::error!(error = %e, "health check: MongoDB ping failed");
+            (
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"status": "error", "mongo": "down"})),
+            )
+        }
+        Err(_) => {
+            tracing::error!("health check: MongoDB ping timed out");
+            (
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"status": "error", "mongo": "down"})),
+            )
+        }
+    }
+}
+
 // AI Prompts handlers
 
 use axum::extract::{Path as AxumPath, Query as AxumQuery, State as AxumState};
-use services::ai_prompts::{AiPrompt, get_prompt_by_id, list_prompts};
+use bytes::Bytes;
+use services::ai_prompts::{
+    AiPrompt, AiPromptError, CreateAiPromptRequest, ListPromptsOptions, NewPromptVersionRequest,
+    create_prompt, create_prompt_version, get_prompt_by_id, list_prompts, resolve_list_namespace,
+};
 use services::extraction::{
-    ImportDefaults, PreparedBatchImport, prepare_batch_import_from_extract,
-    run_bank_statement_extraction,
+    ExtractionEvent, ExtractionProgress, ExtractionStage, ImportDefaults, PreparedBatchImport,
+    RunListFilter, archive_extraction_run, find_existing_txids, list_extraction_runs,
+    prepare_batch_import_from_extract, run_bank_statement_extraction, run_usage_and_cost,
+};
+use services::openai::{
+    ExtractionOutput, ExtractionParams, ExtractionResponseFormat, TokenUsage,
+    extract_bank_statement,
 };
 
 async fn get_ai_prompt_handler(
     AxumState(state): AxumState<Arc<AppState>>,
     AxumPath(prompt_id): AxumPath<String>,
 ) -> Result<Json<AiPrompt>, axum::http::StatusCode> {
-    println!("=== get_ai_prompt_handler START ===");
-    println!("Prompt ID: {}", prompt_id);
-
     let db = state.mongo_client.database("wyat");
 
     match get_prompt_by_id(&db, &prompt_id).await {
-        Ok(prompt) => {
-            println!("=== get_ai_prompt_handler SUCCESS ===");
-            Ok(Json(prompt))
-        }
+        Ok(prompt) => Ok(Json(prompt)),
         Err(e) => {
-            eprintln!("=== get_ai_prompt_handler ERROR ===");
-            eprintln!("Error: {}", e);
+            tracing::warn!(%prompt_id, error = %e, "AI prompt lookup failed");
             Err(axum::http::StatusCode::NOT_FOUND)
         }
     }
@@ -104,53 +148,99 @@ async fn get_ai_prompt_handler(
 #[derive(Deserialize)]
 struct ListPromptsQuery {
     namespace: Option<String>,
+    limit: Option<i64>,
+    offset: Option<u64>,
+    #[serde(default)]
+    latest_only: bool,
 }
 
+/// List AI prompts.
+///
+/// - `?namespace=<ns>` returns prompts in that namespace
+/// - `?namespace=all` returns prompts from every namespace
+/// - Without `namespace`, uses `AI_PROMPTS_DEFAULT_NAMESPACE` if set,
+///   otherwise returns every prompt
+/// - `?limit=&offset=` page the results (ordered by namespace, id, newest version first)
+/// - `?latest_only=true` returns only the highest version of each prompt id
 async fn list_ai_prompts_handler(
     AxumState(state): AxumState<Arc<AppState>>,
     AxumQuery(query): AxumQuery<ListPromptsQuery>,
 ) -> Result<Json<Vec<AiPrompt>>, axum::http::StatusCode> {
-    println!("=== list_ai_prompts_handler START ===");
-
     let db = state.mongo_client.database("wyat");
+    let namespace = resolve_list_namespace(query.namespace.as_deref());
 
-    match list_prompts(&db, query.namespace.as_deref()).await {
-        Ok(prompts) => {
-            println!("=== list_ai_prompts_handler SUCCESS ===");
-            Ok(Json(prompts))
-        }
+    let options = ListPromptsOptions {
+        limit: query.limit,
+        offset: query.offset,
+        latest_only: query.latest_only,
+    };
+
+    match list_prompts(&db, namespace.as_deref(), &options).await {
+        Ok(prompts) => Ok(Json(prompts)),
         Err(e) => {
-            eprintln!("=== list_ai_prompts_handler ERROR ===");
-            eprintln!("Error: {}", e);
+            tracing::error!(namespace = ?namespace, error = %e, "listing AI prompts failed");
             Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+fn ai_prompt_error_response(err: AiPromptError) -> axum::response::Response {
+    let status = match &err {
+        AiPromptError::Validation(_) => axum::http::StatusCode::BAD_REQUEST,
+        AiPromptError::NotFound(_) => axum::http::StatusCode::NOT_FOUND,
+        AiPromptError::Conflict(_) => axum::http::StatusCode::CONFLICT,
+        AiPromptError::Database(_) => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    tracing::warn!(status = status.as_u16(), error = %err, "AI prompt request failed");
+    (status, Json(json!({ "error": err.to_string() }))).into_response()
+}
+
+/// Create an AI prompt. Rejects a duplicate `(prompt_id, version)` pair with 409.
+async fn create_ai_prompt_handler(
+    AxumState(state): AxumState<Arc<AppState>>,
+    Json(req): Json<CreateAiPromptRequest>,
+) -> axum::response::Response {
+    let db = state.mongo_client.database("wyat");
+    match create_prompt(&db, req).await {
+        Ok(prompt) => (axum::http::StatusCode::CREATED, Json(prompt)).into_response(),
+        Err(e) => ai_prompt_error_response(e),
+    }
+}
+
+/// Append a new version of an AI prompt, copying forward omitted fields from the
+/// latest version; `version` defaults to latest + 1.
+async fn create_ai_prompt_version_handler(
+    AxumState(state): AxumState<Arc<AppState>>,
+    AxumPath(prompt_id): AxumPath<String>,
+    Json(req): Json<NewPromptVersionRequest>,
+) -> axum::response::Response {
+    let db = state.mongo_client.database("wyat");
+    match create_prompt_version(&db, &prompt_id, req).await {
+        Ok(prompt) => (axum::http::StatusCode::CREATED, Json(prompt)).into_response(),
+        Err(e) => ai_prompt_error_response(e),
+    }
+}
+
 // Simple OpenAI test handler
 async fn test_openai_handler() -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
-    println!("=== test_openai_handler START ===");
-
     use async_openai::types::{
         ChatCompletionRequestMessage, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        CreateChatCompletionRequestArgs, ResponseFormat,
     };
     use async_openai::{Client, config::OpenAIConfig};
 
     let api_key = std::env::var("OPENAI_API_SECRET").map_err(|_| {
-        eprintln!("OPENAI_API_SECRET not found");
+        tracing::error!("OPENAI_API_SECRET not found");
         axum::http::StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    println!(
-        "API key found: {}...",
-        &api_key.chars().take(10).collect::<String>()
-    );
-
     let client = Client::with_config(OpenAIConfig::new().with_api_key(api_key));
 
+    let params = ExtractionParams::default();
     let request = CreateChatCompletionRequestArgs::default()
-        .model("gpt-3.5-turbo")
+        .model(&params.model)
+        .temperature(params.temperature)
+        .response_format(ResponseFormat::JsonObject)
         .messages(vec![ChatCompletionRequestMessage::User(
             ChatCompletionRequestUserMessageArgs::default()
                 .content("Say 'Hello, World!' in JSON format with a single key 'message'.")
@@ -160,9 +250,8 @@ async fn test_openai_handler() -> Result<Json<serde_json::Value>, axum::http::St
         .build()
         .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    println!("Calling OpenAI API...");
     let response = client.chat().create(request).await.map_err(|e| {
-        eprintln!("OpenAI API error: {}", e);
+        tracing::error!(error = %e, "OpenAI test call failed");
         axum::http::StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
@@ -172,7 +261,6 @@ async fn test_openai_handler() -> Result<Json<serde_json::Value>, axum::http::St
         .as_ref()
         .ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    println!("=== test_openai_handler SUCCESS ===");
     Ok(Json(json!({
         "status": "ok",
         "response": content
@@ -194,6 +282,8 @@ struct ImportOptionsPayload {
     credit_tx_type: Option<String>,
     #[serde(default)]
     fallback_account_id: Option<String>,
+    #[serde(default)]
+    signed_amounts: bool,
 }
 
 #[derive(Deserialize)]
@@ -205,10 +295,34 @@ struct ExtractBankStatementRequest {
     prompt_version: String,
     model: String,
     assistant_name: String,
+    /// Sampling temperature; defaults to 0 for reproducible extractions
+    #[serde(default)]
+    temperature: Option<f32>,
+    #[serde(default)]
+    max_tokens: Option<u32>,
+    /// "auto" (default) or "text"; "json" is rejected alongside file_search
+    #[serde(default)]
+    response_format: Option<ExtractionResponseFormat>,
     #[serde(default)]
     import: Option<ImportOptionsPayload>,
 }
 
+impl ExtractBankStatementRequest {
+    fn extraction_params(&self) -> ExtractionParams {
+        let defaults = ExtractionParams::default();
+        ExtractionParams {
+            model: if self.model.trim().is_empty() {
+                defaults.model
+            } else {
+                self.model.clone()
+            },
+            temperature: self.temperature.unwrap_or(defaults.temperature),
+            max_tokens: self.max_tokens.or(defaults.max_tokens),
+            response_format: self.response_format.unwrap_or(defaults.response_format),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct ExtractBankStatementResponse {
     transactions: Vec<FlatTransaction>,
@@ -216,6 +330,8 @@ struct ExtractBankStatementResponse {
     inferred_meta: serde_json::Value,
     quality: String,
     confidence: f64,
+    /// Previewed txids already in the ledger; submit skips these
+    duplicates: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     import_summary: Option<BatchImportResponse>,
 }
@@ -224,16 +340,127 @@ async fn extract_bank_statement_handler(
     AxumState(state): AxumState<Arc<AppState>>,
     Json(req): Json<ExtractBankStatementRequest>,
 ) -> Result<Json<ExtractBankStatementResponse>, axum::http::StatusCode> {
-    println!("=== extract_bank_statement_handler START ===");
-    println!("Blob ID: {}", req.blob_id);
-    println!("Doc ID: {}", req.doc_id);
-    println!("Model: {}", req.model);
+    let db = state.mongo_client.database("wyat");
+    extract_bank_statement_core(
+        &db,
+        req,
+        &ExtractionProgress::none(),
+        extract_bank_statement,
+    )
+    .await
+    .map(Json)
+}
+
+// Streaming variant: the same pipeline, reporting stage events over SSE and ending
+// with the final result payload (or an `error` event). It never imports; submit the
+// previewed rows through POST /ai/extract/bank-statement
+#[derive(Deserialize)]
+struct ExtractBankStatementStreamQuery {
+    blob_id: String,
+    doc_id: String,
+    #[serde(default)]
+    prompt: String,
+    prompt_id: String,
+    prompt_version: String,
+    #[serde(default)]
+    model: String,
+    assistant_name: String,
+    #[serde(default)]
+    temperature: Option<f32>,
+    #[serde(default)]
+    max_tokens: Option<u32>,
+    #[serde(default)]
+    response_format: Option<ExtractionResponseFormat>,
+}
+
+impl From<ExtractBankStatementStreamQuery> for ExtractBankStatementRequest {
+    fn from(q: ExtractBankStatementStreamQuery) -> Self {
+        Self {
+            blob_id: q.blob_id,
+            doc_id: q.doc_id,
+            prompt: q.prompt,
+            prompt_id: q.prompt_id,
+            prompt_version: q.prompt_version,
+            model: q.model,
+            assistant_name: q.assistant_name,
+            temperature: q.temperature,
+            max_tokens: q.max_tokens,
+            response_format: q.response_format,
+            // Preview only: importing stays on the POST endpoint
+            import: None,
+        }
+    }
+}
 
+async fn extract_bank_statement_stream_handler(
+    AxumState(state): AxumState<Arc<AppState>>,
+    AxumQuery(query): AxumQuery<ExtractBankStatementStreamQuery>,
+) -> Sse<impl futures::Stream<Item = Result<SseEvent, std::convert::Infallible>>> {
     let db = state.mongo_client.database("wyat");
+    let (progress, rx) = ExtractionProgress::channel();
+
+    tokio::spawn(async move {
+        stream_bank_statement_extraction(&db, query.into(), &progress, extract_bank_statement)
+            .await;
+    });
+
+    // The stream ends once the task above finishes and drops the sender
+    let events = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| {
+            let (name, data) = event.name_and_data();
+            (
+                Ok(SseEvent::default().event(name).data(data.to_string())),
+                rx,
+            )
+        })
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Run the pipeline and finish the stream with a `done` payload or an `error` event.
+async fn stream_bank_statement_extraction(
+    db: &mongodb::Database,
+    req: ExtractBankStatementRequest,
+    progress: &ExtractionProgress,
+    model: impl AsyncFnOnce(
+        &str,
+        &Bytes,
+        &ExtractionParams,
+        &str,
+        &ExtractionProgress,
+    ) -> anyhow::Result<ExtractionOutput>,
+) {
+    let outcome = extract_bank_statement_core(db, req, progress, model).await;
+    match outcome.map(|response| serde_json::to_value(&response)) {
+        Ok(Ok(payload)) => progress.send(ExtractionEvent::Done(payload)),
+        Ok(Err(e)) => progress.send(ExtractionEvent::Error(e.to_string())),
+        Err(status) => progress.send(ExtractionEvent::Error(status.to_string())),
+    }
+}
+
+#[tracing::instrument(
+    name = "extract_bank_statement",
+    skip_all,
+    fields(doc_id = %req.doc_id, blob_id = %req.blob_id, model = %req.model)
+)]
+async fn extract_bank_statement_core(
+    db: &mongodb::Database,
+    req: ExtractBankStatementRequest,
+    progress: &ExtractionProgress,
+    model: impl AsyncFnOnce(
+        &str,
+        &Bytes,
+        &ExtractionParams,
+        &str,
+        &ExtractionProgress,
+    ) -> anyhow::Result<ExtractionOutput>,
+) -> Result<ExtractBankStatementResponse, axum::http::StatusCode> {
+    tracing::info!("bank statement extraction requested");
 
     // Parse blob_id to ObjectId
     let blob_oid = mongodb::bson::oid::ObjectId::parse_str(&req.blob_id).map_err(|e| {
-        eprintln!("Invalid blob_id: {}", e);
+        tracing::warn!(error = %e, "invalid blob_id");
         axum::http::StatusCode::BAD_REQUEST
     })?;
 
@@ -247,34 +474,36 @@ async fn extract_bank_statement_handler(
                 .find_one(mongodb::bson::doc! { "doc_id": &req.doc_id }, None)
                 .await
                 .map_err(|e| {
-                    eprintln!("Failed to resolve doc_id '{}': {}", req.doc_id, e);
+                    tracing::error!(error = %e, "failed to resolve doc_id");
                     axum::http::StatusCode::INTERNAL_SERVER_ERROR
                 })? {
                 Some(doc) => doc.id,
                 None => {
-                    eprintln!("Document with doc_id '{}' not found", req.doc_id);
+                    tracing::warn!("document not found");
                     return Err(axum::http::StatusCode::BAD_REQUEST);
                 }
             }
         }
     };
 
+    let params = req.extraction_params();
+
     // Delegate orchestration to service layer
     match run_bank_statement_extraction(
-        &db,
+        db,
         doc_oid,
         blob_oid,
         &req.prompt,
         &req.prompt_id,
         &req.prompt_version,
-        &req.model,
+        &params,
         &req.assistant_name,
+        progress,
+        model,
     )
     .await
     {
         Ok((_run, result)) => {
-            println!("=== extract_bank_statement_handler SUCCESS ===");
-
             let import_opts = req.import.unwrap_or_default();
             let normalize = |value: Option<String>| -> Option<String> {
                 value.and_then(|s| {
@@ -294,6 +523,7 @@ async fn extract_bank_statement_handler(
                 debit_tx_type,
                 credit_tx_type,
                 fallback_account_id,
+                signed_amounts,
             } = import_opts;
 
             let mut defaults = ImportDefaults::new();
@@ -313,44 +543,57 @@ async fn extract_bank_statement_handler(
                 defaults.fallback_account_id = normalize(Some(account_id_value));
             }
 
-            let prepared =
+            progress.stage(ExtractionStage::Importing);
+            let mut prepared =
                 prepare_batch_import_from_extract(&result, &defaults).map_err(|err| {
-                    eprintln!(
-                        "Failed to prepare batch import payload from extraction: {}",
-                        err
-                    );
+                    tracing::error!(error = %err, "failed to prepare batch import from extraction");
                     axum::http::StatusCode::INTERNAL_SERVER_ERROR
                 })?;
+            prepared.duplicates =
+                find_existing_txids(db, &prepared.preview)
+                    .await
+                    .map_err(|err| {
+                        tracing::error!(error = %err, "failed to check previewed txids against the ledger");
+                        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+                    })?;
 
             let mut import_summary: Option<BatchImportResponse> = None;
             let PreparedBatchImport {
                 mut request,
                 preview,
+                duplicates,
             } = prepared;
+            request.signed_amounts = signed_amounts;
+
+            if !duplicates.is_empty() {
+                tracing::info!(
+                    count = duplicates.len(),
+                    "previewed rows already in the ledger"
+                );
+            }
 
             if submit {
-                let transactions = std::mem::take(&mut request.transactions);
-                match process_batch_import(&db, transactions).await {
+                match process_batch_import(db, request).await {
                     Ok(summary) => import_summary = Some(summary),
                     Err(err) => {
-                        eprintln!("Batch import during extraction failed: {}", err);
+                        tracing::error!(error = %err, "batch import during extraction failed");
                         return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
                     }
                 }
             }
 
-            Ok(Json(ExtractBankStatementResponse {
+            Ok(ExtractBankStatementResponse {
                 transactions: preview,
                 audit: result.audit.clone(),
                 inferred_meta: result.inferred_meta.clone(),
                 quality: result.quality.clone(),
                 confidence: result.confidence,
+                duplicates,
                 import_summary,
-            }))
+            })
         }
         Err(e) => {
-            eprintln!("=== extract_bank_statement_handler ERROR ===");
-            eprintln!("Error: {}", e);
+            tracing::error!(error = %e, "extraction failed");
             Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
@@ -367,6 +610,8 @@ struct PlaidLinkTokenRequest<'a> {
     products: Vec<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     redirect_uri: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    webhook: Option<&'a str>,
 }
 
 #[derive(Serialize)]
@@ -379,17 +624,6 @@ struct PlaidLinkTokenResponse {
     link_token: String,
 }
 
-/// Get the Plaid API base URL based on PLAID_ENV environment variable
-/// Defaults to sandbox if not set or invalid
-fn get_plaid_base_url() -> String {
-    let env = env::var("PLAID_ENV").unwrap_or_else(|_| "sandbox".to_string());
-    match env.to_lowercase().as_str() {
-        "production" | "prod" => "https://production.plaid.com".to_string(),
-        "development" | "dev" => "https://development.plaid.com".to_string(),
-        _ => "https://sandbox.plaid.com".to_string(),
-    }
-}
-
 pub async fn create_plaid_link_token() -> impl IntoResponse {
     let client_id = match env::var("PLAID_CLIENT_ID") {
         Ok(id) => id,
@@ -406,6 +640,8 @@ pub async fn create_plaid_link_token() -> impl IntoResponse {
 
     // Optional: Set redirect_uri from environment variable if needed
     let redirect_uri = env::var("PLAID_REDIRECT_URI").ok();
+    // Optional: where Plaid sends webhooks for items linked with this token
+    let webhook = env::var("PLAID_WEBHOOK_URL").ok();
 
     let payload = PlaidLinkTokenRequest {
         client_id: &client_id,
@@ -414,14 +650,15 @@ pub async fn create_plaid_link_token() -> impl IntoResponse {
         language: "en",
         country_codes: vec!["US"],
         user: PlaidUser {
-            client_user_id: "wyat-demo-user".to_string(),
+            client_user_id: PLAID_USER_ID.to_string(),
         },
         products: vec!["transactions"],
         redirect_uri: redirect_uri.as_deref(),
+        webhook: webhook.as_deref(),
     };
 
     let plaid_url = format!("{}/link/token/create", get_plaid_base_url());
-    let client = Client::new();
+    let client = plaid_client();
     let response = match client.post(&plaid_url).json(&payload).send().await {
         Ok(resp) => resp,
         Err(e) => {
@@ -430,16 +667,16 @@ pub async fn create_plaid_link_token() -> impl IntoResponse {
     };
 
     let text = response.text().await.unwrap_or_else(|e| {
-        println!("Failed to read response text: {}", e);
+        tracing::error!(error = %e, "failed to read Plaid link token response");
         "{}".to_string()
     });
-    println!("Plaid response: {}", text);
+    tracing::debug!(body = %text, "Plaid link token response");
 
     // Then try to deserialize it
     let json = match serde_json::from_str::<PlaidLinkTokenResponse>(&text) {
         Ok(json) => json,
         Err(e) => {
-            println!("Deserialization error: {}", e);
+            tracing::error!(error = %e, "failed to deserialize Plaid response");
             return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
         }
     };
@@ -447,103 +684,6 @@ pub async fn create_plaid_link_token() -> impl IntoResponse {
     AxumJson(json).into_response()
 }
 
-#[derive(Deserialize)]
-pub struct ExchangeTokenRequest {
-    pub public_token: String,
-}
-
-#[derive(Serialize)]
-pub struct ExchangeTokenResponse {
-    pub access_token: String,
-    pub item_id: String,
-}
-
-pub async fn exchange_public_token(
-    AxumState(state): AxumState<Arc<AppState>>,
-    AxumJson(payload): AxumJson<ExchangeTokenRequest>,
-) -> impl IntoResponse {
-    let client_id = match env::var("PLAID_CLIENT_ID") {
-        Ok(id) => id,
-        Err(e) => {
-            return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
-        }
-    };
-    let secret = match env::var("PLAID_SECRET") {
-        Ok(secret) => secret,
-        Err(e) => {
-            return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
-        }
-    };
-
-    #[derive(Serialize)]
-    struct PlaidExchangeRequest {
-        client_id: String,
-        secret: String,
-        public_token: String,
-    }
-
-    let request = PlaidExchangeRequest {
-        client_id,
-        secret,
-        public_token: payload.public_token,
-    };
-
-    let plaid_url = format!("{}/item/public_token/exchange", get_plaid_base_url());
-    let client = Client::new();
-    let response = match client.post(&plaid_url).json(&request).send().await {
-        Ok(resp) => resp,
-        Err(e) => {
-            return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
-        }
-    };
-
-    let text = response.text().await.unwrap_or_else(|e| {
-        println!("Failed to read response text: {}", e);
-        "{}".to_string()
-    });
-    println!("Plaid exchange response: {}", text);
-
-    #[derive(Deserialize)]
-    struct PlaidExchangeResponse {
-        access_token: String,
-        item_id: String,
-    }
-
-    let plaid_response = match serde_json::from_str::<PlaidExchangeResponse>(&text) {
-        Ok(json) => json,
-        Err(e) => {
-            println!("Deserialization error: {}", e);
-            return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
-        }
-    };
-
-    // Store the access token in MongoDB
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<mongodb::bson::Document>("plaid_items");
-
-    let doc = mongodb::bson::doc! {
-        "item_id": &plaid_response.item_id,
-        "access_token": &plaid_response.access_token,
-        "created_at": chrono::Utc::now().timestamp(),
-        "updated_at": chrono::Utc::now().timestamp(),
-    };
-
-    match collection.insert_one(doc, None).await {
-        Ok(_) => println!(
-            "Stored Plaid access token for item {}",
-            plaid_response.item_id
-        ),
-        Err(e) => println!("Failed to store access token: {}", e),
-    }
-
-    let response = ExchangeTokenResponse {
-        access_token: plaid_response.access_token,
-        item_id: plaid_response.item_id,
-    };
-
-    AxumJson(response).into_response()
-}
-
 #[derive(Deserialize)]
 pub struct PlaidSyncRequest {
     pub item_id: String,
@@ -621,7 +761,7 @@ pub async fn sync_plaid_transactions(
     };
 
     let plaid_url = format!("{}/transactions/get", get_plaid_base_url());
-    let client = Client::new();
+    let client = plaid_client();
     let response = match client.post(&plaid_url).json(&request).send().await {
         Ok(resp) => resp,
         Err(e) => {
@@ -630,69 +770,40 @@ pub async fn sync_plaid_transactions(
     };
 
     let text = response.text().await.unwrap_or_else(|e| {
-        println!("Failed to read response text: {}", e);
+        tracing::error!(error = %e, "failed to read Plaid transactions response");
         "{}".to_string()
     });
-    println!("Plaid transactions response: {}", text);
-
-    #[derive(Deserialize)]
-    struct PlaidTransaction {
-        transaction_id: String,
-        date: String,
-        name: String,
-        amount: f64,
-        // Add more fields as needed
-    }
+    tracing::debug!(body = %text, "Plaid transactions response");
 
     #[derive(Deserialize)]
     struct PlaidTransactionsResponse {
-        transactions: Vec<PlaidTransaction>,
+        transactions: Vec<services::plaid::PlaidTransaction>,
     }
 
     let plaid_response = match serde_json::from_str::<PlaidTransactionsResponse>(&text) {
         Ok(json) => json,
         Err(e) => {
-            println!("Deserialization error: {}", e);
+            tracing::error!(error = %e, "failed to deserialize Plaid response");
             return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
         }
     };
 
     // Convert Plaid transactions to our FlatTransaction format and import
-    let mut flat_transactions = Vec::new();
-    for tx in plaid_response.transactions {
-        let flat_tx = capital::FlatTransaction {
-            txid: format!("plaid_{}", tx.transaction_id),
-            date: tx.date,
-            posted_ts: None,
-            source: "plaid".to_string(),
-            payee: Some(tx.name),
-            memo: None,
-            account_id: payload.account_id.clone(),
-            direction: if tx.amount < 0.0 {
-                "Debit".to_string()
-            } else {
-                "Credit".to_string()
-            },
-            kind: "Fiat".to_string(),
-            ccy_or_asset: "USD".to_string(),
-            amount_or_qty: tx.amount.abs(),
-            price: None,
-            price_ccy: None,
-            category_id: None,
-            status: Some("posted".to_string()),
-            tx_type: Some(if tx.amount < 0.0 {
-                "spending".to_string()
-            } else {
-                "income".to_string()
-            }),
-            ext1_kind: Some("plaid_transaction_id".to_string()),
-            ext1_val: Some(tx.transaction_id),
-        };
-        flat_transactions.push(flat_tx);
-    }
+    let flat_transactions: Vec<FlatTransaction> = plaid_response
+        .transactions
+        .iter()
+        .map(|tx| services::plaid::flat_from_plaid(tx, &payload.account_id))
+        .collect();
 
     // Import transactions using the existing batch import function
-    let import_result = capital::process_batch_import(&db, flat_transactions).await;
+    let import_result = capital::process_batch_import(
+        &db,
+        BatchImportRequest {
+            transactions: flat_transactions,
+            ..Default::default()
+        },
+    )
+    .await;
 
     let sync_response = match import_result {
         Ok(result) => PlaidSyncResponse {
@@ -716,21 +827,46 @@ pub async fn sync_plaid_transactions(
     paths(
         workout::create_exercise_type_mongo,
         workout::update_exercise_type_mongo,
+        workout::delete_exercise_type_mongo,
+        workout::merge_exercise_types_mongo,
         workout::get_all_exercise_types_mongo,
         workout::create_exercise_entry_mongo,
+        workout::create_exercise_entries_bulk_mongo,
         workout::update_exercise_entry_mongo,
+        workout::delete_exercise_entry_mongo,
         workout::get_all_exercise_entries_mongo,
         workout::get_exercise_entries_by_day,
         workout::find_exercise_type_by_muscle,
+        workout::get_workout_volume,
+        workout::get_workout_frequency,
+        workout::get_exercise_type_prs,
         capital::get_all_envelopes,
+        capital::get_envelope_alerts,
+        capital::get_envelopes_usage,
+        capital::validate_envelope,
         capital::get_all_accounts,
+        capital::create_account,
+        capital::get_account_ledger,
+        capital::get_grouped_accounts,
         capital::get_all_funds,
+        capital::create_fund,
         capital::get_fund_positions,
+        capital::get_fund_compliance,
+        capital::export_capital_config,
+        capital::import_capital_config,
         capital::get_transactions,
+        capital::get_transactions_by_ref,
+        capital::export_transactions,
+        capital::get_reimbursable_transactions,
+        capital::restore_transaction,
         capital::get_watchlist_data,
         capital::add_watchlist_asset,
         capital::update_watchlist_asset,
         capital::remove_watchlist_asset,
+        capital::get_watchlist_history,
+        capital::backfill_watchlist_history,
+        capital::get_watchlist_alerts,
+        capital::update_watchlist_alerts,
     ),
     components(
         schemas(
@@ -740,11 +876,20 @@ pub async fn sync_plaid_transactions(
             workout::ExerciseTypePatch,
             workout::ExerciseEntryInput,
             workout::ExerciseEntryPatch,
+            workout::ExerciseEntryPage,
+            workout::BulkEntryError,
+            workout::BulkEntriesResult,
+            workout::MergeExerciseTypesRequest,
+            workout::ExerciseTypeMergeResult,
             workout::FindByMuscleRequest,
             workout::WeightUnit,
             workout::LoadBasis,
             workout::Muscle,
             workout::Region,
+            workout::MuscleVolume,
+            workout::WeeklyFrequency,
+            workout::PersonalRecord,
+            workout::ExercisePersonalRecords,
             capital::Currency,
             capital::Money,
             capital::Envelope,
@@ -755,6 +900,8 @@ pub async fn sync_plaid_transactions(
             capital::DeficitPolicy,
             capital::RolloverPolicy,
             capital::Account,
+            capital::AccountLedger,
+            capital::AccountGroup,
             capital::AccountNetwork,
             capital::AccountMetadata,
             capital::Transaction,
@@ -763,13 +910,29 @@ pub async fn sync_plaid_transactions(
             capital::LegAmount,
             capital::FxSnapshot,
             capital::BalanceState,
+            capital::ReimbursementSettlement,
+            capital::ReimbursableList,
+            capital::LedgerExportRow,
             capital::PublicFund,
+            capital::NewFund,
+            capital::FundCompliance,
+            capital::Fund,
+            capital::CapitalConfig,
+            capital::ConfigImportCounts,
+            capital::ConfigImportResponse,
             capital::Position,
             capital::EnvelopeUsage,
+            capital::EnvelopeAlert,
+            capital::EnvelopeValidation,
             capital::WatchlistAssetKind,
             capital::WatchlistEntry,
             capital::AddWatchlistAssetRequest,
             capital::WatchlistAssetResponse,
+            capital::WatchlistHistoryPoint,
+            capital::WatchlistBackfillResponse,
+            capital::AlertBound,
+            capital::WatchlistAlert,
+            capital::UpdateWatchlistAlertsRequest,
         )
     ),
     modifiers(&SecurityAddon),
@@ -800,23 +963,55 @@ impl utoipa::Modify for SecurityAddon {
 #[tokio::main]
 async fn main() {
     dotenv().ok();
+    telemetry::init();
 
     // MongoDB: connect to Atlas
     let mongo_uri = std::env::var("MONGODB_URI").expect("Missing MONGODB_URI in .env");
-    let mongo_options = ClientOptions::parse(&mongo_uri)
+    let mut mongo_options = ClientOptions::parse(&mongo_uri)
         .await
         .expect("Failed to parse MongoDB options");
+    mongo::apply_mongo_pool_settings(&mut mongo_options, |name| std::env::var(name).ok());
     let mongo_client =
         MongoClient::with_options(mongo_options).expect("Failed to connect to MongoDB");
 
-    println!("✅ Connected to MongoDB Atlas");
+    // Fail fast rather than letting the first request hang on server selection
+    if let Err(e) = mongo::ping(&mongo_client).await {
+        tracing::error!(
+            error = %e,
+            "MongoDB is unreachable; check MONGODB_URI and network access"
+        );
+        std::process::exit(1);
+    }
+    tracing::info!("connected to MongoDB");
+
+    timezone::check_default_tz();
 
-    // Initialize workout indexes
+    // Initialize workout, capital, journal, Oura and storage indexes
     let db = mongo_client.database("wyat");
     if let Err(e) = init_indexes(&db).await {
-        eprintln!("⚠️  Failed to initialize workout indexes: {:?}", e);
+        tracing::warn!(error = ?e, "failed to initialize workout indexes");
+    } else {
+        tracing::info!("workout indexes initialized");
+    }
+    if let Err(e) = capital::init_indexes(&db).await {
+        tracing::warn!(error = ?e, "failed to initialize capital indexes");
+    } else {
+        tracing::info!("capital indexes initialized");
+    }
+    if let Err(e) = journal::init_indexes(&db).await {
+        tracing::warn!(error = ?e, "failed to initialize journal indexes");
     } else {
-        println!("✅ Workout indexes initialized");
+        tracing::info!("journal indexes initialized");
+    }
+    if let Err(e) = services::oura::init_indexes(&db).await {
+        tracing::warn!(error = ?e, "failed to initialize Oura indexes");
+    } else {
+        tracing::info!("Oura indexes initialized");
+    }
+    if let Err(e) = services::storage::init_indexes(&db).await {
+        tracing::warn!(error = ?e, "failed to initialize storage indexes");
+    } else {
+        tracing::info!("storage indexes initialized");
     }
 
     let state = Arc::new(AppState { mongo_client });
@@ -837,24 +1032,73 @@ async fn main() {
             HeaderName::from_static("content-type"),
             HeaderName::from_static("accept"),
             HeaderName::from_static("x-wyat-api-key"),
+            HeaderName::from_static(request_id::REQUEST_ID_HEADER),
         ])
+        .expose_headers([HeaderName::from_static(request_id::REQUEST_ID_HEADER)])
         .allow_credentials(true);
 
-    let app = Router::new()
-        .route("/", get(|| async { "Hello from backend" }))
+    // Capital, journal, meta, vitals, Oura and workout routes require `x-wyat-api-key`
+    let protected = Router::new()
         .route("/capital/envelopes", get(capital::get_all_envelopes))
+        .route(
+            "/capital/envelopes/validate",
+            post(capital::validate_envelope),
+        )
+        .route(
+            "/capital/envelopes/alerts",
+            get(capital::get_envelope_alerts),
+        )
+        .route(
+            "/capital/envelopes/usage",
+            get(capital::get_envelopes_usage),
+        )
         .route(
             "/capital/envelopes/:envelope_id/usage",
             get(capital::get_envelope_usage),
         )
         .route("/capital/cycles", get(capital::get_cycles))
+        .route(
+            "/capital/cycles/summaries",
+            get(capital::get_cycle_summaries),
+        )
+        .route(
+            "/capital/cycles/:label/savings-rate",
+            get(capital::get_cycle_savings_rate),
+        )
+        .route(
+            "/capital/cycles/:label/summary",
+            get(capital::get_cycle_reconciliation_summary),
+        )
         .route("/capital/accounts", get(capital::get_all_accounts))
         .route("/capital/accounts", post(capital::create_account))
+        .route(
+            "/capital/accounts/grouped",
+            get(capital::get_grouped_accounts),
+        )
         .route(
             "/capital/accounts/:account_id/balance",
             get(capital::get_account_balance),
         )
-        .route("/capital/funds", get(capital::get_all_funds))
+        .route(
+            "/capital/accounts/:account_id/statement",
+            get(capital::get_account_statement),
+        )
+        .route(
+            "/capital/accounts/:account_id/ledger",
+            get(capital::get_account_ledger),
+        )
+        .route(
+            "/capital/funds",
+            get(capital::get_all_funds).post(capital::create_fund),
+        )
+        .route(
+            "/capital/config/export",
+            get(capital::export_capital_config),
+        )
+        .route(
+            "/capital/config/import",
+            post(capital::import_capital_config),
+        )
         .route(
             "/capital/funds/positions",
             get(capital::get_all_fund_positions),
@@ -863,6 +1107,10 @@ async fn main() {
             "/capital/funds/:fund_id/positions",
             get(capital::get_fund_positions),
         )
+        .route(
+            "/capital/funds/:fund_id/compliance",
+            get(capital::get_fund_compliance),
+        )
         .route("/capital/data", get(capital::get_watchlist_data))
         .route(
             "/capital/data/watchlist",
@@ -872,16 +1120,60 @@ async fn main() {
             "/capital/data/watchlist/:symbol",
             delete(capital::remove_watchlist_asset).patch(capital::update_watchlist_asset),
         )
+        .route(
+            "/capital/data/watchlist/:symbol/history",
+            get(capital::get_watchlist_history),
+        )
+        .route(
+            "/capital/data/watchlist/:symbol/backfill",
+            post(capital::backfill_watchlist_history),
+        )
+        .route(
+            "/capital/data/watchlist/alerts",
+            get(capital::get_watchlist_alerts),
+        )
+        .route(
+            "/capital/data/watchlist/:symbol/alerts",
+            patch(capital::update_watchlist_alerts),
+        )
+        .route(
+            "/capital/statements/reconcile",
+            post(capital::reconcile_statement),
+        )
         .route("/capital/transactions", get(capital::get_transactions))
+        .route(
+            "/capital/transactions/by-ref",
+            get(capital::get_transactions_by_ref),
+        )
+        .route(
+            "/capital/transactions/export",
+            get(capital::export_transactions),
+        )
+        .route(
+            "/capital/transactions/reimbursable",
+            get(capital::get_reimbursable_transactions),
+        )
         .route("/capital/transactions", post(capital::create_transaction))
         .route(
             "/capital/transactions/batch-import",
             post(capital::batch_import_transactions),
         )
+        .route(
+            "/capital/transactions/batch-import-v2",
+            post(capital::batch_import_transactions_v2),
+        )
         .route(
             "/capital/transactions/reclassify",
             put(capital::reclassify_transaction),
         )
+        .route(
+            "/capital/transactions/reclassify-bulk",
+            post(capital::reclassify_transactions_bulk),
+        )
+        .route(
+            "/capital/transactions/recompute-balance-state",
+            post(capital::recompute_balance_states),
+        )
         .route(
             "/capital/transactions/:transaction_id",
             get(capital::get_transaction_by_id),
@@ -890,6 +1182,10 @@ async fn main() {
             "/capital/transactions/:transaction_id",
             axum::routing::delete(capital::delete_transaction),
         )
+        .route(
+            "/capital/transactions/:transaction_id/restore",
+            post(capital::restore_transaction),
+        )
         .route(
             "/capital/transactions/:transaction_id/type",
             patch(capital::update_transaction_type),
@@ -902,17 +1198,31 @@ async fn main() {
             "/capital/transactions/:transaction_id/balance",
             post(capital::balance_transaction),
         )
+        .route(
+            "/capital/transactions/:transaction_id/reimbursable",
+            post(capital::mark_transaction_reimbursable),
+        )
+        .route(
+            "/capital/transactions/:transaction_id/settle-reimbursement",
+            post(capital::settle_reimbursement),
+        )
         .route("/journal/mongo", post(create_journal_entry_mongo))
         .route("/journal/mongo/all", get(get_journal_entries_mongo))
         .route("/journal/mongo/:id", get(get_journal_entry_by_id_mongo))
         .route(
             "/journal/mongo/date/:date",
-            get(get_journal_entry_by_date_mongo),
+            get(get_journal_entry_by_date_mongo).delete(delete_journal_entry_by_date_mongo),
         )
         .route("/journal/mongo/:id", patch(edit_journal_entry_mongo))
         .route("/journal/mongo/:id", delete(delete_journal_entry_mongo))
+        .route(
+            "/journal/mongo/:id/restore",
+            post(restore_journal_entry_mongo),
+        )
+        .route("/journal/mongo/purge", post(purge_deleted_journal_entries))
         .route("/journal/mongo/:id/tags", patch(edit_journal_entry_tags))
         .route("/journal/mongo/search", get(search_journal_entries))
+        .route("/journal/mongo/streak", get(get_journal_streak))
         .route(
             "/journal/mongo/search/ids",
             get(search_journal_entries_return_ids),
@@ -947,24 +1257,7 @@ async fn main() {
         .route("/oura/vo2-max/sync", get(handle_oura_vo2_max_sync))
         .route("/oura/heartrate/sync", get(handle_oura_heartrate_sync))
         .route("/oura/historical-sync", get(handle_oura_historical_sync))
-        .route("/api/oura/auth", get(generate_oura_auth_url))
-        .route("/api/oura/callback", get(handle_oura_callback))
-        .route("/plaid/link-token/create", get(create_plaid_link_token))
-        .route("/plaid/exchange-public-token", post(exchange_public_token))
-        .route("/plaid/sync-transactions", post(sync_plaid_transactions))
-        .route("/test-mongo", get(test_mongo))
-        .route("/ai/prompts", get(list_ai_prompts_handler))
-        .route("/ai/prompts/:prompt_id", get(get_ai_prompt_handler))
-        .route("/ai/test-openai", get(test_openai_handler))
-        .route(
-            "/ai/extract/bank-statement",
-            post(extract_bank_statement_handler),
-        )
-        .route("/ai/extraction-runs", get(list_extraction_runs_handler))
-        .route(
-            "/ai/extraction-runs/:run_id",
-            get(get_extraction_run_handler),
-        )
+        .route("/oura/sync-all", get(handle_oura_sync_all))
         .route("/meta/tag-taxonomy", get(get_tag_taxonomy))
         .route("/meta/person-registry", get(get_person_registry))
         .route("/meta/place-registry", get(get_place_registry))
@@ -983,22 +1276,16 @@ async fn main() {
         .route("/meta/persons", post(add_person))
         .route("/meta/persons", patch(update_person))
         .route("/meta/persons/:tag", delete(delete_person))
+        .route("/meta/persons/merge", post(merge_persons_handler))
         // Place registry CRUD operations
         .route("/meta/places", post(add_place))
         .route("/meta/places", patch(update_place))
         .route("/meta/places/:tag", delete(delete_place))
-        // Projects routes
-        .route("/projects", get(projects::get_all_projects))
-        .route(
-            "/projects/with-planning",
-            get(projects::get_projects_with_planning),
-        )
-        .route("/projects/:id", get(projects::get_project_by_id))
-        .route("/project-planning", get(projects::get_all_planning))
-        .route("/project-planning/:id", get(projects::get_planning_by_id))
         // .route("/vitals/daily", get(get_daily_vitals))
         .route("/vitals/readiness", get(get_daily_readiness))
         .route("/vitals/activity", get(get_daily_activity))
+        .route("/vitals/activity/range", get(get_daily_activity_range))
+        .route("/vitals/daily-sleep/range", get(get_daily_sleep_range))
         .route(
             "/vitals/cardiovascular-age",
             get(get_daily_cardiovascular_age),
@@ -1007,21 +1294,32 @@ async fn main() {
         .route("/vitals/spo2", get(get_daily_spo2))
         .route("/vitals/stress", get(get_daily_stress))
         .route("/vitals/vo2-max", get(get_vo2_max))
+        .route("/vitals/summary", get(get_daily_summary_range))
+        .route("/vitals/summary/:day", get(get_daily_summary))
         .route(
             "/workout/exercise-types",
             post(workout::create_exercise_type_mongo),
         )
+        .route(
+            "/workout/exercise-types/merge",
+            post(workout::merge_exercise_types_mongo),
+        )
         .route(
             "/workout/exercise-types/:id",
-            patch(workout::update_exercise_type_mongo),
+            patch(workout::update_exercise_type_mongo).delete(workout::delete_exercise_type_mongo),
         )
         .route(
             "/workout/exercise-entries",
             post(workout::create_exercise_entry_mongo),
         )
+        .route(
+            "/workout/exercise-entries/bulk",
+            post(workout::create_exercise_entries_bulk_mongo),
+        )
         .route(
             "/workout/exercise-entries/:id",
-            patch(workout::update_exercise_entry_mongo),
+            patch(workout::update_exercise_entry_mongo)
+                .delete(workout::delete_exercise_entry_mongo),
         )
         .route(
             "/workout/exercise-types",
@@ -1039,9 +1337,80 @@ async fn main() {
             "/workout/exercise-types/find-by-muscle",
             post(workout::find_exercise_type_by_muscle),
         )
+        .route("/workout/volume", get(workout::get_workout_volume))
+        .route("/workout/frequency", get(workout::get_workout_frequency))
+        .route(
+            "/workout/exercise-types/:id/prs",
+            get(workout::get_exercise_type_prs),
+        )
+        .route("/plaid/link-token/create", get(create_plaid_link_token))
+        .route(
+            "/plaid/item/public-token-exchange",
+            post(handle_public_token_exchange),
+        )
+        .route(
+            "/plaid/exchange-public-token",
+            post(handle_public_token_exchange),
+        )
+        .route(
+            "/plaid/item/:item_id/import-accounts",
+            post(handle_import_plaid_accounts),
+        )
+        .route("/plaid/sync-transactions", post(sync_plaid_transactions))
+        .route("/test-mongo", get(test_mongo))
+        .route("/ai/test-openai", get(test_openai_handler))
+        .route(
+            "/ai/extract/bank-statement",
+            post(extract_bank_statement_handler),
+        )
+        .route(
+            "/ai/extract/bank-statement/stream",
+            get(extract_bank_statement_stream_handler),
+        )
+        .route("/ai/extraction-runs", get(list_extraction_runs_handler))
+        .route(
+            "/ai/extraction-runs/recent",
+            get(list_recent_extraction_runs_handler),
+        )
+        .route(
+            "/ai/extraction-runs/:run_id",
+            get(get_extraction_run_handler).delete(archive_extraction_run_handler),
+        )
+        // Prompt writes require the API key; prompt reads stay on the public router
+        .route("/ai/prompts", post(create_ai_prompt_handler))
+        .route(
+            "/ai/prompts/:prompt_id/versions",
+            post(create_ai_prompt_version_handler),
+        )
+        .route_layer(middleware::from_fn(auth::require_api_key));
+
+    let app = Router::new()
+        .route("/", get(|| async { "Hello from backend" }))
+        // Oura OAuth is a browser redirect round-trip, which cannot carry the API key
+        .route("/api/oura/auth", get(generate_oura_auth_url))
+        .route("/api/oura/callback", get(handle_oura_callback))
+        // Plaid calls the webhook server-to-server; it is verified by Plaid's signed JWT
+        .route("/plaid/webhook", post(handle_plaid_webhook))
+        .route("/healthz", get(healthz))
+        .route("/ai/prompts", get(list_ai_prompts_handler))
+        .route("/ai/prompts/:prompt_id", get(get_ai_prompt_handler))
+        // Projects routes
+        .route("/projects", get(projects::get_all_projects))
+        .route(
+            "/projects/with-planning",
+            get(projects::get_projects_with_planning),
+        )
+        .route("/projects/:id", get(projects::get_project_by_id))
+        .route("/project-planning", get(projects::get_all_planning))
+        .route("/project-planning/:id", get(projects::get_planning_by_id))
+        .merge(protected)
         .with_state(state.clone())
-        .merge(storage_http::routes(state.clone()))
+        .merge(
+            storage_http::routes(state.clone())
+                .route_layer(middleware::from_fn(auth::require_api_key)),
+        )
         .merge(SwaggerUi::new("/docs").url("/docs/openapi.json", ApiDoc::openapi()))
+        .layer(middleware::from_fn(request_id::propagate_request_id))
         .layer(cors);
 
     let port = std::env::var("PORT")
@@ -1049,23 +1418,57 @@ async fn main() {
         .and_then(|p| p.parse().ok())
         .unwrap_or(3001);
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    println!("Backend listening on {}", addr);
+    tracing::info!(%addr, "backend listening");
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     let std_listener = listener.into_std().unwrap();
-    hyper::Server::from_tcp(std_listener)
-        .unwrap()
-        .serve(app.into_make_service())
-        .await
-        .unwrap();
+    shutdown::serve_with_graceful_shutdown(
+        std_listener,
+        app,
+        shutdown::shutdown_signal(),
+        shutdown::grace_period_from_env(),
+    )
+    .await
+    .unwrap();
+    tracing::info!("backend stopped");
 }
 
 #[derive(Serialize)]
 struct PublicRunListItem {
     _id: String,
+    doc_id: String,
     created_at: i64,
     status: String,
     quality: Option<String>,
     confidence: Option<f64>,
+    usage: Option<TokenUsage>,
+    estimated_cost_usd: Option<f64>,
+}
+
+impl From<&mongodb::bson::Document> for PublicRunListItem {
+    fn from(doc: &mongodb::bson::Document) -> Self {
+        let md = doc.get_document("metadata").ok();
+        let (usage, estimated_cost_usd) = md
+            .map(|m| run_usage_and_cost(doc.get_str("model").unwrap_or_default(), m))
+            .unwrap_or_default();
+        PublicRunListItem {
+            _id: doc
+                .get_object_id("_id")
+                .map(|o| o.to_hex())
+                .unwrap_or_default(),
+            doc_id: doc
+                .get_object_id("doc_id")
+                .map(|o| o.to_hex())
+                .unwrap_or_default(),
+            created_at: doc.get_i64("created_at").unwrap_or_default(),
+            status: doc.get_str("status").unwrap_or("unknown").to_string(),
+            quality: md
+                .and_then(|m| m.get_str("quality").ok())
+                .map(|s| s.to_string()),
+            confidence: md.and_then(|m| m.get_f64("confidence").ok()),
+            usage,
+            estimated_cost_usd,
+        }
+    }
 }
 
 async fn list_extraction_runs_handler(
@@ -1093,58 +1496,67 @@ async fn list_extraction_runs_handler(
         }
     };
 
-    let coll = db.collection::<mongodb::bson::Document>("doc_extraction_runs");
-
-    let mut cursor = coll
-        .find(
-            doc! { "doc_id": doc_oid },
-            FindOptions::builder()
-                .sort(doc! { "created_at": -1 })
-                .projection(doc! {
-                    "_id": 1,
-                    "created_at": 1,
-                    "status": 1,
-                    "metadata.quality": 1,
-                    "metadata.confidence": 1,
-                })
-                .limit(50)
-                .build(),
-        )
+    let filter = RunListFilter {
+        doc_id: Some(doc_oid),
+        status: None,
+    };
+    let runs = list_extraction_runs(&db, &filter, 50)
         .await
         .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let mut out = Vec::new();
-    while let Some(doc) = cursor
-        .try_next()
-        .await
-        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?
-    {
-        let id = doc
-            .get_object_id("_id")
-            .map(|o| o.to_hex())
-            .unwrap_or_default();
-        let created_at = doc.get_i64("created_at").unwrap_or_default();
-        let status = doc.get_str("status").unwrap_or("unknown").to_string();
-        let quality = doc
-            .get_document("metadata")
-            .ok()
-            .and_then(|m| m.get_str("quality").ok())
-            .map(|s| s.to_string());
-        let confidence = doc
-            .get_document("metadata")
-            .ok()
-            .and_then(|m| m.get_f64("confidence").ok());
-
-        out.push(PublicRunListItem {
-            _id: id,
-            created_at,
-            status,
-            quality,
-            confidence,
-        });
+    Ok(Json(runs.iter().map(PublicRunListItem::from).collect()))
+}
+
+/// DELETE /ai/extraction-runs/:run_id - Archive a run (kept, but hidden from the lists).
+async fn archive_extraction_run_handler(
+    AxumState(state): AxumState<Arc<AppState>>,
+    AxumPath(run_id): AxumPath<String>,
+) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    let Ok(run_oid) = ObjectId::parse_str(&run_id) else {
+        return Err(axum::http::StatusCode::BAD_REQUEST);
+    };
+    let db = state.mongo_client.database("wyat");
+
+    match archive_extraction_run(&db, run_oid).await {
+        Ok(true) => Ok(Json(json!({ "status": "archived", "run_id": run_id }))),
+        Ok(false) => Err(axum::http::StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(%run_id, error = %e, "archiving extraction run failed");
+            Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
+}
+
+const RECENT_RUNS_DEFAULT_LIMIT: i64 = 50;
+const RECENT_RUNS_MAX_LIMIT: i64 = 200;
 
-    Ok(Json(out))
+#[derive(Debug, Deserialize)]
+struct RecentRunsQuery {
+    limit: Option<i64>,
+    status: Option<String>,
+}
+
+/// GET /ai/extraction-runs/recent?limit=&status= - Newest runs across every document
+/// (limit defaults to 50, capped at 200), each with its `doc_id`.
+async fn list_recent_extraction_runs_handler(
+    AxumState(state): AxumState<Arc<AppState>>,
+    AxumQuery(q): AxumQuery<RecentRunsQuery>,
+) -> Result<Json<Vec<PublicRunListItem>>, axum::http::StatusCode> {
+    let db = state.mongo_client.database("wyat");
+    let limit = q
+        .limit
+        .unwrap_or(RECENT_RUNS_DEFAULT_LIMIT)
+        .clamp(1, RECENT_RUNS_MAX_LIMIT);
+    let filter = RunListFilter {
+        doc_id: None,
+        status: q.status.filter(|s| !s.is_empty()),
+    };
+
+    let runs = list_extraction_runs(&db, &filter, limit)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(runs.iter().map(PublicRunListItem::from).collect()))
 }
 
 #[derive(Serialize)]
@@ -1154,6 +1566,8 @@ struct PublicRunDetail {
     status: String,
     quality: Option<String>,
     confidence: Option<f64>,
+    usage: Option<TokenUsage>,
+    estimated_cost_usd: Option<f64>,
     response_text: String,
 }
 
@@ -1173,9 +1587,9 @@ async fn get_extraction_run_handler(
             doc! { "_id": run_oid },
             FindOneOptions::builder()
                 .projection(doc! {
-                    "_id": 1, "created_at": 1, "status": 1,
+                    "_id": 1, "created_at": 1, "status": 1, "model": 1,
                     "metadata.quality": 1, "metadata.confidence": 1,
-                    "response_text": 1
+                    "metadata.usage": 1, "response_text": 1
                 })
                 .build(),
         )
@@ -1194,6 +1608,9 @@ async fn get_extraction_run_handler(
         .and_then(|m| m.get_str("quality").ok())
         .map(|s| s.to_string());
     let confidence = md.and_then(|m| m.get_f64("confidence").ok());
+    let (usage, estimated_cost_usd) = md
+        .map(|m| run_usage_and_cost(doc.get_str("model").unwrap_or_default(), m))
+        .unwrap_or_default();
     let response_text = doc.get_str("response_text").unwrap_or("{}").to_string();
 
     Ok(Json(PublicRunDetail {
@@ -1202,6 +1619,170 @@ async fn get_extraction_run_handler(
         status,
         quality,
         confidence,
+        usage,
+        estimated_cost_usd,
         response_text,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{doc, oid::ObjectId};
+    use serde_json::json;
+    use services::openai::{ExtractResult, JsonRepair};
+
+    async fn extraction_fixture() -> (mongodb::Database, ExtractBankStatementRequest) {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_main_{}", ObjectId::new().to_hex()));
+        db.collection::<mongodb::bson::Document>("ai_prompts")
+            .insert_one(
+                doc! {
+                    "id": "capital.extract_bank_statement",
+                    "namespace": "capital",
+                    "task": "extract_bank_statement",
+                    "version": 1,
+                    "prompt_template": "Extract every transaction",
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let doc_id = ObjectId::new();
+        db.collection::<mongodb::bson::Document>("documents")
+            .insert_one(doc! { "_id": doc_id, "doc_id": "statement-1" }, None)
+            .await
+            .unwrap();
+        let blob = services::storage::insert_blob(
+            &db,
+            Bytes::from_static(b"%PDF-1.4 statement"),
+            "application/pdf",
+        )
+        .await
+        .unwrap()
+        .blob;
+
+        let query = ExtractBankStatementStreamQuery {
+            blob_id: blob.id.to_hex(),
+            doc_id: "statement-1".to_string(),
+            prompt: String::new(),
+            prompt_id: "capital.extract_bank_statement".to_string(),
+            prompt_version: "1".to_string(),
+            model: String::new(),
+            assistant_name: "test-assistant".to_string(),
+            temperature: None,
+            max_tokens: None,
+            response_format: None,
+        };
+        (db, query.into())
+    }
+
+    async fn collect_events(
+        mut rx: tokio::sync::mpsc::UnboundedReceiver<ExtractionEvent>,
+    ) -> Vec<ExtractionEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn stream_reports_pipeline_stages_and_never_imports() {
+        let (db, req) = extraction_fixture().await;
+        let (progress, rx) = ExtractionProgress::channel();
+
+        stream_bank_statement_extraction(
+            &db,
+            req,
+            &progress,
+            async |prompt: &str,
+                   pdf: &Bytes,
+                   _: &ExtractionParams,
+                   _: &str,
+                   progress: &ExtractionProgress| {
+                assert_eq!(prompt, "Extract every transaction");
+                assert!(pdf.starts_with(b"%PDF"));
+                // Stages the OpenAI client reports around its upload and model call
+                progress.stage(ExtractionStage::Uploaded);
+                progress.stage(ExtractionStage::CallingModel);
+                progress.stage(ExtractionStage::Parsing);
+                Ok(ExtractionOutput {
+                    result: ExtractResult {
+                        transactions: vec![json!({
+                            "txid": "STMT-1",
+                            "date": "2025-09-01",
+                            "account_id": "acct.test",
+                            "direction": "debit",
+                            "kind": "fiat",
+                            "ccy_or_asset": "USD",
+                            "amount_or_qty": "42.10",
+                        })],
+                        audit: json!({}),
+                        inferred_meta: json!({}),
+                        quality: "ok".to_string(),
+                        confidence: 0.9,
+                    },
+                    usage: None,
+                    json_repair: JsonRepair::None,
+                })
+            },
+        )
+        .await;
+        drop(progress);
+
+        let events = collect_events(rx).await;
+        let names: Vec<_> = events.iter().map(|e| e.name_and_data().0).collect();
+        assert_eq!(
+            names,
+            vec!["uploaded", "calling_model", "parsing", "importing", "done"]
+        );
+        let ExtractionEvent::Done(payload) = &events[4] else {
+            panic!("expected a done event, got {:?}", events[4]);
+        };
+        assert_eq!(payload["transactions"][0]["txid"], "STMT-1");
+        assert!(payload.get("import_summary").is_none());
+
+        // The run was recorded against the document, but nothing reached the ledger
+        let document = db
+            .collection::<mongodb::bson::Document>("documents")
+            .find_one(doc! { "doc_id": "statement-1" }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(document.get_object_id("latest_extraction_run_id").is_ok());
+        let ledger_rows = db
+            .collection::<mongodb::bson::Document>("capital_ledger")
+            .count_documents(None, None)
+            .await
+            .unwrap();
+        assert_eq!(ledger_rows, 0);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stream_ends_with_error_when_the_model_call_fails() {
+        let (db, req) = extraction_fixture().await;
+        let (progress, rx) = ExtractionProgress::channel();
+
+        stream_bank_statement_extraction(
+            &db,
+            req,
+            &progress,
+            async |_: &str, _: &Bytes, _: &ExtractionParams, _: &str, _: &ExtractionProgress| {
+                Err(anyhow::anyhow!("model unavailable"))
+            },
+        )
+        .await;
+        drop(progress);
+
+        let events = collect_events(rx).await;
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], ExtractionEvent::Error(_)));
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -1,10 +1,14 @@
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
-use mongodb::bson::{Bson, Document, doc};
+use futures::stream::TryStreamExt;
+use mongodb::bson::{Bson, Document, doc, to_bson};
+use mongodb::{ClientSession, Collection, Database};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
 
 use crate::AppState;
+use crate::journal::JournalEntry;
+use crate::mongo::supports_transactions;
 
 // Custom response structs for frontend consumption
 #[derive(Serialize)]
@@ -310,6 +314,263 @@ pub async fn delete_person(state: State<Arc<AppState>>, tag: String) -> impl Int
     }
 }
 
+#[derive(Deserialize)]
+pub struct MergePersonsRequest {
+    pub from_tag: String,
+    pub into_tag: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PersonMergeResult {
+    pub journal_entries_updated: u64,
+    pub ledger_transactions_updated: u64,
+    /// False when the server has no transaction support (standalone) and the
+    /// writes ran one after another
+    pub transactional: bool,
+}
+
+#[derive(Debug)]
+pub enum PersonMergeError {
+    Validation(String),
+    NotFound(String),
+    Database(String),
+}
+
+impl From<mongodb::error::Error> for PersonMergeError {
+    fn from(e: mongodb::error::Error) -> Self {
+        PersonMergeError::Database(e.to_string())
+    }
+}
+
+/// Rewrite `from` to `into` in an entry's tags and `@person:` mentions. Returns the
+/// `$set` document, or `None` when the entry does not reference `from`.
+fn rewrite_person_refs(entry: &JournalEntry, from: &str, into: &str) -> Option<Document> {
+    let rewrite = |text: &str| -> String {
+        crate::journal::REGISTRY_REFERENCE
+            .replace_all(text, |caps: &regex::Captures| {
+                if &caps[1] == "person" && &caps[2] == from {
+                    format!("@person:{}", into)
+                } else {
+                    caps[0].to_string()
+                }
+            })
+            .into_owned()
+    };
+
+    let tags = entry.tags.as_ref().map(|tags| {
+        let mut merged: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = if tag == from { into } else { tag.as_str() };
+            if !merged.iter().any(|t| t == tag) {
+                merged.push(tag.to_string());
+            }
+        }
+        merged
+    });
+    let mut versions = entry.versions.clone();
+    for version in &mut versions {
+        version.text = rewrite(&version.text);
+    }
+    let preview_text = rewrite(&entry.preview_text);
+
+    let changed = tags != entry.tags
+        || preview_text != entry.preview_text
+        || versions
+            .iter()
+            .zip(&entry.versions)
+            .any(|(new, old)| new.text != old.text);
+    if !changed {
+        return None;
+    }
+
+    let mut set = doc! { "preview_text": preview_text };
+    set.insert("versions", to_bson(&versions).ok()?);
+    if let Some(tags) = tags {
+        set.insert("tags", tags);
+    }
+    Some(set)
+}
+
+async fn update_with<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    update: Document,
+    many: bool,
+    session: Option<&mut ClientSession>,
+) -> mongodb::error::Result<u64> {
+    let result = match (session, many) {
+        (Some(session), true) => {
+            collection
+                .update_many_with_session(filter, update, None, session)
+                .await?
+        }
+        (Some(session), false) => {
+            collection
+                .update_one_with_session(filter, update, None, session)
+                .await?
+        }
+        (None, true) => collection.update_many(filter, update, None).await?,
+        (None, false) => collection.update_one(filter, update, None).await?,
+    };
+    Ok(result.modified_count)
+}
+
+/// The writes of a merge: drop `from` from the registry, then rewrite journal entries
+/// and ledger payees. Runs inside `session`'s transaction when one is given.
+async fn apply_person_merge(
+    db: &Database,
+    from: &str,
+    into: &str,
+    journal_updates: &[(mongodb::bson::oid::ObjectId, Document)],
+    mut session: Option<&mut ClientSession>,
+) -> Result<(u64, u64), PersonMergeError> {
+    let meta = db.collection::<Document>("meta");
+    let removed = update_with(
+        &meta,
+        doc! { "type": "person_registry", "persons.tag": from },
+        doc! {
+            "$pull": { "persons": { "tag": from } },
+            "$set": { "updatedAt": mongodb::bson::DateTime::now() }
+        },
+        false,
+        session.as_deref_mut(),
+    )
+    .await?;
+    if removed == 0 {
+        return Err(PersonMergeError::NotFound(format!(
+            "Person '{}' not found",
+            from
+        )));
+    }
+
+    let journal = db.collection::<Document>("journal");
+    let mut journal_updated = 0;
+    for (id, set) in journal_updates {
+        journal_updated += update_with(
+            &journal,
+            doc! { "_id": id },
+            doc! { "$set": set.clone() },
+            false,
+            session.as_deref_mut(),
+        )
+        .await?;
+    }
+
+    let ledger_updated = update_with(
+        &db.collection::<Document>("capital_ledger"),
+        doc! { "payee": from },
+        doc! { "$set": { "payee": into } },
+        true,
+        session,
+    )
+    .await?;
+
+    Ok((journal_updated, ledger_updated))
+}
+
+/// Merge person `from_tag` into `into_tag`: removes `from_tag` from the registry and
+/// rewrites journal tags, `@person:` mentions and ledger payees that reference it.
+/// Transactional when the server supports it.
+pub async fn merge_persons(
+    db: &Database,
+    from: &str,
+    into: &str,
+) -> Result<PersonMergeResult, PersonMergeError> {
+    let (from, into) = (from.trim(), into.trim());
+    if from.is_empty() || into.is_empty() {
+        return Err(PersonMergeError::Validation(
+            "from_tag and into_tag are required".to_string(),
+        ));
+    }
+    if from == into {
+        return Err(PersonMergeError::Validation(
+            "from_tag and into_tag must differ".to_string(),
+        ));
+    }
+
+    let meta = db.collection::<Document>("meta");
+    for tag in [from, into] {
+        let exists = meta
+            .count_documents(doc! { "type": "person_registry", "persons.tag": tag }, None)
+            .await?;
+        if exists == 0 {
+            return Err(PersonMergeError::NotFound(format!(
+                "Person '{}' not found",
+                tag
+            )));
+        }
+    }
+
+    let journal = db.collection::<JournalEntry>("journal");
+    let mention = regex::escape(&format!("@person:{}", from));
+    let candidates: Vec<JournalEntry> = journal
+        .find(
+            doc! { "$or": [
+                { "tags": from },
+                { "versions.text": { "$regex": &mention } },
+                { "preview_text": { "$regex": &mention } },
+            ] },
+            None,
+        )
+        .await?
+        .try_collect()
+        .await?;
+    let journal_updates: Vec<_> = candidates
+        .iter()
+        .filter_map(|entry| Some((entry.id?, rewrite_person_refs(entry, from, into)?)))
+        .collect();
+
+    let transactional = supports_transactions(db).await;
+    let (journal_entries_updated, ledger_transactions_updated) = if transactional {
+        let mut session = meta.client().start_session(None).await?;
+        session.start_transaction(None).await?;
+        match apply_person_merge(db, from, into, &journal_updates, Some(&mut session)).await {
+            Ok(counts) => {
+                session.commit_transaction().await?;
+                counts
+            }
+            Err(e) => {
+                let _ = session.abort_transaction().await;
+                return Err(e);
+            }
+        }
+    } else {
+        apply_person_merge(db, from, into, &journal_updates, None).await?
+    };
+
+    Ok(PersonMergeResult {
+        journal_entries_updated,
+        ledger_transactions_updated,
+        transactional,
+    })
+}
+
+/// POST /meta/persons/merge - Body `{ "from_tag": "...", "into_tag": "..." }`
+pub async fn merge_persons_handler(
+    state: State<Arc<AppState>>,
+    request: Json<MergePersonsRequest>,
+) -> impl IntoResponse {
+    let db = state.mongo_client.database("wyat");
+
+    match merge_persons(&db, &request.from_tag, &request.into_tag).await {
+        Ok(result) => Json(json!({
+            "message": format!("Merged {} into {}", request.from_tag, request.into_tag),
+            "from_tag": request.from_tag,
+            "into_tag": request.into_tag,
+            "updated": result.journal_entries_updated + result.ledger_transactions_updated,
+            "journal_entries_updated": result.journal_entries_updated,
+            "ledger_transactions_updated": result.ledger_transactions_updated,
+            "transactional": result.transactional,
+        }))
+        .into_response(),
+        Err(PersonMergeError::Validation(e)) => (StatusCode::BAD_REQUEST, e).into_response(),
+        Err(PersonMergeError::NotFound(e)) => (StatusCode::NOT_FOUND, e).into_response(),
+        Err(PersonMergeError::Database(e)) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+        }
+    }
+}
+
 // Place operations
 pub async fn add_place(
     state: State<Arc<AppState>>,
@@ -763,3 +1024,124 @@ pub async fn update_capital_readme(
 ) -> impl IntoResponse {
     update_meta_document(state, "capital_readme".to_string(), update_data).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalVersion;
+    use mongodb::bson::oid::ObjectId;
+
+    fn entry(tags: &[&str], text: &str) -> JournalEntry {
+        serde_json::from_value(json!({
+            "date": "2025-01-01",
+            "versions": [JournalVersion { text: text.to_string(), timestamp: chrono::Utc::now() }],
+            "preview_text": text,
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn rewrites_only_exact_person_references() {
+        let e = entry(
+            &["alice-smith", "alice", "travel"],
+            "Saw @person:alice-smith and @person:alice-smithers",
+        );
+        let set = rewrite_person_refs(&e, "alice-smith", "alice").unwrap();
+        assert_eq!(
+            set.get_array("tags").unwrap(),
+            &vec![Bson::from("alice"), Bson::from("travel")]
+        );
+        assert_eq!(
+            set.get_str("preview_text").unwrap(),
+            "Saw @person:alice and @person:alice-smithers"
+        );
+
+        assert!(
+            rewrite_person_refs(&entry(&["bob"], "@person:bob"), "alice-smith", "alice").is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_rewrites_references_and_removes_duplicate() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_meta_{}", ObjectId::new().to_hex()));
+
+        db.collection::<Document>("meta")
+            .insert_one(
+                doc! {
+                    "type": "person_registry",
+                    "persons": [
+                        { "tag": "alice", "name": "Alice", "nicknames": [], "visibility": "public" },
+                        { "tag": "alice-smith", "name": "Alice Smith", "nicknames": [], "visibility": "public" },
+                    ],
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let journal = db.collection::<JournalEntry>("journal");
+        journal
+            .insert_many(
+                [
+                    entry(&["alice-smith"], "Dinner with @person:alice-smith"),
+                    entry(&[], "Called @person:alice-smith"),
+                    entry(&["alice"], "Unrelated @person:alice"),
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        let ledger = db.collection::<Document>("capital_ledger");
+        ledger
+            .insert_many(
+                [
+                    doc! { "id": "tx_1", "payee": "alice-smith" },
+                    doc! { "id": "tx_2", "payee": "Starbucks" },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let result = merge_persons(&db, "alice-smith", "alice").await.unwrap();
+        assert_eq!(result.journal_entries_updated, 2);
+        assert_eq!(result.ledger_transactions_updated, 1);
+
+        let registry = db
+            .collection::<Document>("meta")
+            .find_one(doc! { "type": "person_registry" }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(registry.get_array("persons").unwrap().len(), 1);
+        assert_eq!(
+            journal
+                .count_documents(
+                    doc! { "$or": [
+                        { "tags": "alice-smith" },
+                        { "versions.text": { "$regex": "alice-smith" } },
+                    ] },
+                    None,
+                )
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            ledger
+                .count_documents(doc! { "payee": "alice" }, None)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(matches!(
+            merge_persons(&db, "alice-smith", "alice").await,
+            Err(PersonMergeError::NotFound(_))
+        ));
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+//! MongoDB client setup: pool tuning from env and a startup reachability check.
+
+use std::time::Duration;
+
+use mongodb::{Client, Database, bson::doc, options::ClientOptions};
+
+/// Used when neither env nor the connection string set a value.
+pub const DEFAULT_MAX_POOL_SIZE: u32 = 10;
+pub const DEFAULT_MIN_POOL_SIZE: u32 = 1;
+/// Short enough that an unreachable server fails startup quickly instead of hanging.
+pub const DEFAULT_SERVER_SELECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Apply pool settings from `var` (normally `std::env::var`):
+/// MONGO_MAX_POOL_SIZE, MONGO_MIN_POOL_SIZE, MONGO_MAX_IDLE_TIME_SECS and
+/// MONGO_SERVER_SELECTION_TIMEOUT_MS. Env beats the connection string; when neither sets
+/// pool size or server selection timeout, the defaults above apply. Unparsable values are
+/// ignored with a warning, and a min above max is clamped to max.
+pub fn apply_mongo_pool_settings(
+    options: &mut ClientOptions,
+    var: impl Fn(&str) -> Option<String>,
+) {
+    let read_u64 = |name: &str| -> Option<u64> {
+        let v = var(name)?;
+        match v.trim().parse::<u64>() {
+            Ok(n) => Some(n),
+            Err(_) => {
+                tracing::warn!(name, value = %v, "ignoring invalid pool setting");
+                None
+            }
+        }
+    };
+
+    if let Some(max) = read_u64("MONGO_MAX_POOL_SIZE") {
+        options.max_pool_size = Some(max as u32);
+    }
+    if let Some(min) = read_u64("MONGO_MIN_POOL_SIZE") {
+        options.min_pool_size = Some(min as u32);
+    }
+    if let Some(idle) = read_u64("MONGO_MAX_IDLE_TIME_SECS") {
+        options.max_idle_time = Some(Duration::from_secs(idle));
+    }
+    if let Some(ms) = read_u64("MONGO_SERVER_SELECTION_TIMEOUT_MS") {
+        options.server_selection_timeout = Some(Duration::from_millis(ms));
+    }
+
+    options.max_pool_size.get_or_insert(DEFAULT_MAX_POOL_SIZE);
+    options.min_pool_size.get_or_insert(DEFAULT_MIN_POOL_SIZE);
+    options
+        .server_selection_timeout
+        .get_or_insert(DEFAULT_SERVER_SELECTION_TIMEOUT);
+
+    if let (Some(min), Some(max)) = (options.min_pool_size, options.max_pool_size)
+        && min > max
+    {
+        tracing::warn!(
+            min,
+            max,
+            "MONGO_MIN_POOL_SIZE exceeds MONGO_MAX_POOL_SIZE; clamping"
+        );
+        options.min_pool_size = Some(max);
+    }
+
+    tracing::info!(
+        max_pool_size = ?options.max_pool_size,
+        min_pool_size = ?options.min_pool_size,
+        max_idle_time_secs = ?options.max_idle_time.map(|d| d.as_secs()),
+        server_selection_timeout_ms = ?options.server_selection_timeout.map(|d| d.as_millis()),
+        "MongoDB pool settings"
+    );
+}
+
+/// Ping the server so an unreachable MongoDB is reported at startup, bounded by the
+/// client's server selection timeout.
+pub async fn ping(client: &Client) -> mongodb::error::Result<()> {
+    client
+        .database("admin")
+        .run_command(doc! { "ping": 1 }, None)
+        .await
+        .map(|_| ())
+}
+
+/// True when the server can run multi-document transactions (replica set or mongos).
+pub async fn supports_transactions(db: &Database) -> bool {
+    match db.run_command(doc! { "hello": 1 }, None).await {
+        Ok(hello) => hello.contains_key("setName") || hello.get_str("msg") == Ok("isdbgrid"),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    async fn options_with(uri: &str, vars: &[(&str, &str)]) -> ClientOptions {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut options = ClientOptions::parse(uri).await.unwrap();
+        apply_mongo_pool_settings(&mut options, |name| vars.get(name).cloned());
+        options
+    }
+
+    #[tokio::test]
+    async fn env_overrides_are_applied() {
+        let options = options_with(
+            "mongodb://localhost:27017/?maxPoolSize=50",
+            &[
+                ("MONGO_MAX_POOL_SIZE", "25"),
+                ("MONGO_MIN_POOL_SIZE", "5"),
+                ("MONGO_MAX_IDLE_TIME_SECS", "60"),
+                ("MONGO_SERVER_SELECTION_TIMEOUT_MS", "2500"),
+            ],
+        )
+        .await;
+
+        assert_eq!(options.max_pool_size, Some(25));
+        assert_eq!(options.min_pool_size, Some(5));
+        assert_eq!(options.max_idle_time, Some(Duration::from_secs(60)));
+        assert_eq!(
+            options.server_selection_timeout,
+            Some(Duration::from_millis(2500))
+        );
+    }
+
+    #[tokio::test]
+    async fn unset_values_fall_back_to_uri_then_defaults() {
+        let options = options_with(
+            "mongodb://localhost:27017/?maxPoolSize=50",
+            &[("MONGO_MIN_POOL_SIZE", "not-a-number")],
+        )
+        .await;
+
+        assert_eq!(options.max_pool_size, Some(50));
+        assert_eq!(options.min_pool_size, Some(DEFAULT_MIN_POOL_SIZE));
+        assert_eq!(options.max_idle_time, None);
+        assert_eq!(
+            options.server_selection_timeout,
+            Some(DEFAULT_SERVER_SELECTION_TIMEOUT)
+        );
+    }
+
+    #[tokio::test]
+    async fn min_above_max_is_clamped() {
+        let options = options_with(
+            "mongodb://localhost:27017",
+            &[("MONGO_MAX_POOL_SIZE", "4"), ("MONGO_MIN_POOL_SIZE", "8")],
+        )
+        .await;
+
+        assert_eq!(options.max_pool_size, Some(4));
+        assert_eq!(options.min_pool_size, Some(4));
+    }
+}
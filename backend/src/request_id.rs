@@ -0,0 +1,192 @@
+//! Request ids for correlating responses with server logs.
+//!
+//! Every request gets an id: the incoming `x-request-id` when it is a usable value,
+//! otherwise a fresh UUID. The id is recorded on a `request` tracing span, echoed in the
+//! `x-request-id` response header and added as `request_id` to JSON error bodies.
+//! Error responses with an empty body get `{ "error": <reason>, "request_id": <id> }`;
+//! other non-JSON error bodies are left alone and only carry the header.
+
+use axum::{
+    body::{Full, HttpBody as _, boxed},
+    http::{HeaderMap, HeaderValue, Request, header},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{Value, json};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Incoming ids longer than this are replaced rather than echoed.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The id assigned to the current request, available to handlers as an extension.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Honor a client-supplied id when it is short, visible ASCII; otherwise mint a UUID.
+pub fn request_id_for(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Middleware for `axum::middleware::from_fn`: assign, log and echo the request id.
+pub async fn propagate_request_id<B>(mut req: Request<B>, next: Next<B>) -> Response {
+    let request_id = RequestId(request_id_for(req.headers()));
+    req.extensions_mut().insert(request_id.clone());
+    let id = request_id.0;
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    let response = next.run(req).instrument(span.clone()).await;
+    if response.status().is_server_error() {
+        span.in_scope(|| tracing::error!(status = response.status().as_u16(), "request failed"));
+    }
+
+    let mut response = with_request_id_in_error_body(response, &id).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+async fn with_request_id_in_error_body(response: Response, id: &str) -> Response {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let is_empty = response.body().size_hint().exact() == Some(0);
+    if !is_json && !is_empty {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "could not read error body to add request_id");
+            return Response::from_parts(parts, boxed(Full::from(Vec::new())));
+        }
+    };
+
+    let body = if bytes.is_empty() {
+        json!({
+            "error": status.canonical_reason().unwrap_or("error"),
+            "request_id": id,
+        })
+    } else {
+        match serde_json::from_slice::<Value>(&bytes) {
+            Ok(Value::Object(mut map)) => {
+                map.entry("request_id").or_insert_with(|| json!(id));
+                Value::Object(map)
+            }
+            _ => return Response::from_parts(parts, boxed(Full::from(bytes))),
+        }
+    };
+
+    let bytes = serde_json::to_vec(&body).unwrap_or_default();
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    parts
+        .headers
+        .insert(header::CONTENT_LENGTH, bytes.len().into());
+    Response::from_parts(parts, boxed(Full::from(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json, Router, body::Body, http::StatusCode, middleware, response::IntoResponse,
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/json-error",
+                get(|| async {
+                    (StatusCode::NOT_FOUND, Json(json!({ "error": "missing" }))).into_response()
+                }),
+            )
+            .route(
+                "/empty-error",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            )
+            .layer(middleware::from_fn(propagate_request_id))
+    }
+
+    async fn send(path: &str, incoming: Option<&str>) -> Response {
+        let mut req = Request::builder().uri(path);
+        if let Some(id) = incoming {
+            req = req.header(REQUEST_ID_HEADER, id);
+        }
+        app()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn header_id(response: &Response) -> String {
+        response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    async fn json_body(response: Response) -> Value {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn responses_carry_a_request_id_header() {
+        let response = send("/ok", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(uuid::Uuid::parse_str(&header_id(&response)).is_ok());
+
+        let response = send("/ok", Some("trace-abc-123")).await;
+        assert_eq!(header_id(&response), "trace-abc-123");
+
+        // Unusable incoming ids are replaced
+        let response = send("/ok", Some("has spaces")).await;
+        assert_ne!(header_id(&response), "has spaces");
+    }
+
+    #[tokio::test]
+    async fn error_bodies_include_the_request_id() {
+        let response = send("/json-error", Some("req-1")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = json_body(response).await;
+        assert_eq!(body["error"], "missing");
+        assert_eq!(body["request_id"], "req-1");
+
+        let response = send("/empty-error", None).await;
+        let id = header_id(&response);
+        let body = json_body(response).await;
+        assert_eq!(body["error"], "Internal Server Error");
+        assert_eq!(body["request_id"], id.as_str());
+    }
+}
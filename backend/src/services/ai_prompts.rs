@@ -21,13 +21,28 @@ pub struct AiPrompt {
     pub updated_at: Option<mongodb::bson::DateTime>,
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum AiPromptError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+    #[error("Prompt not found: {0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("Database error: {0}")]
+    Database(#[from] mongodb::error::Error),
+}
+
+fn ai_prompts(db: &Database) -> mongodb::Collection<AiPrompt> {
+    db.collection("ai_prompts")
+}
+
 /// Get an AI prompt by its ID
 pub async fn get_prompt_by_id(db: &Database, prompt_id: &str) -> Result<AiPrompt> {
     println!("=== get_prompt_by_id START ===");
     println!("Fetching prompt with id: {}", prompt_id);
 
-    let coll = db.collection::<AiPrompt>("ai_prompts");
-    let prompt = coll
+    let prompt = ai_prompts(db)
         .find_one(doc! {"id": prompt_id}, None)
         .await?
         .ok_or_else(|| anyhow::anyhow!("Prompt not found: {}", prompt_id))?;
@@ -42,24 +57,231 @@ pub async fn get_prompt_by_id(db: &Database, prompt_id: &str) -> Result<AiPrompt
     Ok(prompt)
 }
 
-/// List all prompts (optionally filtered by namespace)
-pub async fn list_prompts(db: &Database, namespace: Option<&str>) -> Result<Vec<AiPrompt>> {
-    println!("=== list_prompts START ===");
+async fn find_latest_version(
+    db: &Database,
+    prompt_id: &str,
+) -> std::result::Result<Option<AiPrompt>, mongodb::error::Error> {
+    ai_prompts(db)
+        .find_one(
+            doc! {"id": prompt_id},
+            mongodb::options::FindOneOptions::builder()
+                .sort(doc! {"version": -1})
+                .build(),
+        )
+        .await
+}
 
-    let coll = db.collection::<AiPrompt>("ai_prompts");
-    let filter = if let Some(ns) = namespace {
-        println!("Filtering by namespace: {}", ns);
-        doc! {"namespace": ns}
-    } else {
-        doc! {}
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAiPromptRequest {
+    pub namespace: String,
+    #[serde(alias = "prompt_id")]
+    pub id: String,
+    pub task: String,
+    pub version: i32,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub prompt_template: String,
+    #[serde(default)]
+    pub prompt_variables: Option<Vec<String>>,
+}
+
+/// New version of an existing prompt; omitted fields are copied from the latest
+/// version and an omitted `version` becomes latest + 1.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NewPromptVersionRequest {
+    #[serde(default)]
+    pub version: Option<i32>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub prompt_template: Option<String>,
+    #[serde(default)]
+    pub prompt_variables: Option<Vec<String>>,
+}
+
+fn validate_prompt(prompt: &AiPrompt) -> std::result::Result<(), AiPromptError> {
+    if prompt.namespace.trim().is_empty() {
+        return Err(AiPromptError::Validation(
+            "namespace cannot be empty".to_string(),
+        ));
+    }
+    if prompt.id.trim().is_empty() {
+        return Err(AiPromptError::Validation(
+            "prompt_id cannot be empty".to_string(),
+        ));
+    }
+    if prompt.version < 1 {
+        return Err(AiPromptError::Validation(
+            "version must be a positive integer".to_string(),
+        ));
+    }
+    if prompt.prompt_template.trim().is_empty() {
+        return Err(AiPromptError::Validation(
+            "prompt_template cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn insert_prompt_version(
+    db: &Database,
+    prompt: AiPrompt,
+) -> std::result::Result<AiPrompt, AiPromptError> {
+    validate_prompt(&prompt)?;
+
+    let coll = ai_prompts(db);
+    let existing = coll
+        .find_one(doc! {"id": &prompt.id, "version": prompt.version}, None)
+        .await?;
+    if existing.is_some() {
+        return Err(AiPromptError::Conflict(format!(
+            "Prompt {} version {} already exists",
+            prompt.id, prompt.version
+        )));
+    }
+
+    coll.insert_one(&prompt, None).await?;
+    Ok(prompt)
+}
+
+/// Create a prompt; rejects a duplicate `(prompt_id, version)` pair
+pub async fn create_prompt(
+    db: &Database,
+    req: CreateAiPromptRequest,
+) -> std::result::Result<AiPrompt, AiPromptError> {
+    let now = mongodb::bson::DateTime::now();
+    let prompt = AiPrompt {
+        _id: mongodb::bson::oid::ObjectId::new(),
+        id: req.id.trim().to_string(),
+        namespace: req.namespace.trim().to_string(),
+        task: req.task,
+        version: req.version,
+        description: req.description,
+        model: req.model,
+        prompt_template: req.prompt_template,
+        prompt_variables: req.prompt_variables,
+        created_at: Some(now),
+        updated_at: Some(now),
+    };
+    insert_prompt_version(db, prompt).await
+}
+
+/// Append a new version of `prompt_id`, copying forward unspecified fields
+pub async fn create_prompt_version(
+    db: &Database,
+    prompt_id: &str,
+    req: NewPromptVersionRequest,
+) -> std::result::Result<AiPrompt, AiPromptError> {
+    let latest = find_latest_version(db, prompt_id)
+        .await?
+        .ok_or_else(|| AiPromptError::NotFound(prompt_id.to_string()))?;
+
+    let now = mongodb::bson::DateTime::now();
+    let prompt = AiPrompt {
+        _id: mongodb::bson::oid::ObjectId::new(),
+        id: latest.id,
+        namespace: req.namespace.unwrap_or(latest.namespace),
+        task: req.task.unwrap_or(latest.task),
+        version: req.version.unwrap_or(latest.version + 1),
+        description: req.description.or(latest.description),
+        model: req.model.or(latest.model),
+        prompt_template: req.prompt_template.unwrap_or(latest.prompt_template),
+        prompt_variables: req.prompt_variables.or(latest.prompt_variables),
+        created_at: Some(now),
+        updated_at: Some(now),
     };
+    insert_prompt_version(db, prompt).await
+}
+
+/// Namespace value that explicitly requests prompts from every namespace
+pub const ALL_NAMESPACES: &str = "all";
+
+/// Resolve the namespace filter for listing prompts.
+/// An explicit `namespace` wins, with `all` meaning no filter. When omitted,
+/// falls back to `AI_PROMPTS_DEFAULT_NAMESPACE`, or no filter if that is unset.
+pub fn resolve_list_namespace(requested: Option<&str>) -> Option<String> {
+    let default = std::env::var("AI_PROMPTS_DEFAULT_NAMESPACE").ok();
+    let namespace = requested
+        .map(str::trim)
+        .filter(|ns| !ns.is_empty())
+        .map(str::to_string)
+        .or(default.map(|ns| ns.trim().to_string()))
+        .filter(|ns| !ns.is_empty())?;
+
+    if namespace.eq_ignore_ascii_case(ALL_NAMESPACES) {
+        None
+    } else {
+        Some(namespace)
+    }
+}
+
+/// Paging and version options for `list_prompts`.
+#[derive(Debug, Clone, Default)]
+pub struct ListPromptsOptions {
+    pub limit: Option<i64>,
+    pub offset: Option<u64>,
+    /// Only the highest `version` of each prompt id
+    pub latest_only: bool,
+}
+
+/// Aggregation pipeline for `list_prompts`: optional namespace match, then (for
+/// `latest_only`) one document per prompt id holding its highest version, ordered by
+/// namespace, id and newest version first, then paged.
+fn list_prompts_pipeline(
+    namespace: Option<&str>,
+    options: &ListPromptsOptions,
+) -> Vec<mongodb::bson::Document> {
+    let mut pipeline = Vec::new();
+    if let Some(ns) = namespace {
+        pipeline.push(doc! { "$match": { "namespace": ns } });
+    }
+    if options.latest_only {
+        pipeline.push(doc! { "$sort": { "id": 1, "version": -1 } });
+        pipeline.push(doc! { "$group": { "_id": "$id", "latest": { "$first": "$$ROOT" } } });
+        pipeline.push(doc! { "$replaceRoot": { "newRoot": "$latest" } });
+    }
+    pipeline.push(doc! { "$sort": { "namespace": 1, "id": 1, "version": -1 } });
+    if let Some(offset) = options.offset.filter(|o| *o > 0) {
+        pipeline.push(doc! { "$skip": offset as i64 });
+    }
+    if let Some(limit) = options.limit.filter(|l| *l > 0) {
+        pipeline.push(doc! { "$limit": limit });
+    }
+    pipeline
+}
+
+/// List prompts (optionally filtered by namespace), paged and optionally
+/// collapsed to the latest version of each prompt
+pub async fn list_prompts(
+    db: &Database,
+    namespace: Option<&str>,
+    options: &ListPromptsOptions,
+) -> Result<Vec<AiPrompt>> {
+    println!("=== list_prompts START ===");
+    if let Some(ns) = namespace {
+        println!("Filtering by namespace: {}", ns);
+    }
 
-    let mut cursor = coll.find(filter, None).await?;
+    let coll = db.collection::<mongodb::bson::Document>("ai_prompts");
+    let mut cursor = coll
+        .aggregate(list_prompts_pipeline(namespace, options), None)
+        .await?;
     let mut prompts = Vec::new();
 
     use futures::stream::StreamExt;
     while let Some(result) = cursor.next().await {
-        match result {
+        match result
+            .map_err(anyhow::Error::from)
+            .and_then(|d| mongodb::bson::from_document::<AiPrompt>(d).map_err(anyhow::Error::from))
+        {
             Ok(prompt) => prompts.push(prompt),
             Err(e) => eprintln!("Error reading prompt: {}", e),
         }
@@ -70,3 +292,135 @@ pub async fn list_prompts(db: &Database, namespace: Option<&str>) -> Result<Vec<
 
     Ok(prompts)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::{Client, bson::oid::ObjectId};
+
+    fn prompt(id: &str, namespace: &str, version: i32) -> AiPrompt {
+        AiPrompt {
+            _id: ObjectId::new(),
+            id: id.to_string(),
+            namespace: namespace.to_string(),
+            task: "extract".to_string(),
+            version,
+            description: None,
+            model: None,
+            prompt_template: format!("template v{}", version),
+            prompt_variables: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_only_returns_newest_version_per_prompt() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_ai_prompts_{}", ObjectId::new()));
+        let coll = db.collection::<AiPrompt>("ai_prompts");
+        coll.insert_many(
+            vec![
+                prompt("capital.extract", "capital", 1),
+                prompt("capital.extract", "capital", 3),
+                prompt("capital.extract", "capital", 2),
+                prompt("capital.summary", "capital", 1),
+                prompt("journal.tags", "journal", 4),
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+        let latest = ListPromptsOptions {
+            latest_only: true,
+            ..Default::default()
+        };
+        let prompts = list_prompts(&db, Some("capital"), &latest).await.unwrap();
+        let ids: Vec<(&str, i32)> = prompts.iter().map(|p| (p.id.as_str(), p.version)).collect();
+        assert_eq!(ids, vec![("capital.extract", 3), ("capital.summary", 1)]);
+
+        let all = list_prompts(&db, Some("capital"), &ListPromptsOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].version, 3);
+
+        let page = ListPromptsOptions {
+            limit: Some(2),
+            offset: Some(1),
+            latest_only: false,
+        };
+        let paged = list_prompts(&db, Some("capital"), &page).await.unwrap();
+        let versions: Vec<i32> = paged.iter().map(|p| p.version).collect();
+        assert_eq!(versions, vec![2, 1]);
+        assert_eq!(paged[1].id, "capital.extract");
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_duplicate_prompt_version() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_ai_prompts_{}", ObjectId::new()));
+
+        let req = CreateAiPromptRequest {
+            namespace: "capital".to_string(),
+            id: "capital.extract".to_string(),
+            task: "extract".to_string(),
+            version: 1,
+            description: Some("bank statements".to_string()),
+            model: Some("gpt-4o-mini".to_string()),
+            prompt_template: "Extract transactions as JSON".to_string(),
+            prompt_variables: None,
+        };
+        create_prompt(&db, req.clone()).await.unwrap();
+        let err = create_prompt(&db, req.clone()).await.unwrap_err();
+        assert!(matches!(err, AiPromptError::Conflict(_)));
+
+        let blank = CreateAiPromptRequest {
+            namespace: " ".to_string(),
+            ..req.clone()
+        };
+        assert!(matches!(
+            create_prompt(&db, blank).await.unwrap_err(),
+            AiPromptError::Validation(_)
+        ));
+
+        // Omitted version auto-increments and unspecified fields carry forward
+        let v2 = create_prompt_version(
+            &db,
+            "capital.extract",
+            NewPromptVersionRequest {
+                prompt_template: Some("Extract transactions as strict JSON".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(v2.version, 2);
+        assert_eq!(v2.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(v2.namespace, "capital");
+
+        let dup = create_prompt_version(
+            &db,
+            "capital.extract",
+            NewPromptVersionRequest {
+                version: Some(1),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(dup, AiPromptError::Conflict(_)));
+
+        let fetched = get_prompt_by_id(&db, "capital.extract").await.unwrap();
+        assert_eq!(fetched.version, 2);
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::services::data_feeds::{DataFeed, DataFeedError, DataSnapshot, DataSnapshotData};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use reqwest::{Client, StatusCode};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::FromPrimitive;
@@ -94,16 +94,74 @@ impl CoingeckoClient {
         // Ping CoinGecko API first to check if it's available
         self.ping().await?;
 
-        // Use CoinGecko's simple/price endpoint
-        let vs_currency = unit.as_deref().unwrap_or("usd").to_lowercase();
+        let vs_currency = vs_currency_for(unit.as_deref());
+        let payload = self.fetch_simple_price(&feed.symbol, &vs_currency).await?;
+        let snapshot = snapshot_from_payload(feed, pair, &vs_currency, &payload)?;
 
+        println!("✅ Successfully created snapshot for {}", feed.symbol);
+        println!("=== COINGECKO FETCH PRICE END ===");
+
+        Ok(snapshot)
+    }
+
+    /// Fetch several coins with one simple/price call (comma-separated `ids` and
+    /// `vs_currencies`). Results are in request order; a coin missing from the
+    /// response only fails its own entry.
+    pub async fn fetch_price_snapshots(
+        &self,
+        requests: &[(&DataFeed, Option<String>, Option<String>)],
+    ) -> Result<Vec<Result<DataSnapshot, DataFeedError>>, DataFeedError> {
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut ids: Vec<&str> = Vec::new();
+        let mut vs_currencies: Vec<String> = Vec::new();
+        for (feed, _, unit) in requests {
+            if !ids.contains(&feed.symbol.as_str()) {
+                ids.push(&feed.symbol);
+            }
+            let vs_currency = vs_currency_for(unit.as_deref());
+            if !vs_currencies.contains(&vs_currency) {
+                vs_currencies.push(vs_currency);
+            }
+        }
+
+        println!(
+            "CoinGecko batch price fetch: {} coins in {} currencies",
+            ids.len(),
+            vs_currencies.len()
+        );
+        let payload = self
+            .fetch_simple_price(&ids.join(","), &vs_currencies.join(","))
+            .await?;
+
+        Ok(requests
+            .iter()
+            .map(|(feed, pair, unit)| {
+                snapshot_from_payload(
+                    feed,
+                    pair.clone(),
+                    &vs_currency_for(unit.as_deref()),
+                    &payload,
+                )
+            })
+            .collect())
+    }
+
+    /// GET simple/price for comma-separated `ids` and `vs_currencies`, with 24h change.
+    async fn fetch_simple_price(
+        &self,
+        ids: &str,
+        vs_currencies: &str,
+    ) -> Result<Value, DataFeedError> {
         // If base_url already includes /simple/price, use it directly
         // Otherwise, construct the full URL
         // Include 24h change data in the request
         let url = if self.base_url.contains("/simple/price") {
             format!(
                 "{}?ids={}&vs_currencies={}&include_24hr_change=true",
-                self.base_url, feed.symbol, vs_currency
+                self.base_url, ids, vs_currencies
             )
         } else {
             let base_url = self
@@ -112,12 +170,11 @@ impl CoingeckoClient {
                 .trim_end_matches('/');
             format!(
                 "{}/simple/price?ids={}&vs_currencies={}&include_24hr_change=true",
-                base_url, feed.symbol, vs_currency
+                base_url, ids, vs_currencies
             )
         };
 
         println!("Price fetch URL: {}", url);
-        println!("VS Currency: {}", vs_currency);
 
         let mut request = self.client.get(&url);
         if let Some(key) = &self.api_key {
@@ -140,80 +197,48 @@ impl CoingeckoClient {
 
         let payload: Value = response.error_for_status()?.json().await?;
         println!("Price response payload: {}", payload);
+        Ok(payload)
+    }
 
-        // Response format: {"bitcoin": {"usd": 50000.0}}
-        let coin_data = payload.get(&feed.symbol).ok_or_else(|| {
-            eprintln!("❌ Coin '{}' not found in response", feed.symbol);
-            DataFeedError::Parse(format!("Coin '{}' not found in response", feed.symbol))
-        })?;
-
-        println!("Coin data: {}", coin_data);
-
-        let price = coin_data
-            .get(&vs_currency)
-            .and_then(|v| v.as_f64())
-            .ok_or_else(|| {
-                eprintln!("❌ Price in '{}' not found", vs_currency);
-                DataFeedError::Parse(format!("Price in '{}' not found", vs_currency))
-            })?;
-
-        println!("Extracted price: {}", price);
+    /// Fetch historical prices for coin `id` from `/coins/{id}/market_chart`, one
+    /// snapshot per point, oldest first.
+    /// Example: https://api.coingecko.com/api/v3/coins/bitcoin/market_chart?vs_currency=usd&days=90
+    pub async fn fetch_market_chart(
+        &self,
+        feed: &DataFeed,
+        vs_currency: &str,
+        days: u32,
+    ) -> Result<Vec<DataSnapshot>, DataFeedError> {
+        let vs_currency = vs_currency.to_lowercase();
+        let base_url = self
+            .base_url
+            .trim_end_matches("/simple/price")
+            .trim_end_matches("/coins/{id}")
+            .trim_end_matches('/');
+        let url = format!(
+            "{}/coins/{}/market_chart?vs_currency={}&days={}",
+            base_url, feed.symbol, vs_currency, days
+        );
 
-        // Extract 24h change percentage
-        let change_24h = coin_data
-            .get(&format!("{}_24h_change", vs_currency))
-            .and_then(|v| v.as_f64());
+        println!("Market chart URL: {}", url);
 
-        if let Some(change) = change_24h {
-            println!("Extracted 24h change: {}%", change);
-        } else {
-            println!("No 24h change data available");
+        let mut request = self.client.get(&url);
+        if let Some(key) = &self.api_key {
+            request = request.header(&self.api_header, key);
         }
 
-        let value = Decimal::from_f64(price).ok_or(DataFeedError::Decimal)?;
-        println!("Decimal value: {}", value);
-
-        // simple/price doesn't include timestamps, use current time
-        let source_time = Some(Utc::now());
-
-        let asset_symbol = feed.symbol.to_uppercase();
-        println!("Asset symbol (uppercase): {}", asset_symbol);
-
-        // Store 24h change in metadata if available
-        let mut metadata_doc = mongodb::bson::Document::new();
-        if let Some(change) = change_24h {
-            metadata_doc.insert("change_24h_pct", change);
+        let response = request.send().await?;
+        if response.status() == StatusCode::UNAUTHORIZED
+            || response.status() == StatusCode::FORBIDDEN
+        {
+            eprintln!("❌ Authentication failed: {}", response.status());
+            return Err(DataFeedError::Http(
+                response.error_for_status().unwrap_err(),
+            ));
         }
 
-        let data = DataSnapshotData {
-            r#type: "price".to_string(),
-            feed_symbol: feed.symbol.clone(),
-            source: Some(feed.source.clone()),
-            symbol: Some(asset_symbol.clone()),
-            pair: pair.or_else(|| Some(format!("{}/{}", asset_symbol, vs_currency.to_uppercase()))),
-            value,
-            unit: Some(vs_currency.to_uppercase()),
-            label: Some("spot".to_string()),
-            metadata: if metadata_doc.is_empty() {
-                None
-            } else {
-                Some(metadata_doc)
-            },
-        };
-
-        let snapshot = DataSnapshot {
-            id: None,
-            feed_symbol: feed.symbol.clone(),
-            fetch_time: Utc::now(),
-            source_time,
-            data: vec![data],
-            metadata: None,
-        };
-
-        println!("✅ Successfully created snapshot for {}", feed.symbol);
-        println!("=== COINGECKO FETCH PRICE END ===");
-
-        Ok(snapshot)
+        let payload: Value = response.error_for_status()?.json().await?;
+        snapshots_from_market_chart(feed, &vs_currency, &payload)
     }
 
     pub fn get_source_url(&self, symbol: &str) -> String {
@@ -232,3 +257,264 @@ impl CoingeckoClient {
         }
     }
 }
+
+fn vs_currency_for(unit: Option<&str>) -> String {
+    unit.unwrap_or("usd").to_lowercase()
+}
+
+/// Build a snapshot for `feed` from a simple/price payload.
+/// Response format: {"bitcoin": {"usd": 50000.0, "usd_24h_change": 1.2}}
+fn snapshot_from_payload(
+    feed: &DataFeed,
+    pair: Option<String>,
+    vs_currency: &str,
+    payload: &Value,
+) -> Result<DataSnapshot, DataFeedError> {
+    let coin_data = payload.get(&feed.symbol).ok_or_else(|| {
+        eprintln!("❌ Coin '{}' not found in response", feed.symbol);
+        DataFeedError::Parse(format!("Coin '{}' not found in response", feed.symbol))
+    })?;
+
+    let price = coin_data
+        .get(vs_currency)
+        .and_then(|v| v.as_f64())
+        .ok_or_else(|| {
+            eprintln!("❌ Price in '{}' not found", vs_currency);
+            DataFeedError::Parse(format!("Price in '{}' not found", vs_currency))
+        })?;
+
+    // Extract 24h change percentage
+    let change_24h = coin_data
+        .get(format!("{}_24h_change", vs_currency))
+        .and_then(|v| v.as_f64());
+
+    let value = Decimal::from_f64(price).ok_or(DataFeedError::Decimal)?;
+
+    // simple/price doesn't include timestamps, use current time
+    let source_time = Some(Utc::now());
+
+    let asset_symbol = feed.symbol.to_uppercase();
+
+    // Store 24h change in metadata if available
+    let mut metadata_doc = mongodb::bson::Document::new();
+    if let Some(change) = change_24h {
+        metadata_doc.insert("change_24h_pct", change);
+    }
+
+    let data = DataSnapshotData {
+        r#type: "price".to_string(),
+        feed_symbol: feed.symbol.clone(),
+        source: Some(feed.source.clone()),
+        symbol: Some(asset_symbol.clone()),
+        pair: pair.or_else(|| Some(format!("{}/{}", asset_symbol, vs_currency.to_uppercase()))),
+        value,
+        unit: Some(vs_currency.to_uppercase()),
+        label: Some("spot".to_string()),
+        metadata: if metadata_doc.is_empty() {
+            None
+        } else {
+            Some(metadata_doc)
+        },
+    };
+
+    Ok(DataSnapshot {
+        id: None,
+        feed_symbol: feed.symbol.clone(),
+        fetch_time: Utc::now(),
+        source_time,
+        data: vec![data],
+        metadata: None,
+    })
+}
+
+/// Build one snapshot per point of a market_chart payload.
+/// Response format: {"prices": [[1711929600000, 70000.5], ...], "market_caps": [...], ...}
+fn snapshots_from_market_chart(
+    feed: &DataFeed,
+    vs_currency: &str,
+    payload: &Value,
+) -> Result<Vec<DataSnapshot>, DataFeedError> {
+    let prices = payload
+        .get("prices")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| DataFeedError::Parse("missing 'prices' in market chart".to_string()))?;
+
+    let asset_symbol = feed.symbol.to_uppercase();
+    let unit = vs_currency.to_uppercase();
+    let fetch_time = Utc::now();
+
+    prices
+        .iter()
+        .map(|point| {
+            let (ms, price) = match point.as_array().map(Vec::as_slice) {
+                Some([ms, price]) => (ms.as_i64(), price.as_f64()),
+                _ => (None, None),
+            };
+            let (ms, price) = ms.zip(price).ok_or_else(|| {
+                DataFeedError::Parse(format!("invalid market chart point: {}", point))
+            })?;
+            let source_time =
+                DateTime::from_timestamp_millis(ms).ok_or(DataFeedError::InvalidDateTime)?;
+            let value = Decimal::from_f64(price).ok_or(DataFeedError::Decimal)?;
+
+            let mut metadata = mongodb::bson::Document::new();
+            metadata.insert("backfilled_at", fetch_time.to_rfc3339());
+
+            Ok(DataSnapshot {
+                id: None,
+                feed_symbol: feed.symbol.clone(),
+                // Historical points are keyed by their own time so `/history` ranges find them
+                fetch_time: source_time,
+                source_time: Some(source_time),
+                data: vec![DataSnapshotData {
+                    r#type: "price".to_string(),
+                    feed_symbol: feed.symbol.clone(),
+                    source: Some(feed.source.clone()),
+                    symbol: Some(asset_symbol.clone()),
+                    pair: Some(format!("{}/{}", asset_symbol, unit)),
+                    value,
+                    unit: Some(unit.clone()),
+                    label: Some("market_chart".to_string()),
+                    metadata: None,
+                }],
+                metadata: Some(metadata),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::data_feeds::{DataFeedProvider, DataFeedSource};
+    use axum::{Json, Router, extract::Query, routing::get};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn coin_feed(symbol: &str) -> DataFeed {
+        DataFeed {
+            name: symbol.to_string(),
+            symbol: symbol.to_string(),
+            categories: vec!["crypto".to_string()],
+            source: DataFeedSource {
+                provider: DataFeedProvider::Coingecko,
+                publisher: Some("Coingecko".to_string()),
+                publish_url: String::new(),
+                fetch_method: "GET".to_string(),
+                format: None,
+                parser: None,
+            },
+            last_fetch: None,
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn batched_fetch_uses_one_request_for_shared_provider() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/simple/price",
+            get(move |Query(q): Query<HashMap<String, String>>| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(q.get("ids").map(String::as_str), Some("bitcoin,ethereum"));
+                    Json(serde_json::json!({
+                        "bitcoin": { "usd": 65000.5, "usd_24h_change": 1.5 },
+                        "ethereum": { "usd": 3200.25 }
+                    }))
+                }
+            }),
+        );
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let client = CoingeckoClient::new(
+            Client::new(),
+            format!("http://{}/simple/price", addr),
+            None,
+            "x-cg-demo-api-key".to_string(),
+        );
+        let btc = coin_feed("bitcoin");
+        let eth = coin_feed("ethereum");
+        let results = client
+            .fetch_price_snapshots(&[(&btc, None, None), (&eth, None, Some("USD".to_string()))])
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let btc_snapshot = results[0].as_ref().unwrap();
+        let eth_snapshot = results[1].as_ref().unwrap();
+        assert_eq!(
+            btc_snapshot.data[0].value,
+            "65000.5".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(
+            eth_snapshot.data[0].value,
+            "3200.25".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(eth_snapshot.data[0].unit.as_deref(), Some("USD"));
+    }
+
+    #[tokio::test]
+    async fn market_chart_points_become_snapshots() {
+        let app = Router::new().route(
+            "/coins/bitcoin/market_chart",
+            get(|Query(q): Query<HashMap<String, String>>| async move {
+                assert_eq!(q.get("vs_currency").map(String::as_str), Some("usd"));
+                assert_eq!(q.get("days").map(String::as_str), Some("90"));
+                Json(serde_json::json!({
+                    "prices": [[1711929600000_i64, 70000.5], [1712016000000_i64, 69500.25]],
+                    "market_caps": [[1711929600000_i64, 1.3e12]],
+                    "total_volumes": [[1711929600000_i64, 2.1e10]]
+                }))
+            }),
+        );
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let client = CoingeckoClient::new(
+            Client::new(),
+            format!("http://{}/simple/price", addr),
+            None,
+            "x-cg-demo-api-key".to_string(),
+        );
+        let snapshots = client
+            .fetch_market_chart(&coin_feed("bitcoin"), "USD", 90)
+            .await
+            .unwrap();
+
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(
+            snapshots[0].fetch_time,
+            DateTime::from_timestamp(1711929600, 0).unwrap()
+        );
+        assert_eq!(snapshots[0].source_time, Some(snapshots[0].fetch_time));
+        assert_eq!(
+            snapshots[1].data[0].value,
+            "69500.25".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(snapshots[1].data[0].unit.as_deref(), Some("USD"));
+    }
+
+    #[test]
+    fn malformed_market_chart_point_is_a_parse_error() {
+        let payload = serde_json::json!({ "prices": [[1711929600000_i64]] });
+        assert!(matches!(
+            snapshots_from_market_chart(&coin_feed("bitcoin"), "usd", &payload),
+            Err(DataFeedError::Parse(_))
+        ));
+    }
+}
@@ -1,4 +1,7 @@
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
 
 use chrono::{DateTime, Duration, Utc};
 use futures::TryStreamExt;
@@ -14,8 +17,9 @@ use thiserror::Error;
 use utoipa::ToSchema;
 
 use super::coingecko::CoingeckoClient;
+use super::offline::is_offline;
 
-#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DataFeedProvider {
     YahooFinance,
@@ -79,6 +83,8 @@ pub enum DataFeedError {
     MissingConfig(&'static str),
     #[error("unsupported data feed provider")]
     UnsupportedProvider,
+    #[error("external data feeds are disabled (WYAT_OFFLINE)")]
+    Offline,
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
     #[error("database error: {0}")]
@@ -91,6 +97,8 @@ pub enum DataFeedError {
     Json(#[from] serde_json::Error),
     #[error("failed to parse upstream response: {0}")]
     Parse(String),
+    #[error("batched request failed: {0}")]
+    Batch(String),
     #[error("decimal conversion error")]
     Decimal,
     #[error("invalid datetime in upstream response")]
@@ -104,18 +112,59 @@ pub struct DataFeedService {
     yahoo_api_header: String,
     coingecko_client: CoingeckoClient,
     staleness: Duration,
+    offline: bool,
+    min_interval: std::time::Duration,
+}
+
+/// A feed due for refresh, with the pair/unit to request.
+pub struct FeedRefresh {
+    pub feed: DataFeed,
+    pub pair: Option<String>,
+    pub unit: Option<String>,
+}
+
+/// Next allowed request time per provider. Process-wide because handlers build a
+/// fresh `DataFeedService` per request.
+fn provider_slots() -> &'static Mutex<HashMap<DataFeedProvider, Instant>> {
+    static SLOTS: OnceLock<Mutex<HashMap<DataFeedProvider, Instant>>> = OnceLock::new();
+    SLOTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Reserve the next request slot at least `min_interval` after the previous one and
+/// return how long to wait for it.
+fn reserve_slot(
+    slots: &mut HashMap<DataFeedProvider, Instant>,
+    provider: &DataFeedProvider,
+    min_interval: std::time::Duration,
+    now: Instant,
+) -> std::time::Duration {
+    let slot = slots
+        .get(provider)
+        .map(|last| *last + min_interval)
+        .filter(|next| *next > now)
+        .unwrap_or(now);
+    slots.insert(provider.clone(), slot);
+    slot - now
+}
+
+/// Required provider URL; offline mode never calls out, so a missing one is fine there.
+fn provider_url(name: &'static str, offline: bool) -> Result<String, DataFeedError> {
+    match env::var(name) {
+        Ok(url) => Ok(url),
+        Err(_) if offline => Ok(String::new()),
+        Err(_) => Err(DataFeedError::MissingConfig(name)),
+    }
 }
 
 impl DataFeedService {
     pub fn new() -> Result<Self, DataFeedError> {
-        let yahoo_url = env::var("YAHOO_FINANCE_API_URL")
-            .map_err(|_| DataFeedError::MissingConfig("YAHOO_FINANCE_API_URL"))?;
+        let offline = is_offline();
+        let yahoo_url = provider_url("YAHOO_FINANCE_API_URL", offline)?;
         let yahoo_api_key = env::var("YAHOO_FINANCE_API_KEY").ok();
         let yahoo_api_header =
             env::var("YAHOO_FINANCE_API_KEY_HEADER").unwrap_or_else(|_| "x-api-key".to_string());
 
-        let coingecko_url = env::var("COINGECKO_API_URL")
-            .map_err(|_| DataFeedError::MissingConfig("COINGECKO_API_URL"))?;
+        let coingecko_url = provider_url("COINGECKO_API_URL", offline)?;
         let coingecko_api_key = env::var("COINGECKO_API_KEY").ok();
         let coingecko_api_header = env::var("COINGECKO_API_KEY_HEADER")
             .unwrap_or_else(|_| "x-cg-demo-api-key".to_string());
@@ -126,6 +175,11 @@ impl DataFeedService {
             .filter(|minutes| *minutes > 0)
             .unwrap_or(5);
 
+        let min_interval_ms: u64 = env::var("DATA_FEED_MIN_INTERVAL_MS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(1000);
+
         let client = reqwest::Client::new();
         let coingecko_client = CoingeckoClient::new(
             client.clone(),
@@ -141,9 +195,16 @@ impl DataFeedService {
             yahoo_api_header,
             coingecko_client,
             staleness: Duration::minutes(staleness_minutes),
+            offline,
+            min_interval: std::time::Duration::from_millis(min_interval_ms),
         })
     }
 
+    /// True when `WYAT_OFFLINE` is set; snapshots then come only from MongoDB.
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
     pub fn source_for(&self, provider: &DataFeedProvider, symbol: &str) -> DataFeedSource {
         match provider {
             DataFeedProvider::YahooFinance => DataFeedSource {
@@ -188,6 +249,11 @@ impl DataFeedService {
         pair: Option<String>,
         unit: Option<String>,
     ) -> Result<DataSnapshot, DataFeedError> {
+        if self.offline {
+            return Err(DataFeedError::Offline);
+        }
+
+        self.throttle(&feed.source.provider).await;
         let snapshot = match feed.source.provider {
             DataFeedProvider::YahooFinance => {
                 self.fetch_yahoo_snapshot(feed, pair.clone(), unit.clone())
@@ -200,10 +266,171 @@ impl DataFeedService {
             }
         };
 
+        self.store_snapshot(db, feed, &snapshot).await?;
+        Ok(snapshot)
+    }
+
+    /// Refresh several feeds, throttled per provider. Coingecko feeds share one
+    /// batched request; Yahoo feeds are fetched one by one. Results are in input
+    /// order, and each `feed.last_fetch` is updated on success.
+    pub async fn fetch_and_store_snapshots(
+        &self,
+        db: &Database,
+        refreshes: &mut [FeedRefresh],
+    ) -> Vec<Result<DataSnapshot, DataFeedError>> {
+        if self.offline {
+            return refreshes
+                .iter()
+                .map(|_| Err(DataFeedError::Offline))
+                .collect();
+        }
+
+        let mut results: Vec<Option<Result<DataSnapshot, DataFeedError>>> =
+            refreshes.iter().map(|_| None).collect();
+
+        let coingecko: Vec<usize> = (0..refreshes.len())
+            .filter(|&i| refreshes[i].feed.source.provider == DataFeedProvider::Coingecko)
+            .collect();
+        if !coingecko.is_empty() {
+            let requests: Vec<_> = coingecko
+                .iter()
+                .map(|&i| {
+                    let r = &refreshes[i];
+                    (&r.feed, r.pair.clone(), r.unit.clone())
+                })
+                .collect();
+            self.throttle(&DataFeedProvider::Coingecko).await;
+            match self.coingecko_client.fetch_price_snapshots(&requests).await {
+                Ok(snapshots) => {
+                    for (&i, snapshot) in coingecko.iter().zip(snapshots) {
+                        results[i] = Some(snapshot);
+                    }
+                }
+                Err(err) => {
+                    // One failed request fails every feed in the batch
+                    let message = err.to_string();
+                    for &i in &coingecko {
+                        results[i] = Some(Err(DataFeedError::Batch(message.clone())));
+                    }
+                }
+            }
+        }
+
+        for (i, refresh) in refreshes.iter().enumerate() {
+            if refresh.feed.source.provider == DataFeedProvider::YahooFinance {
+                self.throttle(&DataFeedProvider::YahooFinance).await;
+                results[i] = Some(
+                    self.fetch_yahoo_snapshot(
+                        &refresh.feed,
+                        refresh.pair.clone(),
+                        refresh.unit.clone(),
+                    )
+                    .await,
+                );
+            }
+        }
+
+        let mut stored = Vec::with_capacity(refreshes.len());
+        for (refresh, result) in refreshes.iter_mut().zip(results) {
+            let result = match result.expect("every feed has a provider") {
+                Ok(snapshot) => self
+                    .store_snapshot(db, &mut refresh.feed, &snapshot)
+                    .await
+                    .map(|_| snapshot),
+                Err(err) => Err(err),
+            };
+            stored.push(result);
+        }
+        stored
+    }
+
+    async fn store_snapshot(
+        &self,
+        db: &Database,
+        feed: &mut DataFeed,
+        snapshot: &DataSnapshot,
+    ) -> Result<(), DataFeedError> {
         let snapshots = db.collection::<DataSnapshot>("capital_data_snapshots");
-        snapshots.insert_one(&snapshot, None).await?;
+        snapshots.insert_one(snapshot, None).await?;
 
         feed.last_fetch = Some(snapshot.fetch_time);
+        self.save_feed(db, feed).await
+    }
+
+    /// Fetch up to `days` of historical prices for `feed` and store the points not
+    /// already present. Returns how many snapshots were inserted.
+    pub async fn backfill_history(
+        &self,
+        db: &Database,
+        feed: &DataFeed,
+        unit: Option<String>,
+        days: u32,
+    ) -> Result<usize, DataFeedError> {
+        if self.offline {
+            return Err(DataFeedError::Offline);
+        }
+
+        self.throttle(&feed.source.provider).await;
+        let snapshots = match feed.source.provider {
+            DataFeedProvider::Coingecko => {
+                let vs_currency = unit.as_deref().unwrap_or("usd");
+                self.coingecko_client
+                    .fetch_market_chart(feed, vs_currency, days)
+                    .await?
+            }
+            DataFeedProvider::YahooFinance => self.fetch_yahoo_history(feed, unit, days).await?,
+        };
+
+        self.store_history(db, &feed.symbol, snapshots).await
+    }
+
+    /// Insert historical snapshots, skipping any whose `fetch_time` is already stored
+    /// for `feed_symbol`. Returns how many were inserted.
+    async fn store_history(
+        &self,
+        db: &Database,
+        feed_symbol: &str,
+        snapshots: Vec<DataSnapshot>,
+    ) -> Result<usize, DataFeedError> {
+        let (Some(first), Some(last)) = (
+            snapshots.iter().map(|s| s.fetch_time).min(),
+            snapshots.iter().map(|s| s.fetch_time).max(),
+        ) else {
+            return Ok(0);
+        };
+
+        let mut seen: HashSet<DateTime<Utc>> = self
+            .get_snapshot_history(db, feed_symbol, first, last)
+            .await?
+            .into_iter()
+            .map(|s| s.fetch_time)
+            .collect();
+        let fresh: Vec<DataSnapshot> = snapshots
+            .into_iter()
+            .filter(|s| seen.insert(s.fetch_time))
+            .collect();
+        if fresh.is_empty() {
+            return Ok(0);
+        }
+
+        let collection = db.collection::<DataSnapshot>("capital_data_snapshots");
+        collection.insert_many(&fresh, None).await?;
+        Ok(fresh.len())
+    }
+
+    /// Wait until `provider` may be called again (`DATA_FEED_MIN_INTERVAL_MS`).
+    async fn throttle(&self, provider: &DataFeedProvider) {
+        let wait = {
+            let mut slots = provider_slots().lock().unwrap_or_else(|e| e.into_inner());
+            reserve_slot(&mut slots, provider, self.min_interval, Instant::now())
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Upsert a feed definition by symbol.
+    pub async fn save_feed(&self, db: &Database, feed: &DataFeed) -> Result<(), DataFeedError> {
         let feeds = db.collection::<DataFeed>("capital_data_feeds");
         let feed_doc = bson::to_document(feed)?;
         feeds
@@ -213,8 +440,7 @@ impl DataFeedService {
                 UpdateOptions::builder().upsert(true).build(),
             )
             .await?;
-
-        Ok(snapshot)
+        Ok(())
     }
 
     pub async fn get_latest_snapshot(
@@ -239,7 +465,55 @@ impl DataFeedService {
         }
     }
 
+    /// Most recent snapshot fetched at or before `before`.
+    pub async fn get_snapshot_at_or_before(
+        &self,
+        db: &Database,
+        feed_symbol: &str,
+        before: DateTime<Utc>,
+    ) -> Result<Option<DataSnapshot>, DataFeedError> {
+        let snapshots = db.collection::<DataSnapshot>("capital_data_snapshots");
+        let options = FindOptions::builder()
+            .sort(doc! { "fetch_time": -1 })
+            .limit(1)
+            .build();
+        let before = bson::to_bson(&before)?;
+
+        let mut cursor = snapshots
+            .find(
+                doc! { "feed_symbol": feed_symbol, "fetch_time": { "$lte": before } },
+                options,
+            )
+            .await?;
+
+        Ok(cursor.try_next().await?)
+    }
+
+    /// Every stored snapshot for `feed_symbol` fetched within `[from, to]`, oldest first.
+    /// Snapshots are appended on each fetch, never overwritten.
+    pub async fn get_snapshot_history(
+        &self,
+        db: &Database,
+        feed_symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<DataSnapshot>, DataFeedError> {
+        let snapshots = db.collection::<DataSnapshot>("capital_data_snapshots");
+        let options = FindOptions::builder()
+            .sort(doc! { "fetch_time": 1 })
+            .build();
+        let filter = doc! {
+            "feed_symbol": feed_symbol,
+            "fetch_time": { "$gte": bson::to_bson(&from)?, "$lte": bson::to_bson(&to)? },
+        };
+
+        Ok(snapshots.find(filter, options).await?.try_collect().await?)
+    }
+
     pub fn needs_refresh(&self, feed: &DataFeed) -> bool {
+        if self.offline {
+            return false;
+        }
         match feed.last_fetch {
             Some(last_fetch) => Utc::now() - last_fetch > self.staleness,
             None => true,
@@ -321,4 +595,300 @@ impl DataFeedService {
             metadata: None,
         })
     }
+
+    /// Daily closes for the last `days` days from Yahoo's chart endpoint, oldest first.
+    async fn fetch_yahoo_history(
+        &self,
+        feed: &DataFeed,
+        unit: Option<String>,
+        days: u32,
+    ) -> Result<Vec<DataSnapshot>, DataFeedError> {
+        let url = self.interpolate_url(&self.yahoo_url, &feed.symbol);
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}range={}d&interval=1d", url, separator, days);
+        let mut request = self.client.get(&url).header(
+            "User-Agent",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36",
+        );
+        if let Some(key) = &self.yahoo_api_key {
+            request = request.header(&self.yahoo_api_header, key);
+        }
+
+        let response = request.send().await?;
+        if response.status() == StatusCode::UNAUTHORIZED
+            || response.status() == StatusCode::FORBIDDEN
+        {
+            return Err(DataFeedError::Http(
+                response.error_for_status().unwrap_err(),
+            ));
+        }
+        let payload: Value = response.error_for_status()?.json().await?;
+
+        yahoo_history_snapshots(feed, unit, &payload)
+    }
+}
+
+/// Build one snapshot per trading day from a Yahoo chart payload, skipping days
+/// with a null close.
+/// Response format: {"chart": {"result": [{"meta": {...}, "timestamp": [...],
+/// "indicators": {"quote": [{"close": [...]}]}}]}}
+fn yahoo_history_snapshots(
+    feed: &DataFeed,
+    unit: Option<String>,
+    payload: &Value,
+) -> Result<Vec<DataSnapshot>, DataFeedError> {
+    let result = payload
+        .pointer("/chart/result/0")
+        .ok_or_else(|| DataFeedError::Parse("missing chart result".to_string()))?;
+    let timestamps = result
+        .get("timestamp")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| DataFeedError::Parse("missing chart timestamps".to_string()))?;
+    let closes = result
+        .pointer("/indicators/quote/0/close")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| DataFeedError::Parse("missing chart closes".to_string()))?;
+
+    let unit = unit
+        .or_else(|| {
+            result
+                .pointer("/meta/currency")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "USD".to_string());
+    let fetch_time = Utc::now();
+
+    let mut snapshots = Vec::with_capacity(timestamps.len());
+    for (ts, close) in timestamps.iter().zip(closes) {
+        let Some(close) = close.as_f64() else {
+            continue;
+        };
+        let source_time = ts
+            .as_i64()
+            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+            .ok_or(DataFeedError::InvalidDateTime)?;
+        let value = Decimal::from_f64(close).ok_or(DataFeedError::Decimal)?;
+
+        let mut metadata = Document::new();
+        metadata.insert("backfilled_at", fetch_time.to_rfc3339());
+
+        snapshots.push(DataSnapshot {
+            id: None,
+            feed_symbol: feed.symbol.clone(),
+            // Historical points are keyed by their own time so `/history` ranges find them
+            fetch_time: source_time,
+            source_time: Some(source_time),
+            data: vec![DataSnapshotData {
+                r#type: "price".to_string(),
+                feed_symbol: feed.symbol.clone(),
+                source: Some(feed.source.clone()),
+                symbol: Some(feed.symbol.clone()),
+                pair: Some(format!("{}/{}", feed.symbol, unit)),
+                value,
+                unit: Some(unit.clone()),
+                label: Some("daily_close".to_string()),
+                metadata: None,
+            }],
+            metadata: Some(metadata),
+        });
+    }
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_service() -> DataFeedService {
+        let client = reqwest::Client::new();
+        DataFeedService {
+            client: client.clone(),
+            yahoo_url: String::new(),
+            yahoo_api_key: None,
+            yahoo_api_header: "x-api-key".to_string(),
+            coingecko_client: CoingeckoClient::new(
+                client,
+                String::new(),
+                None,
+                "x-cg-demo-api-key".to_string(),
+            ),
+            staleness: Duration::minutes(5),
+            offline: false,
+            min_interval: std::time::Duration::ZERO,
+        }
+    }
+
+    fn price_snapshot(symbol: &str, value: &str, fetch_time: DateTime<Utc>) -> DataSnapshot {
+        DataSnapshot {
+            id: None,
+            feed_symbol: symbol.to_string(),
+            fetch_time,
+            source_time: None,
+            data: vec![DataSnapshotData {
+                r#type: "price".to_string(),
+                feed_symbol: symbol.to_string(),
+                source: None,
+                symbol: Some(symbol.to_uppercase()),
+                pair: None,
+                value: value.parse().unwrap(),
+                unit: Some("USD".to_string()),
+                label: None,
+                metadata: None,
+            }],
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_fetches_are_retained_as_history() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_feeds_{}", ObjectId::new().to_hex()));
+        let service = test_service();
+        let mut feed = DataFeed {
+            name: "Bitcoin".to_string(),
+            symbol: "bitcoin".to_string(),
+            categories: vec!["crypto".to_string()],
+            source: DataFeedSource {
+                provider: DataFeedProvider::Coingecko,
+                publisher: None,
+                publish_url: String::new(),
+                fetch_method: "GET".to_string(),
+                format: None,
+                parser: None,
+            },
+            last_fetch: None,
+            metadata: None,
+        };
+
+        let t0 = Utc::now() - Duration::hours(2);
+        let t1 = t0 + Duration::hours(1);
+        service
+            .store_snapshot(&db, &mut feed, &price_snapshot("bitcoin", "100", t0))
+            .await
+            .unwrap();
+        service
+            .store_snapshot(&db, &mut feed, &price_snapshot("bitcoin", "101", t1))
+            .await
+            .unwrap();
+
+        let history = service
+            .get_snapshot_history(&db, "bitcoin", t0 - Duration::hours(1), Utc::now())
+            .await
+            .unwrap();
+        let values: Vec<String> = history
+            .iter()
+            .map(|s| s.data[0].value.to_string())
+            .collect();
+        assert_eq!(values, vec!["100", "101"]);
+        assert_eq!(feed.last_fetch, Some(t1));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn backfilled_history_skips_stored_timestamps() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_feeds_{}", ObjectId::new().to_hex()));
+        let service = test_service();
+
+        let t0 = DateTime::from_timestamp(1711929600, 0).unwrap();
+        let t1 = t0 + Duration::days(1);
+        let first = service
+            .store_history(&db, "bitcoin", vec![price_snapshot("bitcoin", "100", t0)])
+            .await
+            .unwrap();
+        let second = service
+            .store_history(
+                &db,
+                "bitcoin",
+                vec![
+                    price_snapshot("bitcoin", "100", t0),
+                    price_snapshot("bitcoin", "101", t1),
+                    price_snapshot("bitcoin", "101", t1),
+                ],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!((first, second), (1, 1));
+        let history = service
+            .get_snapshot_history(&db, "bitcoin", t0, t1)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn yahoo_chart_closes_skip_nulls() {
+        let payload = serde_json::json!({
+            "chart": {
+                "result": [{
+                    "meta": { "currency": "USD", "symbol": "AAPL" },
+                    "timestamp": [1711978200, 1712064600, 1712151000],
+                    "indicators": {
+                        "quote": [{
+                            "open": [171.19, 169.08, 168.79],
+                            "close": [170.03, null, 169.65]
+                        }]
+                    }
+                }],
+                "error": null
+            }
+        });
+        let feed = DataFeed {
+            name: "Apple".to_string(),
+            symbol: "AAPL".to_string(),
+            categories: vec!["stock".to_string()],
+            source: DataFeedSource {
+                provider: DataFeedProvider::YahooFinance,
+                publisher: None,
+                publish_url: String::new(),
+                fetch_method: "GET".to_string(),
+                format: None,
+                parser: None,
+            },
+            last_fetch: None,
+            metadata: None,
+        };
+
+        let snapshots = yahoo_history_snapshots(&feed, None, &payload).unwrap();
+
+        let points: Vec<(i64, String)> = snapshots
+            .iter()
+            .map(|s| (s.fetch_time.timestamp(), s.data[0].value.to_string()))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (1711978200, "170.03".to_string()),
+                (1712151000, "169.65".to_string())
+            ]
+        );
+        assert_eq!(snapshots[0].data[0].unit.as_deref(), Some("USD"));
+    }
+
+    #[test]
+    fn reserved_slots_are_spaced_by_min_interval() {
+        let mut slots = HashMap::new();
+        let gap = std::time::Duration::from_millis(1000);
+        let now = Instant::now();
+
+        let first = reserve_slot(&mut slots, &DataFeedProvider::Coingecko, gap, now);
+        let second = reserve_slot(&mut slots, &DataFeedProvider::Coingecko, gap, now);
+        let other = reserve_slot(&mut slots, &DataFeedProvider::YahooFinance, gap, now);
+
+        assert!(first.is_zero());
+        assert_eq!(second, gap);
+        assert!(other.is_zero());
+
+        let later = now + std::time::Duration::from_secs(5);
+        assert!(reserve_slot(&mut slots, &DataFeedProvider::Coingecko, gap, later).is_zero());
+    }
 }
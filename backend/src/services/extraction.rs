@@ -1,16 +1,97 @@
 use anyhow::{Result, anyhow};
+use bytes::Bytes;
+use futures::stream::TryStreamExt;
 use mongodb::{
     Database,
-    bson::{doc, oid::ObjectId},
+    bson::{Document, doc, oid::ObjectId},
+    options::FindOptions,
 };
 use sha2::{Digest, Sha256};
 
 use crate::capital::{BatchImportRequest, FlatTransaction};
 use crate::services::ai_prompts::get_prompt_by_id;
-use crate::services::openai::{ExtractResult, extract_bank_statement};
+use crate::services::openai::{
+    ExtractResult, ExtractionOutput, ExtractionParams, JsonRepair, TokenUsage, estimated_cost_usd,
+};
 use crate::services::storage as storage_svc;
 use crate::storage::ExtractionRun;
+use serde::Serialize;
 use serde_json::Value;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Pipeline stages reported while an extraction runs, in emission order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionStage {
+    Uploaded,
+    CallingModel,
+    Parsing,
+    Importing,
+    Done,
+}
+
+impl ExtractionStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtractionStage::Uploaded => "uploaded",
+            ExtractionStage::CallingModel => "calling_model",
+            ExtractionStage::Parsing => "parsing",
+            ExtractionStage::Importing => "importing",
+            ExtractionStage::Done => "done",
+        }
+    }
+}
+
+/// A progress message: either a stage transition or the terminal outcome.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtractionEvent {
+    Stage(ExtractionStage),
+    /// Final result payload; always the last event of a successful run
+    Done(Value),
+    Error(String),
+}
+
+impl ExtractionEvent {
+    /// SSE event name and JSON data for this event.
+    pub fn name_and_data(&self) -> (&'static str, Value) {
+        match self {
+            ExtractionEvent::Stage(stage) => {
+                (stage.as_str(), serde_json::json!({ "stage": stage }))
+            }
+            ExtractionEvent::Done(payload) => (ExtractionStage::Done.as_str(), payload.clone()),
+            ExtractionEvent::Error(message) => ("error", serde_json::json!({ "error": message })),
+        }
+    }
+}
+
+/// Optional progress sink threaded through the extraction pipeline.
+/// `ExtractionProgress::none()` is a no-op for non-streaming callers.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionProgress {
+    tx: Option<UnboundedSender<ExtractionEvent>>,
+}
+
+impl ExtractionProgress {
+    pub fn none() -> Self {
+        Self { tx: None }
+    }
+
+    pub fn channel() -> (Self, UnboundedReceiver<ExtractionEvent>) {
+        let (tx, rx) = unbounded_channel();
+        (Self { tx: Some(tx) }, rx)
+    }
+
+    pub fn stage(&self, stage: ExtractionStage) {
+        self.send(ExtractionEvent::Stage(stage));
+    }
+
+    pub fn send(&self, event: ExtractionEvent) {
+        // A closed receiver just means the client went away; the run still completes.
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(event);
+        }
+    }
+}
 
 /// Orchestrate the full bank statement extraction pipeline.
 ///
@@ -28,12 +109,24 @@ use serde_json::Value;
 /// * `prompt_text` - Raw prompt content supplied by the client (falls back to stored template when empty)
 /// * `prompt_id` - AI prompt identifier (e.g., "capital.extract_bank_statement")
 /// * `prompt_version` - Prompt version for tracking
-/// * `model` - OpenAI model to use (e.g., "gpt-4o-mini")
+/// * `params` - Model, temperature, max tokens and response format for the run
 /// * `assistant_name` - Assistant identifier for OpenAI
+/// * `progress` - Receives `uploaded`, `calling_model` and `parsing` stage events
+/// * `model` - The model call; `extract_bank_statement` outside of tests
 ///
 /// # Returns
 /// * `Ok((ExtractionRun, ExtractResult))` - The created run record and parsed extraction result
 /// * `Err` - If any step fails (prompt not found, blob not found, extraction fails, etc.)
+#[tracing::instrument(
+    skip_all,
+    fields(
+        doc_id = %doc_oid.to_hex(),
+        blob_id = %blob_oid.to_hex(),
+        prompt_id = %prompt_id,
+        prompt_version = %prompt_version,
+        model = %params.model
+    )
+)]
 pub async fn run_bank_statement_extraction(
     db: &Database,
     doc_oid: ObjectId,
@@ -41,39 +134,51 @@ pub async fn run_bank_statement_extraction(
     prompt_text: &str,
     prompt_id: &str,
     prompt_version: &str,
-    model: &str,
+    params: &ExtractionParams,
     assistant_name: &str,
+    progress: &ExtractionProgress,
+    model: impl AsyncFnOnce(
+        &str,
+        &Bytes,
+        &ExtractionParams,
+        &str,
+        &ExtractionProgress,
+    ) -> Result<ExtractionOutput>,
 ) -> Result<(ExtractionRun, ExtractResult)> {
-    println!("=== run_bank_statement_extraction START ===");
-    println!("Document: {}", doc_oid.to_hex());
-    println!("Blob: {}", blob_oid.to_hex());
-    println!("Prompt: {} v{}", prompt_id, prompt_version);
-    println!("Model: {}, Assistant: {}", model, assistant_name);
-
     // 1) Retrieve AI prompt from database
-    println!("Fetching AI prompt...");
     let ai_prompt = get_prompt_by_id(db, prompt_id).await?;
     let effective_prompt = if prompt_text.trim().is_empty() {
-        println!("Request prompt empty, using stored template");
+        tracing::debug!("request prompt empty, using stored template");
         ai_prompt.prompt_template.clone()
     } else {
         prompt_text.to_string()
     };
-    println!("Prompt ready: {} chars", effective_prompt.len());
 
     // 2) Load blob bytes
-    println!("Loading PDF bytes...");
     let pdf_bytes = storage_svc::get_blob_bytes_by_id(db, blob_oid).await?;
-    println!("Blob size: {} bytes", pdf_bytes.len());
+    tracing::info!(
+        prompt_chars = effective_prompt.len(),
+        blob_bytes = pdf_bytes.len(),
+        "calling OpenAI extraction"
+    );
 
     // 3) Call OpenAI extraction
-    println!("Calling OpenAI extraction...");
-    let result =
-        extract_bank_statement(&effective_prompt, &pdf_bytes, model, assistant_name).await?;
-    println!(
-        "Extraction succeeded: {} transactions, quality={}",
-        result.transactions.len(),
-        result.quality
+    let ExtractionOutput {
+        result,
+        usage,
+        json_repair,
+    } = model(
+        &effective_prompt,
+        &pdf_bytes,
+        params,
+        assistant_name,
+        progress,
+    )
+    .await?;
+    tracing::info!(
+        count = result.transactions.len(),
+        quality = %result.quality,
+        "extraction succeeded"
     );
 
     // 4) Build metadata document for the run
@@ -81,8 +186,11 @@ pub async fn run_bank_statement_extraction(
     let result_hash = format!("{:x}", Sha256::digest(result_json.as_bytes()));
     let prompt_hash = format!("{:x}", Sha256::digest(effective_prompt.as_bytes()));
 
-    let metadata = doc! {
-        "model": model,
+    let mut metadata = doc! {
+        "model": &params.model,
+        "temperature": params.temperature as f64,
+        "max_tokens": params.max_tokens.map(|t| t as i64),
+        "response_format": params.response_format.as_str(),
         "assistant_name": assistant_name,
         "blob_id": blob_oid.to_hex(),
         "prompt_id": prompt_id,
@@ -92,15 +200,17 @@ pub async fn run_bank_statement_extraction(
         "transaction_count": result.transactions.len() as i32,
         "quality": &result.quality,
         "confidence": result.confidence,
+        "json_repair": json_repair.as_str(),
+        "json_repaired": json_repair != JsonRepair::None,
     };
+    append_usage(&mut metadata, usage.as_ref());
 
     // 5) Create extraction run record (links document on success)
-    println!("Creating extraction run record...");
     let run = crate::storage::create_extraction_run(
         db,
         doc_oid,
         "bank_statement",
-        model,
+        &params.model,
         effective_prompt,
         metadata,
     )
@@ -112,12 +222,147 @@ pub async fn run_bank_statement_extraction(
         .update_one(doc! { "_id": &run.id }, update, None)
         .await?;
 
-    println!("=== run_bank_statement_extraction SUCCESS ===");
-    println!("ExtractionRun ID: {}", run.id);
+    tracing::info!(run_id = %run.id, "extraction run recorded");
 
     Ok((run, result))
 }
 
+/// Record token usage on a run's metadata so spend can be tracked per run.
+pub fn append_usage(metadata: &mut mongodb::bson::Document, usage: Option<&TokenUsage>) {
+    if let Some(usage) = usage {
+        metadata.insert(
+            "usage",
+            doc! {
+                "prompt_tokens": usage.prompt_tokens as i64,
+                "completion_tokens": usage.completion_tokens as i64,
+                "total_tokens": usage.total_tokens as i64,
+            },
+        );
+    }
+}
+
+/// Token usage stored by `append_usage`, if the run recorded any.
+pub fn usage_from_metadata(metadata: &mongodb::bson::Document) -> Option<TokenUsage> {
+    let usage = metadata.get_document("usage").ok()?;
+    let count = |key: &str| usage.get_i64(key).ok().map(|n| n as u32);
+    Some(TokenUsage {
+        prompt_tokens: count("prompt_tokens")?,
+        completion_tokens: count("completion_tokens")?,
+        total_tokens: count("total_tokens")?,
+    })
+}
+
+/// Usage plus estimated cost for API responses: `(usage, estimated_cost_usd)`.
+pub fn run_usage_and_cost(
+    model: &str,
+    metadata: &mongodb::bson::Document,
+) -> (Option<TokenUsage>, Option<f64>) {
+    let usage = usage_from_metadata(metadata);
+    let cost = usage.and_then(|u| estimated_cost_usd(model, &u));
+    (usage, cost)
+}
+
+/// Collection holding `ExtractionRun` records.
+pub const EXTRACTION_RUNS_COLLECTION: &str = "doc_extraction_runs";
+
+/// Run fields returned by the list endpoints (no prompt or response text).
+pub fn run_list_projection() -> Document {
+    doc! {
+        "_id": 1,
+        "doc_id": 1,
+        "created_at": 1,
+        "status": 1,
+        "model": 1,
+        "metadata.quality": 1,
+        "metadata.confidence": 1,
+        "metadata.usage": 1,
+    }
+}
+
+/// Which runs to list; an empty filter lists runs across every document.
+#[derive(Debug, Clone, Default)]
+pub struct RunListFilter {
+    pub doc_id: Option<ObjectId>,
+    pub status: Option<String>,
+}
+
+/// Newest non-archived runs first, projected with `run_list_projection`.
+pub async fn list_extraction_runs(
+    db: &Database,
+    filter: &RunListFilter,
+    limit: i64,
+) -> mongodb::error::Result<Vec<Document>> {
+    let mut query = doc! { "archived": { "$ne": true } };
+    if let Some(doc_id) = filter.doc_id {
+        query.insert("doc_id", doc_id);
+    }
+    if let Some(status) = &filter.status {
+        query.insert("status", status);
+    }
+
+    db.collection::<Document>(EXTRACTION_RUNS_COLLECTION)
+        .find(
+            query,
+            FindOptions::builder()
+                .sort(doc! { "created_at": -1, "_id": -1 })
+                .projection(run_list_projection())
+                .limit(limit)
+                .build(),
+        )
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Archive a run so it drops out of the list endpoints (the record is kept). If it was
+/// its document's latest run, the document is repointed at the newest remaining run.
+/// Returns false when no unarchived run has that id.
+pub async fn archive_extraction_run(
+    db: &Database,
+    run_id: ObjectId,
+) -> mongodb::error::Result<bool> {
+    let runs = db.collection::<Document>(EXTRACTION_RUNS_COLLECTION);
+    let Some(run) = runs
+        .find_one_and_update(
+            doc! { "_id": run_id, "archived": { "$ne": true } },
+            doc! { "$set": {
+                "archived": true,
+                "archived_at": chrono::Utc::now().timestamp(),
+            } },
+            None,
+        )
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    if let Ok(doc_id) = run.get_object_id("doc_id") {
+        let latest = list_extraction_runs(
+            db,
+            &RunListFilter {
+                doc_id: Some(doc_id),
+                status: None,
+            },
+            1,
+        )
+        .await?
+        .first()
+        .and_then(|r| r.get_object_id("_id").ok());
+        let update = match latest {
+            Some(latest) => doc! { "$set": { "latest_extraction_run_id": latest } },
+            None => doc! { "$set": { "latest_extraction_run_id": null } },
+        };
+        db.collection::<Document>("documents")
+            .update_one(
+                doc! { "_id": doc_id, "latest_extraction_run_id": run_id },
+                update,
+                None,
+            )
+            .await?;
+    }
+    Ok(true)
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ImportDefaults {
     pub source: String,
@@ -143,6 +388,43 @@ impl ImportDefaults {
 pub struct PreparedBatchImport {
     pub request: BatchImportRequest,
     pub preview: Vec<FlatTransaction>,
+    /// Preview txids already in `capital_ledger` (skipped on submit); filled in by
+    /// `find_existing_txids`, empty until then
+    pub duplicates: Vec<String>,
+}
+
+/// Preview txids that already exist in `capital_ledger`, in preview order, looked up
+/// with a single query.
+pub async fn find_existing_txids(
+    db: &Database,
+    preview: &[FlatTransaction],
+) -> mongodb::error::Result<Vec<String>> {
+    if preview.is_empty() {
+        return Ok(Vec::new());
+    }
+    let candidates: Vec<&str> = preview.iter().map(|row| row.txid.as_str()).collect();
+    let existing: std::collections::HashSet<String> = db
+        .collection::<Document>("capital_ledger")
+        .find(
+            doc! { "id": { "$in": &candidates } },
+            FindOptions::builder()
+                .projection(doc! { "_id": 0, "id": 1 })
+                .build(),
+        )
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .iter()
+        .filter_map(|row| row.get_str("id").ok().map(str::to_string))
+        .collect();
+
+    let mut duplicates: Vec<String> = Vec::new();
+    for txid in candidates {
+        if existing.contains(txid) && !duplicates.iter().any(|d| d == txid) {
+            duplicates.push(txid.to_string());
+        }
+    }
+    Ok(duplicates)
 }
 
 pub fn prepare_batch_import_from_extract(
@@ -229,9 +511,16 @@ pub fn prepare_batch_import_from_extract(
     }
 
     let preview = rows.clone();
-    let request = BatchImportRequest { transactions: rows };
+    let request = BatchImportRequest {
+        transactions: rows,
+        ..Default::default()
+    };
 
-    Ok(PreparedBatchImport { request, preview })
+    Ok(PreparedBatchImport {
+        request,
+        preview,
+        duplicates: Vec::new(),
+    })
 }
 
 fn optional_string(value: Option<&Value>) -> Option<String> {
@@ -398,4 +687,170 @@ mod tests {
         assert_eq!(row.direction, "Debit");
         assert!((row.amount_or_qty - 42.10).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn run_metadata_stores_reported_usage() {
+        // Shape of `usage` on a completed Assistants run
+        let reported: async_openai::types::RunCompletionUsage = serde_json::from_value(json!({
+            "prompt_tokens": 12_000,
+            "completion_tokens": 3_000,
+            "total_tokens": 15_000,
+        }))
+        .unwrap();
+
+        let mut metadata = doc! { "model": "gpt-4o-mini" };
+        append_usage(&mut metadata, Some(&TokenUsage::from(reported)));
+
+        let (usage, cost) = run_usage_and_cost("gpt-4o-mini", &metadata);
+        let usage = usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 12_000);
+        assert_eq!(usage.completion_tokens, 3_000);
+        assert_eq!(usage.total_tokens, 15_000);
+        assert!((cost.unwrap() - 0.0036).abs() < 1e-9);
+
+        let mut empty = doc! {};
+        append_usage(&mut empty, None);
+        assert_eq!(run_usage_and_cost("gpt-4o-mini", &empty), (None, None));
+    }
+
+    #[tokio::test]
+    async fn recent_runs_span_documents_newest_first() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_extraction_{}", ObjectId::new().to_hex()));
+        let runs = db.collection::<Document>(EXTRACTION_RUNS_COLLECTION);
+
+        let (doc_a, doc_b) = (ObjectId::new(), ObjectId::new());
+        runs.insert_many(
+            [
+                doc! { "doc_id": doc_a, "created_at": 100_i64, "status": "succeeded", "prompt": "p" },
+                doc! { "doc_id": doc_b, "created_at": 300_i64, "status": "failed", "prompt": "p" },
+                doc! { "doc_id": doc_a, "created_at": 200_i64, "status": "succeeded", "prompt": "p" },
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+        let recent = list_extraction_runs(&db, &RunListFilter::default(), 50)
+            .await
+            .unwrap();
+        let created: Vec<i64> = recent
+            .iter()
+            .map(|run| run.get_i64("created_at").unwrap())
+            .collect();
+        assert_eq!(created, vec![300, 200, 100]);
+        assert_eq!(recent[0].get_object_id("doc_id").unwrap(), doc_b);
+        assert_eq!(recent[1].get_object_id("doc_id").unwrap(), doc_a);
+        assert!(!recent[0].contains_key("prompt"));
+
+        let succeeded = RunListFilter {
+            status: Some("succeeded".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            list_extraction_runs(&db, &succeeded, 1).await.unwrap()[0]
+                .get_i64("created_at")
+                .unwrap(),
+            200
+        );
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn archived_run_drops_out_of_document_list() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_extraction_{}", ObjectId::new().to_hex()));
+        let runs = db.collection::<Document>(EXTRACTION_RUNS_COLLECTION);
+
+        let (doc_id, older, newer) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        db.collection::<Document>("documents")
+            .insert_one(
+                doc! { "_id": doc_id, "latest_extraction_run_id": newer },
+                None,
+            )
+            .await
+            .unwrap();
+        runs.insert_many(
+            [
+                doc! { "_id": older, "doc_id": doc_id, "created_at": 100_i64, "status": "succeeded" },
+                doc! { "_id": newer, "doc_id": doc_id, "created_at": 200_i64, "status": "succeeded" },
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(archive_extraction_run(&db, newer).await.unwrap());
+
+        let filter = RunListFilter {
+            doc_id: Some(doc_id),
+            status: None,
+        };
+        let listed: Vec<ObjectId> = list_extraction_runs(&db, &filter, 50)
+            .await
+            .unwrap()
+            .iter()
+            .map(|run| run.get_object_id("_id").unwrap())
+            .collect();
+        assert_eq!(listed, vec![older]);
+        let document = db
+            .collection::<Document>("documents")
+            .find_one(doc! { "_id": doc_id }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            document.get_object_id("latest_extraction_run_id").unwrap(),
+            older
+        );
+
+        // Already archived, or never existed
+        assert!(!archive_extraction_run(&db, newer).await.unwrap());
+        assert!(!archive_extraction_run(&db, ObjectId::new()).await.unwrap());
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn flags_previewed_rows_already_in_ledger() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_extraction_{}", ObjectId::new().to_hex()));
+        db.collection::<Document>("capital_ledger")
+            .insert_one(doc! { "id": "TX-2", "payee": "Already imported" }, None)
+            .await
+            .unwrap();
+
+        let row = |txid: &str| {
+            json!({
+                "txid": txid,
+                "date": "2025-09-01",
+                "account_id": "acct.test",
+                "direction": "debit",
+                "kind": "fiat",
+                "ccy_or_asset": "USD",
+                "amount_or_qty": "10.00",
+            })
+        };
+        let result = ExtractResult {
+            transactions: vec![row("TX-1"), row("TX-2"), row("TX-3")],
+            audit: json!({}),
+            inferred_meta: json!({}),
+            quality: "ok".to_string(),
+            confidence: 0.9,
+        };
+        let prepared = prepare_batch_import_from_extract(&result, &ImportDefaults::new()).unwrap();
+        let duplicates = find_existing_txids(&db, &prepared.preview).await.unwrap();
+
+        assert_eq!(duplicates, vec!["TX-2".to_string()]);
+        assert_eq!(prepared.preview.len(), 3);
+
+        db.drop(None).await.unwrap();
+    }
 }
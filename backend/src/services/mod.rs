@@ -2,8 +2,9 @@ pub mod ai_prompts;
 pub mod coingecko;
 pub mod data_feeds;
 pub mod extraction;
+pub mod offline;
 pub mod openai;
 pub mod oura;
-pub mod plaid; // Add this only if you migrate Plaid logic later
+pub mod plaid;
 pub mod storage;
 pub mod storage_http;
@@ -0,0 +1,35 @@
+//! `WYAT_OFFLINE` switch for running without external APIs.
+//!
+//! When enabled, data feeds and Oura fetchers skip the network and serve
+//! cached or empty data. MongoDB reads and writes are unaffected.
+
+use std::env;
+
+pub const OFFLINE_ENV: &str = "WYAT_OFFLINE";
+
+fn parse_flag(value: Option<&str>) -> bool {
+    matches!(
+        value.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("1" | "true" | "yes" | "on")
+    )
+}
+
+/// True when `WYAT_OFFLINE` is set to `true`/`1`/`yes`/`on`.
+pub fn is_offline() -> bool {
+    parse_flag(env::var(OFFLINE_ENV).ok().as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_flag_accepts_common_truthy_values() {
+        assert!(parse_flag(Some("true")));
+        assert!(parse_flag(Some(" TRUE ")));
+        assert!(parse_flag(Some("1")));
+        assert!(!parse_flag(Some("false")));
+        assert!(!parse_flag(Some("")));
+        assert!(!parse_flag(None));
+    }
+}
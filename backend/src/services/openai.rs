@@ -1,9 +1,11 @@
+use crate::services::extraction::{ExtractionProgress, ExtractionStage};
 use anyhow::{Result, anyhow};
 use async_openai::types::{
-    ChatCompletionRequestMessage, ChatCompletionRequestUserMessageArgs, CreateAssistantRequestArgs,
-    CreateChatCompletionRequestArgs, CreateFileRequest, CreateMessageRequestArgs,
+    AssistantsApiResponseFormatOption, ChatCompletionRequestMessage,
+    ChatCompletionRequestUserMessageArgs, CreateAssistantRequestArgs,
+    CreateChatCompletionRequestArgs, CreateFileRequest, CreateMessageRequestArgs, CreateRunRequest,
     CreateRunRequestArgs, CreateThreadRequestArgs, FilePurpose, MessageContent, MessageRole,
-    RunStatus,
+    ResponseFormat, RunCompletionUsage, RunStatus,
 };
 use async_openai::{Client, config::OpenAIConfig};
 use bytes::Bytes;
@@ -110,16 +112,153 @@ pub struct ExtractResult {
     pub confidence: f64,
 }
 
+/// Output format requested from the model for an extraction run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionResponseFormat {
+    /// No `response_format` is sent. Extraction runs attach the statement through
+    /// file_search, which OpenAI rejects in combination with JSON mode
+    #[default]
+    Auto,
+    /// JSON mode: the model must return a single JSON object (chat completions only)
+    Json,
+    /// Free-form text (fenced JSON or CSV fallback is still parsed)
+    Text,
+}
+
+impl ExtractionResponseFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtractionResponseFormat::Auto => "auto",
+            ExtractionResponseFormat::Json => "json",
+            ExtractionResponseFormat::Text => "text",
+        }
+    }
+
+    pub fn to_openai(self) -> Option<ResponseFormat> {
+        match self {
+            ExtractionResponseFormat::Auto => None,
+            ExtractionResponseFormat::Json => Some(ResponseFormat::JsonObject),
+            ExtractionResponseFormat::Text => Some(ResponseFormat::Text),
+        }
+    }
+}
+
+/// Model parameters for an extraction run.
+/// Defaults to deterministic (`temperature = 0`) output so that re-running the same
+/// prompt on the same statement is reproducible.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractionParams {
+    pub model: String,
+    pub temperature: f32,
+    pub max_tokens: Option<u32>,
+    pub response_format: ExtractionResponseFormat,
+}
+
+pub const DEFAULT_EXTRACTION_MODEL: &str = "gpt-4o-mini";
+
+impl Default for ExtractionParams {
+    fn default() -> Self {
+        Self {
+            model: DEFAULT_EXTRACTION_MODEL.to_string(),
+            temperature: 0.0,
+            max_tokens: None,
+            response_format: ExtractionResponseFormat::Auto,
+        }
+    }
+}
+
+/// Token counts reported by OpenAI for a completed run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl From<RunCompletionUsage> for TokenUsage {
+    fn from(usage: RunCompletionUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
+
+/// USD per 1M (prompt, completion) tokens. Dated snapshots match by prefix, so more
+/// specific names must come before their shorter prefixes.
+const MODEL_RATES_PER_MILLION: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("o4-mini", 1.10, 4.40),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+];
+
+/// Estimated spend for `usage` on `model`; `None` when the model has no known rate.
+pub fn estimated_cost_usd(model: &str, usage: &TokenUsage) -> Option<f64> {
+    let (_, prompt_rate, completion_rate) = MODEL_RATES_PER_MILLION
+        .iter()
+        .find(|(name, _, _)| model.starts_with(name))?;
+    Some(
+        (usage.prompt_tokens as f64 * prompt_rate
+            + usage.completion_tokens as f64 * completion_rate)
+            / 1_000_000.0,
+    )
+}
+
+/// How the model's reply had to be repaired before it parsed as JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonRepair {
+    /// Parsed as returned
+    None,
+    /// Parsed after stripping fences / trailing commas locally
+    Local,
+    /// Needed a follow-up completion asking for valid JSON
+    Model,
+}
+
+impl JsonRepair {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsonRepair::None => "none",
+            JsonRepair::Local => "local",
+            JsonRepair::Model => "model",
+        }
+    }
+}
+
+/// Parsed extraction plus run bookkeeping.
+#[derive(Debug)]
+pub struct ExtractionOutput {
+    pub result: ExtractResult,
+    /// Summed over the initial run and any repair run
+    pub usage: Option<TokenUsage>,
+    pub json_repair: JsonRepair,
+}
+
 pub async fn extract_bank_statement(
     prompt: &str,
     pdf_bytes: &Bytes,
-    model: &str,
+    params: &ExtractionParams,
     assistant_name: &str,
-) -> Result<ExtractResult> {
+    progress: &ExtractionProgress,
+) -> Result<ExtractionOutput> {
     println!("=== extract_bank_statement START (Assistants API) ===");
     println!("PDF bytes length: {}", pdf_bytes.len());
     println!("Prompt length: {} chars", prompt.len());
-    println!("Model: {}, Assistant: {}", model, assistant_name);
+    println!(
+        "Model: {}, temperature: {}, max_tokens: {:?}, format: {}, Assistant: {}",
+        params.model,
+        params.temperature,
+        params.max_tokens,
+        params.response_format.as_str(),
+        assistant_name
+    );
 
     let api_key = std::env::var("OPENAI_API_SECRET")?;
     let client = Client::with_config(OpenAIConfig::new().with_api_key(api_key));
@@ -128,10 +267,11 @@ pub async fn extract_bank_statement(
     println!("Uploading PDF to OpenAI...");
     let file_id = upload_pdf_to_openai(&client, pdf_bytes).await?;
     println!("PDF uploaded with file_id: {}", file_id);
+    progress.stage(ExtractionStage::Uploaded);
 
     // 2) Get or create assistant
     println!("Getting assistant...");
-    let assistant_id = get_or_create_assistant(&client, model, assistant_name, prompt).await?;
+    let assistant_id = get_or_create_assistant(&client, params, assistant_name, prompt).await?;
     println!("Using assistant_id: {}", assistant_id);
 
     // 3) Create thread
@@ -141,30 +281,82 @@ pub async fn extract_bank_statement(
 
     // 4) Add message with file attachment
     println!("Adding message to thread...");
-    add_message_to_thread(&client, &thread_id, prompt, &file_id).await?;
+    add_message_to_thread(&client, &thread_id, prompt, Some(&file_id)).await?;
 
     // 5) Run assistant
     println!("Running assistant...");
-    let run_id = run_assistant(&client, &thread_id, &assistant_id).await?;
+    progress.stage(ExtractionStage::CallingModel);
+    let run_id = run_assistant(&client, &thread_id, &assistant_id, params).await?;
     println!("Run created: {}", run_id);
 
     // 6) Poll for completion
     println!("Polling for completion...");
-    let response_text = poll_run_completion(&client, &thread_id, &run_id).await?;
-    println!("Response received: {} chars", response_text.len());
+    let (response_text, usage) = poll_run_completion(&client, &thread_id, &run_id).await?;
+    println!(
+        "Response received: {} chars, usage: {:?}",
+        response_text.len(),
+        usage
+    );
 
-    // 7) Cleanup
+    // 7) Parse to structured shape (JSON-first, CSV fallback), asking the model once
+    //    to repair its output if it is not valid JSON even after local cleanup
+    progress.stage(ExtractionStage::Parsing);
+    let outcome = match parse_extraction_result(&response_text) {
+        Ok(parsed) => Ok((parsed, usage)),
+        Err(e) => {
+            println!("Initial parse failed ({}), requesting JSON repair...", e);
+            request_json_repair(&client, &thread_id, &assistant_id, params)
+                .await
+                .and_then(|(repaired_text, repair_usage)| {
+                    let (result, _) = parse_extraction_result(&repaired_text)?;
+                    Ok(((result, JsonRepair::Model), add_usage(usage, repair_usage)))
+                })
+        }
+    };
+
+    // 8) Cleanup
     println!("Cleaning up resources...");
     cleanup_resources(&client, &file_id, &thread_id).await;
 
-    // 8) Parse to structured shape (JSON-first, CSV fallback)
-    let parsed = parse_extraction_result(&response_text)?;
+    let ((parsed, json_repair), usage) = outcome?;
     println!(
-        "Parsed: txns={}, quality={:?}",
+        "Parsed: txns={}, quality={:?}, json_repair={}",
         parsed.transactions.len(),
-        parsed.quality
+        parsed.quality,
+        json_repair.as_str()
     );
-    Ok(parsed)
+    Ok(ExtractionOutput {
+        result: parsed,
+        usage,
+        json_repair,
+    })
+}
+
+/// Follow-up turn on the same thread asking the model to resend its answer as valid JSON.
+async fn request_json_repair(
+    client: &Client<OpenAIConfig>,
+    thread_id: &str,
+    assistant_id: &str,
+    params: &ExtractionParams,
+) -> Result<(String, Option<TokenUsage>)> {
+    add_message_to_thread(client, thread_id, JSON_REPAIR_PROMPT, None).await?;
+    let run_id = run_assistant(client, thread_id, assistant_id, params).await?;
+    poll_run_completion(client, thread_id, &run_id).await
+}
+
+const JSON_REPAIR_PROMPT: &str = "Your previous reply was not valid JSON. Return the same \
+extraction again as a single valid JSON object only: no markdown fences, no comments, \
+no trailing commas and no text before or after the object.";
+
+fn add_usage(a: Option<TokenUsage>, b: Option<TokenUsage>) -> Option<TokenUsage> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(TokenUsage {
+            prompt_tokens: a.prompt_tokens + b.prompt_tokens,
+            completion_tokens: a.completion_tokens + b.completion_tokens,
+            total_tokens: a.total_tokens + b.total_tokens,
+        }),
+        (a, b) => a.or(b),
+    }
 }
 
 /// Upload PDF bytes to OpenAI Files API
@@ -189,7 +381,7 @@ async fn upload_pdf_to_openai(client: &Client<OpenAIConfig>, pdf_bytes: &Bytes)
 #[allow(dead_code)]
 async fn get_or_create_assistant(
     client: &Client<OpenAIConfig>,
-    model: &str,
+    params: &ExtractionParams,
     name: &str,
     instructions: &str,
 ) -> Result<String> {
@@ -201,17 +393,17 @@ async fn get_or_create_assistant(
 
     // Create new assistant
     println!("Creating new assistant...");
-    println!("  Model: {}", model);
+    println!("  Model: {}", params.model);
     println!("  Name: {}", name);
     println!("  Instructions length: {} chars", instructions.len());
 
     use async_openai::types::{AssistantTools, AssistantToolsFileSearch};
 
     let request = CreateAssistantRequestArgs::default()
-        .model(model)
+        .model(&params.model)
         .name(name)
         .instructions(instructions)
-        .temperature(0.0)
+        .temperature(params.temperature)
         .top_p(0.1)
         .tools(vec![AssistantTools::FileSearch(
             AssistantToolsFileSearch::default(),
@@ -234,23 +426,24 @@ async fn create_thread(client: &Client<OpenAIConfig>) -> Result<String> {
     Ok(thread.id)
 }
 
-/// Add a message with file attachment to thread
+/// Add a message (with optional file attachment) to thread
 async fn add_message_to_thread(
     client: &Client<OpenAIConfig>,
     thread_id: &str,
     prompt: &str,
-    file_id: &str,
+    file_id: Option<&str>,
 ) -> Result<()> {
     use async_openai::types::MessageAttachmentTool;
 
-    let request = CreateMessageRequestArgs::default()
-        .role(MessageRole::User)
-        .content(prompt.to_string())
-        .attachments(vec![async_openai::types::MessageAttachment {
+    let mut args = CreateMessageRequestArgs::default();
+    args.role(MessageRole::User).content(prompt.to_string());
+    if let Some(file_id) = file_id {
+        args.attachments(vec![async_openai::types::MessageAttachment {
             file_id: file_id.to_string(),
             tools: vec![MessageAttachmentTool::FileSearch],
-        }])
-        .build()?;
+        }]);
+    }
+    let request = args.build()?;
 
     client.threads().messages(thread_id).create(request).await?;
     Ok(())
@@ -261,23 +454,36 @@ async fn run_assistant(
     client: &Client<OpenAIConfig>,
     thread_id: &str,
     assistant_id: &str,
+    params: &ExtractionParams,
 ) -> Result<String> {
-    let request = CreateRunRequestArgs::default()
-        .assistant_id(assistant_id)
-        .temperature(0.0)
-        .top_p(0.1)
-        .build()?;
-
+    let request = build_run_request(assistant_id, params)?;
     let run = client.threads().runs(thread_id).create(request).await?;
     Ok(run.id)
 }
 
+/// Build the run request, overriding the assistant's model settings with `params`
+/// (an assistant reused via OPENAI_ASSISTANT_ID may have been created with others).
+fn build_run_request(assistant_id: &str, params: &ExtractionParams) -> Result<CreateRunRequest> {
+    let mut args = CreateRunRequestArgs::default();
+    args.assistant_id(assistant_id)
+        .model(&params.model)
+        .temperature(params.temperature)
+        .top_p(0.1);
+    if let Some(format) = params.response_format.to_openai() {
+        args.response_format(AssistantsApiResponseFormatOption::Format(format));
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        args.max_completion_tokens(max_tokens);
+    }
+    Ok(args.build()?)
+}
+
 /// Poll run until completion and extract response
 async fn poll_run_completion(
     client: &Client<OpenAIConfig>,
     thread_id: &str,
     run_id: &str,
-) -> Result<String> {
+) -> Result<(String, Option<TokenUsage>)> {
     // Allow long-running extractions. Configure via env:
     // OPENAI_ASSISTANT_RUN_TIMEOUT_SECS (default 180s), OPENAI_ASSISTANT_POLL_MS (default 1000ms)
     let timeout_secs: u64 = std::env::var("OPENAI_ASSISTANT_RUN_TIMEOUT_SECS")
@@ -316,7 +522,8 @@ async fn poll_run_completion(
                 // Find the assistant's response (most recent assistant message)
                 for message in messages.data {
                     if message.role == MessageRole::Assistant {
-                        return extract_text_from_message(message.content);
+                        let text = extract_text_from_message(message.content)?;
+                        return Ok((text, run.usage.map(TokenUsage::from)));
                     }
                 }
 
@@ -371,29 +578,33 @@ async fn cleanup_resources(client: &Client<OpenAIConfig>, file_id: &str, thread_
     }
 }
 
-// TODO: Re-enable once we validate the assistant output format
 /// Parse the extraction result from response text
-#[allow(dead_code)]
-fn parse_extraction_result(response_text: &str) -> Result<ExtractResult> {
+fn parse_extraction_result(response_text: &str) -> Result<(ExtractResult, JsonRepair)> {
     // 0) Try raw JSON first
     if let Ok(v) = serde_json::from_str::<serde_json::Value>(response_text) {
-        return extract_result_from_value(v);
+        return Ok((extract_result_from_value(v)?, JsonRepair::None));
     }
 
     // 1) Strip code fences and retry
     let cleaned = strip_code_fences(response_text);
     if let Ok(v) = serde_json::from_str::<serde_json::Value>(&cleaned) {
-        return extract_result_from_value(v);
+        return Ok((extract_result_from_value(v)?, JsonRepair::Local));
     }
 
-    // 2) Double-encoded path: the whole payload is a JSON *string* containing JSON
-    if let Ok(inner_string) = serde_json::from_str::<String>(&cleaned) {
-        if let Ok(v) = serde_json::from_str::<serde_json::Value>(&inner_string) {
-            return extract_result_from_value(v);
-        }
+    // 2) Drop trailing commas before } or ]
+    let without_commas = strip_trailing_commas(&cleaned);
+    if let Ok(v) = serde_json::from_str::<serde_json::Value>(&without_commas) {
+        return Ok((extract_result_from_value(v)?, JsonRepair::Local));
     }
 
-    // 3) Last resort: unescape common sequences and try again
+    // 3) Double-encoded path: the whole payload is a JSON *string* containing JSON
+    if let Ok(inner_string) = serde_json::from_str::<String>(&cleaned)
+        && let Ok(v) = serde_json::from_str::<serde_json::Value>(&inner_string)
+    {
+        return Ok((extract_result_from_value(v)?, JsonRepair::Local));
+    }
+
+    // 4) Last resort: unescape common sequences and try again
     let lossy = cleaned.replace("\\n", "\n").replace("\\\"", "\"");
     let v: serde_json::Value = serde_json::from_str(&lossy).map_err(|e| {
         println!("JSON parsing error after fallbacks: {}", e);
@@ -404,7 +615,38 @@ fn parse_extraction_result(response_text: &str) -> Result<ExtractResult> {
         anyhow!("Model did not return valid JSON: {}", e)
     })?;
 
-    extract_result_from_value(v)
+    Ok((extract_result_from_value(v)?, JsonRepair::Local))
+}
+
+/// Remove commas that directly precede a closing `}` or `]` (outside of strings).
+fn strip_trailing_commas(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ','
+            && chars[i + 1..]
+                .iter()
+                .find(|n| !n.is_whitespace())
+                .is_some_and(|n| *n == '}' || *n == ']')
+        {
+            continue;
+        }
+        out.push(c);
+    }
+    out
 }
 
 fn extract_result_from_value(v: serde_json::Value) -> Result<ExtractResult> {
@@ -464,8 +706,6 @@ fn extract_result_from_value(v: serde_json::Value) -> Result<ExtractResult> {
     ))
 }
 
-// TODO: Helper for parse_extraction_result
-#[allow(dead_code)]
 fn strip_code_fences(input: &str) -> String {
     // Trim BOM/zero-width/nbsp and outer whitespace
     let s = input
@@ -504,3 +744,91 @@ fn strip_code_fences(input: &str) -> String {
     // No (useful) fenced block found; return original
     s.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extraction_params_propagate_into_run_request() {
+        let defaults = build_run_request("asst_1", &ExtractionParams::default()).unwrap();
+        assert_eq!(defaults.model.as_deref(), Some(DEFAULT_EXTRACTION_MODEL));
+        assert_eq!(defaults.temperature, Some(0.0));
+        assert_eq!(defaults.max_completion_tokens, None);
+        assert_eq!(defaults.response_format, None);
+
+        let params = ExtractionParams {
+            model: "gpt-4o".to_string(),
+            temperature: 0.3,
+            max_tokens: Some(4096),
+            response_format: ExtractionResponseFormat::Text,
+        };
+        let request = build_run_request("asst_1", &params).unwrap();
+        assert_eq!(request.assistant_id, "asst_1");
+        assert_eq!(request.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(request.temperature, Some(0.3));
+        assert_eq!(request.max_completion_tokens, Some(4096));
+        assert_eq!(
+            request.response_format,
+            Some(AssistantsApiResponseFormatOption::Format(
+                ResponseFormat::Text
+            ))
+        );
+    }
+
+    #[test]
+    fn default_run_with_file_search_sends_no_response_format() {
+        // Extraction runs attach the PDF for file_search, which rejects JSON mode
+        let request = build_run_request("asst_1", &ExtractionParams::default()).unwrap();
+        assert_eq!(request.response_format, None);
+        let body = serde_json::to_value(&request).unwrap();
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn strips_json_code_fences() {
+        let fenced = "Here you go:\n```json\n{\"transactions\": []}\n```\nThanks";
+        assert_eq!(strip_code_fences(fenced), "{\"transactions\": []}");
+        assert_eq!(strip_code_fences("  {\"a\": 1}  "), "{\"a\": 1}");
+
+        let (parsed, repair) =
+            parse_extraction_result("```json\n{\"transactions\": [], \"quality\": \"ok\"}\n```")
+                .unwrap();
+        assert_eq!(parsed.quality, "ok");
+        assert_eq!(repair, JsonRepair::Local);
+
+        let (_, repair) = parse_extraction_result("{\"transactions\": []}").unwrap();
+        assert_eq!(repair, JsonRepair::None);
+    }
+
+    #[test]
+    fn strips_trailing_commas_outside_strings() {
+        let input =
+            "{\"transactions\": [{\"memo\": \"a, ]\", \"amount\": 1,},], \"confidence\": 0.5,\n}";
+        assert_eq!(
+            strip_trailing_commas(input),
+            "{\"transactions\": [{\"memo\": \"a, ]\", \"amount\": 1}], \"confidence\": 0.5\n}"
+        );
+
+        let (parsed, repair) =
+            parse_extraction_result(&format!("```json\n{}\n```", input)).unwrap();
+        assert_eq!(parsed.transactions.len(), 1);
+        assert_eq!(parsed.transactions[0]["memo"], "a, ]");
+        assert_eq!(repair, JsonRepair::Local);
+        assert!(parse_extraction_result("{\"transactions\": [").is_err());
+    }
+
+    #[test]
+    fn estimated_cost_uses_most_specific_model_rate() {
+        let usage = TokenUsage {
+            prompt_tokens: 1_000_000,
+            completion_tokens: 500_000,
+            total_tokens: 1_500_000,
+        };
+        let mini = estimated_cost_usd("gpt-4o-mini-2024-07-18", &usage).unwrap();
+        assert!((mini - 0.45).abs() < 1e-9);
+        let full = estimated_cost_usd("gpt-4o", &usage).unwrap();
+        assert!((full - 7.50).abs() < 1e-9);
+        assert_eq!(estimated_cost_usd("claude-unknown", &usage), None);
+    }
+}
@@ -6,14 +6,21 @@ use axum::{
     response::Redirect,
 };
 use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt, join_all};
+use futures::stream::TryStreamExt;
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::env;
+use std::future::Future;
+use std::time::Duration;
 
 use crate::AppState;
+use crate::services::offline::is_offline;
 use mongodb::bson::doc;
-use mongodb::options::ReplaceOptions;
+use mongodb::error::{BulkWriteFailure, ErrorKind};
+use mongodb::options::{FindOneOptions, IndexOptions, InsertManyOptions, ReplaceOptions};
 use std::sync::Arc;
 
 // =============================================
@@ -25,6 +32,10 @@ pub struct OuraTokens {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<mongodb::bson::oid::ObjectId>,
     pub user_id: String, // For future multi-user support
+    /// Oura's id for the connected account (from `personal_info`); absent on tokens
+    /// stored before multi-account support.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_account_id: Option<String>,
     pub access_token: String,
     pub refresh_token: Option<String>,
     pub token_type: String,
@@ -93,6 +104,10 @@ pub async fn refresh_oura_tokens(
         None => return Ok(Some(current_tokens)), // No refresh token, return current tokens
     };
 
+    if is_offline() {
+        return Ok(Some(current_tokens)); // Can't reach Oura; use what we have
+    }
+
     // Check if token is expired (with 5 minute buffer)
     if let Some(expires_at) = current_tokens.expires_at {
         if Utc::now() < expires_at - chrono::Duration::minutes(5) {
@@ -140,6 +155,7 @@ pub async fn refresh_oura_tokens(
     let new_tokens = OuraTokens {
         id: current_tokens.id, // Keep the same ID
         user_id: current_tokens.user_id.clone(),
+        provider_account_id: current_tokens.provider_account_id.clone(),
         access_token: token_data.access_token,
         refresh_token: token_data.refresh_token.or(current_tokens.refresh_token), // Keep old refresh token if new one not provided
         token_type: token_data.token_type,
@@ -155,6 +171,21 @@ pub async fn refresh_oura_tokens(
     Ok(Some(new_tokens))
 }
 
+/// Upsert key for stored tokens: `(user_id, provider_account_id)`. Tokens for a known
+/// account also claim the user's untagged document so single-account setups keep one row.
+fn oura_tokens_filter(tokens: &OuraTokens) -> mongodb::bson::Document {
+    match &tokens.provider_account_id {
+        Some(account_id) => doc! {
+            "user_id": &tokens.user_id,
+            "$or": [
+                { "provider_account_id": account_id },
+                { "provider_account_id": null },
+            ],
+        },
+        None => doc! { "user_id": &tokens.user_id, "provider_account_id": null },
+    }
+}
+
 pub async fn save_oura_tokens_to_mongo(
     mongo_client: &mongodb::Client,
     tokens: &OuraTokens,
@@ -162,7 +193,7 @@ pub async fn save_oura_tokens_to_mongo(
     let db = mongo_client.database("wyat");
     let collection = db.collection::<OuraTokens>("oura_tokens");
 
-    let filter = doc! { "user_id": &tokens.user_id };
+    let filter = oura_tokens_filter(tokens);
     let options = ReplaceOptions::builder().upsert(true).build();
 
     collection
@@ -185,14 +216,126 @@ pub async fn get_oura_tokens_from_mongo(
     let collection = db.collection::<OuraTokens>("oura_tokens");
 
     let filter = doc! { "user_id": user_id };
+    // With several accounts per user, use the most recently updated tokens
+    let options = FindOneOptions::builder()
+        .sort(doc! { "updated_at": -1 })
+        .build();
 
-    match collection.find_one(filter, None).await {
+    match collection.find_one(filter, options).await {
         Ok(tokens) => Ok(tokens),
         Err(e) => Err(format!("MongoDB error: {}", e)),
     }
 }
 
-pub async fn generate_oura_auth_url() -> impl IntoResponse {
+// ================================
+// * * * * OAuth CSRF State * * * *
+// ================================
+// Each auth URL carries a random `state` that the callback must return; states are
+// single-use and expire after OURA_OAUTH_STATE_TTL_SECS.
+const OURA_OAUTH_STATE_TTL_SECS: i64 = 600;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OuraOAuthState {
+    pub state: String,
+    pub created_at: mongodb::bson::DateTime,
+}
+
+fn oauth_states(db: &mongodb::Database) -> mongodb::Collection<OuraOAuthState> {
+    db.collection::<OuraOAuthState>("oura_oauth_states")
+}
+
+/// Create the TTL index that expires unused OAuth states and the unique heartrate
+/// timestamp index. Each is attempted independently; the first failure is returned.
+pub async fn init_indexes(db: &mongodb::Database) -> mongodb::error::Result<()> {
+    let oauth_states_index = oauth_states(db)
+        .create_index(
+            mongodb::IndexModel::builder()
+                .keys(doc! { "created_at": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(OURA_OAUTH_STATE_TTL_SECS as u64))
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await;
+    if let Err(e) = &oauth_states_index {
+        tracing::error!(error = %e, "failed to create the Oura OAuth state TTL index");
+    }
+
+    let heartrate_index = ensure_heartrate_index(db).await;
+    if let Err(e) = &heartrate_index {
+        tracing::error!(error = %e, "failed to create the unique heartrate timestamp index");
+    }
+
+    oauth_states_index?;
+    heartrate_index
+}
+
+/// Check a consumed state is still fresh. TTL cleanup runs lazily, so expired
+/// states can still be found and are rejected here.
+fn validate_oauth_state(stored: Option<&OuraOAuthState>, now: DateTime<Utc>) -> Result<(), String> {
+    let stored = stored.ok_or("unknown OAuth state")?;
+    let age = now.timestamp_millis() - stored.created_at.timestamp_millis();
+    if age > OURA_OAUTH_STATE_TTL_SECS * 1000 {
+        return Err("expired OAuth state".to_string());
+    }
+    Ok(())
+}
+
+/// Consume the state returned to the callback; each state verifies at most once.
+async fn verify_oauth_state(db: &mongodb::Database, state: Option<&str>) -> Result<(), String> {
+    let state = state
+        .filter(|s| !s.is_empty())
+        .ok_or("missing OAuth state")?;
+    let stored = oauth_states(db)
+        .find_one_and_delete(doc! { "state": state }, None)
+        .await
+        .map_err(|e| format!("MongoDB error: {}", e))?;
+    validate_oauth_state(stored.as_ref(), Utc::now())
+}
+
+#[derive(Deserialize)]
+struct OuraPersonalInfo {
+    id: String,
+}
+
+/// Oura's account id for `access_token`, from `usercollection/personal_info`.
+async fn fetch_oura_account_id(access_token: &str) -> Result<String, String> {
+    let base_url =
+        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
+    let request = Client::new()
+        .get(format!("{}/usercollection/personal_info", base_url))
+        .bearer_auth(access_token);
+    let res = send_oura_request(&OuraRetryPolicy::from_env(), request).await?;
+
+    if !res.status().is_success() {
+        return Err(format!("Oura API error: {}", res.status()));
+    }
+
+    res.json::<OuraPersonalInfo>()
+        .await
+        .map(|info| info.id)
+        .map_err(|e| format!("Failed to parse personal info: {}", e))
+}
+
+pub async fn generate_oura_auth_url(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let oauth_state = uuid::Uuid::new_v4().simple().to_string();
+    let stored = OuraOAuthState {
+        state: oauth_state.clone(),
+        created_at: mongodb::bson::DateTime::now(),
+    };
+    let db = state.mongo_client.database("wyat");
+    if let Err(e) = oauth_states(&db).insert_one(&stored, None).await {
+        println!("❌ Failed to store Oura OAuth state: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to start Oura OAuth",
+        )
+            .into_response();
+    }
+
     let client_id = env::var("OURA_CLIENT_ID").unwrap_or_else(|_| "missing".to_string());
     let backend_url =
         env::var("BACKEND_URL").unwrap_or_else(|_| "http://localhost:3001".to_string());
@@ -204,20 +347,32 @@ pub async fn generate_oura_auth_url() -> impl IntoResponse {
     println!("🔐 Oura OAuth - Scope: {}", scope);
 
     let auth_url = format!(
-        "https://cloud.ouraring.com/oauth/authorize?response_type=code&client_id={}&redirect_uri={}&scope={}",
+        "https://cloud.ouraring.com/oauth/authorize?response_type=code&client_id={}&redirect_uri={}&scope={}&state={}",
         client_id,
         urlencoding::encode(&redirect_uri),
-        scope
+        scope,
+        oauth_state
     );
 
     println!("🔐 Oura OAuth - Generated URL: {}", auth_url);
-    Redirect::to(&auth_url)
+    Redirect::to(&auth_url).into_response()
 }
 
 pub async fn handle_oura_callback(
     State(state): State<Arc<AppState>>,
     Query(query): Query<OuraCallbackQuery>,
 ) -> impl IntoResponse {
+    let db = state.mongo_client.database("wyat");
+    if let Err(e) = verify_oauth_state(&db, query.state.as_deref()).await {
+        println!("❌ Oura Callback - Rejected: {}", e);
+        let frontend_url =
+            env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        return Redirect::to(&format!(
+            "{}/oura-error",
+            frontend_url.trim_end_matches('/')
+        ));
+    }
+
     let client_id = env::var("OURA_CLIENT_ID").unwrap_or_else(|_| "missing".to_string());
     let client_secret = env::var("OURA_CLIENT_SECRET").unwrap_or_else(|_| "missing".to_string());
     let backend_url =
@@ -274,9 +429,19 @@ pub async fn handle_oura_callback(
                             Utc::now() + chrono::Duration::seconds(expires_in as i64)
                         });
 
+                        let provider_account_id =
+                            match fetch_oura_account_id(&token_data.access_token).await {
+                                Ok(account_id) => Some(account_id),
+                                Err(e) => {
+                                    println!("⚠️ Could not fetch Oura personal info: {}", e);
+                                    None
+                                }
+                            };
+
                         let tokens = OuraTokens {
                             id: None,
                             user_id: "default_user".to_string(), // For single-user app
+                            provider_account_id,
                             access_token: token_data.access_token,
                             refresh_token: token_data.refresh_token,
                             token_type: token_data.token_type,
@@ -346,6 +511,310 @@ pub async fn handle_oura_callback(
     }
 }
 
+// ===================================
+// * * * * Oura API Pagination * * * *
+// ===================================
+// Oura v2 collection endpoints return a `next_token` when a date range spans
+// more than one page; these helpers follow it until every page is read.
+#[derive(Debug, Deserialize)]
+pub struct OuraPage<T> {
+    pub data: Vec<T>,
+    #[serde(default)]
+    pub next_token: Option<String>,
+}
+
+/// Repeatedly call `fetch_page` (passing the previous page's `next_token`)
+/// until a page comes back without one, accumulating all records.
+pub async fn collect_oura_pages<T, F, Fut>(mut fetch_page: F) -> Result<Vec<T>, String>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<OuraPage<T>, String>>,
+{
+    let mut records = Vec::new();
+    let mut next_token: Option<String> = None;
+
+    loop {
+        let page = fetch_page(next_token.clone()).await?;
+        records.extend(page.data);
+
+        match page.next_token {
+            Some(token) if !token.is_empty() => {
+                if next_token.as_deref() == Some(token.as_str()) {
+                    return Err(format!("Oura API returned repeated next_token: {}", token));
+                }
+                next_token = Some(token);
+            }
+            _ => break,
+        }
+    }
+
+    Ok(records)
+}
+
+/// Fetch every record from an Oura `usercollection/{endpoint}` for the date range.
+/// Returns nothing without calling Oura when `WYAT_OFFLINE` is set.
+async fn fetch_oura_collection<T: DeserializeOwned>(
+    endpoint: &str,
+    start_date: &str,
+    end_date: &str,
+    access_token: &str,
+) -> Result<Vec<T>, String> {
+    if is_offline() {
+        tracing::info!(endpoint, "offline mode: skipping Oura fetch");
+        return Ok(Vec::new());
+    }
+
+    let base_url =
+        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
+    let url = format!(
+        "{}/usercollection/{}?start_date={}&end_date={}",
+        base_url, endpoint, start_date, end_date
+    );
+    let client = Client::new();
+    let policy = OuraRetryPolicy::from_env();
+
+    collect_oura_pages(|next_token| {
+        let mut request = client.get(&url).bearer_auth(access_token);
+        if let Some(token) = next_token {
+            request = request.query(&[("next_token", token)]);
+        }
+        async move {
+            let res = send_oura_request(&policy, request).await?;
+
+            if !res.status().is_success() {
+                return Err(format!("Oura API error: {}", res.status()));
+            }
+
+            res.json::<OuraPage<T>>().await.map_err(|e| e.to_string())
+        }
+    })
+    .await
+}
+
+// ======================================
+// * * * * Oura API Retry/Backoff * * * *
+// ======================================
+// Oura rate-limits aggressively during backfills; 429 and 5xx responses are
+// retried with exponential backoff, honoring `Retry-After` when present.
+#[derive(Debug, Clone, Copy)]
+pub struct OuraRetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl OuraRetryPolicy {
+    /// Read the policy from env:
+    /// OURA_RETRY_MAX_ATTEMPTS (default 5), OURA_RETRY_BASE_DELAY_MS (default 1000ms),
+    /// OURA_RETRY_MAX_DELAY_MS (default 60000ms)
+    pub fn from_env() -> Self {
+        let max_attempts: u32 = env::var("OURA_RETRY_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(5);
+        let base_delay_ms: u64 = env::var("OURA_RETRY_BASE_DELAY_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1000);
+        let max_delay_ms: u64 = env::var("OURA_RETRY_MAX_DELAY_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60_000);
+
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay_ms,
+            max_delay_ms,
+        }
+    }
+
+    /// Delay before the retry following failed attempt `attempt` (1-based).
+    /// A server-provided `Retry-After` wins over the exponential schedule;
+    /// both are capped at `max_delay_ms`.
+    pub fn backoff_delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.max_delay_ms);
+        if let Some(wait) = retry_after {
+            return wait.min(max);
+        }
+        let factor = 1u64 << attempt.saturating_sub(1).min(20);
+        Duration::from_millis(self.base_delay_ms.saturating_mul(factor)).min(max)
+    }
+}
+
+/// Outcome of a single failed attempt passed to `retry_with_backoff`.
+#[derive(Debug)]
+pub enum OuraAttemptError {
+    /// Transient failure (429/5xx); try again after backoff.
+    Retry {
+        reason: String,
+        retry_after: Option<Duration>,
+    },
+    /// Permanent failure; give up immediately.
+    Fatal(String),
+}
+
+/// Run `attempt_fn` until it succeeds, fails fatally, or `max_attempts` is reached.
+pub async fn retry_with_backoff<T, F, Fut>(
+    policy: &OuraRetryPolicy,
+    mut attempt_fn: F,
+) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, OuraAttemptError>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        attempt += 1;
+        match attempt_fn().await {
+            Ok(value) => return Ok(value),
+            Err(OuraAttemptError::Fatal(reason)) => return Err(reason),
+            Err(OuraAttemptError::Retry {
+                reason,
+                retry_after,
+            }) => {
+                if attempt >= policy.max_attempts {
+                    return Err(format!("{} (gave up after {} attempts)", reason, attempt));
+                }
+                let delay = policy.backoff_delay(attempt, retry_after);
+                println!(
+                    "⏳ Oura API retry {}/{} in {:?}: {}",
+                    attempt, policy.max_attempts, delay, reason
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Send a request, retrying on HTTP 429 and 5xx responses.
+async fn send_oura_request(
+    policy: &OuraRetryPolicy,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, String> {
+    retry_with_backoff(policy, || {
+        let request = request.try_clone();
+        async move {
+            let request = request.ok_or_else(|| {
+                OuraAttemptError::Fatal("Oura request could not be cloned for retry".to_string())
+            })?;
+            let res = request
+                .send()
+                .await
+                .map_err(|e| OuraAttemptError::Fatal(e.to_string()))?;
+
+            let status = res.status();
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                return Err(OuraAttemptError::Retry {
+                    reason: format!("Oura API error: {}", status),
+                    retry_after: parse_retry_after(res.headers()),
+                });
+            }
+            Ok(res)
+        }
+    })
+    .await
+}
+
+// =====================================
+// * * * * Persisted Sync Cursor * * * *
+// =====================================
+// Sync cursors advance only to the latest day actually confirmed in MongoDB,
+// so a partial save (or a day Oura hasn't published yet) is re-fetched next time.
+#[derive(Debug, Default)]
+pub struct OuraSaveOutcome {
+    pub inserted: usize,
+    pub skipped: usize,
+    /// Latest day for which every record in the batch is confirmed saved
+    pub saved_through: Option<String>,
+    /// Error that stopped the batch early, if any
+    pub error: Option<String>,
+}
+
+impl OuraSaveOutcome {
+    pub fn into_result(self) -> Result<Option<String>, String> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.saved_through),
+        }
+    }
+}
+
+/// Given record days sorted ascending and the number of leading records saved,
+/// return the latest day whose records are all saved.
+fn saved_through_day(sorted_days: &[&str], saved: usize) -> Option<String> {
+    let (done, rest) = sorted_days.split_at(saved.min(sorted_days.len()));
+    match rest.first() {
+        None => done.last().map(|d| d.to_string()),
+        Some(first_unsaved) => done
+            .iter()
+            .rev()
+            .find(|d| *d < first_unsaved)
+            .map(|d| d.to_string()),
+    }
+}
+
+/// Save records one at a time in day order, stopping at the first error.
+/// `save_one` returns `Ok(true)` when inserted and `Ok(false)` when already stored.
+async fn save_records_by_day<'a, T, F, Fut>(
+    records: &'a [T],
+    day_of: fn(&T) -> &str,
+    mut save_one: F,
+) -> OuraSaveOutcome
+where
+    F: FnMut(&'a T) -> Fut,
+    Fut: Future<Output = Result<bool, String>>,
+{
+    let mut ordered: Vec<&T> = records.iter().collect();
+    ordered.sort_by(|a, b| day_of(a).cmp(day_of(b)));
+    let days: Vec<&str> = ordered.iter().map(|r| day_of(r)).collect();
+
+    let mut outcome = OuraSaveOutcome::default();
+    let mut saved = 0;
+    for record in ordered {
+        match save_one(record).await {
+            Ok(true) => outcome.inserted += 1,
+            Ok(false) => outcome.skipped += 1,
+            Err(e) => {
+                outcome.error = Some(e);
+                break;
+            }
+        }
+        saved += 1;
+    }
+    outcome.saved_through = saved_through_day(&days, saved);
+    outcome
+}
+
+/// Advance the metric's sync cursor to the last persisted day (if any),
+/// then surface the save error, if one occurred.
+async fn commit_oura_save(
+    db: &mongodb::Database,
+    user_id: &str,
+    data_type: &str,
+    outcome: OuraSaveOutcome,
+) -> Result<Option<String>, String> {
+    if let Some(day) = &outcome.saved_through
+        && let Err(e) = update_oura_sync_status(db, user_id, data_type, day).await
+    {
+        println!(
+            "⚠️ {} sync - Warning: Failed to update sync status: {}",
+            data_type, e
+        );
+    }
+    outcome.into_result()
+}
+
 // ==============================
 // * * * * Daily Activity * * * *
 // ==============================
@@ -402,180 +871,61 @@ pub async fn get_oura_daily_activity_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<DailyActivityData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_activity?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailyActivityResponse {
-        data: Vec<DailyActivityData>,
-    }
-
-    let response: OuraDailyActivityResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection("daily_activity", start_date, end_date, access_token).await
 }
 
 pub async fn save_daily_activity_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     daily_activity_data: &[DailyActivityData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<DailyActivityData>("oura_daily_activity");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_activity_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_activity_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
+            } else {
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "💾 Daily activity data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_activity_sync(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status =
-        get_oura_sync_status(&state.mongo_client, user_id, "daily_activity").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!("🏃 Daily Activity Sync - Error getting sync status: {}", e);
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    // Use personal token directly for daily activity endpoint (OAuth token doesn't have access)
-    let access_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-    println!(
-        "🏃 Daily Activity Sync - Using personal token: {}",
-        &access_token[..10]
-    );
-
-    println!(
-        "🏃 Daily Activity Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    match get_oura_daily_activity_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(daily_activity_data) => {
-            println!(
-                "🏃 Daily Activity Sync - Retrieved {} daily activity records",
-                daily_activity_data.len()
-            );
-
-            match save_daily_activity_data_to_mongo(&state.mongo_client, &daily_activity_data).await
-            {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_activity",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "🏃 Daily Activity Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "🏃 Daily Activity Sync - Saved {} daily activity records to MongoDB",
-                        daily_activity_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily activity records from {} to {}", daily_activity_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_activity_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    // The OAuth token doesn't have access to daily activity
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_activity",
+        OuraTokenSource::Personal,
+        |s, e, t| async move { get_oura_daily_activity_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_activity_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // ========================================
@@ -593,203 +943,79 @@ pub async fn get_oura_daily_cardiovascular_age_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<DailyCardiovascularAgeData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_cardiovascular_age?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailyCardiovascularAgeResponse {
-        data: Vec<DailyCardiovascularAgeData>,
-    }
-
-    let response: OuraDailyCardiovascularAgeResponse =
-        res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection(
+        "daily_cardiovascular_age",
+        start_date,
+        end_date,
+        access_token,
+    )
+    .await
 }
 
 pub async fn save_daily_cardiovascular_age_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     daily_cardiovascular_age_data: &[DailyCardiovascularAgeData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<DailyCardiovascularAgeData>("oura_daily_cardiovascular_age");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_cardiovascular_age_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = doc! { "day": &entry.day };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_cardiovascular_age_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = doc! { "day": &entry.day };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "💾 Daily cardiovascular age data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_cardiovascular_age_sync(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status =
-        get_oura_sync_status(&state.mongo_client, user_id, "daily_cardiovascular_age").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_cardiovascular_age",
+        OuraTokenSource::Personal,
+        |s, e, t| async move { get_oura_daily_cardiovascular_age_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_cardiovascular_age_data_to_mongo(&c, &d).await },
+    )
+    .await
+}
 
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!(
-                "❤️ Daily Cardiovascular Age Sync - Error getting sync status: {}",
-                e
-            );
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    // Use personal token directly for daily cardiovascular age endpoint (OAuth token doesn't have access)
-    let access_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-    println!(
-        "❤️ Daily Cardiovascular Age Sync - Using personal token: {}",
-        &access_token[..10]
-    );
-
-    println!(
-        "❤️ Daily Cardiovascular Age Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    match get_oura_daily_cardiovascular_age_data_from_api(&start_date, &end_date, &access_token)
-        .await
-    {
-        Ok(daily_cardiovascular_age_data) => {
-            println!(
-                "❤️ Daily Cardiovascular Age Sync - Retrieved {} daily cardiovascular age records",
-                daily_cardiovascular_age_data.len()
-            );
-
-            match save_daily_cardiovascular_age_data_to_mongo(
-                &state.mongo_client,
-                &daily_cardiovascular_age_data,
-            )
-            .await
-            {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_cardiovascular_age",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "❤️ Daily Cardiovascular Age Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "❤️ Daily Cardiovascular Age Sync - Saved {} daily cardiovascular age records to MongoDB",
-                        daily_cardiovascular_age_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily cardiovascular age records from {} to {}", daily_cardiovascular_age_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_cardiovascular_age_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
-}
-
-// ===============================
-// * * * * Daily Readiness * * * *
-// ===============================
-// Data structures, API fetch, and MongoDB storage for daily readiness
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DailyReadinessData {
-    pub id: Option<String>,
-    pub day: String,
-    pub score: Option<i32>,
-    pub temperature_deviation: Option<f64>,
-    pub temperature_trend_deviation: Option<f64>,
-    pub timestamp: Option<String>,
-    #[serde(alias = "contribitors")]
-    pub contributors: Option<DailyReadinessContributors>,
-}
+// ===============================
+// * * * * Daily Readiness * * * *
+// ===============================
+// Data structures, API fetch, and MongoDB storage for daily readiness
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DailyReadinessData {
+    pub id: Option<String>,
+    pub day: String,
+    pub score: Option<i32>,
+    pub temperature_deviation: Option<f64>,
+    pub temperature_trend_deviation: Option<f64>,
+    pub timestamp: Option<String>,
+    #[serde(alias = "contribitors")]
+    pub contributors: Option<DailyReadinessContributors>,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DailyReadinessContributors {
@@ -808,196 +1034,60 @@ pub async fn get_oura_daily_readiness_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<DailyReadinessData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_readiness?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailyReadinessResponse {
-        data: Vec<DailyReadinessData>,
-    }
-
-    let response: OuraDailyReadinessResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection("daily_readiness", start_date, end_date, access_token).await
 }
 
 pub async fn save_daily_readiness_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     daily_readiness_data: &[DailyReadinessData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<DailyReadinessData>("oura_daily_readiness");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_readiness_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_readiness_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
+            } else {
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "💾 Daily readiness data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_readiness_sync(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status =
-        get_oura_sync_status(&state.mongo_client, user_id, "daily_readiness").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!("⚡ Daily Readiness Sync - Error getting sync status: {}", e);
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    // Try to get OAuth access token, fallback to personal token
-    let access_token = match get_valid_oura_access_token(&state.mongo_client, user_id).await {
-        Ok(Some(token)) => {
-            println!(
-                "⚡ Daily Readiness Sync - Using OAuth token: {}",
-                &token[..10]
-            );
-            token
-        }
-        Ok(None) => {
-            let personal_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-            personal_token
-        }
-        Err(e) => {
-            println!(
-                "⚡ Daily Readiness Sync - Token error: {}, using personal token",
-                e
-            );
-            env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
-        }
-    };
-
-    println!(
-        "⚡ Daily Readiness Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    match get_oura_daily_readiness_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(daily_readiness_data) => {
-            println!(
-                "⚡ Daily Readiness Sync - Retrieved {} daily readiness records",
-                daily_readiness_data.len()
-            );
-
-            match save_daily_readiness_data_to_mongo(&state.mongo_client, &daily_readiness_data)
-                .await
-            {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_readiness",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "⚡ Daily Readiness Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "⚡ Daily Readiness Sync - Saved {} daily readiness records to MongoDB",
-                        daily_readiness_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily readiness records from {} to {}", daily_readiness_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_readiness_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_readiness",
+        OuraTokenSource::OAuth,
+        |s, e, t| async move { get_oura_daily_readiness_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_readiness_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // ================================
@@ -1024,182 +1114,60 @@ pub async fn get_oura_daily_resilience_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<DailyResilienceData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_resilience?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailyResilienceResponse {
-        data: Vec<DailyResilienceData>,
-    }
-
-    let response: OuraDailyResilienceResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection("daily_resilience", start_date, end_date, access_token).await
 }
 
 pub async fn save_daily_resilience_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     daily_resilience_data: &[DailyResilienceData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<DailyResilienceData>("oura_daily_resilience");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_resilience_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_resilience_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
+            } else {
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "Saved {} daily resilience records, skipped {} duplicates",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_resilience_sync(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status =
-        get_oura_sync_status(&state.mongo_client, user_id, "daily_resilience").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!(
-                "🔄 Daily Resilience Sync - Error getting sync status: {}",
-                e
-            );
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    println!(
-        "🔄 Syncing daily resilience data from {} to {}",
-        start_date, end_date
-    );
-
-    // Use personal token directly for daily resilience endpoint (OAuth token doesn't have access)
-    let access_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-    println!(
-        "🔄 Daily Resilience Sync - Using personal token: {}",
-        &access_token[..10]
-    );
-
-    match get_oura_daily_resilience_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(daily_resilience_data) => {
-            println!(
-                "🔄 Daily Resilience Sync - Retrieved {} daily resilience records",
-                daily_resilience_data.len()
-            );
-
-            match save_daily_resilience_data_to_mongo(&state.mongo_client, &daily_resilience_data)
-                .await
-            {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_resilience",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "🔄 Daily Resilience Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "🔄 Daily Resilience Sync - Saved {} daily resilience records to MongoDB",
-                        daily_resilience_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily resilience records from {} to {}", daily_resilience_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_resilience_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_resilience",
+        OuraTokenSource::Personal,
+        |s, e, t| async move { get_oura_daily_resilience_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_resilience_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // ===========================
@@ -1231,359 +1199,129 @@ pub async fn get_oura_daily_sleep_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<DailySleepData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_sleep?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailySleepResponse {
-        data: Vec<DailySleepData>,
-    }
-
-    let response: OuraDailySleepResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection("daily_sleep", start_date, end_date, access_token).await
 }
 
 pub async fn save_daily_sleep_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     daily_sleep_data: &[DailySleepData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<DailySleepData>("oura_daily_sleep");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_sleep_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_sleep_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
+            } else {
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "💾 Daily sleep data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_sleep_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status = get_oura_sync_status(&state.mongo_client, user_id, "daily_sleep").await;
-    let today = chrono::Utc::now().date_naive();
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_sleep",
+        OuraTokenSource::OAuth,
+        |s, e, t| async move { get_oura_daily_sleep_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_sleep_data_to_mongo(&c, &d).await },
+    )
+    .await
+}
 
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
+// ==========================
+// * * * * Daily SpO2 * * * *
+// ==========================
+// Data structures, API fetch, and MongoDB storage for daily SpO2
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DailySpO2Data {
+    pub id: Option<String>,
+    pub day: String,
+    pub spo2_percentage: Option<serde_json::Value>,
+    pub breathing_disturbance_index: Option<i32>,
+}
 
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
+pub async fn get_oura_daily_spo2_data_from_api(
+    start_date: &str,
+    end_date: &str,
+    access_token: &str,
+) -> Result<Vec<DailySpO2Data>, String> {
+    fetch_oura_collection("daily_spo2", start_date, end_date, access_token).await
+}
 
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
+pub async fn save_daily_spo2_data_to_mongo(
+    db: &mongodb::Database,
+    daily_spo2_data: &[DailySpO2Data],
+) -> OuraSaveOutcome {
+    let collection = db.collection::<DailySpO2Data>("oura_daily_spo2");
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_spo2_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
             } else {
-                next_day.format("%Y-%m-%d").to_string()
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
             }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!("😴 Daily Sleep Sync - Error getting sync status: {}", e);
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    // Try to get OAuth access token, fallback to personal token
-    let access_token = match get_valid_oura_access_token(&state.mongo_client, user_id).await {
-        Ok(Some(token)) => {
-            println!("😴 Daily Sleep Sync - Using OAuth token: {}", &token[..10]);
-            token
-        }
-        Ok(None) => {
-            let personal_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-            personal_token
-        }
-        Err(e) => {
-            println!(
-                "😴 Daily Sleep Sync - Token error: {}, using personal token",
-                e
-            );
-            env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
-        }
-    };
-
-    println!(
-        "😴 Daily Sleep Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    match get_oura_daily_sleep_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(daily_sleep_data) => {
-            println!(
-                "😴 Daily Sleep Sync - Retrieved {} daily sleep records",
-                daily_sleep_data.len()
-            );
-
-            match save_daily_sleep_data_to_mongo(&state.mongo_client, &daily_sleep_data).await {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_sleep",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "😴 Daily Sleep Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "😴 Daily Sleep Sync - Saved {} daily sleep records to MongoDB",
-                        daily_sleep_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily sleep records from {} to {}", daily_sleep_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_sleep_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
-}
-
-// ==========================
-// * * * * Daily SpO2 * * * *
-// ==========================
-// Data structures, API fetch, and MongoDB storage for daily SpO2
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DailySpO2Data {
-    pub id: Option<String>,
-    pub day: String,
-    pub spo2_percentage: Option<serde_json::Value>,
-    pub breathing_disturbance_index: Option<i32>,
-}
-
-pub async fn get_oura_daily_spo2_data_from_api(
-    start_date: &str,
-    end_date: &str,
-    access_token: &str,
-) -> Result<Vec<DailySpO2Data>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_spo2?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailySpO2Response {
-        data: Vec<DailySpO2Data>,
-    }
-
-    let response: OuraDailySpO2Response = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
-}
-
-pub async fn save_daily_spo2_data_to_mongo(
-    mongo_client: &mongodb::Client,
-    daily_spo2_data: &[DailySpO2Data],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
-    let collection = db.collection::<DailySpO2Data>("oura_daily_spo2");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_spo2_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-        inserted_count += 1;
-    }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "Saved {} daily SpO2 records, skipped {} duplicates",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_spo2_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status = get_oura_sync_status(&state.mongo_client, user_id, "daily_spo2").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-            next_day.format("%Y-%m-%d").to_string()
-        }
-        _ => {
-            // Default to yesterday if no previous sync
-            let yesterday = today - chrono::Duration::days(1);
-            yesterday.format("%Y-%m-%d").to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    println!(
-        "🫁 Daily SpO2 Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    // Use personal token directly for SpO2 endpoint (OAuth token doesn't have access)
-    let access_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-    println!(
-        "🫁 Daily SpO2 Sync - Using personal token: {}",
-        &access_token[..10]
-    );
-
-    match get_oura_daily_spo2_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(daily_spo2_data) => {
-            println!(
-                "🫁 Daily SpO2 Sync - Retrieved {} daily SpO2 records",
-                daily_spo2_data.len()
-            );
-
-            match save_daily_spo2_data_to_mongo(&state.mongo_client, &daily_spo2_data).await {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_spo2",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "🫁 Daily SpO2 Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "🫁 Daily SpO2 Sync - Saved {} daily SpO2 records to MongoDB",
-                        daily_spo2_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily SpO2 records from {} to {}", daily_spo2_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_spo2_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_spo2",
+        OuraTokenSource::Personal,
+        |s, e, t| async move { get_oura_daily_spo2_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_spo2_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // ============================
@@ -1604,190 +1342,60 @@ pub async fn get_oura_daily_stress_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<DailyStressData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/daily_stress?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraDailyStressResponse {
-        data: Vec<DailyStressData>,
-    }
-
-    let response: OuraDailyStressResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection("daily_stress", start_date, end_date, access_token).await
 }
 
 pub async fn save_daily_stress_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     daily_stress_data: &[DailyStressData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<DailyStressData>("oura_daily_stress");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in daily_stress_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        daily_stress_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
+            } else {
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "💾 Daily stress data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_daily_stress_sync(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status = get_oura_sync_status(&state.mongo_client, user_id, "daily_stress").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!("😰 Daily Stress Sync - Error getting sync status: {}", e);
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    // Try to get OAuth access token, fallback to personal token
-    let access_token = match get_valid_oura_access_token(&state.mongo_client, user_id).await {
-        Ok(Some(token)) => {
-            println!("😰 Daily Stress Sync - Using OAuth token: {}", &token[..10]);
-            token
-        }
-        Ok(None) => {
-            let personal_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-            personal_token
-        }
-        Err(e) => {
-            println!(
-                "😰 Daily Stress Sync - Token error: {}, using personal token",
-                e
-            );
-            env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
-        }
-    };
-
-    println!(
-        "😰 Daily Stress Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    match get_oura_daily_stress_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(daily_stress_data) => {
-            println!(
-                "😰 Daily Stress Sync - Retrieved {} daily stress records",
-                daily_stress_data.len()
-            );
-
-            match save_daily_stress_data_to_mongo(&state.mongo_client, &daily_stress_data).await {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "daily_stress",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "😰 Daily Stress Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "😰 Daily Stress Sync - Saved {} daily stress records to MongoDB",
-                        daily_stress_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} daily stress records from {} to {}", daily_stress_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": daily_stress_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "daily_stress",
+        OuraTokenSource::OAuth,
+        |s, e, t| async move { get_oura_daily_stress_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_daily_stress_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // ==========================
@@ -1805,196 +1413,170 @@ pub async fn get_oura_heartrate_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<HeartRateData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-    let url = format!(
-        "{}/usercollection/heartrate?start_date={}&end_date={}",
-        base_url, start_date, end_date
+    println!(
+        "💓 Heart Rate API - Requesting: {} to {}",
+        start_date, end_date
     );
 
-    println!("💓 Heart Rate API - Requesting: {}", url);
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    println!("💓 Heart Rate API - Response status: {}", res.status());
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
+    let data: Vec<HeartRateData> =
+        fetch_oura_collection("heartrate", start_date, end_date, access_token).await?;
 
-    #[derive(Deserialize)]
-    struct OuraHeartRateResponse {
-        data: Vec<HeartRateData>,
-    }
+    println!("💓 Heart Rate API - Received {} points", data.len());
+    Ok(data)
+}
 
-    let response: OuraHeartRateResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+/// Max heartrate points per `insert_many`. Configure via OURA_HEARTRATE_BATCH_SIZE (default 1000).
+fn heartrate_batch_size() -> usize {
+    env::var("OURA_HEARTRATE_BATCH_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(1000)
 }
 
 pub async fn save_heartrate_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     heartrate_data: &[HeartRateData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
-    let collection = db.collection::<HeartRateData>("oura_heartrate");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in heartrate_data {
-        // Check if this timestamp already exists
-        let filter = doc! { "timestamp": &entry.timestamp };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
+) -> OuraSaveOutcome {
+    let collection = heartrate_collection(db);
+
+    let batch_size = heartrate_batch_size();
+    let mut outcome = OuraSaveOutcome::default();
+
+    // Save in timestamp order so a failed batch leaves a contiguous saved prefix
+    let mut ordered: Vec<&HeartRateData> = heartrate_data.iter().collect();
+    ordered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let days: Vec<&str> = ordered
+        .iter()
+        .map(|e| heartrate_day(&e.timestamp))
+        .collect();
+    let mut saved = 0;
+
+    for chunk in ordered.chunks(batch_size) {
+        match save_heartrate_chunk(&collection, chunk).await {
+            Ok((inserted, skipped)) => {
+                outcome.inserted += inserted;
+                outcome.skipped += skipped;
+                saved += chunk.len();
+            }
+            Err(e) => {
+                outcome.error = Some(e);
+                break;
+            }
         }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
     }
+    outcome.saved_through = saved_through_day(&days, saved);
 
     println!(
-        "💾 Heart rate data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        "💾 Heart rate data: {} new entries inserted, {} duplicates skipped (batch size {})",
+        outcome.inserted, outcome.skipped, batch_size
     );
-    Ok(())
+    outcome
 }
 
-pub async fn handle_oura_heartrate_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let user_id = "default_user";
+/// Day portion ("YYYY-MM-DD") of an Oura heartrate timestamp.
+fn heartrate_day(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
 
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status = get_oura_sync_status(&state.mongo_client, user_id, "heartrate").await;
-    let today = chrono::Utc::now().date_naive();
+/// MongoDB duplicate-key error code
+const DUPLICATE_KEY_CODE: i32 = 11000;
 
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
+fn heartrate_collection(db: &mongodb::Database) -> mongodb::Collection<HeartRateData> {
+    db.collection::<HeartRateData>("oura_heartrate")
+}
 
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
+/// Unique index on heartrate `timestamp`; it is what deduplicates points on insert.
+/// Points stored twice before the index existed are removed first, since the index
+/// build would otherwise fail on them.
+async fn ensure_heartrate_index(db: &mongodb::Database) -> mongodb::error::Result<()> {
+    let removed = dedupe_heartrate_timestamps(db).await?;
+    if removed > 0 {
+        tracing::warn!(
+            removed,
+            "removed duplicate heartrate points before indexing"
+        );
+    }
+    heartrate_collection(db)
+        .create_index(
+            mongodb::IndexModel::builder()
+                .keys(doc! { "timestamp": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            None,
+        )
+        .await?;
+    Ok(())
+}
 
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!("💓 Heart Rate Sync - Error getting sync status: {}", e);
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
+/// Delete all but the first stored point for each repeated `timestamp`, returning
+/// how many were deleted.
+async fn dedupe_heartrate_timestamps(db: &mongodb::Database) -> mongodb::error::Result<u64> {
+    let collection = db.collection::<mongodb::bson::Document>("oura_heartrate");
+    let pipeline = vec![
+        doc! { "$sort": { "_id": 1 } },
+        doc! { "$group": {
+            "_id": "$timestamp",
+            "ids": { "$push": "$_id" },
+            "count": { "$sum": 1 },
+        } },
+        doc! { "$match": { "count": { "$gt": 1 } } },
+    ];
+    let mut cursor = collection.aggregate(pipeline, None).await?;
+    let mut extra_ids = Vec::new();
+    while let Some(group) = cursor.try_next().await? {
+        if let Ok(ids) = group.get_array("ids") {
+            extra_ids.extend(ids.iter().skip(1).cloned());
         }
-    };
+    }
+    if extra_ids.is_empty() {
+        return Ok(0);
+    }
 
-    let end_date = today.format("%Y-%m-%d").to_string();
+    let result = collection
+        .delete_many(doc! { "_id": { "$in": extra_ids } }, None)
+        .await?;
+    Ok(result.deleted_count)
+}
 
-    // Try to get OAuth access token, fallback to personal token
-    let access_token = match get_valid_oura_access_token(&state.mongo_client, user_id).await {
-        Ok(Some(token)) => {
-            println!("💓 Heart Rate Sync - Using OAuth token: {}", &token[..10]);
-            token
-        }
-        Ok(None) => {
-            let personal_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-            personal_token
-        }
+/// Insert one batch of heartrate points, returning (inserted, skipped).
+/// The insert is unordered, so points already stored (or repeated within the batch)
+/// fail individually on the unique `timestamp` index and are counted as skipped.
+async fn save_heartrate_chunk(
+    collection: &mongodb::Collection<HeartRateData>,
+    chunk: &[&HeartRateData],
+) -> Result<(usize, usize), String> {
+    let options = InsertManyOptions::builder().ordered(false).build();
+    match collection.insert_many(chunk.iter().copied(), options).await {
+        Ok(result) => Ok((result.inserted_ids.len(), 0)),
         Err(e) => {
-            println!(
-                "💓 Heart Rate Sync - Token error: {}, using personal token",
-                e
-            );
-            env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
-        }
-    };
-
-    println!(
-        "💓 Heart Rate Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    match get_oura_heartrate_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(heartrate_data) => {
-            println!(
-                "💓 Heart Rate Sync - Retrieved {} data points",
-                heartrate_data.len()
-            );
-
-            // Save to MongoDB
-            match save_heartrate_data_to_mongo(&state.mongo_client, &heartrate_data).await {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) = update_oura_sync_status(
-                        &state.mongo_client,
-                        user_id,
-                        "heartrate",
-                        &end_date,
-                    )
-                    .await
-                    {
-                        println!(
-                            "💓 Heart Rate Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "💓 Heart Rate Sync - Saved {} data points to MongoDB",
-                        heartrate_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} heart rate data points from {} to {}", heartrate_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": heartrate_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => {
-                    println!("💓 Heart Rate Sync - MongoDB save error: {}", e);
-                    (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+            let duplicates = match e.kind.as_ref() {
+                ErrorKind::BulkWrite(BulkWriteFailure {
+                    write_errors: Some(write_errors),
+                    write_concern_error: None,
+                    ..
+                }) if write_errors.iter().all(|w| w.code == DUPLICATE_KEY_CODE) => {
+                    write_errors.len()
                 }
-            }
-        }
-        Err(err) => {
-            println!("💓 Heart Rate Sync - Error: {}", err);
-            (StatusCode::BAD_GATEWAY, err).into_response()
+                _ => return Err(format!("MongoDB insert error: {}", e)),
+            };
+            Ok((chunk.len() - duplicates, duplicates))
         }
     }
 }
 
+pub async fn handle_oura_heartrate_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "heartrate",
+        OuraTokenSource::OAuth,
+        |s, e, t| async move { get_oura_heartrate_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_heartrate_data_to_mongo(&c, &d).await },
+    )
+    .await
+}
+
 // ========================================
 // * * * * Sleep (Classic Endpoint) * * * *
 // ========================================
@@ -2019,31 +1601,6 @@ pub async fn get_oura_sleep_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<SleepData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/sleep?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraSleepResponse {
-        data: Vec<OuraSleepRecord>,
-    }
-
     #[derive(Deserialize)]
     struct OuraSleepRecord {
         day: String,
@@ -2059,9 +1616,9 @@ pub async fn get_oura_sleep_data_from_api(
         rmssd: Option<f32>,
     }
 
-    let response: OuraSleepResponse = res.json().await.map_err(|e| e.to_string())?;
-    let results = response
-        .data
+    let records: Vec<OuraSleepRecord> =
+        fetch_oura_collection("sleep", start_date, end_date, access_token).await?;
+    let results = records
         .into_iter()
         .map(|record| SleepData {
             date: record.day,
@@ -2082,144 +1639,50 @@ pub async fn get_oura_sleep_data_from_api(
 }
 
 pub async fn save_sleep_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     sleep_data: &[SleepData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<SleepData>("oura_sleep");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in sleep_data {
-        // Check if this date already exists
-        let filter = doc! { "date": &entry.date };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        // Insert new entry
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        sleep_data,
+        |e| e.date.as_str(),
+        |entry| async move {
+            let filter = doc! { "date": &entry.date };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "💾 Sleep data: {} new entries inserted, {} duplicates skipped",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_sleep_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status = get_oura_sync_status(&state.mongo_client, user_id, "sleep").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-
-            // Ensure start date is not after today
-            if next_day > today {
-                // If next day is in the future, use yesterday
-                (today - chrono::Duration::days(1))
-                    .format("%Y-%m-%d")
-                    .to_string()
-            } else {
-                next_day.format("%Y-%m-%d").to_string()
-            }
-        }
-        Ok(None) => {
-            // First time sync - default to yesterday
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-        Err(e) => {
-            println!("💤 Sleep Sync - Error getting sync status: {}", e);
-            (today - chrono::Duration::days(1))
-                .format("%Y-%m-%d")
-                .to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    // Try to get OAuth access token, fallback to personal token
-    let access_token = match get_valid_oura_access_token(&state.mongo_client, user_id).await {
-        Ok(Some(token)) => {
-            println!("💤 Sleep Sync - Using OAuth token: {}", &token[..10]);
-            token
-        }
-        Ok(None) => {
-            let personal_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-            personal_token
-        }
-        Err(e) => {
-            println!("💤 Sleep Sync - Token error: {}, using personal token", e);
-            env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
-        }
-    };
-
-    println!("💤 Sleep Sync - Date range: {} → {}", start_date, end_date);
-
-    match get_oura_sleep_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(sleep_data) => {
-            println!(
-                "💤 Sleep Sync - Retrieved {} sleep records",
-                sleep_data.len()
-            );
-
-            match save_sleep_data_to_mongo(&state.mongo_client, &sleep_data).await {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) =
-                        update_oura_sync_status(&state.mongo_client, user_id, "sleep", &end_date)
-                            .await
-                    {
-                        println!(
-                            "💤 Sleep Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "💤 Sleep Sync - Saved {} sleep records to MongoDB",
-                        sleep_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} sleep records from {} to {}", sleep_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": sleep_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "sleep",
+        OuraTokenSource::OAuth,
+        |s, e, t| async move { get_oura_sleep_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_sleep_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // =======================
@@ -2240,154 +1703,58 @@ pub async fn get_oura_vo2_max_data_from_api(
     end_date: &str,
     access_token: &str,
 ) -> Result<Vec<VO2MaxData>, String> {
-    let base_url =
-        env::var("OURA_API_URL").unwrap_or_else(|_| "https://api.ouraring.com/v2".to_string());
-
-    let url = format!(
-        "{}/usercollection/vo2_max?start_date={}&end_date={}",
-        base_url, start_date, end_date
-    );
-
-    let client = Client::new();
-    let res = client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("Oura API error: {}", res.status()));
-    }
-
-    #[derive(Deserialize)]
-    struct OuraVO2MaxResponse {
-        data: Vec<VO2MaxData>,
-    }
-
-    let response: OuraVO2MaxResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(response.data)
+    fetch_oura_collection("vo2_max", start_date, end_date, access_token).await
 }
 
 pub async fn save_vo2_max_data_to_mongo(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     vo2_max_data: &[VO2MaxData],
-) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
+) -> OuraSaveOutcome {
     let collection = db.collection::<VO2MaxData>("oura_vo2_max");
-
-    let mut inserted_count = 0;
-    let mut skipped_count = 0;
-
-    for entry in vo2_max_data {
-        // Check if this record already exists using id if available, otherwise use day
-        let filter = if let Some(ref id) = entry.id {
-            doc! { "id": id }
-        } else {
-            doc! { "day": &entry.day }
-        };
-        let existing = collection
-            .find_one(filter.clone(), None)
-            .await
-            .map_err(|e| format!("MongoDB find error: {}", e))?;
-
-        if existing.is_some() {
-            skipped_count += 1;
-            continue; // Skip if already exists
-        }
-
-        collection
-            .insert_one(entry, None)
-            .await
-            .map_err(|e| format!("MongoDB insert error: {}", e))?;
-        inserted_count += 1;
-    }
+    let collection = &collection;
+
+    let outcome = save_records_by_day(
+        vo2_max_data,
+        |e| e.day.as_str(),
+        |entry| async move {
+            let filter = if let Some(ref id) = entry.id {
+                doc! { "id": id }
+            } else {
+                doc! { "day": &entry.day }
+            };
+            let existing = collection
+                .find_one(filter, None)
+                .await
+                .map_err(|e| format!("MongoDB find error: {}", e))?;
+            if existing.is_some() {
+                return Ok(false); // Skip if already exists
+            }
+            collection
+                .insert_one(entry, None)
+                .await
+                .map_err(|e| format!("MongoDB insert error: {}", e))?;
+            Ok(true)
+        },
+    )
+    .await;
 
     println!(
         "Saved {} VO2 max records, skipped {} duplicates",
-        inserted_count, skipped_count
+        outcome.inserted, outcome.skipped
     );
-    Ok(())
+    outcome
 }
 
 pub async fn handle_oura_vo2_max_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let user_id = "default_user";
-
-    // Get last sync date from database, or default to yesterday
-    let last_sync_status = get_oura_sync_status(&state.mongo_client, user_id, "vo2_max").await;
-    let today = chrono::Utc::now().date_naive();
-
-    let start_date = match last_sync_status {
-        Ok(Some(status)) => {
-            // Use the day after last sync as start date
-            let last_date = chrono::NaiveDate::parse_from_str(&status.last_sync_date, "%Y-%m-%d")
-                .unwrap_or_else(|_| today - chrono::Duration::days(1));
-
-            // Calculate the next day after last sync
-            let next_day = last_date + chrono::Duration::days(1);
-            next_day.format("%Y-%m-%d").to_string()
-        }
-        _ => {
-            // Default to yesterday if no previous sync
-            let yesterday = today - chrono::Duration::days(1);
-            yesterday.format("%Y-%m-%d").to_string()
-        }
-    };
-
-    let end_date = today.format("%Y-%m-%d").to_string();
-
-    println!(
-        "🫁 VO2 Max Sync - Date range: {} → {}",
-        start_date, end_date
-    );
-
-    // Use personal token directly for VO2 max endpoint (OAuth token doesn't have access)
-    let access_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-    println!(
-        "🫁 VO2 Max Sync - Using personal token: {}",
-        &access_token[..10]
-    );
-
-    match get_oura_vo2_max_data_from_api(&start_date, &end_date, &access_token).await {
-        Ok(vo2_max_data) => {
-            println!(
-                "🫁 VO2 Max Sync - Retrieved {} VO2 max records",
-                vo2_max_data.len()
-            );
-
-            match save_vo2_max_data_to_mongo(&state.mongo_client, &vo2_max_data).await {
-                Ok(_) => {
-                    // Update sync status
-                    if let Err(e) =
-                        update_oura_sync_status(&state.mongo_client, user_id, "vo2_max", &end_date)
-                            .await
-                    {
-                        println!(
-                            "🫁 VO2 Max Sync - Warning: Failed to update sync status: {}",
-                            e
-                        );
-                    }
-
-                    println!(
-                        "🫁 VO2 Max Sync - Saved {} VO2 max records to MongoDB",
-                        vo2_max_data.len()
-                    );
-                    Json(serde_json::json!({
-                        "status": "success",
-                        "message": format!("Synced {} VO2 max records from {} to {}", vo2_max_data.len(), start_date, end_date),
-                        "sync_range": {
-                            "start_date": start_date,
-                            "end_date": end_date
-                        },
-                        "data": vo2_max_data
-                    }))
-                    .into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
-            }
-        }
-        Err(err) => (StatusCode::BAD_GATEWAY, err).into_response(),
-    }
+    run_oura_sync(
+        &state,
+        &state.mongo_client.database("wyat"),
+        "vo2_max",
+        OuraTokenSource::Personal,
+        |s, e, t| async move { get_oura_vo2_max_data_from_api(&s, &e, &t).await },
+        |c, d| async move { save_vo2_max_data_to_mongo(&c, &d).await },
+    )
+    .await
 }
 
 // ===================================
@@ -2408,11 +1775,10 @@ pub struct OuraSyncStatus {
 }
 
 pub async fn get_oura_sync_status(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     user_id: &str,
     data_type: &str,
 ) -> Result<Option<OuraSyncStatus>, String> {
-    let db = mongo_client.database("wyat");
     let collection = db.collection::<OuraSyncStatus>("oura_sync_status");
 
     let filter = doc! {
@@ -2420,19 +1786,28 @@ pub async fn get_oura_sync_status(
         "data_type": data_type
     };
 
-    match collection.find_one(filter, None).await {
-        Ok(status) => Ok(status),
-        Err(e) => Err(format!("MongoDB error: {}", e)),
+    let status = collection
+        .find_one(filter, None)
+        .await
+        .map_err(|e| format!("MongoDB error: {}", e))?;
+
+    if let Some(status) = &status
+        && let Err(e) = parse_oura_day(&status.last_sync_date)
+    {
+        let message = format!("Corrupt sync cursor for {} ({}): {}", data_type, user_id, e);
+        tracing::error!(data_type, user_id, error = %e, "corrupt Oura sync cursor");
+        return Err(message);
     }
+
+    Ok(status)
 }
 
 pub async fn update_oura_sync_status(
-    mongo_client: &mongodb::Client,
+    db: &mongodb::Database,
     user_id: &str,
     data_type: &str,
     last_sync_date: &str,
 ) -> Result<(), String> {
-    let db = mongo_client.database("wyat");
     let collection = db.collection::<OuraSyncStatus>("oura_sync_status");
 
     let now = Utc::now();
@@ -2457,297 +1832,983 @@ pub async fn update_oura_sync_status(
         .await
         .map_err(|e| format!("MongoDB error: {}", e))?;
 
-    println!(
-        "💾 Updated sync status for {}: {}",
-        data_type, last_sync_date
-    );
+    tracing::debug!(data_type, last_sync_date, "updated Oura sync cursor");
     Ok(())
 }
 
-// ===========================================
-// * * * * One-off Historical Data Sync * * * *
-// ===========================================
-// Sync historical data from May 31 to July 19, 2024
+// ====================================
+// * * * * Combined Metric Sync * * * *
+// ====================================
+// Runs every daily metric sync concurrently, each from its own sync cursor
+
+/// Parse a `YYYY-MM-DD` day string as stored in sync cursors and sent by Oura.
+/// Strict: no missing zero padding, no trailing time or whitespace.
+pub fn parse_oura_day(day: &str) -> Result<chrono::NaiveDate, String> {
+    let well_formed = day.len() == 10
+        && day.bytes().enumerate().all(|(i, b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        });
+    if !well_formed {
+        return Err(format!("Invalid date '{}', expected YYYY-MM-DD", day));
+    }
+    chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", day))
+}
 
-pub async fn handle_oura_historical_sync(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let user_id = "default_user";
-    let start_date = "2025-05-31";
-    let end_date = "2025-07-19";
+/// First day to request for a metric given its stored sync status:
+/// the day after the last sync (never later than yesterday), or yesterday on first sync.
+/// A failed status read (including a malformed cursor) is an error rather than a reset.
+fn next_sync_start_date(
+    last_sync_status: &Result<Option<OuraSyncStatus>, String>,
+    today: chrono::NaiveDate,
+) -> Result<String, String> {
+    let yesterday = today - chrono::Duration::days(1);
+    let start = match last_sync_status {
+        Ok(Some(status)) => {
+            let next_day = parse_oura_day(&status.last_sync_date)? + chrono::Duration::days(1);
+            if next_day > today {
+                yesterday
+            } else {
+                next_day
+            }
+        }
+        Ok(None) => yesterday,
+        Err(e) => return Err(e.clone()),
+    };
+    Ok(start.format("%Y-%m-%d").to_string())
+}
 
-    println!(
-        "🔄 Starting historical Oura data sync from {} to {}",
-        start_date, end_date
-    );
+/// Where a metric sync starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OuraSyncStart {
+    /// The day after the stored sync cursor (see `next_sync_start_date`)
+    Cursor,
+    /// A fixed day, ignoring the cursor
+    From(chrono::NaiveDate),
+}
 
-    // Get valid access token
-    let access_token = match get_valid_oura_access_token(&state.mongo_client, user_id).await {
-        Ok(Some(token)) => {
-            println!("🔑 Using OAuth token: {}", &token[..10]);
-            token
-        }
-        Ok(None) => {
-            let personal_token = env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string());
-            println!("🔑 Using personal token: {}", &personal_token[..10]);
-            personal_token
-        }
+fn resolve_sync_start(
+    start: OuraSyncStart,
+    last_sync_status: &Result<Option<OuraSyncStatus>, String>,
+    today: chrono::NaiveDate,
+) -> Result<String, String> {
+    match start {
+        OuraSyncStart::Cursor => next_sync_start_date(last_sync_status, today),
+        OuraSyncStart::From(day) => Ok(day.format("%Y-%m-%d").to_string()),
+    }
+}
+
+/// OAuth token for the user when available, otherwise the personal `OURA_TOKEN`.
+async fn resolve_oura_access_token(mongo_client: &mongodb::Client, user_id: &str) -> String {
+    match get_valid_oura_access_token(mongo_client, user_id).await {
+        Ok(Some(token)) => token,
+        Ok(None) => personal_oura_token(),
         Err(e) => {
-            println!("❌ Token error: {}, using personal token", e);
-            env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
+            tracing::warn!(error = %e, "Oura token error, using personal token");
+            personal_oura_token()
         }
+    }
+}
+
+fn personal_oura_token() -> String {
+    env::var("OURA_TOKEN").unwrap_or_else(|_| "missing".to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct OuraMetricSyncResult {
+    pub synced: usize,
+    pub start_date: String,
+    pub end_date: String,
+    pub persisted_through: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Fetch and save one metric from its last-sync cursor through today,
+/// advancing the cursor to the last persisted day. Errors are reported, never propagated.
+async fn sync_oura_metric<T, F, FFut, S, SFut>(
+    db: &mongodb::Database,
+    user_id: &str,
+    data_type: &str,
+    start: OuraSyncStart,
+    access_token: String,
+    fetch: F,
+    save: S,
+) -> OuraMetricSyncResult
+where
+    T: Clone,
+    F: FnOnce(String, String, String) -> FFut,
+    FFut: Future<Output = Result<Vec<T>, String>>,
+    S: FnOnce(mongodb::Database, Vec<T>) -> SFut,
+    SFut: Future<Output = OuraSaveOutcome>,
+{
+    sync_oura_metric_with_data(db, user_id, data_type, start, access_token, fetch, save)
+        .await
+        .0
+}
+
+/// `sync_oura_metric`, also returning the fetched records, or the status to report when
+/// nothing was fetched (500 for an unreadable sync cursor, 502 for a failed fetch).
+#[tracing::instrument(name = "oura_sync", skip_all, fields(data_type = %data_type, user_id = %user_id))]
+async fn sync_oura_metric_with_data<T, F, FFut, S, SFut>(
+    db: &mongodb::Database,
+    user_id: &str,
+    data_type: &str,
+    start: OuraSyncStart,
+    access_token: String,
+    fetch: F,
+    save: S,
+) -> (OuraMetricSyncResult, Result<Vec<T>, StatusCode>)
+where
+    T: Clone,
+    F: FnOnce(String, String, String) -> FFut,
+    FFut: Future<Output = Result<Vec<T>, String>>,
+    S: FnOnce(mongodb::Database, Vec<T>) -> SFut,
+    SFut: Future<Output = OuraSaveOutcome>,
+{
+    let today = chrono::Utc::now().date_naive();
+    let last_sync_status = get_oura_sync_status(db, user_id, data_type).await;
+    let end_date = today.format("%Y-%m-%d").to_string();
+
+    let mut result = OuraMetricSyncResult {
+        synced: 0,
+        start_date: String::new(),
+        end_date: end_date.clone(),
+        persisted_through: None,
+        error: None,
     };
 
-    let mut results = Vec::new();
-
-    // Sync Daily Stress
-    println!("😰 Syncing daily stress data...");
-    match get_oura_daily_stress_data_from_api(start_date, end_date, &access_token).await {
-        Ok(stress_data) => {
-            match save_daily_stress_data_to_mongo(&state.mongo_client, &stress_data).await {
-                Ok(_) => {
-                    println!("✅ Saved {} daily stress records", stress_data.len());
-                    results.push(json!({
-                        "data_type": "daily_stress",
-                        "status": "success",
-                        "count": stress_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save stress data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_stress",
-                        "status": "error",
-                        "error": e
-                    }));
-                }
-            }
+    let start_date = match resolve_sync_start(start, &last_sync_status, today) {
+        Ok(start_date) => start_date,
+        Err(e) => {
+            tracing::error!(error = %e, "cannot resolve Oura sync start date");
+            result.error = Some(e);
+            return (result, Err(StatusCode::INTERNAL_SERVER_ERROR));
         }
+    };
+    result.start_date = start_date.clone();
+
+    let data = match fetch(start_date, end_date, access_token).await {
+        Ok(data) => data,
         Err(e) => {
-            println!("❌ Failed to fetch stress data: {}", e);
-            results.push(json!({
-                "data_type": "daily_stress",
-                "status": "error",
-                "error": e
-            }));
+            tracing::error!(start_date = %result.start_date, error = %e, "Oura fetch failed");
+            result.error = Some(e);
+            return (result, Err(StatusCode::BAD_GATEWAY));
         }
+    };
+
+    let outcome = save(db.clone(), data.clone()).await;
+    result.synced = outcome.inserted + outcome.skipped;
+    result.persisted_through = outcome.saved_through.clone();
+
+    if let Err(e) = commit_oura_save(db, user_id, data_type, outcome).await {
+        tracing::error!(error = %e, "saving Oura data failed");
+        result.error = Some(e);
     }
 
-    // Sync Daily SpO2
-    println!("🫁 Syncing daily SpO2 data...");
-    match get_oura_daily_spo2_data_from_api(start_date, end_date, &access_token).await {
-        Ok(spo2_data) => {
-            match save_daily_spo2_data_to_mongo(&state.mongo_client, &spo2_data).await {
-                Ok(_) => {
-                    println!("✅ Saved {} daily SpO2 records", spo2_data.len());
-                    results.push(json!({
-                        "data_type": "daily_spo2",
-                        "status": "success",
-                        "count": spo2_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save SpO2 data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_spo2",
-                        "status": "error",
-                        "error": e
-                    }));
-                }
-            }
+    (result, Ok(data))
+}
+
+/// Which token a metric's Oura endpoint accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OuraTokenSource {
+    /// The user's OAuth token, falling back to `OURA_TOKEN`
+    OAuth,
+    /// Always the personal `OURA_TOKEN` (some endpoints reject the OAuth token)
+    Personal,
+}
+
+/// Shared body of the per-metric `GET /oura/<metric>/sync` handlers: sync from the
+/// cursor stored in `db`, save there, and return the standard envelope
+/// `{ status, offline, message, sync_range, persisted_through, data }`.
+/// A failed fetch is a 502; a failed save or corrupt sync cursor a 500.
+pub async fn run_oura_sync<T, F, FFut, S, SFut>(
+    state: &AppState,
+    db: &mongodb::Database,
+    data_type: &str,
+    token: OuraTokenSource,
+    fetch: F,
+    save: S,
+) -> axum::response::Response
+where
+    T: Clone + Serialize,
+    F: FnOnce(String, String, String) -> FFut,
+    FFut: Future<Output = Result<Vec<T>, String>>,
+    S: FnOnce(mongodb::Database, Vec<T>) -> SFut,
+    SFut: Future<Output = OuraSaveOutcome>,
+{
+    let user_id = "default_user";
+    let access_token = match token {
+        OuraTokenSource::OAuth => resolve_oura_access_token(&state.mongo_client, user_id).await,
+        OuraTokenSource::Personal => personal_oura_token(),
+    };
+
+    let (result, data) = sync_oura_metric_with_data(
+        db,
+        user_id,
+        data_type,
+        OuraSyncStart::Cursor,
+        access_token,
+        fetch,
+        save,
+    )
+    .await;
+
+    let data = match data {
+        Ok(data) => data,
+        Err(status) => return (status, result.error.unwrap_or_default()).into_response(),
+    };
+    if let Some(error) = result.error {
+        return (StatusCode::INTERNAL_SERVER_ERROR, error).into_response();
+    }
+
+    tracing::info!(
+        data_type,
+        count = data.len(),
+        start_date = %result.start_date,
+        end_date = %result.end_date,
+        "Oura sync saved records"
+    );
+    Json(json!({
+        "status": "success",
+        "offline": is_offline(),
+        "message": format!(
+            "Synced {} {} records from {} to {}",
+            data.len(),
+            data_type.replace('_', " "),
+            result.start_date,
+            result.end_date
+        ),
+        "sync_range": {
+            "start_date": result.start_date,
+            "end_date": result.end_date
+        },
+        "persisted_through": result.persisted_through,
+        "data": data
+    }))
+    .into_response()
+}
+
+/// One sync future per daily metric, each using the token its endpoint accepts.
+/// Futures are lazy, so callers choose whether to run them concurrently or in turn.
+fn oura_metric_syncs<'a>(
+    db: &'a mongodb::Database,
+    user_id: &'a str,
+    oauth_token: &'a str,
+    personal_token: &'a str,
+    start: OuraSyncStart,
+) -> Vec<BoxFuture<'a, (&'static str, OuraMetricSyncResult)>> {
+    vec![
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_sleep",
+                start,
+                oauth_token.to_string(),
+                |s, e, t| async move { get_oura_daily_sleep_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_daily_sleep_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_sleep", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_activity",
+                start,
+                personal_token.to_string(),
+                |s, e, t| async move { get_oura_daily_activity_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_daily_activity_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_activity", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_readiness",
+                start,
+                oauth_token.to_string(),
+                |s, e, t| async move { get_oura_daily_readiness_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_daily_readiness_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_readiness", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_stress",
+                start,
+                oauth_token.to_string(),
+                |s, e, t| async move { get_oura_daily_stress_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_daily_stress_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_stress", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_spo2",
+                start,
+                personal_token.to_string(),
+                |s, e, t| async move { get_oura_daily_spo2_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_daily_spo2_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_spo2", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_resilience",
+                start,
+                personal_token.to_string(),
+                |s, e, t| async move { get_oura_daily_resilience_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_daily_resilience_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_resilience", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "daily_cardiovascular_age",
+                start,
+                personal_token.to_string(),
+                |s, e, t| async move {
+                    get_oura_daily_cardiovascular_age_data_from_api(&s, &e, &t).await
+                },
+                |c, d| async move { save_daily_cardiovascular_age_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("daily_cardiovascular_age", r)
+        }
+        .boxed(),
+        async move {
+            let r = sync_oura_metric(
+                db,
+                user_id,
+                "vo2_max",
+                start,
+                personal_token.to_string(),
+                |s, e, t| async move { get_oura_vo2_max_data_from_api(&s, &e, &t).await },
+                |c, d| async move { save_vo2_max_data_to_mongo(&c, &d).await },
+            )
+            .await;
+            ("vo2_max", r)
         }
-        Err(e) => {
-            println!("❌ Failed to fetch SpO2 data: {}", e);
-            results.push(json!({
-                "data_type": "daily_spo2",
-                "status": "error",
-                "error": e
-            }));
+        .boxed(),
+    ]
+}
+
+/// GET /oura/sync-all
+///
+/// Syncs all daily metrics concurrently and returns
+/// `{ <metric>: { synced, start_date, end_date, error } }`.
+/// One metric failing does not abort the others.
+pub async fn handle_oura_sync_all(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let user_id = "default_user";
+    let client = &state.mongo_client;
+    let db = client.database("wyat");
+
+    // Some endpoints only accept the personal token (see per-metric handlers)
+    let oauth_token = resolve_oura_access_token(client, user_id).await;
+    let personal_token = personal_oura_token();
+
+    tracing::info!("Oura sync-all starting concurrent metric sync");
+
+    let tasks = oura_metric_syncs(
+        &db,
+        user_id,
+        &oauth_token,
+        &personal_token,
+        OuraSyncStart::Cursor,
+    );
+
+    let results = join_all(tasks).await;
+    let failed = results.iter().filter(|(_, r)| r.error.is_some()).count();
+    tracing::info!(metrics = results.len(), failed, "Oura sync-all finished");
+
+    let mut body: serde_json::Map<String, serde_json::Value> = results
+        .into_iter()
+        .map(|(data_type, result)| (data_type.to_string(), json!(result)))
+        .collect();
+    body.insert("offline".to_string(), json!(is_offline()));
+
+    Json(serde_json::Value::Object(body)).into_response()
+}
+
+// ===================================
+// * * * * Historical Data Sync * * * *
+// ===================================
+// Re-pull every daily metric, either catching up from each cursor or from a fixed day
+
+/// First day of data the Oura ring recorded; the default `start_date` for full syncs
+const OURA_HISTORY_START: &str = "2025-05-31";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OuraSyncMode {
+    /// Start each metric from its stored sync cursor
+    #[default]
+    Incremental,
+    /// Start every metric from `start_date`, ignoring cursors
+    Full,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OuraHistoricalSyncQuery {
+    #[serde(default)]
+    pub mode: OuraSyncMode,
+    /// YYYY-MM-DD; only used in full mode (default OURA_HISTORY_START)
+    pub start_date: Option<String>,
+}
+
+fn historical_sync_start(
+    mode: OuraSyncMode,
+    start_date: Option<&str>,
+) -> Result<OuraSyncStart, String> {
+    match mode {
+        OuraSyncMode::Incremental => Ok(OuraSyncStart::Cursor),
+        OuraSyncMode::Full => {
+            let day = start_date.unwrap_or(OURA_HISTORY_START);
+            parse_oura_day(day)
+                .map(OuraSyncStart::From)
+                .map_err(|_| format!("Invalid start_date '{}': expected YYYY-MM-DD", day))
         }
     }
+}
+
+/// GET /oura/historical-sync?mode=incremental|full&start_date=YYYY-MM-DD
+///
+/// Syncs every daily metric through today, one at a time, and returns
+/// `{ status, mode, offline, results: { <metric>: { synced, start_date, end_date, persisted_through, error } } }`.
+/// Incremental (default) starts each metric from its sync cursor; full starts all of
+/// them from `start_date`.
+pub async fn handle_oura_historical_sync(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OuraHistoricalSyncQuery>,
+) -> impl IntoResponse {
+    let user_id = "default_user";
+    let client = &state.mongo_client;
+    let db = client.database("wyat");
+
+    let start = match historical_sync_start(query.mode, query.start_date.as_deref()) {
+        Ok(start) => start,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    tracing::info!(mode = ?query.mode, start = ?start, "starting historical Oura sync");
+
+    let oauth_token = resolve_oura_access_token(client, user_id).await;
+    let personal_token = personal_oura_token();
+
+    // Run in turn rather than concurrently: a full re-pull is many pages per metric
+    let mut results = serde_json::Map::new();
+    for task in oura_metric_syncs(&db, user_id, &oauth_token, &personal_token, start) {
+        let (data_type, result) = task.await;
+        results.insert(data_type.to_string(), json!(result));
+    }
 
-    // Sync Daily Activity
-    println!("🏃 Syncing daily activity data...");
-    match get_oura_daily_activity_data_from_api(start_date, end_date, &access_token).await {
-        Ok(activity_data) => {
-            match save_daily_activity_data_to_mongo(&state.mongo_client, &activity_data).await {
-                Ok(_) => {
-                    println!("✅ Saved {} daily activity records", activity_data.len());
-                    results.push(json!({
-                        "data_type": "daily_activity",
-                        "status": "success",
-                        "count": activity_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save activity data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_activity",
-                        "status": "error",
-                        "error": e
-                    }));
+    tracing::info!("historical Oura sync completed");
+
+    Json(json!({
+        "status": "completed",
+        "mode": query.mode,
+        "offline": is_offline(),
+        "results": results
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn collect_oura_pages_follows_next_token() {
+        let mut requested_tokens = Vec::new();
+
+        let records: Vec<u32> = collect_oura_pages(|next_token| {
+            requested_tokens.push(next_token.clone());
+            async move {
+                match next_token.as_deref() {
+                    None => Ok(OuraPage {
+                        data: vec![1, 2],
+                        next_token: Some("page-2".to_string()),
+                    }),
+                    Some("page-2") => Ok(OuraPage {
+                        data: vec![3],
+                        next_token: None,
+                    }),
+                    Some(other) => Err(format!("unexpected token {}", other)),
                 }
             }
-        }
-        Err(e) => {
-            println!("❌ Failed to fetch activity data: {}", e);
-            results.push(json!({
-                "data_type": "daily_activity",
-                "status": "error",
-                "error": e
-            }));
-        }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(records, vec![1, 2, 3]);
+        assert_eq!(requested_tokens, vec![None, Some("page-2".to_string())]);
     }
 
-    // Sync Daily Cardiovascular Age
-    println!("❤️ Syncing daily cardiovascular age data...");
-    match get_oura_daily_cardiovascular_age_data_from_api(start_date, end_date, &access_token).await
-    {
-        Ok(cv_age_data) => {
-            match save_daily_cardiovascular_age_data_to_mongo(&state.mongo_client, &cv_age_data)
-                .await
-            {
-                Ok(_) => {
-                    println!(
-                        "✅ Saved {} daily cardiovascular age records",
-                        cv_age_data.len()
-                    );
-                    results.push(json!({
-                        "data_type": "daily_cardiovascular_age",
-                        "status": "success",
-                        "count": cv_age_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save cardiovascular age data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_cardiovascular_age",
-                        "status": "error",
-                        "error": e
-                    }));
+    #[test]
+    fn oura_page_parses_missing_next_token() {
+        let page: OuraPage<serde_json::Value> =
+            serde_json::from_str(r#"{"data":[{"day":"2025-01-01"}]}"#).unwrap();
+        assert_eq!(page.data.len(), 1);
+        assert!(page.next_token.is_none());
+    }
+
+    #[test]
+    fn backoff_delay_doubles_and_caps() {
+        let policy = OuraRetryPolicy {
+            max_attempts: 6,
+            base_delay_ms: 500,
+            max_delay_ms: 5_000,
+        };
+        let schedule: Vec<u64> = (1..=5)
+            .map(|attempt| policy.backoff_delay(attempt, None).as_millis() as u64)
+            .collect();
+        assert_eq!(schedule, vec![500, 1_000, 2_000, 4_000, 5_000]);
+    }
+
+    #[test]
+    fn backoff_delay_respects_retry_after() {
+        let policy = OuraRetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 10_000,
+        };
+        assert_eq!(
+            policy.backoff_delay(1, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            policy.backoff_delay(1, Some(Duration::from_secs(60))),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_with_backoff_retries_until_success() {
+        let policy = OuraRetryPolicy {
+            max_attempts: 4,
+            base_delay_ms: 0,
+            max_delay_ms: 0,
+        };
+        let mut calls = 0u32;
+        let result = retry_with_backoff(&policy, || {
+            calls += 1;
+            let call = calls;
+            async move {
+                if call < 3 {
+                    Err(OuraAttemptError::Retry {
+                        reason: "Oura API error: 429 Too Many Requests".to_string(),
+                        retry_after: None,
+                    })
+                } else {
+                    Ok("page")
                 }
             }
-        }
-        Err(e) => {
-            println!("❌ Failed to fetch cardiovascular age data: {}", e);
-            results.push(json!({
-                "data_type": "daily_cardiovascular_age",
-                "status": "error",
-                "error": e
-            }));
-        }
+        })
+        .await;
+
+        assert_eq!(result, Ok("page"));
+        assert_eq!(calls, 3);
     }
 
-    // Sync Daily Readiness
-    println!("⚡ Syncing daily readiness data...");
-    match get_oura_daily_readiness_data_from_api(start_date, end_date, &access_token).await {
-        Ok(readiness_data) => {
-            match save_daily_readiness_data_to_mongo(&state.mongo_client, &readiness_data).await {
-                Ok(_) => {
-                    println!("✅ Saved {} daily readiness records", readiness_data.len());
-                    results.push(json!({
-                        "data_type": "daily_readiness",
-                        "status": "success",
-                        "count": readiness_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save readiness data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_readiness",
-                        "status": "error",
-                        "error": e
-                    }));
-                }
+    #[tokio::test]
+    async fn retry_with_backoff_gives_up_after_max_attempts() {
+        let policy = OuraRetryPolicy {
+            max_attempts: 2,
+            base_delay_ms: 0,
+            max_delay_ms: 0,
+        };
+        let mut calls = 0u32;
+        let result: Result<(), String> = retry_with_backoff(&policy, || {
+            calls += 1;
+            async {
+                Err(OuraAttemptError::Retry {
+                    reason: "Oura API error: 503 Service Unavailable".to_string(),
+                    retry_after: None,
+                })
             }
-        }
-        Err(e) => {
-            println!("❌ Failed to fetch readiness data: {}", e);
-            results.push(json!({
-                "data_type": "daily_readiness",
-                "status": "error",
-                "error": e
-            }));
+        })
+        .await;
+
+        assert!(result.unwrap_err().contains("gave up after 2 attempts"));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn saved_through_day_stops_before_partially_saved_day() {
+        let days = ["2025-01-01", "2025-01-02", "2025-01-02", "2025-01-03"];
+        assert_eq!(saved_through_day(&days, 4), Some("2025-01-03".to_string()));
+        // Only one of the two 01-02 records made it in
+        assert_eq!(saved_through_day(&days, 2), Some("2025-01-01".to_string()));
+        assert_eq!(saved_through_day(&days, 3), Some("2025-01-02".to_string()));
+        assert_eq!(saved_through_day(&days, 0), None);
+    }
+
+    #[tokio::test]
+    async fn resaving_heartrate_batch_inserts_nothing() {
+        let mongo_client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = mongo_client.database(&format!(
+            "test_oura_{}",
+            mongodb::bson::oid::ObjectId::new()
+        ));
+        ensure_heartrate_index(&db).await.unwrap();
+        let collection = heartrate_collection(&db);
+
+        let points: Vec<HeartRateData> = (0..5)
+            .map(|i| HeartRateData {
+                timestamp: format!("2025-03-01T00:0{}:00+00:00", i),
+                bpm: 60 + i,
+            })
+            .collect();
+        let mut batch: Vec<&HeartRateData> = points.iter().collect();
+
+        assert_eq!(save_heartrate_chunk(&collection, &batch).await, Ok((5, 0)));
+        assert_eq!(save_heartrate_chunk(&collection, &batch).await, Ok((0, 5)));
+
+        // Mixed batch: one new point, one stored, one repeated within the batch
+        let extra = HeartRateData {
+            timestamp: "2025-03-01T00:09:00+00:00".to_string(),
+            bpm: 70,
+        };
+        batch = vec![&extra, &points[0], &extra];
+        assert_eq!(save_heartrate_chunk(&collection, &batch).await, Ok((1, 2)));
+        assert_eq!(collection.count_documents(None, None).await.unwrap(), 6);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn heartrate_index_builds_over_existing_duplicates() {
+        let mongo_client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = mongo_client.database(&format!(
+            "test_oura_{}",
+            mongodb::bson::oid::ObjectId::new()
+        ));
+        let collection = heartrate_collection(&db);
+        let point = |minute: u32, bpm: u32| HeartRateData {
+            timestamp: format!("2025-03-01T00:0{}:00+00:00", minute),
+            bpm,
+        };
+        // Stored before the unique index existed
+        collection
+            .insert_many(
+                vec![point(0, 60), point(0, 61), point(1, 62), point(0, 63)],
+                None,
+            )
+            .await
+            .unwrap();
+
+        init_indexes(&db).await.unwrap();
+
+        assert_eq!(collection.count_documents(None, None).await.unwrap(), 2);
+        let kept = collection
+            .find_one(doc! { "timestamp": "2025-03-01T00:00:00+00:00" }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.bpm, 60);
+        let batch = vec![&kept];
+        assert_eq!(save_heartrate_chunk(&collection, &batch).await, Ok((0, 1)));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn metric_sync_wrapper_applies_future_date_guard_and_envelope() {
+        let mongo_client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = mongo_client.database(&format!(
+            "test_oura_{}",
+            mongodb::bson::oid::ObjectId::new()
+        ));
+        let state = AppState {
+            mongo_client: mongo_client.clone(),
+        };
+        // Shaped like the per-metric handlers, with the Oura fetch and save stubbed
+        let sync = |fetch: Result<Vec<serde_json::Value>, String>| {
+            run_oura_sync(
+                &state,
+                &db,
+                "test_metric",
+                OuraTokenSource::Personal,
+                move |start, _end, _token| async move {
+                    fetch.map(|mut records| {
+                        for record in &mut records {
+                            record["day"] = json!(start);
+                        }
+                        records
+                    })
+                },
+                |_db, records: Vec<serde_json::Value>| async move {
+                    OuraSaveOutcome {
+                        inserted: records.len(),
+                        saved_through: records
+                            .first()
+                            .and_then(|r| r["day"].as_str())
+                            .map(str::to_string),
+                        ..Default::default()
+                    }
+                },
+            )
+        };
+        let today = Utc::now().date_naive();
+        let yesterday = (today - chrono::Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+
+        // A cursor at today would make the next day tomorrow; the guard clamps to yesterday
+        update_oura_sync_status(
+            &db,
+            "default_user",
+            "test_metric",
+            &today.format("%Y-%m-%d").to_string(),
+        )
+        .await
+        .unwrap();
+
+        let response = sync(Ok(vec![json!({ "score": 80 })])).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "success");
+        assert_eq!(body["sync_range"]["start_date"], yesterday.as_str());
+        assert_eq!(body["persisted_through"], yesterday.as_str());
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+        let status = get_oura_sync_status(&db, "default_user", "test_metric")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.last_sync_date, yesterday);
+
+        let failed = sync(Err("Oura API error: 503".to_string())).await;
+        assert_eq!(failed.status(), StatusCode::BAD_GATEWAY);
+
+        db.drop(None).await.unwrap();
+    }
+
+    fn sync_status(last_sync_date: &str) -> Result<Option<OuraSyncStatus>, String> {
+        let now = Utc::now();
+        Ok(Some(OuraSyncStatus {
+            id: None,
+            user_id: "default_user".to_string(),
+            data_type: "daily_sleep".to_string(),
+            last_sync_at: now,
+            last_sync_date: last_sync_date.to_string(),
+            created_at: now,
+            updated_at: now,
+        }))
+    }
+
+    #[test]
+    fn incremental_sync_uses_cursor_and_full_ignores_it() {
+        let today = chrono::NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        let status = sync_status("2025-06-10");
+
+        let incremental = historical_sync_start(OuraSyncMode::Incremental, Some("2025-01-01"));
+        assert_eq!(incremental, Ok(OuraSyncStart::Cursor));
+        assert_eq!(
+            resolve_sync_start(incremental.unwrap(), &status, today),
+            Ok("2025-06-11".to_string())
+        );
+
+        let full = historical_sync_start(OuraSyncMode::Full, Some("2025-05-31")).unwrap();
+        assert_eq!(
+            resolve_sync_start(full, &status, today),
+            Ok("2025-05-31".to_string())
+        );
+        assert_eq!(
+            resolve_sync_start(full, &Ok(None), today),
+            Ok("2025-05-31".to_string())
+        );
+
+        let default_full = historical_sync_start(OuraSyncMode::Full, None).unwrap();
+        assert_eq!(
+            resolve_sync_start(default_full, &status, today),
+            Ok(OURA_HISTORY_START.to_string())
+        );
+        assert!(historical_sync_start(OuraSyncMode::Full, Some("31/05/2025")).is_err());
+    }
+
+    #[test]
+    fn parse_oura_day_accepts_valid_and_rejects_invalid() {
+        assert_eq!(
+            parse_oura_day("2025-06-01"),
+            Ok(chrono::NaiveDate::from_ymd_opt(2025, 6, 1).unwrap())
+        );
+        assert!(parse_oura_day("2024-02-29").is_ok());
+
+        for bad in [
+            "",
+            "2025-6-1",
+            "2025-13-01",
+            "2025-02-30",
+            "2023-02-29",
+            "2025/06/01",
+            "2025-06-01T00:00:00",
+            " 2025-06-01",
+            "yesterday",
+        ] {
+            assert!(parse_oura_day(bad).is_err(), "accepted {:?}", bad);
         }
     }
 
-    // Sync Daily Resilience
-    println!("🔄 Syncing daily resilience data...");
-    match get_oura_daily_resilience_data_from_api(start_date, end_date, &access_token).await {
-        Ok(resilience_data) => {
-            match save_daily_resilience_data_to_mongo(&state.mongo_client, &resilience_data).await {
-                Ok(_) => {
-                    println!(
-                        "✅ Saved {} daily resilience records",
-                        resilience_data.len()
-                    );
-                    results.push(json!({
-                        "data_type": "daily_resilience",
-                        "status": "success",
-                        "count": resilience_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save resilience data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_resilience",
-                        "status": "error",
-                        "error": e
-                    }));
+    #[test]
+    fn malformed_cursor_is_an_error_not_a_reset() {
+        let today = chrono::NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+
+        assert_eq!(
+            next_sync_start_date(&Ok(None), today),
+            Ok("2025-06-30".to_string())
+        );
+        assert_eq!(
+            next_sync_start_date(&sync_status("2025-07-01"), today),
+            Ok("2025-06-30".to_string())
+        );
+        assert!(next_sync_start_date(&sync_status("2025-6-10"), today).is_err());
+        assert!(next_sync_start_date(&Err("MongoDB error".to_string()), today).is_err());
+        assert!(resolve_sync_start(OuraSyncStart::Cursor, &sync_status("garbage"), today).is_err());
+    }
+
+    #[tokio::test]
+    async fn partial_save_advances_cursor_to_last_saved_day() {
+        struct Record {
+            day: String,
+        }
+        let records: Vec<Record> = ["2025-03-03", "2025-03-01", "2025-03-02", "2025-03-04"]
+            .iter()
+            .map(|d| Record { day: d.to_string() })
+            .collect();
+        let requested_end = "2025-03-04";
+
+        // Simulate the insert failing on the third day
+        let outcome = save_records_by_day(
+            &records,
+            |r| r.day.as_str(),
+            |r| async move {
+                if r.day == "2025-03-03" {
+                    Err("MongoDB insert error: connection reset".to_string())
+                } else {
+                    Ok(true)
                 }
-            }
-        }
-        Err(e) => {
-            println!("❌ Failed to fetch resilience data: {}", e);
-            results.push(json!({
-                "data_type": "daily_resilience",
-                "status": "error",
-                "error": e
-            }));
+            },
+        )
+        .await;
+
+        assert_eq!(outcome.inserted, 2);
+        assert_eq!(outcome.saved_through.as_deref(), Some("2025-03-02"));
+        assert_ne!(outcome.saved_through.as_deref(), Some(requested_end));
+        assert!(outcome.into_result().is_err());
+    }
+
+    fn tokens(provider_account_id: Option<&str>) -> OuraTokens {
+        OuraTokens {
+            id: None,
+            user_id: "default_user".to_string(),
+            provider_account_id: provider_account_id.map(str::to_string),
+            access_token: "access".to_string(),
+            refresh_token: None,
+            token_type: "Bearer".to_string(),
+            expires_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }
     }
 
-    // Sync Daily Sleep
-    println!("😴 Syncing daily sleep data...");
-    match get_oura_daily_sleep_data_from_api(start_date, end_date, &access_token).await {
-        Ok(sleep_data) => {
-            match save_daily_sleep_data_to_mongo(&state.mongo_client, &sleep_data).await {
-                Ok(_) => {
-                    println!("✅ Saved {} daily sleep records", sleep_data.len());
-                    results.push(json!({
-                        "data_type": "daily_sleep",
-                        "status": "success",
-                        "count": sleep_data.len()
-                    }));
-                }
-                Err(e) => {
-                    println!("❌ Failed to save sleep data: {}", e);
-                    results.push(json!({
-                        "data_type": "daily_sleep",
-                        "status": "error",
-                        "error": e
-                    }));
-                }
+    #[test]
+    fn token_filter_keys_on_user_and_account() {
+        assert_eq!(
+            oura_tokens_filter(&tokens(None)),
+            doc! { "user_id": "default_user", "provider_account_id": null }
+        );
+        assert_eq!(
+            oura_tokens_filter(&tokens(Some("acct-1"))),
+            doc! {
+                "user_id": "default_user",
+                "$or": [
+                    { "provider_account_id": "acct-1" },
+                    { "provider_account_id": null },
+                ],
             }
-        }
-        Err(e) => {
-            println!("❌ Failed to fetch sleep data: {}", e);
-            results.push(json!({
-                "data_type": "daily_sleep",
-                "status": "error",
-                "error": e
-            }));
-        }
+        );
+
+        // Legacy documents without the field still deserialize
+        let legacy: OuraTokens = mongodb::bson::from_document(doc! {
+            "user_id": "default_user",
+            "access_token": "access",
+            "refresh_token": null,
+            "token_type": "Bearer",
+            "expires_at": null,
+            "created_at": mongodb::bson::to_bson(&Utc::now()).unwrap(),
+            "updated_at": mongodb::bson::to_bson(&Utc::now()).unwrap(),
+        })
+        .unwrap();
+        assert_eq!(legacy.provider_account_id, None);
     }
 
-    println!("🎉 Historical sync completed!");
+    #[test]
+    fn oauth_state_must_be_known_and_fresh() {
+        let now = Utc::now();
+        let fresh = OuraOAuthState {
+            state: "abc".to_string(),
+            created_at: mongodb::bson::DateTime::from_millis(now.timestamp_millis() - 60_000),
+        };
+        let stale = OuraOAuthState {
+            state: "abc".to_string(),
+            created_at: mongodb::bson::DateTime::from_millis(
+                now.timestamp_millis() - (OURA_OAUTH_STATE_TTL_SECS + 1) * 1000,
+            ),
+        };
 
-    Json(json!({
-        "status": "completed",
-        "message": format!("Historical sync from {} to {}", start_date, end_date),
-        "sync_range": {
-            "start_date": start_date,
-            "end_date": end_date
-        },
-        "results": results
-    }))
-    .into_response()
+        assert!(validate_oauth_state(Some(&fresh), now).is_ok());
+        assert!(validate_oauth_state(Some(&stale), now).is_err());
+        assert!(validate_oauth_state(None, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn callback_without_state_redirects_to_error_page() {
+        // Rejected before any Mongo or Oura call, so the client never connects
+        let mongo_client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let state = Arc::new(AppState { mongo_client });
+        let query = OuraCallbackQuery {
+            code: "code-from-attacker".to_string(),
+            state: None,
+        };
+
+        let response = handle_oura_callback(State(state), Query(query))
+            .await
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response.headers()["location"].to_str().unwrap();
+        assert!(location.ends_with("/oura-error"));
+    }
 }
@@ -0,0 +1,877 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::Jwk};
+use mongodb::bson::{Document, doc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::AppState;
+use crate::capital::{
+    Account, AccountMetadata, BatchImportRequest, Currency, FlatTransaction, insert_account,
+    process_batch_import,
+};
+
+/// Get the Plaid API base URL based on PLAID_ENV environment variable
+/// Defaults to sandbox if not set or invalid
+pub fn get_plaid_base_url() -> String {
+    let env = env::var("PLAID_ENV").unwrap_or_else(|_| "sandbox".to_string());
+    match env.to_lowercase().as_str() {
+        "production" | "prod" => "https://production.plaid.com".to_string(),
+        "development" | "dev" => "https://development.plaid.com".to_string(),
+        _ => "https://sandbox.plaid.com".to_string(),
+    }
+}
+
+/// Link user id; items are stored under it in `plaid_items`.
+pub const PLAID_USER_ID: &str = "wyat-demo-user";
+
+/// Shared HTTP client for Plaid calls.
+pub fn plaid_client() -> Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new).clone()
+}
+
+fn plaid_credentials() -> Result<(String, String), String> {
+    let client_id = env::var("PLAID_CLIENT_ID").map_err(|_| "PLAID_CLIENT_ID not set")?;
+    let secret = env::var("PLAID_SECRET").map_err(|_| "PLAID_SECRET not set")?;
+    Ok((client_id, secret))
+}
+
+/// POST `body` (plus credentials) to a Plaid endpoint and decode the JSON reply.
+async fn plaid_post<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    path: &str,
+    mut body: serde_json::Value,
+) -> Result<T, String> {
+    let (client_id, secret) = plaid_credentials()?;
+    body["client_id"] = json!(client_id);
+    body["secret"] = json!(secret);
+
+    let url = format!("{}{}", get_plaid_base_url(), path);
+    let response = client
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Plaid request to {} failed: {}", path, e))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Plaid response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Plaid {} returned {}: {}", path, status, text));
+    }
+    serde_json::from_str(&text).map_err(|e| format!("Unexpected Plaid {} response: {}", path, e))
+}
+
+// =============================================
+// * * * * Items * * * *
+// =============================================
+
+#[derive(Debug, Deserialize)]
+pub struct PublicTokenExchangeRequest {
+    pub public_token: String,
+}
+
+/// Plaid's `/item/public_token/exchange` reply. Holds the access token, so it is
+/// never returned to clients.
+#[derive(Debug, Deserialize)]
+struct PlaidExchangeResponse {
+    access_token: String,
+    item_id: String,
+}
+
+/// What clients get back after linking an item.
+#[derive(Debug, Serialize)]
+pub struct PlaidItemResponse {
+    pub item_id: String,
+}
+
+/// Exchange a Link `public_token` and store the item's access token under `user_id`.
+/// Re-linking the same item replaces its token and keeps its sync cursor. Items are
+/// matched on `item_id` alone so docs stored before `user_id` existed are claimed
+/// rather than duplicated.
+pub async fn exchange_public_token(
+    db: &mongodb::Database,
+    client: &Client,
+    user_id: &str,
+    public_token: &str,
+) -> Result<PlaidItemResponse, String> {
+    let exchange: PlaidExchangeResponse = plaid_post(
+        client,
+        "/item/public_token/exchange",
+        json!({ "public_token": public_token }),
+    )
+    .await?;
+
+    let now = chrono::Utc::now().timestamp();
+    db.collection::<Document>("plaid_items")
+        .update_one(
+            doc! { "item_id": &exchange.item_id },
+            doc! {
+                "$set": {
+                    "user_id": user_id,
+                    "access_token": &exchange.access_token,
+                    "updated_at": now,
+                },
+                "$setOnInsert": { "created_at": now },
+            },
+            mongodb::options::UpdateOptions::builder()
+                .upsert(true)
+                .build(),
+        )
+        .await
+        .map_err(|e| format!("Failed to store Plaid item: {}", e))?;
+    println!("Stored Plaid access token for item {}", exchange.item_id);
+
+    Ok(PlaidItemResponse {
+        item_id: exchange.item_id,
+    })
+}
+
+/// POST /plaid/item/public-token-exchange - Finish Link for a new item
+///
+/// Body: `{ "public_token": "..." }`. Returns `{ "item_id": "..." }`; the access
+/// token stays server-side.
+pub async fn handle_public_token_exchange(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PublicTokenExchangeRequest>,
+) -> impl IntoResponse {
+    let db = state.mongo_client.database("wyat");
+    match exchange_public_token(&db, &plaid_client(), PLAID_USER_ID, &payload.public_token).await {
+        Ok(item) => Json(item).into_response(),
+        Err(e) => {
+            eprintln!("Plaid public token exchange failed: {}", e);
+            (StatusCode::BAD_GATEWAY, e).into_response()
+        }
+    }
+}
+
+// =============================================
+// * * * * Accounts * * * *
+// =============================================
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlaidBalances {
+    #[serde(default)]
+    pub iso_currency_code: Option<String>,
+}
+
+/// Account as returned by `/accounts/get`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaidAccount {
+    pub account_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub official_name: Option<String>,
+    #[serde(default)]
+    pub mask: Option<String>,
+    #[serde(rename = "type")]
+    pub account_type: String,
+    #[serde(default)]
+    pub subtype: Option<String>,
+    #[serde(default)]
+    pub balances: PlaidBalances,
+}
+
+/// Map a Plaid account's `{ type, subtype }` onto ledger account metadata. Plaid only
+/// exposes the account number's `mask` (last four digits) without the Auth product.
+/// Unmapped combinations become `Tagged` with Plaid's raw type.
+pub fn account_metadata_from_plaid(
+    account: &PlaidAccount,
+    institution_name: &str,
+    owner_name: &str,
+) -> AccountMetadata {
+    let account_number = account.mask.clone().unwrap_or_default();
+    match (account.account_type.as_str(), account.subtype.as_deref()) {
+        ("depository", Some("checking")) => AccountMetadata::Checking {
+            bank_name: institution_name.to_string(),
+            owner_name: owner_name.to_string(),
+            account_number,
+            routing_number: None,
+            color: None,
+            txid_prefix: None,
+        },
+        ("depository", Some("savings")) => AccountMetadata::Savings {
+            bank_name: institution_name.to_string(),
+            owner_name: owner_name.to_string(),
+            account_number,
+            routing_number: None,
+            color: None,
+            txid_prefix: None,
+        },
+        ("credit", Some("credit card")) => AccountMetadata::Credit {
+            credit_card_name: account
+                .official_name
+                .clone()
+                .unwrap_or_else(|| account.name.clone()),
+            owner_name: owner_name.to_string(),
+            account_number,
+            routing_number: None,
+            color: None,
+            txid_prefix: None,
+        },
+        ("investment", Some("brokerage")) => AccountMetadata::BrokerageAccount {
+            broker_name: institution_name.to_string(),
+            owner_name: owner_name.to_string(),
+            account_number,
+            account_type: None,
+            color: None,
+            txid_prefix: None,
+        },
+        (account_type, subtype) => AccountMetadata::Tagged {
+            account_type: account_type.to_string(),
+            color: String::new(),
+            data: json!({
+                "subtype": subtype,
+                "institution_name": institution_name,
+                "owner_name": owner_name,
+                "account_number": account_number,
+            }),
+        },
+    }
+}
+
+/// Ledger id for an account imported from Plaid.
+pub fn plaid_ledger_account_id(plaid_account_id: &str) -> String {
+    format!("acct.plaid_{}", plaid_account_id)
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportAccountsRequest {
+    /// Owner recorded on the imported accounts; Plaid needs Identity to supply it.
+    #[serde(default)]
+    pub owner_name: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportAccountsResponse {
+    pub item_id: String,
+    /// Ledger account ids created by this import.
+    pub created: Vec<String>,
+    /// Ledger account ids that already existed.
+    pub existing: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Best-effort institution display name for an item.
+async fn institution_name(client: &Client, institution_id: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Institution {
+        name: String,
+    }
+    #[derive(Deserialize)]
+    struct InstitutionResponse {
+        institution: Institution,
+    }
+    let response: InstitutionResponse = plaid_post(
+        client,
+        "/institutions/get_by_id",
+        json!({ "institution_id": institution_id, "country_codes": ["US"] }),
+    )
+    .await
+    .ok()?;
+    Some(response.institution.name)
+}
+
+/// Fetch an item's accounts and create any that aren't in the ledger yet, recording
+/// each one in the item's `account_map` so transaction sync can route to it.
+pub async fn import_plaid_accounts(
+    db: &mongodb::Database,
+    client: &Client,
+    item_id: &str,
+    owner_name: &str,
+) -> Result<ImportAccountsResponse, String> {
+    let items = db.collection::<Document>("plaid_items");
+    let item = items
+        .find_one(doc! { "item_id": item_id }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Plaid item {} not found", item_id))?;
+    let access_token = item
+        .get_str("access_token")
+        .map_err(|_| format!("Plaid item {} has no access token", item_id))?;
+
+    #[derive(Deserialize)]
+    struct PlaidItem {
+        #[serde(default)]
+        institution_id: Option<String>,
+    }
+    #[derive(Deserialize)]
+    struct AccountsResponse {
+        accounts: Vec<PlaidAccount>,
+        item: PlaidItem,
+    }
+    let response: AccountsResponse = plaid_post(
+        client,
+        "/accounts/get",
+        json!({ "access_token": access_token }),
+    )
+    .await?;
+
+    let institution = match &response.item.institution_id {
+        Some(id) => institution_name(client, id)
+            .await
+            .unwrap_or_else(|| id.clone()),
+        None => "Plaid".to_string(),
+    };
+
+    let mut result = ImportAccountsResponse {
+        item_id: item_id.to_string(),
+        ..Default::default()
+    };
+    let mut account_map = Document::new();
+    for plaid_account in &response.accounts {
+        let code = plaid_account
+            .balances
+            .iso_currency_code
+            .as_deref()
+            .unwrap_or("USD");
+        let Some(currency) = Currency::from_code(code) else {
+            result.errors.push(format!(
+                "{}: unsupported currency {}",
+                plaid_account.account_id, code
+            ));
+            continue;
+        };
+        let account = Account {
+            id: plaid_ledger_account_id(&plaid_account.account_id),
+            name: plaid_account.name.clone(),
+            currency,
+            metadata: account_metadata_from_plaid(plaid_account, &institution, owner_name),
+            group_id: None,
+            group_order: None,
+        };
+        match insert_account(db, &account).await {
+            Ok(true) => result.created.push(account.id.clone()),
+            Ok(false) => result.existing.push(account.id.clone()),
+            Err(e) => {
+                result
+                    .errors
+                    .push(format!("{}: {}", plaid_account.account_id, e));
+                continue;
+            }
+        }
+        account_map.insert(
+            format!("account_map.{}", plaid_account.account_id),
+            account.id,
+        );
+    }
+
+    if !account_map.is_empty() {
+        items
+            .update_one(
+                doc! { "item_id": item_id },
+                doc! { "$set": account_map },
+                None,
+            )
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+    Ok(result)
+}
+
+/// POST /plaid/item/:item_id/import-accounts - Create ledger accounts for an item
+///
+/// Optional body: `{ "owner_name": "..." }`. Accounts already in the ledger are
+/// left untouched and reported under `existing`.
+pub async fn handle_import_plaid_accounts(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    body: Option<Json<ImportAccountsRequest>>,
+) -> impl IntoResponse {
+    let request = body.map(|Json(req)| req).unwrap_or_default();
+    let db = state.mongo_client.database("wyat");
+    let owner_name = request.owner_name.unwrap_or_default();
+    match import_plaid_accounts(&db, &plaid_client(), &item_id, &owner_name).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => {
+            eprintln!("Plaid account import for item {} failed: {}", item_id, e);
+            (StatusCode::BAD_GATEWAY, e).into_response()
+        }
+    }
+}
+
+// =============================================
+// * * * * Transactions * * * *
+// =============================================
+
+/// Transaction as returned by `/transactions/get` and `/transactions/sync`.
+/// Plaid amounts are positive when money leaves the account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaidTransaction {
+    pub transaction_id: String,
+    #[serde(default)]
+    pub account_id: String,
+    pub date: String,
+    pub name: String,
+    #[serde(default)]
+    pub merchant_name: Option<String>,
+    pub amount: f64,
+    #[serde(default)]
+    pub iso_currency_code: Option<String>,
+    #[serde(default)]
+    pub pending: bool,
+}
+
+/// Ledger id for a Plaid transaction.
+pub fn plaid_txid(transaction_id: &str) -> String {
+    format!("plaid_{}", transaction_id)
+}
+
+/// Map a Plaid transaction onto a single-leg import row for `account_id`.
+pub fn flat_from_plaid(tx: &PlaidTransaction, account_id: &str) -> FlatTransaction {
+    let inflow = tx.amount < 0.0;
+    FlatTransaction {
+        txid: plaid_txid(&tx.transaction_id),
+        date: tx.date.clone(),
+        posted_ts: None,
+        source: "plaid".to_string(),
+        payee: Some(tx.merchant_name.clone().unwrap_or_else(|| tx.name.clone())),
+        memo: tx.merchant_name.as_ref().map(|_| tx.name.clone()),
+        account_id: account_id.to_string(),
+        // Money in debits the account, money out credits it
+        direction: if inflow { "Debit" } else { "Credit" }.to_string(),
+        kind: "Fiat".to_string(),
+        ccy_or_asset: tx
+            .iso_currency_code
+            .clone()
+            .unwrap_or_else(|| "USD".to_string()),
+        amount_or_qty: tx.amount.abs(),
+        price: None,
+        price_ccy: None,
+        category_id: None,
+        status: Some(if tx.pending { "pending" } else { "posted" }.to_string()),
+        tx_type: Some(if inflow { "income" } else { "spending" }.to_string()),
+        ext1_kind: Some("plaid_transaction_id".to_string()),
+        ext1_val: Some(tx.transaction_id.clone()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RemovedTransaction {
+    transaction_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionsSyncPage {
+    #[serde(default)]
+    added: Vec<PlaidTransaction>,
+    #[serde(default)]
+    modified: Vec<PlaidTransaction>,
+    #[serde(default)]
+    removed: Vec<RemovedTransaction>,
+    next_cursor: String,
+    has_more: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PlaidItemSyncSummary {
+    pub item_id: String,
+    pub imported: usize,
+    pub skipped: usize,
+    pub removed: usize,
+    pub errors: Vec<String>,
+}
+
+/// Pull changes for one item with `/transactions/sync`, starting from its stored
+/// `cursor`, and feed them through the batch import.
+///
+/// Plaid accounts map to ledger accounts via the item's `account_map`
+/// (`{ plaid_account_id: ledger_account_id }`), falling back to its `account_id`.
+/// Pending rows are skipped; Plaid re-sends them once posted. Modified rows replace
+/// their unreconciled ledger copy and removed rows are soft-deleted. The new cursor
+/// is saved only after the import ran, so a failed sync is retried from the old one.
+pub async fn sync_plaid_item(
+    db: &mongodb::Database,
+    client: &Client,
+    item_id: &str,
+) -> Result<PlaidItemSyncSummary, String> {
+    let items = db.collection::<Document>("plaid_items");
+    let item = items
+        .find_one(doc! { "item_id": item_id }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Plaid item {} not found", item_id))?;
+    let access_token = item
+        .get_str("access_token")
+        .map_err(|_| format!("Plaid item {} has no access token", item_id))?
+        .to_string();
+    let account_map = item.get_document("account_map").ok();
+    let default_account = item.get_str("account_id").ok();
+
+    let mut cursor = item.get_str("cursor").ok().map(str::to_string);
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
+    loop {
+        let mut request = json!({ "access_token": &access_token, "count": 500 });
+        if let Some(cursor) = &cursor {
+            request["cursor"] = json!(cursor);
+        }
+        let page: TransactionsSyncPage = plaid_post(client, "/transactions/sync", request).await?;
+        changed.extend(page.added);
+        changed.extend(page.modified);
+        removed.extend(page.removed);
+        cursor = Some(page.next_cursor);
+        if !page.has_more {
+            break;
+        }
+    }
+
+    let mut summary = PlaidItemSyncSummary {
+        item_id: item_id.to_string(),
+        ..Default::default()
+    };
+    let ledger = db.collection::<Document>("capital_ledger");
+    let mut rows = Vec::new();
+    for tx in changed.iter().filter(|tx| !tx.pending) {
+        let mapped = account_map
+            .and_then(|map| map.get_str(&tx.account_id).ok())
+            .or(default_account);
+        let Some(account_id) = mapped else {
+            summary.errors.push(format!(
+                "{}: no ledger account mapped for Plaid account {}",
+                plaid_txid(&tx.transaction_id),
+                tx.account_id
+            ));
+            continue;
+        };
+        // A modified row replaces its earlier copy unless that was already reconciled
+        ledger
+            .delete_one(
+                doc! { "id": plaid_txid(&tx.transaction_id), "reconciled": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        rows.push(flat_from_plaid(tx, account_id));
+    }
+
+    if !rows.is_empty() {
+        let result = process_batch_import(
+            db,
+            BatchImportRequest {
+                transactions: rows,
+                ..Default::default()
+            },
+        )
+        .await?;
+        summary.imported = result.imported;
+        summary.skipped = result.skipped;
+        summary.errors.extend(result.errors);
+    }
+
+    if !removed.is_empty() {
+        let ids: Vec<String> = removed
+            .iter()
+            .map(|r| plaid_txid(&r.transaction_id))
+            .collect();
+        let result = ledger
+            .update_many(
+                doc! { "id": { "$in": ids }, "deleted": { "$ne": true } },
+                doc! { "$set": { "deleted": true, "deleted_at": chrono::Utc::now().timestamp() } },
+                None,
+            )
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        summary.removed = result.modified_count as usize;
+    }
+
+    items
+        .update_one(
+            doc! { "item_id": item_id },
+            doc! { "$set": {
+                "cursor": cursor,
+                "updated_at": chrono::Utc::now().timestamp(),
+            }},
+            None,
+        )
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(summary)
+}
+
+// =============================================
+// * * * * Webhooks * * * *
+// =============================================
+
+/// Maximum age of a webhook's `iat`, per Plaid's verification guide.
+const WEBHOOK_MAX_AGE_SECS: i64 = 5 * 60;
+
+#[derive(Debug, Deserialize)]
+struct WebhookClaims {
+    iat: i64,
+    request_body_sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookEvent {
+    webhook_type: String,
+    webhook_code: String,
+    item_id: String,
+}
+
+/// Verification keys by `kid`. Plaid rotates keys rarely, so fetch each once.
+fn webhook_keys() -> &'static Mutex<HashMap<String, Jwk>> {
+    static KEYS: OnceLock<Mutex<HashMap<String, Jwk>>> = OnceLock::new();
+    KEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+async fn webhook_verification_key(client: &Client, kid: &str) -> Result<Jwk, String> {
+    if let Some(key) = webhook_keys()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(kid)
+    {
+        return Ok(key.clone());
+    }
+
+    #[derive(Deserialize)]
+    struct KeyResponse {
+        key: serde_json::Value,
+    }
+    let response: KeyResponse = plaid_post(
+        client,
+        "/webhook_verification_key/get",
+        json!({ "key_id": kid }),
+    )
+    .await?;
+    if !response.key["expired_at"].is_null() {
+        return Err(format!("Plaid verification key {} has expired", kid));
+    }
+    let key: Jwk = serde_json::from_value(response.key)
+        .map_err(|e| format!("Invalid Plaid verification key: {}", e))?;
+
+    webhook_keys()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(kid.to_string(), key.clone());
+    Ok(key)
+}
+
+/// Check a verified token's claims against the raw body it signed.
+fn check_webhook_claims(claims: &WebhookClaims, body: &[u8], now: i64) -> Result<(), String> {
+    if now - claims.iat > WEBHOOK_MAX_AGE_SECS {
+        return Err("Webhook verification token is too old".to_string());
+    }
+    let body_hash = format!("{:x}", Sha256::digest(body));
+    if !body_hash.eq_ignore_ascii_case(&claims.request_body_sha256) {
+        return Err("Webhook body does not match its signature".to_string());
+    }
+    Ok(())
+}
+
+/// Verify the `Plaid-Verification` JWT (ES256) for a webhook body.
+async fn verify_webhook(client: &Client, headers: &HeaderMap, body: &[u8]) -> Result<(), String> {
+    let token = headers
+        .get("plaid-verification")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("Missing Plaid-Verification header")?;
+    let header =
+        jsonwebtoken::decode_header(token).map_err(|e| format!("Invalid webhook JWT: {}", e))?;
+    if header.alg != Algorithm::ES256 {
+        return Err(format!("Unexpected webhook JWT algorithm {:?}", header.alg));
+    }
+    let kid = header.kid.ok_or("Webhook JWT has no key id")?;
+
+    let jwk = webhook_verification_key(client, &kid).await?;
+    let key = DecodingKey::from_jwk(&jwk).map_err(|e| format!("Invalid Plaid key: {}", e))?;
+    let mut validation = Validation::new(Algorithm::ES256);
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let claims = jsonwebtoken::decode::<WebhookClaims>(token, &key, &validation)
+        .map_err(|e| format!("Webhook signature check failed: {}", e))?
+        .claims;
+
+    check_webhook_claims(&claims, body, chrono::Utc::now().timestamp())
+}
+
+/// POST /plaid/webhook - Receive Plaid webhooks
+///
+/// Rejects requests whose `Plaid-Verification` JWT doesn't verify. A
+/// `TRANSACTIONS` / `SYNC_UPDATES_AVAILABLE` event syncs that item; other events
+/// are acknowledged and ignored.
+pub async fn handle_plaid_webhook(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let client = plaid_client();
+    if let Err(e) = verify_webhook(&client, &headers, &body).await {
+        eprintln!("Rejected Plaid webhook: {}", e);
+        return (StatusCode::UNAUTHORIZED, e).into_response();
+    }
+
+    let event: WebhookEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    println!(
+        "Plaid webhook {}/{} for item {}",
+        event.webhook_type, event.webhook_code, event.item_id
+    );
+    if event.webhook_type != "TRANSACTIONS" || event.webhook_code != "SYNC_UPDATES_AVAILABLE" {
+        return Json(json!({ "status": "ignored" })).into_response();
+    }
+
+    let db = state.mongo_client.database("wyat");
+    match sync_plaid_item(&db, &client, &event.item_id).await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => {
+            eprintln!("Plaid sync for item {} failed: {}", event.item_id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plaid_tx(amount: f64, merchant_name: Option<&str>) -> PlaidTransaction {
+        serde_json::from_value(json!({
+            "transaction_id": "lPNjeW1nR6CDn5okmGQ6hEpMo4lLNoSrzqDje",
+            "account_id": "BxBXxLj1m4HMXBm9WZZmCWVbPjX16EHwv99vp",
+            "date": "2025-03-14",
+            "name": "SQ *BLUE BOTTLE COFFEE",
+            "merchant_name": merchant_name,
+            "amount": amount,
+            "iso_currency_code": "USD",
+            "pending": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn plaid_outflow_maps_to_credit_spending_row() {
+        let flat = flat_from_plaid(&plaid_tx(6.33, Some("Blue Bottle Coffee")), "acct.chase");
+
+        assert_eq!(flat.txid, "plaid_lPNjeW1nR6CDn5okmGQ6hEpMo4lLNoSrzqDje");
+        assert_eq!(flat.account_id, "acct.chase");
+        assert_eq!(flat.date, "2025-03-14");
+        assert_eq!(flat.direction, "Credit");
+        assert_eq!(flat.amount_or_qty, 6.33);
+        assert_eq!(flat.ccy_or_asset, "USD");
+        assert_eq!(flat.tx_type.as_deref(), Some("spending"));
+        assert_eq!(flat.payee.as_deref(), Some("Blue Bottle Coffee"));
+        assert_eq!(flat.memo.as_deref(), Some("SQ *BLUE BOTTLE COFFEE"));
+        assert_eq!(flat.ext1_kind.as_deref(), Some("plaid_transaction_id"));
+        assert_eq!(
+            flat.ext1_val.as_deref(),
+            Some("lPNjeW1nR6CDn5okmGQ6hEpMo4lLNoSrzqDje")
+        );
+    }
+
+    #[test]
+    fn plaid_inflow_maps_to_debit_income_row() {
+        let flat = flat_from_plaid(&plaid_tx(-2500.0, None), "acct.chase");
+
+        assert_eq!(flat.direction, "Debit");
+        assert_eq!(flat.amount_or_qty, 2500.0);
+        assert_eq!(flat.tx_type.as_deref(), Some("income"));
+        assert_eq!(flat.payee.as_deref(), Some("SQ *BLUE BOTTLE COFFEE"));
+        assert_eq!(flat.memo, None);
+    }
+
+    fn plaid_account(account_type: &str, subtype: Option<&str>) -> PlaidAccount {
+        serde_json::from_value(json!({
+            "account_id": "vzeNDwK7KQIm4yEog683uElbp9GRLEFXGK98D",
+            "name": "Plaid Account",
+            "official_name": "Plaid Diamond 12.5% APR Interest Credit Card",
+            "mask": "3333",
+            "type": account_type,
+            "subtype": subtype,
+            "balances": { "iso_currency_code": "USD" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn plaid_account_types_map_to_metadata_variants() {
+        let meta = |account_type: &str, subtype: Option<&str>| {
+            account_metadata_from_plaid(&plaid_account(account_type, subtype), "Chase", "Will")
+        };
+
+        assert!(matches!(
+            meta("depository", Some("checking")),
+            AccountMetadata::Checking { ref bank_name, ref owner_name, ref account_number, .. }
+                if bank_name == "Chase" && owner_name == "Will" && account_number == "3333"
+        ));
+        assert!(matches!(
+            meta("depository", Some("savings")),
+            AccountMetadata::Savings { ref bank_name, .. } if bank_name == "Chase"
+        ));
+        assert!(matches!(
+            meta("credit", Some("credit card")),
+            AccountMetadata::Credit { ref credit_card_name, .. }
+                if credit_card_name == "Plaid Diamond 12.5% APR Interest Credit Card"
+        ));
+        assert!(matches!(
+            meta("investment", Some("brokerage")),
+            AccountMetadata::BrokerageAccount { ref broker_name, .. } if broker_name == "Chase"
+        ));
+        match meta("loan", Some("mortgage")) {
+            AccountMetadata::Tagged {
+                account_type, data, ..
+            } => {
+                assert_eq!(account_type, "loan");
+                assert_eq!(data["subtype"], "mortgage");
+                assert_eq!(data["account_number"], "3333");
+            }
+            other => panic!("expected Tagged, got {:?}", other),
+        }
+        // A known type with an unknown subtype is not guessed at
+        assert!(matches!(
+            meta("depository", Some("cd")),
+            AccountMetadata::Tagged { ref account_type, .. } if account_type == "depository"
+        ));
+    }
+
+    #[test]
+    fn exchange_response_yields_item_id_only() {
+        let exchange: PlaidExchangeResponse = serde_json::from_value(json!({
+            "access_token": "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6",
+            "item_id": "M5eVJqLnv3tbzdngLDp9FL5OlDNxlNhlE55op",
+            "request_id": "Aim3b"
+        }))
+        .unwrap();
+        assert_eq!(
+            exchange.access_token,
+            "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6"
+        );
+
+        let body = serde_json::to_value(PlaidItemResponse {
+            item_id: exchange.item_id,
+        })
+        .unwrap();
+        assert_eq!(
+            body,
+            json!({ "item_id": "M5eVJqLnv3tbzdngLDp9FL5OlDNxlNhlE55op" })
+        );
+    }
+
+    #[test]
+    fn webhook_claims_must_match_body_and_be_fresh() {
+        let body = br#"{"webhook_type":"TRANSACTIONS"}"#;
+        let claims = WebhookClaims {
+            iat: 1_000,
+            request_body_sha256: format!("{:x}", Sha256::digest(body)),
+        };
+
+        assert!(check_webhook_claims(&claims, body, 1_100).is_ok());
+        assert!(check_webhook_claims(&claims, b"{}", 1_100).is_err());
+        assert!(check_webhook_claims(&claims, body, 1_000 + WEBHOOK_MAX_AGE_SECS + 1).is_err());
+    }
+}
@@ -2,9 +2,10 @@ use anyhow::{Context as _, Result, bail};
 use bytes::Bytes;
 use chrono::Utc;
 use mongodb::{
-    Database,
+    Database, IndexModel,
     bson::{self, Binary, doc, oid::ObjectId},
     error::{ErrorKind, WriteFailure},
+    options::IndexOptions,
 };
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -70,9 +71,36 @@ pub struct DocumentStatus {
 }
 
 /// Maximum size for inline storage (15 MB to stay under MongoDB's 16MB limit)
-const MAX_INLINE_SIZE_BYTES: usize = 15 * 1024 * 1024;
+pub const MAX_INLINE_SIZE_BYTES: usize = 15 * 1024 * 1024;
 
-pub async fn insert_blob(db: &Database, bytes: Bytes, content_type: &str) -> Result<Blob> {
+/// Unique index on `blobs.sha256`, so identical uploads resolve to one blob.
+pub async fn init_indexes(db: &Database) -> mongodb::error::Result<()> {
+    let index = IndexModel::builder()
+        .keys(doc! { "sha256": 1 })
+        .options(
+            IndexOptions::builder()
+                .unique(true)
+                .name(Some("blobs_sha256".to_string()))
+                .build(),
+        )
+        .build();
+    db.collection::<Blob>("blobs")
+        .create_index(index, None)
+        .await?;
+    Ok(())
+}
+
+/// Result of `insert_blob`: the stored blob, and whether it already existed.
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub blob: Blob,
+    /// True when the bytes matched an existing blob by SHA-256 and nothing was written
+    pub deduped: bool,
+}
+
+/// Store `bytes` content-addressed by SHA-256, returning the existing blob when the
+/// same bytes were stored before.
+pub async fn insert_blob(db: &Database, bytes: Bytes, content_type: &str) -> Result<StoredBlob> {
     if bytes.is_empty() {
         anyhow::bail!("Empty file not allowed");
     }
@@ -87,7 +115,10 @@ pub async fn insert_blob(db: &Database, bytes: Bytes, content_type: &str) -> Res
     let coll = db.collection::<Blob>("blobs");
     if let Some(existing) = coll.find_one(doc! {"sha256": &sha256}, None).await? {
         println!("insert_blob: found existing blob with sha256: {}", sha256);
-        return Ok(existing);
+        return Ok(StoredBlob {
+            blob: existing,
+            deduped: true,
+        });
     }
 
     if bytes.len() > MAX_INLINE_SIZE_BYTES {
@@ -114,12 +145,19 @@ pub async fn insert_blob(db: &Database, bytes: Bytes, content_type: &str) -> Res
     };
 
     match coll.insert_one(&blob, None).await {
-        Ok(_) => Ok(blob),
+        Ok(_) => Ok(StoredBlob {
+            blob,
+            deduped: false,
+        }),
         Err(e) => {
             let dup = matches!(e.kind.as_ref(), ErrorKind::Write(WriteFailure::WriteError(we)) if we.code == 11000);
             if dup {
+                // Lost a race with a concurrent upload of the same bytes
                 if let Some(existing) = coll.find_one(doc! {"sha256": &sha256}, None).await? {
-                    return Ok(existing);
+                    return Ok(StoredBlob {
+                        blob: existing,
+                        deduped: true,
+                    });
                 }
             }
             Err(e.into())
@@ -233,3 +271,40 @@ pub async fn create_document(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn identical_uploads_share_one_blob() {
+        let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_storage_{}", ObjectId::new().to_hex()));
+        init_indexes(&db).await.unwrap();
+
+        let bytes = Bytes::from_static(b"%PDF-1.4 statement");
+        let first = insert_blob(&db, bytes.clone(), "application/pdf")
+            .await
+            .unwrap();
+        let second = insert_blob(&db, bytes, "application/pdf").await.unwrap();
+
+        assert!(!first.deduped);
+        assert!(second.deduped);
+        assert_eq!(first.blob.id, second.blob.id);
+        assert_eq!(
+            first.blob.sha256,
+            format!("{:x}", Sha256::digest(b"%PDF-1.4 statement"))
+        );
+        assert_eq!(
+            db.collection::<Blob>("blobs")
+                .count_documents(doc! {}, None)
+                .await
+                .unwrap(),
+            1
+        );
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -1,9 +1,11 @@
-use crate::services::storage::{Document, create_document, get_blob_bytes_by_id, insert_blob};
+use crate::services::storage::{
+    Blob, Document, MAX_INLINE_SIZE_BYTES, create_document, insert_blob,
+};
 use axum::{
     Json, Router,
-    body::Bytes,
-    extract::{Path, Query, State},
-    http::{StatusCode, header},
+    body::{Bytes, StreamBody},
+    extract::{BodyStream, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Response},
     routing::{get, post},
 };
@@ -16,6 +18,7 @@ pub fn routes(state: Arc<crate::AppState>) -> Router {
     Router::new()
         .route("/blobs", post(upload_blob_handler))
         .route("/blobs/:blob_id", get(get_blob_handler))
+        .route("/storage/blobs/:blob_id", get(get_blob_handler))
         .route(
             "/capital/documents",
             get(list_documents_handler).post(create_doc_handler),
@@ -30,6 +33,8 @@ struct BlobResponse {
     sha256: String,
     size_bytes: i64,
     content_type: String,
+    /// True when identical bytes were already stored and that blob was returned
+    deduped: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -65,22 +70,151 @@ struct CreateDocResponse {
     doc: Document,
 }
 
+/// MIME types accepted by `POST /blobs` unless `STORAGE_ALLOWED_TYPES` overrides them.
+const DEFAULT_ALLOWED_TYPES: &[&str] = &["application/pdf", "text/csv", "image/png", "image/jpeg"];
+
+/// Upload limits, from `STORAGE_MAX_BYTES` and `STORAGE_ALLOWED_TYPES` (comma-separated).
+#[derive(Debug, Clone, PartialEq)]
+struct StorageLimits {
+    max_bytes: usize,
+    allowed_types: Vec<String>,
+}
+
+impl StorageLimits {
+    fn from_env() -> Self {
+        Self::from_values(
+            std::env::var("STORAGE_MAX_BYTES").ok().as_deref(),
+            std::env::var("STORAGE_ALLOWED_TYPES").ok().as_deref(),
+        )
+    }
+
+    /// Unset or unparsable values fall back to the inline blob limit and the default types.
+    fn from_values(max_bytes: Option<&str>, allowed_types: Option<&str>) -> Self {
+        let max_bytes = max_bytes
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(MAX_INLINE_SIZE_BYTES);
+        let allowed_types: Vec<String> = allowed_types
+            .map(|v| {
+                v.split(',')
+                    .map(|t| t.trim().to_ascii_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            })
+            .filter(|types: &Vec<String>| !types.is_empty())
+            .unwrap_or_else(|| {
+                DEFAULT_ALLOWED_TYPES
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect()
+            });
+        Self {
+            max_bytes,
+            allowed_types,
+        }
+    }
+
+    /// The upload's MIME type without parameters, if allowed. A missing header is
+    /// treated as PDF, which is what the upload form sends by default.
+    fn check_content_type(&self, header: Option<&str>) -> Result<String, UploadRejection> {
+        let mime = header
+            .and_then(|h| h.split(';').next())
+            .map(|m| m.trim().to_ascii_lowercase())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| "application/pdf".to_string());
+        if self.allowed_types.contains(&mime) {
+            Ok(mime)
+        } else {
+            Err(UploadRejection::UnsupportedType(mime))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum UploadRejection {
+    TooLarge { limit: usize },
+    UnsupportedType(String),
+    Body(String),
+}
+
+impl IntoResponse for UploadRejection {
+    fn into_response(self) -> Response {
+        match self {
+            UploadRejection::TooLarge { limit } => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Upload exceeds the {} byte limit", limit),
+            ),
+            UploadRejection::UnsupportedType(mime) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("Content type '{}' is not allowed", mime),
+            ),
+            UploadRejection::Body(e) => (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to read body: {}", e),
+            ),
+        }
+        .into_response()
+    }
+}
+
+/// Buffer a request body, giving up as soon as it grows past `limit` bytes.
+async fn read_capped<S, E>(mut stream: S, limit: usize) -> Result<Bytes, UploadRejection>
+where
+    S: futures::Stream<Item = Result<Bytes, E>> + Unpin,
+    E: std::fmt::Display,
+{
+    use futures::StreamExt;
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| UploadRejection::Body(e.to_string()))?;
+        if buffer.len() + chunk.len() > limit {
+            return Err(UploadRejection::TooLarge { limit });
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(buffer))
+}
+
+/// POST /blobs - Store the raw request body. 415 for a disallowed `Content-Type`,
+/// 413 past `STORAGE_MAX_BYTES` (checked against `Content-Length` up front and while
+/// reading, so oversized bodies are never fully buffered).
 async fn upload_blob_handler(
     State(state): State<Arc<crate::AppState>>,
-    body: Bytes,
-) -> Result<Json<BlobResponse>, StatusCode> {
+    headers: HeaderMap,
+    body: BodyStream,
+) -> Result<Json<BlobResponse>, Response> {
     println!("=== upload_blob_handler START ===");
+
+    let limits = StorageLimits::from_env();
+    let content_type = limits
+        .check_content_type(
+            headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok()),
+        )
+        .map_err(IntoResponse::into_response)?;
+    let declared_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if declared_length.is_some_and(|len| len > limits.max_bytes) {
+        return Err(UploadRejection::TooLarge {
+            limit: limits.max_bytes,
+        }
+        .into_response());
+    }
+    let body = read_capped(body, limits.max_bytes)
+        .await
+        .map_err(IntoResponse::into_response)?;
     println!("Body size: {} bytes", body.len());
 
     let db = state.mongo_client.database("wyat");
-    // Is the colleciton stated?
-    // For now, assume PDF - in production, parse Content-Type header
-    let content_type = "application/pdf";
 
-    let blob = insert_blob(&db, body, content_type).await.map_err(|e| {
+    let stored = insert_blob(&db, body, &content_type).await.map_err(|e| {
         eprintln!("upload_blob_handler: insert_blob failed: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
     })?;
+    let blob = stored.blob;
 
     println!("=== upload_blob_handler SUCCESS ===");
     println!("Blob ID: {}", blob.id.to_hex());
@@ -91,6 +225,7 @@ async fn upload_blob_handler(
         sha256: blob.sha256,
         size_bytes: blob.size_bytes,
         content_type: blob.content_type,
+        deduped: stored.deduped,
     };
 
     println!("Response: {:?}", response);
@@ -98,6 +233,97 @@ async fn upload_blob_handler(
     Ok(Json(response))
 }
 
+/// Chunk size used when streaming a blob back out.
+const BLOB_STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Split `bytes` into a body stream of `chunk`-sized slices, produced lazily as the
+/// body is polled (no copying).
+fn blob_stream(
+    bytes: Bytes,
+    chunk: usize,
+) -> impl futures::Stream<Item = Result<Bytes, std::io::Error>> {
+    futures::stream::iter(
+        (0..bytes.len())
+            .step_by(chunk)
+            .map(move |start| Ok(bytes.slice(start..(start + chunk).min(bytes.len())))),
+    )
+}
+
+/// Read `reader` into a body stream of chunks of at most `chunk` bytes, one read per
+/// poll, so only the chunk in flight is held in memory.
+fn read_stream<R>(
+    reader: R,
+    chunk: usize,
+) -> impl futures::Stream<Item = Result<Bytes, std::io::Error>>
+where
+    R: futures::AsyncRead + Unpin,
+{
+    use futures::AsyncReadExt;
+    futures::stream::try_unfold(reader, move |mut reader| async move {
+        let mut buffer = vec![0; chunk];
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.truncate(read);
+        Ok(Some((Bytes::from(buffer), reader)))
+    })
+}
+
+type BlobBody = futures::stream::BoxStream<'static, Result<Bytes, std::io::Error>>;
+
+/// Body stream for a blob, read from wherever it is stored: inline bytes are sliced,
+/// GridFS files are read from their chunks as the response is sent.
+async fn blob_body(db: &mongodb::Database, blob: Blob) -> Result<BlobBody, String> {
+    use futures::StreamExt;
+    if let Some(bin) = blob.bytes {
+        return Ok(blob_stream(Bytes::from(bin.bytes), BLOB_STREAM_CHUNK_BYTES).boxed());
+    }
+    let Some(file_id) = blob.gridfs_file_id else {
+        return Err(format!(
+            "Blob {} has neither inline bytes nor a GridFS file",
+            blob.id
+        ));
+    };
+    let download = db
+        .gridfs_bucket(None)
+        .open_download_stream(bson::Bson::ObjectId(file_id))
+        .await
+        .map_err(|e| format!("Failed to open GridFS file {}: {}", file_id, e))?;
+    Ok(read_stream(Box::pin(download), BLOB_STREAM_CHUNK_BYTES).boxed())
+}
+
+/// Download name for a blob: the title of a document pointing at it when there is one,
+/// else the blob id, with an extension from the content type.
+fn blob_filename(blob: &Blob, title: Option<&str>) -> String {
+    let extension = match blob.content_type.as_str() {
+        "application/pdf" => ".pdf",
+        "text/csv" => ".csv",
+        "image/png" => ".png",
+        "image/jpeg" => ".jpg",
+        _ => "",
+    };
+    let stem: String = title
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '"' | '\\' | '/'))
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let stem = if stem.is_empty() {
+        blob.id.to_hex()
+    } else {
+        stem
+    };
+    if extension.is_empty() || stem.to_lowercase().ends_with(extension) {
+        stem
+    } else {
+        format!("{}{}", stem, extension)
+    }
+}
+
+/// GET /blobs/:blob_id (also /storage/blobs/:blob_id) - Stream a stored blob with its
+/// `Content-Type` and an inline `Content-Disposition` filename. 404 for unknown ids.
 async fn get_blob_handler(
     State(state): State<Arc<crate::AppState>>,
     Path(blob_id): Path<String>,
@@ -105,16 +331,18 @@ async fn get_blob_handler(
     println!("=== get_blob_handler START ===");
     println!("blob_id: {}", blob_id);
 
-    let db = state.mongo_client.database("wyat");
+    blob_response(&state.mongo_client.database("wyat"), &blob_id).await
+}
 
+/// Download response for blob `blob_id` in `db`.
+async fn blob_response(db: &mongodb::Database, blob_id: &str) -> Result<Response, StatusCode> {
     // Parse blob_id
-    let blob_id = ObjectId::parse_str(&blob_id).map_err(|e| {
+    let blob_id = ObjectId::parse_str(blob_id).map_err(|e| {
         eprintln!("Invalid blob_id: {}", e);
         StatusCode::BAD_REQUEST
     })?;
 
-    // Get the blob metadata first to determine content type
-    let blobs = db.collection::<crate::services::storage::Blob>("blobs");
+    let blobs = db.collection::<Blob>("blobs");
     let blob = blobs
         .find_one(doc! { "_id": &blob_id }, None)
         .await
@@ -124,25 +352,40 @@ async fn get_blob_handler(
         })?
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    // Get the blob bytes
-    let bytes = get_blob_bytes_by_id(&db, blob_id).await.map_err(|e| {
-        eprintln!("Failed to get blob bytes: {}", e);
+    let title = db
+        .collection::<Document>("documents")
+        .find_one(doc! { "blob_id": &blob_id }, None)
+        .await
+        .ok()
+        .flatten()
+        .map(|document| document.title);
+    let filename = blob_filename(&blob, title.as_deref());
+    let content_type = blob.content_type.clone();
+    let size_bytes = blob.size_bytes;
+
+    let body = blob_body(db, blob).await.map_err(|e| {
+        eprintln!("{}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    println!("=== get_blob_handler SUCCESS: {} bytes ===", bytes.len());
+    println!("=== get_blob_handler SUCCESS: {} bytes ===", size_bytes);
 
     // Return the blob with proper content type and CORS headers
     Ok((
         [
-            (header::CONTENT_TYPE, blob.content_type),
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_LENGTH, size_bytes.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
             (
                 header::ACCESS_CONTROL_ALLOW_ORIGIN,
                 std::env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| "*".to_string()),
             ),
             (header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true".to_string()),
         ],
-        bytes,
+        StreamBody::new(body),
     )
         .into_response())
 }
@@ -281,3 +524,156 @@ async fn create_doc_handler(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn blob_stream_yields_every_byte_in_order() {
+        let bytes = Bytes::from((0..=255u8).cycle().take(1000).collect::<Vec<u8>>());
+        let chunks: Vec<Bytes> = blob_stream(bytes.clone(), 64)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 16);
+        assert_eq!(chunks.concat(), bytes.to_vec());
+    }
+
+    fn chunks(
+        sizes: &[usize],
+    ) -> impl futures::Stream<Item = Result<Bytes, std::io::Error>> + Unpin {
+        futures::stream::iter(
+            sizes
+                .iter()
+                .map(|n| Ok(Bytes::from(vec![b'x'; *n])))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn upload_over_byte_limit_is_rejected_with_413() {
+        assert_eq!(
+            read_capped(chunks(&[400, 400, 400]), 1000).await,
+            Err(UploadRejection::TooLarge { limit: 1000 })
+        );
+        assert_eq!(
+            read_capped(chunks(&[500, 500]), 1000).await.unwrap().len(),
+            1000
+        );
+        assert_eq!(
+            UploadRejection::TooLarge { limit: 1000 }
+                .into_response()
+                .status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn upload_with_disallowed_type_is_rejected_with_415() {
+        let limits = StorageLimits::from_values(None, None);
+        assert_eq!(limits.max_bytes, MAX_INLINE_SIZE_BYTES);
+        assert_eq!(
+            limits.check_content_type(Some("text/csv; charset=utf-8")),
+            Ok("text/csv".to_string())
+        );
+        assert_eq!(
+            limits.check_content_type(None),
+            Ok("application/pdf".to_string())
+        );
+
+        let rejected = limits.check_content_type(Some("application/x-msdownload"));
+        assert_eq!(
+            rejected,
+            Err(UploadRejection::UnsupportedType(
+                "application/x-msdownload".to_string()
+            ))
+        );
+        assert_eq!(
+            rejected.unwrap_err().into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        let custom = StorageLimits::from_values(Some("2048"), Some("image/png, IMAGE/JPEG"));
+        assert_eq!(custom.max_bytes, 2048);
+        assert!(custom.check_content_type(Some("image/jpeg")).is_ok());
+        assert!(custom.check_content_type(Some("application/pdf")).is_err());
+    }
+
+    #[tokio::test]
+    async fn uploaded_blob_round_trips_through_download() {
+        let mongo_client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = mongo_client.database(&format!("test_storage_{}", ObjectId::new().to_hex()));
+
+        let contents = format!("date,amount\n2025-01-01,{}\n", ObjectId::new().to_hex());
+        let stored = insert_blob(&db, Bytes::from(contents.clone()), "text/csv")
+            .await
+            .unwrap();
+
+        let response = blob_response(&db, &stored.blob.id.to_hex()).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            format!("inline; filename=\"{}.csv\"", stored.blob.id.to_hex()).as_str()
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, contents.as_bytes());
+
+        let missing = blob_response(&db, &ObjectId::new().to_hex()).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn gridfs_blob_streams_from_its_chunks() {
+        let mongo_client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = mongo_client.database(&format!("test_storage_{}", ObjectId::new().to_hex()));
+
+        // Spans several GridFS chunks (255 KiB each) and response chunks
+        let contents: Vec<u8> = (0..=255u8).cycle().take(600 * 1024).collect();
+        let file_id = db
+            .gridfs_bucket(None)
+            .upload_from_futures_0_3_reader("statement.pdf", &contents[..], None)
+            .await
+            .unwrap();
+        let blob = Blob {
+            id: ObjectId::new(),
+            sha256: "gridfs-test".to_string(),
+            bytes: None,
+            gridfs_file_id: Some(file_id),
+            size_bytes: contents.len() as i64,
+            content_type: "application/pdf".to_string(),
+            iv: None,
+            key_id: None,
+            created_at: 0,
+        };
+        db.collection::<Blob>("blobs")
+            .insert_one(&blob, None)
+            .await
+            .unwrap();
+
+        let chunks: Vec<Bytes> = blob_body(&db, blob.clone())
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= BLOB_STREAM_CHUNK_BYTES));
+        assert_eq!(chunks.concat(), contents);
+
+        let response = blob_response(&db, &blob.id.to_hex()).await.unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_LENGTH],
+            contents.len().to_string().as_str()
+        );
+
+        db.drop(None).await.unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! Graceful shutdown.
+//!
+//! On SIGTERM or SIGINT the server stops accepting connections and lets in-flight
+//! requests (extractions, batch imports) finish. Requests still running after the
+//! grace period (`SHUTDOWN_GRACE_SECS`, default 30) are dropped and the process exits.
+
+use std::{fmt, future::Future, time::Duration};
+
+use axum::Router;
+use tokio::sync::oneshot;
+
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// Why the server is shutting down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownReason {
+    Sigterm,
+    Sigint,
+}
+
+impl fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ShutdownReason::Sigterm => "SIGTERM",
+            ShutdownReason::Sigint => "SIGINT",
+        })
+    }
+}
+
+/// Grace period from `SHUTDOWN_GRACE_SECS`; unset or invalid values use the default.
+pub fn grace_period_from_env() -> Duration {
+    match std::env::var("SHUTDOWN_GRACE_SECS") {
+        Ok(v) => match v.trim().parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                tracing::warn!(value = %v, "ignoring invalid SHUTDOWN_GRACE_SECS");
+                DEFAULT_SHUTDOWN_GRACE
+            }
+        },
+        Err(_) => DEFAULT_SHUTDOWN_GRACE,
+    }
+}
+
+/// Resolves on the first SIGTERM or SIGINT (Ctrl-C).
+pub async fn shutdown_signal() -> ShutdownReason {
+    let interrupt = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => ShutdownReason::Sigint,
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGINT");
+                std::future::pending().await
+            }
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+                ShutdownReason::Sigterm
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<ShutdownReason>();
+
+    tokio::select! {
+        reason = interrupt => reason,
+        reason = terminate => reason,
+    }
+}
+
+/// Serve `app` until `signal` resolves, then drain in-flight requests for up to `grace`.
+pub async fn serve_with_graceful_shutdown<S>(
+    listener: std::net::TcpListener,
+    app: Router,
+    signal: S,
+    grace: Duration,
+) -> Result<(), hyper::Error>
+where
+    S: Future<Output = ShutdownReason> + Send + 'static,
+{
+    let (signaled_tx, signaled_rx) = oneshot::channel();
+    let server = hyper::Server::from_tcp(listener)?
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async move {
+            let reason = signal.await;
+            tracing::info!(
+                %reason,
+                grace_secs = grace.as_secs(),
+                "shutting down; draining in-flight requests"
+            );
+            let _ = signaled_tx.send(());
+        });
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => return result,
+        _ = signaled_rx => {}
+    }
+
+    match tokio::time::timeout(grace, &mut server).await {
+        Ok(result) => {
+            tracing::info!("in-flight requests drained");
+            result
+        }
+        Err(_) => {
+            tracing::warn!(
+                grace_secs = grace.as_secs(),
+                "requests still running after the grace period; exiting anyway"
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use std::{net::SocketAddr, sync::Arc};
+    use tokio::{sync::Notify, task::JoinHandle};
+
+    /// Handler coordination: `started` fires once `/slow` is running, and the
+    /// handler only returns after `release` is notified.
+    #[derive(Clone, Default)]
+    struct Gate {
+        started: Arc<Notify>,
+        release: Arc<Notify>,
+        signaled: Arc<Notify>,
+    }
+
+    async fn start(
+        gate: Gate,
+        grace: Duration,
+    ) -> (
+        SocketAddr,
+        oneshot::Sender<()>,
+        JoinHandle<Result<(), hyper::Error>>,
+    ) {
+        let handler_gate = gate.clone();
+        let app = Router::new().route(
+            "/slow",
+            get(move || {
+                let gate = handler_gate.clone();
+                async move {
+                    gate.started.notify_one();
+                    gate.release.notified().await;
+                    "done"
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let signal = async move {
+            let _ = rx.await;
+            gate.signaled.notify_one();
+            ShutdownReason::Sigterm
+        };
+        let server = tokio::spawn(serve_with_graceful_shutdown(
+            listener.into_std().unwrap(),
+            app,
+            signal,
+            grace,
+        ));
+        (addr, tx, server)
+    }
+
+    #[tokio::test]
+    async fn in_flight_request_finishes_after_signal() {
+        let gate = Gate::default();
+        let (addr, trigger, server) = start(gate.clone(), Duration::from_secs(30)).await;
+
+        let request = tokio::spawn(async move {
+            reqwest::get(format!("http://{}/slow", addr))
+                .await?
+                .text()
+                .await
+        });
+        gate.started.notified().await;
+        trigger.send(()).unwrap();
+        gate.signaled.notified().await;
+        assert!(
+            !server.is_finished(),
+            "server waits for the in-flight request"
+        );
+        gate.release.notify_one();
+
+        assert_eq!(request.await.unwrap().unwrap(), "done");
+        tokio::time::timeout(Duration::from_secs(10), server)
+            .await
+            .expect("server stops once drained")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown_gives_up_after_grace_period() {
+        let gate = Gate::default();
+        let (addr, trigger, server) = start(gate.clone(), Duration::from_millis(200)).await;
+
+        let _request = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+        gate.started.notified().await;
+        trigger.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), server)
+            .await
+            .expect("server exits when the grace period runs out")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn reason_names_the_signal() {
+        assert_eq!(ShutdownReason::Sigterm.to_string(), "SIGTERM");
+        assert_eq!(ShutdownReason::Sigint.to_string(), "SIGINT");
+    }
+}
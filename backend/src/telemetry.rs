@@ -0,0 +1,36 @@
+//! Structured logging setup.
+//!
+//! Handlers log through `tracing` macros with structured fields (`doc_id`, `count`, ...).
+//! Verbosity is controlled by `RUST_LOG` using `EnvFilter` directives, e.g.
+//! `RUST_LOG=backend=debug,mongodb=warn`; without it everything at `info` and above is emitted.
+
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when `RUST_LOG` is unset or unparsable.
+pub const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Build the log filter from `RUST_LOG`, falling back to [`DEFAULT_LOG_FILTER`].
+pub fn env_filter() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER))
+}
+
+/// Install the global `tracing` subscriber. Calling it again is a no-op, so tests and
+/// binaries can both call it without coordinating.
+pub fn init() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(env_filter())
+        .with_target(true)
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriber_initializes_and_tolerates_repeat_calls() {
+        init();
+        init();
+        tracing::info!(count = 1, "telemetry smoke test");
+    }
+}
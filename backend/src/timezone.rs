@@ -0,0 +1,65 @@
+//! Default timezone for day-boundary resolution.
+//!
+//! Endpoints that work out "today" or a local day use `WYAT_DEFAULT_TZ` (an IANA
+//! name such as `America/New_York`) when the request omits `tz`. An unset or
+//! invalid value falls back to UTC.
+
+use chrono_tz::Tz;
+
+pub const DEFAULT_TZ_ENV: &str = "WYAT_DEFAULT_TZ";
+
+fn parse_default_tz(value: Option<&str>) -> Result<Tz, String> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        None => Ok(Tz::UTC),
+        Some(name) => name
+            .parse()
+            .map_err(|_| format!("{} '{}' is not a valid IANA timezone", DEFAULT_TZ_ENV, name)),
+    }
+}
+
+/// The configured default timezone, or UTC when unset or invalid.
+pub fn default_tz() -> Tz {
+    parse_default_tz(std::env::var(DEFAULT_TZ_ENV).ok().as_deref()).unwrap_or(Tz::UTC)
+}
+
+/// Parse a request's `tz` parameter, falling back to [`default_tz`] when absent.
+/// On failure the error carries the rejected name.
+pub fn resolve_tz(param: Option<&str>) -> Result<Tz, String> {
+    match param {
+        Some(name) => name.parse().map_err(|_| name.to_string()),
+        None => Ok(default_tz()),
+    }
+}
+
+/// Startup check: warn if `WYAT_DEFAULT_TZ` is set but does not parse.
+pub fn check_default_tz() {
+    match parse_default_tz(std::env::var(DEFAULT_TZ_ENV).ok().as_deref()) {
+        Ok(tz) => println!("✅ Default timezone: {}", tz),
+        Err(e) => eprintln!("⚠️  {}; falling back to UTC", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_tz_parses_iana_names_and_falls_back_to_utc() {
+        assert_eq!(
+            parse_default_tz(Some("Asia/Hong_Kong")),
+            Ok(chrono_tz::Asia::Hong_Kong)
+        );
+        assert_eq!(parse_default_tz(None), Ok(Tz::UTC));
+        assert_eq!(parse_default_tz(Some("  ")), Ok(Tz::UTC));
+        assert!(parse_default_tz(Some("Mars/Olympus")).is_err());
+    }
+
+    #[test]
+    fn explicit_tz_param_wins_over_default() {
+        assert_eq!(
+            resolve_tz(Some("America/New_York")),
+            Ok(chrono_tz::America::New_York)
+        );
+        assert_eq!(resolve_tz(Some("nope")), Err("nope".to_string()));
+    }
+}
@@ -1,11 +1,11 @@
 use crate::AppState;
 use crate::services::oura::{
     DailyActivityData, DailyCardiovascularAgeData, DailyReadinessData, DailyResilienceData,
-    DailySleepData, DailySpO2Data, DailyStressData, VO2MaxData,
+    DailySleepData, DailySpO2Data, DailyStressData, VO2MaxData, parse_oura_day,
 };
 use axum::{
     Json,
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
@@ -293,3 +293,318 @@ pub async fn get_vo2_max(
     println!("[get_vo2_max] Found {} documents", docs.len());
     Json(docs).into_response()
 }
+
+#[derive(Deserialize)]
+pub struct DateRangeQuery {
+    from: Option<String>, // Format: "YYYY-MM-DD"
+    to: Option<String>,   // Format: "YYYY-MM-DD"
+}
+
+/// Longest span a range query may cover, so a bad `from` can't pull years of data.
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// Resolve a `from`/`to` range, defaulting to the last 30 days ending today in the
+/// default timezone (`WYAT_DEFAULT_TZ`, else UTC).
+fn resolve_date_range(
+    query: &DateRangeQuery,
+) -> Result<(chrono::NaiveDate, chrono::NaiveDate), String> {
+    let to = match query.to.as_deref() {
+        Some(value) => parse_oura_day(value)?,
+        None => chrono::Utc::now()
+            .with_timezone(&crate::timezone::default_tz())
+            .date_naive(),
+    };
+    let from = match query.from.as_deref() {
+        Some(value) => parse_oura_day(value)?,
+        None => to - ChronoDuration::days(30),
+    };
+    if from > to {
+        return Err(format!("'from' ({}) must not be after 'to' ({})", from, to));
+    }
+    if (to - from).num_days() > MAX_RANGE_DAYS {
+        return Err(format!(
+            "range {} to {} spans more than {} days",
+            from, to, MAX_RANGE_DAYS
+        ));
+    }
+
+    Ok((from, to))
+}
+
+/// Fetch docs whose `day` falls within [from, to], sorted by day ascending
+async fn find_days_in_range<T>(
+    db: &mongodb::Database,
+    collection_name: &str,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> mongodb::error::Result<Vec<T>>
+where
+    T: serde::de::DeserializeOwned + Unpin + Send + Sync,
+{
+    let filter = mongodb::bson::doc! {
+        "day": {
+            "$gte": from.format("%Y-%m-%d").to_string(),
+            "$lte": to.format("%Y-%m-%d").to_string(),
+        }
+    };
+    let options = mongodb::options::FindOptions::builder()
+        .sort(mongodb::bson::doc! { "day": 1 })
+        .build();
+
+    db.collection::<T>(collection_name)
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Respond with the docs in the query's range, or 400 for a bad range
+async fn days_in_range_response<T>(
+    state: &AppState,
+    collection_name: &str,
+    query: &DateRangeQuery,
+) -> axum::response::Response
+where
+    T: serde::de::DeserializeOwned + Serialize + Unpin + Send + Sync,
+{
+    let (from, to) = match resolve_date_range(query) {
+        Ok(range) => range,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let db = state.mongo_client.database("wyat");
+    let docs: Vec<T> = match find_days_in_range(&db, collection_name, from, to).await {
+        Ok(docs) => docs,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Mongo error: {}", e),
+            )
+                .into_response();
+        }
+    };
+
+    println!(
+        "[find_days_in_range] {}: found {} documents from {} to {}",
+        collection_name,
+        docs.len(),
+        from,
+        to
+    );
+    Json(docs).into_response()
+}
+
+/// Fetch daily activity docs for a date range (defaults to the last 30 days)
+pub async fn get_daily_activity_range(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateRangeQuery>,
+) -> impl IntoResponse {
+    days_in_range_response::<DailyActivityData>(&state, "oura_daily_activity", &query).await
+}
+
+/// Fetch daily sleep docs for a date range (defaults to the last 30 days)
+pub async fn get_daily_sleep_range(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateRangeQuery>,
+) -> impl IntoResponse {
+    days_in_range_response::<DailySleepData>(&state, "oura_daily_sleep", &query).await
+}
+
+// ==============================
+// * * * * Daily Summary * * * *
+// ==============================
+// Key metrics from the sleep, readiness, activity and stress collections merged per day
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyHealthSummary {
+    pub day: String,
+    pub sleep_score: Option<i32>,
+    pub readiness_score: Option<i32>,
+    pub steps: Option<i32>,
+    pub stress_high: Option<i32>,
+}
+
+/// One summary per calendar day in [from, to], with `None` for metrics missing that day.
+fn merge_daily_summaries(
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+    sleep: &[DailySleepData],
+    readiness: &[DailyReadinessData],
+    activity: &[DailyActivityData],
+    stress: &[DailyStressData],
+) -> Vec<DailyHealthSummary> {
+    let sleep: HashMap<&str, Option<i32>> =
+        sleep.iter().map(|d| (d.day.as_str(), d.score)).collect();
+    let readiness: HashMap<&str, Option<i32>> = readiness
+        .iter()
+        .map(|d| (d.day.as_str(), d.score))
+        .collect();
+    let activity: HashMap<&str, Option<i32>> =
+        activity.iter().map(|d| (d.day.as_str(), d.steps)).collect();
+    let stress: HashMap<&str, Option<i32>> = stress
+        .iter()
+        .map(|d| (d.day.as_str(), d.stress_high))
+        .collect();
+
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            let key = day.format("%Y-%m-%d").to_string();
+            let metric = |m: &HashMap<&str, Option<i32>>| m.get(key.as_str()).copied().flatten();
+            DailyHealthSummary {
+                sleep_score: metric(&sleep),
+                readiness_score: metric(&readiness),
+                steps: metric(&activity),
+                stress_high: metric(&stress),
+                day: key,
+            }
+        })
+        .collect()
+}
+
+/// Daily summaries for every day in [from, to] (YYYY-MM-DD).
+pub async fn daily_health_summaries(
+    db: &mongodb::Database,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> mongodb::error::Result<Vec<DailyHealthSummary>> {
+    let (sleep, readiness, activity, stress) = futures::try_join!(
+        find_days_in_range::<DailySleepData>(db, "oura_daily_sleep", from, to),
+        find_days_in_range::<DailyReadinessData>(db, "oura_daily_readiness", from, to),
+        find_days_in_range::<DailyActivityData>(db, "oura_daily_activity", from, to),
+        find_days_in_range::<DailyStressData>(db, "oura_daily_stress", from, to),
+    )?;
+    Ok(merge_daily_summaries(
+        from, to, &sleep, &readiness, &activity, &stress,
+    ))
+}
+
+/// GET /vitals/summary/:day
+///
+/// Sleep score, readiness score, steps and stress high for one day, with nulls for
+/// any metric Oura has no record of that day.
+pub async fn get_daily_summary(
+    State(state): State<Arc<AppState>>,
+    Path(day): Path<String>,
+) -> impl IntoResponse {
+    let day = match parse_oura_day(&day) {
+        Ok(day) => day,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let db = state.mongo_client.database("wyat");
+    match daily_health_summaries(&db, day, day).await {
+        Ok(mut summaries) => Json(summaries.remove(0)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Mongo error: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /vitals/summary?from=&to=
+///
+/// One summary per day in the range (defaults to the last 30 days), oldest first.
+pub async fn get_daily_summary_range(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateRangeQuery>,
+) -> impl IntoResponse {
+    let (from, to) = match resolve_date_range(&query) {
+        Ok(range) => range,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let db = state.mongo_client.database("wyat");
+    match daily_health_summaries(&db, from, to).await {
+        Ok(summaries) => Json(summaries).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Mongo error: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn from_json<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn summary_merges_metrics_with_nulls_for_missing_days() {
+        let day = |s| parse_oura_day(s).unwrap();
+        let sleep: Vec<DailySleepData> = vec![
+            from_json(json!({ "day": "2025-06-01", "score": 82 })),
+            from_json(json!({ "day": "2025-06-02", "score": 74 })),
+        ];
+        let readiness: Vec<DailyReadinessData> =
+            vec![from_json(json!({ "day": "2025-06-01", "score": 77 }))];
+        let activity: Vec<DailyActivityData> =
+            vec![from_json(json!({ "day": "2025-06-01", "steps": 9120 }))];
+        let stress: Vec<DailyStressData> = vec![from_json(
+            json!({ "day": "2025-06-01", "stress_high": 3600, "recovery_high": 1800 }),
+        )];
+
+        let summaries = merge_daily_summaries(
+            day("2025-06-01"),
+            day("2025-06-03"),
+            &sleep,
+            &readiness,
+            &activity,
+            &stress,
+        );
+
+        assert_eq!(
+            summaries,
+            vec![
+                DailyHealthSummary {
+                    day: "2025-06-01".to_string(),
+                    sleep_score: Some(82),
+                    readiness_score: Some(77),
+                    steps: Some(9120),
+                    stress_high: Some(3600),
+                },
+                DailyHealthSummary {
+                    day: "2025-06-02".to_string(),
+                    sleep_score: Some(74),
+                    readiness_score: None,
+                    steps: None,
+                    stress_high: None,
+                },
+                DailyHealthSummary {
+                    day: "2025-06-03".to_string(),
+                    sleep_score: None,
+                    readiness_score: None,
+                    steps: None,
+                    stress_high: None,
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&summaries[1]).unwrap()["steps"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn date_range_rejects_spans_over_a_year() {
+        let range = |from: &str, to: &str| {
+            resolve_date_range(&DateRangeQuery {
+                from: Some(from.to_string()),
+                to: Some(to.to_string()),
+            })
+        };
+        let day = |s| parse_oura_day(s).unwrap();
+
+        assert_eq!(
+            range("2024-01-01", "2024-12-31"),
+            Ok((day("2024-01-01"), day("2024-12-31")))
+        );
+        assert!(range("2024-01-01", "2025-01-01").is_ok());
+        assert!(range("2024-01-01", "2025-01-02").is_err());
+        assert!(range("2025-01-02", "2025-01-01").is_err());
+    }
+}
@@ -21,6 +21,19 @@ pub enum WeightUnit {
     Lb,
 }
 
+impl WeightUnit {
+    /// Parse a `unit` query value (`kg` or `lb`, case-insensitive).
+    pub fn parse(s: &str) -> Option<Self> {
+        if s.eq_ignore_ascii_case("kg") {
+            Some(WeightUnit::Kg)
+        } else if s.eq_ignore_ascii_case("lb") {
+            Some(WeightUnit::Lb)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum LoadBasis {
@@ -142,6 +155,21 @@ pub struct ExerciseTypePatch {
     pub default_load_basis: Option<LoadBasis>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MergeExerciseTypesRequest {
+    #[schema(value_type = String)]
+    pub from_id: ObjectId,
+    #[schema(value_type = String)]
+    pub into_id: ObjectId,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExerciseTypeMergeResult {
+    pub entries_updated: u64,
+    /// The surviving type, with `from`'s name and aliases added to its aliases.
+    pub merged: ExerciseType,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct FindByMuscleRequest {
     pub muscles: Vec<String>,
@@ -292,6 +320,38 @@ fn validate_weight_data(
     Ok(())
 }
 
+/// The gym/cardio fields of an entry, as created or as they would be after a patch.
+struct EntryKindFields {
+    sets: Option<u16>,
+    reps: Option<u16>,
+    weight_value: Option<f32>,
+    time_seconds: Option<u32>,
+    distance_meters: Option<u32>,
+}
+
+/// An entry is either gym (sets and reps, optional weight) or cardio (time and/or
+/// distance), never both; log a mixed session as two entries.
+fn validate_entry_kind(fields: &EntryKindFields) -> Result<(), WorkoutError> {
+    let has_gym_data =
+        fields.sets.is_some() || fields.reps.is_some() || fields.weight_value.is_some();
+    let has_cardio_data = fields.time_seconds.is_some() || fields.distance_meters.is_some();
+
+    match (has_gym_data, has_cardio_data) {
+        (false, false) => Err(WorkoutError::Validation(
+            "Exercise entry must have either gym data (sets/reps/weight) or cardio data (time/distance)".to_string()
+        )),
+        (true, true) => Err(WorkoutError::Validation(
+            "Exercise entry cannot mix gym data (sets/reps/weight) with cardio data (time/distance); log them as separate entries".to_string()
+        )),
+        (true, false) if fields.sets.is_none() || fields.reps.is_none() => {
+            Err(WorkoutError::Validation(
+                "Gym entries require both sets and reps".to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
 fn validate_exercise_entry_data(input: &ExerciseEntryInput) -> Result<(), WorkoutError> {
     validate_date_unix(input.date_unix)?;
 
@@ -301,17 +361,53 @@ fn validate_exercise_entry_data(input: &ExerciseEntryInput) -> Result<(), Workou
 
     validate_weight_data(input.weight_value, input.weight_unit)?;
 
-    // Ensure at least some exercise data is provided
-    let has_gym_data = input.sets.is_some() || input.reps.is_some() || input.weight_value.is_some();
-    let has_cardio_data = input.time_seconds.is_some() || input.distance_meters.is_some();
+    validate_entry_kind(&EntryKindFields {
+        sets: input.sets,
+        reps: input.reps,
+        weight_value: input.weight_value,
+        time_seconds: input.time_seconds,
+        distance_meters: input.distance_meters,
+    })
+}
 
-    if !has_gym_data && !has_cardio_data {
-        return Err(WorkoutError::Validation(
-            "Exercise entry must have either gym data (sets/reps/weight) or cardio data (time/distance)".to_string()
-        ));
+/// Check the gym/cardio fields a patch would leave on `current`. Patches that leave
+/// those fields alone skip the check, so older entries stay editable.
+fn validate_patched_entry_kind(
+    current: &ExerciseEntry,
+    patch: &ExerciseEntryPatch,
+) -> Result<(), WorkoutError> {
+    let touches_kind = patch.sets.is_some()
+        || patch.reps.is_some()
+        || patch.weight_value.is_some()
+        || patch.time_seconds.is_some()
+        || patch.distance_meters.is_some();
+    if !touches_kind {
+        return Ok(());
+    }
+    validate_entry_kind(&EntryKindFields {
+        sets: patch.sets.or(current.sets),
+        reps: patch.reps.or(current.reps),
+        weight_value: patch.weight_value.or(current.weight_value),
+        time_seconds: patch.time_seconds.or(current.time_seconds),
+        distance_meters: patch.distance_meters.or(current.distance_meters),
+    })
+}
+
+fn validation_message(err: WorkoutError) -> String {
+    match err {
+        WorkoutError::Validation(message) => message,
+        other => other.to_string(),
     }
+}
 
-    Ok(())
+/// 400 response carrying the validator's message, as the handlers return it.
+fn validation_error_response(err: WorkoutError) -> axum::response::Response {
+    let message = validation_message(err);
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
 }
 
 pub fn muscle_for_region(region: Region) -> &'static [Muscle] {
@@ -350,10 +446,46 @@ pub fn muscle_for_alias(term: &str) -> Option<&'static [Muscle]> {
     }
 }
 
+/// Expand search terms into `primary_muscles` values for a `$in` filter.
+/// Group aliases ("arms", "legs") and regions ("upper body", "lower_body")
+/// become their concrete muscles; anything else is passed through as a
+/// literal muscle name. Duplicates are dropped, first occurrence wins.
+pub fn expand_muscle_terms(terms: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::new();
+    for term in terms {
+        let term = term.trim();
+        if term.is_empty() {
+            continue;
+        }
+
+        let region = serde_json::from_value::<Region>(serde_json::Value::String(
+            term.to_lowercase().replace(' ', "_"),
+        ))
+        .ok();
+        let muscles = muscle_for_alias(term).or_else(|| region.map(muscle_for_region));
+
+        let names: Vec<String> = match muscles {
+            Some(muscles) => muscles
+                .iter()
+                .filter_map(|m| serde_json::to_value(m).ok())
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            None => vec![term.to_string()],
+        };
+
+        for name in names {
+            if !expanded.contains(&name) {
+                expanded.push(name);
+            }
+        }
+    }
+    expanded
+}
+
 #[utoipa::path(
     post,
     path = "/workout/exercise-types/find-by-muscle",
-    request_body(content = FindByMuscleRequest, description = "List of muscle names to filter by"),
+    request_body(content = FindByMuscleRequest, description = "Muscle names, group aliases (arms, legs, core) or regions (upper body, lower body) to filter by"),
     responses(
         (status = 200, description = "Exercise types matching muscles", body = Vec<ExerciseType>),
         (status = 401, description = "Unauthorized"),
@@ -365,55 +497,180 @@ pub fn muscle_for_alias(term: &str) -> Option<&'static [Muscle]> {
 )]
 pub async fn find_exercise_type_by_muscle(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Json(req): Json<FindByMuscleRequest>,
 ) -> impl IntoResponse {
-    // API key check
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
+    let db = state.mongo_client.database("wyat");
+    match find_exercise_types_by_muscles(&db, &req.muscles).await {
+        Ok(results) => (StatusCode::OK, Json(results)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
 
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        ).into_response();
+/// Exercise types whose primary muscles match any of the (alias-expanded) terms.
+pub async fn find_exercise_types_by_muscles(
+    db: &Database,
+    terms: &[String],
+) -> Result<Vec<ExerciseType>, WorkoutError> {
+    let muscles = expand_muscle_terms(terms);
+    let filter = doc! { "primary_muscles": { "$in": muscles } };
+    Ok(exercise_types(db)
+        .find(filter, None)
+        .await?
+        .try_collect()
+        .await?)
+}
+
+/// Pounds → kilograms conversion factor (the international pound, exact by definition).
+const LB_TO_KG: f64 = 0.45359237;
+
+/// Convert a logged weight to kilograms.
+pub fn weight_in_kg(value: f32, unit: WeightUnit) -> f64 {
+    match unit {
+        WeightUnit::Kg => value as f64,
+        WeightUnit::Lb => value as f64 * LB_TO_KG,
     }
+}
 
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<ExerciseType>("exercise_types");
+/// Convert a weight between units for display, rounded to 2 decimal places.
+/// Same-unit values pass through unrounded.
+pub fn convert_weight(value: f32, from: WeightUnit, to: WeightUnit) -> f32 {
+    if from == to {
+        return value;
+    }
+    let converted = match to {
+        WeightUnit::Kg => weight_in_kg(value, from),
+        WeightUnit::Lb => weight_in_kg(value, from) / LB_TO_KG,
+    };
+    ((converted * 100.0).round() / 100.0) as f32
+}
 
-    let filter = doc! {
-        "primary_muscles": {
-            "$in": req.muscles
-        }
+fn invalid_unit_response() -> axum::response::Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": "unit must be 'kg' or 'lb'" })),
+    )
+        .into_response()
+}
+
+/// Rewrite an entry's weight into `unit` for a response; entries without a weight are left as is.
+fn present_weight_in(entry: &mut ExerciseEntry, unit: WeightUnit) {
+    if let (Some(value), Some(from)) = (entry.weight_value, entry.weight_unit) {
+        entry.weight_value = Some(convert_weight(value, from, unit));
+        entry.weight_unit = Some(unit);
+    }
+}
+
+/// Training volume of a gym entry in kg: sets × reps × weight.
+/// `PerSide` loads count both sides; a missing `sets` counts as one set.
+/// Returns None for entries without reps or weight (cardio, bodyweight).
+pub fn entry_volume_kg(entry: &ExerciseEntry, default_basis: Option<LoadBasis>) -> Option<f64> {
+    let reps = entry.reps? as f64;
+    let weight = weight_in_kg(entry.weight_value?, entry.weight_unit?);
+    let sets = entry.sets.unwrap_or(1) as f64;
+    let sides = match entry.load_basis.or(default_basis) {
+        Some(LoadBasis::PerSide) => 2.0,
+        Some(LoadBasis::Total) | None => 1.0,
     };
+    Some(sets * reps * weight * sides)
+}
 
-    match collection.find(filter, None).await {
-        Ok(mut cursor) => {
-            let mut results = Vec::new();
-            while let Some(doc) = cursor.try_next().await.unwrap_or(None) {
-                results.push(doc);
-            }
-            (StatusCode::OK, Json(results)).into_response()
+/// Load entries in `[since_unix, until_unix)` and pair each with its exercise type.
+/// Types are fetched in a single `$in` query; entries whose type no longer exists are dropped.
+async fn load_entries_with_types(
+    db: &Database,
+    since_unix: i64,
+    until_unix: Option<i64>,
+) -> Result<Vec<(ExerciseEntry, ExerciseType)>, WorkoutError> {
+    let mut date_filter = doc! { "$gte": since_unix };
+    if let Some(until) = until_unix {
+        date_filter.insert("$lt", until);
+    }
+
+    let entries: Vec<ExerciseEntry> = exercise_entries(db)
+        .find(doc! { "date_unix": date_filter }, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut type_ids: Vec<ObjectId> = entries.iter().filter_map(|e| e.exercise_id).collect();
+    type_ids.sort();
+    type_ids.dedup();
+
+    let types_by_id: HashMap<ObjectId, ExerciseType> = exercise_types(db)
+        .find(doc! { "_id": { "$in": &type_ids } }, None)
+        .await?
+        .try_collect::<Vec<ExerciseType>>()
+        .await?
+        .into_iter()
+        .filter_map(|t| t.id.map(|id| (id, t)))
+        .collect();
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let exercise_type = types_by_id.get(&entry.exercise_id?)?.clone();
+            Some((entry, exercise_type))
+        })
+        .collect())
+}
+
+/// One count per primary muscle per entry.
+fn tally_muscle_counts(pairs: &[(ExerciseEntry, ExerciseType)]) -> HashMap<Muscle, u32> {
+    let mut counts = HashMap::new();
+    for (_, exercise_type) in pairs {
+        for muscle in &exercise_type.primary_muscles {
+            *counts.entry(*muscle).or_insert(0) += 1;
         }
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": e.to_string() })),
-        )
-        .into_response(),
     }
+    counts
 }
 
+/// Total volume (kg) per primary muscle; each muscle is credited the entry's full volume.
+fn tally_muscle_volume(pairs: &[(ExerciseEntry, ExerciseType)]) -> HashMap<Muscle, f64> {
+    let mut volume = HashMap::new();
+    for (entry, exercise_type) in pairs {
+        let Some(kg) = entry_volume_kg(entry, exercise_type.default_load_basis) else {
+            continue;
+        };
+        for muscle in &exercise_type.primary_muscles {
+            *volume.entry(*muscle).or_insert(0.0) += kg;
+        }
+    }
+    volume
+}
 
+/// Entries per primary muscle since `since_unix`, one count per muscle per entry.
 pub async fn recent_muscle_counts(
     db: &Database,
     since_unix: i64,
 ) -> Result<HashMap<Muscle, u32>, WorkoutError> {
-    // 1) fetch entries since `since_unix`
-    // 2) join to ExerciseType to read primary_muscles
-    // 3) count hits per Muscle
-    // keep this as a TODO if you’re not ready to join yet
-    Ok(HashMap::new())
+    Ok(muscle_volume(db, since_unix, None)
+        .await?
+        .into_iter()
+        .map(|(muscle, totals)| (muscle, totals.entries))
+        .collect())
+}
+
+/// Entry count and volume per primary muscle in `[since_unix, until_unix)`: the
+/// entry→type join behind both `recent_muscle_counts` and `/workout/volume`.
+async fn muscle_volume(
+    db: &Database,
+    since_unix: i64,
+    until_unix: Option<i64>,
+) -> Result<HashMap<Muscle, MuscleVolume>, WorkoutError> {
+    let pairs = load_entries_with_types(db, since_unix, until_unix).await?;
+    let volume = tally_muscle_volume(&pairs);
+    Ok(tally_muscle_counts(&pairs)
+        .into_iter()
+        .map(|(muscle, entries)| {
+            let volume_kg = volume.get(&muscle).copied().unwrap_or(0.0);
+            (muscle, MuscleVolume { volume_kg, entries })
+        })
+        .collect())
 }
 
 // ExerciseType service functions
@@ -509,15 +766,21 @@ pub async fn create_exercise_entry(
 
     // Verify exercise_id exists and get the exercise type
     let exercise_type = get_exercise_type_by_id(db, input.exercise_id).await?;
+    let exercise_entry = build_exercise_entry(input, &exercise_type);
 
-    // Determine load_basis
-    let load_basis = if let Some(provided_load_basis) = input.load_basis {
-        Some(provided_load_basis)
-    } else {
-        exercise_type.default_load_basis
-    };
+    let collection = exercise_entries(db);
+    let result = collection.insert_one(&exercise_entry, None).await?;
 
-    let exercise_entry = ExerciseEntry {
+    let mut created_entry = exercise_entry;
+    created_entry.id = Some(result.inserted_id.as_object_id().unwrap());
+
+    Ok(created_entry)
+}
+
+/// New entry from validated input: the label comes from the type, and a missing
+/// `load_basis` falls back to the type's default.
+fn build_exercise_entry(input: ExerciseEntryInput, exercise_type: &ExerciseType) -> ExerciseEntry {
+    ExerciseEntry {
         id: None,
         exercise_id: Some(input.exercise_id),
         exercise_label: exercise_type.name.clone(),
@@ -529,18 +792,86 @@ pub async fn create_exercise_entry(
         reps: input.reps,
         weight_value: input.weight_value,
         weight_unit: input.weight_unit,
-        load_basis,
+        load_basis: input.load_basis.or(exercise_type.default_load_basis),
         time_seconds: input.time_seconds,
         distance_meters: input.distance_meters,
-    };
+    }
+}
 
-    let collection = exercise_entries(db);
-    let result = collection.insert_one(&exercise_entry, None).await?;
+/// Most entries accepted by one bulk request.
+pub const BULK_ENTRIES_MAX: usize = 100;
 
-    let mut created_entry = exercise_entry;
-    created_entry.id = Some(result.inserted_id.as_object_id().unwrap());
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkEntryError {
+    /// Position of the failed input in the request array.
+    pub index: usize,
+    pub error: String,
+}
 
-    Ok(created_entry)
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkEntriesResult {
+    /// Created entries, in input order.
+    pub created: Vec<ExerciseEntry>,
+    pub errors: Vec<BulkEntryError>,
+}
+
+/// Create several entries at once. Each input is validated on its own and failures are
+/// reported by index without stopping the rest. Exercise types are fetched in one `$in`
+/// query and the valid entries written with a single `insert_many`.
+pub async fn create_exercise_entries_bulk(
+    db: &Database,
+    inputs: Vec<ExerciseEntryInput>,
+) -> Result<BulkEntriesResult, WorkoutError> {
+    let mut errors = Vec::new();
+    let mut valid = Vec::new();
+    for (index, input) in inputs.into_iter().enumerate() {
+        match validate_exercise_entry_data(&input) {
+            Ok(()) => valid.push((index, input)),
+            Err(e) => errors.push(BulkEntryError {
+                index,
+                error: validation_message(e),
+            }),
+        }
+    }
+
+    let mut type_ids: Vec<ObjectId> = valid.iter().map(|(_, input)| input.exercise_id).collect();
+    type_ids.sort();
+    type_ids.dedup();
+    let types_by_id: HashMap<ObjectId, ExerciseType> = exercise_types(db)
+        .find(doc! { "_id": { "$in": &type_ids } }, None)
+        .await?
+        .try_collect::<Vec<ExerciseType>>()
+        .await?
+        .into_iter()
+        .filter_map(|t| t.id.map(|id| (id, t)))
+        .collect();
+
+    let mut entries = Vec::new();
+    for (index, input) in valid {
+        match types_by_id.get(&input.exercise_id) {
+            Some(exercise_type) => entries.push(build_exercise_entry(input, exercise_type)),
+            None => errors.push(BulkEntryError {
+                index,
+                error: WorkoutError::ExerciseTypeNotFound.to_string(),
+            }),
+        }
+    }
+    errors.sort_by_key(|e| e.index);
+
+    if !entries.is_empty() {
+        let result = exercise_entries(db).insert_many(&entries, None).await?;
+        for (position, entry) in entries.iter_mut().enumerate() {
+            entry.id = result
+                .inserted_ids
+                .get(&position)
+                .and_then(|id| id.as_object_id());
+        }
+    }
+
+    Ok(BulkEntriesResult {
+        created: entries,
+        errors,
+    })
 }
 
 pub async fn update_exercise_entry(
@@ -573,6 +904,7 @@ pub async fn update_exercise_entry(
     let weight_value = patch.weight_value.or(current_entry.weight_value);
     let weight_unit = patch.weight_unit.or(current_entry.weight_unit);
     validate_weight_data(weight_value, weight_unit)?;
+    validate_patched_entry_kind(&current_entry, &patch)?;
 
     // 1) Decide id and label once
     let (new_exercise_id, new_exercise_label, id_changed) =
@@ -669,6 +1001,142 @@ pub async fn update_exercise_entry(
     ))
 }
 
+pub async fn delete_exercise_entry(db: &Database, id: ObjectId) -> Result<bool, WorkoutError> {
+    let result = exercise_entries(db)
+        .delete_one(doc! { "_id": id }, None)
+        .await?;
+    Ok(result.deleted_count > 0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeDeleteOutcome {
+    /// The type was removed; `orphaned_entries` had their `exercise_id` cleared.
+    Deleted {
+        orphaned_entries: u64,
+    },
+    /// Entries still reference the type and `force` was not set.
+    InUse {
+        entries: u64,
+    },
+    NotFound,
+}
+
+/// Delete an exercise type. Referenced types are kept unless `force` is set, in which
+/// case referencing entries are orphaned (`exercise_id = null`) but keep their
+/// `exercise_label`.
+pub async fn delete_exercise_type(
+    db: &Database,
+    id: ObjectId,
+    force: bool,
+) -> Result<TypeDeleteOutcome, WorkoutError> {
+    if exercise_types(db)
+        .find_one(doc! { "_id": id }, None)
+        .await?
+        .is_none()
+    {
+        return Ok(TypeDeleteOutcome::NotFound);
+    }
+
+    let referencing = doc! { "exercise_id": id };
+    let entries = exercise_entries(db)
+        .count_documents(referencing.clone(), None)
+        .await?;
+    if entries > 0 && !force {
+        return Ok(TypeDeleteOutcome::InUse { entries });
+    }
+
+    // Orphan first, so a failure part way never leaves entries pointing at a missing type
+    let orphaned_entries = if entries > 0 {
+        exercise_entries(db)
+            .update_many(
+                referencing,
+                doc! { "$set": { "exercise_id": Bson::Null } },
+                None,
+            )
+            .await?
+            .modified_count
+    } else {
+        0
+    };
+
+    let result = exercise_types(db)
+        .delete_one(doc! { "_id": id }, None)
+        .await?;
+    if result.deleted_count == 0 {
+        return Ok(TypeDeleteOutcome::NotFound);
+    }
+    Ok(TypeDeleteOutcome::Deleted { orphaned_entries })
+}
+
+/// `into`'s aliases followed by `from`'s name and aliases, deduped case-insensitively
+/// and leaving out `into`'s own name.
+fn merged_aliases(into: &ExerciseType, from: &ExerciseType) -> Vec<String> {
+    let candidates = into
+        .aliases
+        .iter()
+        .flatten()
+        .chain(std::iter::once(&from.name))
+        .chain(from.aliases.iter().flatten());
+
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in candidates {
+        let alias = alias.trim();
+        if alias.is_empty()
+            || alias.eq_ignore_ascii_case(&into.name)
+            || aliases.iter().any(|a| a.eq_ignore_ascii_case(alias))
+        {
+            continue;
+        }
+        aliases.push(alias.to_string());
+    }
+    aliases
+}
+
+/// Merge exercise type `from_id` into `into_id`: `from`'s name and aliases become
+/// aliases of `into`, its entries are repointed and relabelled, and `from` is deleted.
+/// Each step is safe to repeat, so a merge interrupted part way can simply be re-run.
+pub async fn merge_exercise_types(
+    db: &Database,
+    from_id: ObjectId,
+    into_id: ObjectId,
+) -> Result<ExerciseTypeMergeResult, WorkoutError> {
+    if from_id == into_id {
+        return Err(WorkoutError::Validation(
+            "from_id and into_id must differ".to_string(),
+        ));
+    }
+    let from = get_exercise_type_by_id(db, from_id).await?;
+    let mut into = get_exercise_type_by_id(db, into_id).await?;
+
+    let aliases = merged_aliases(&into, &from);
+    exercise_types(db)
+        .update_one(
+            doc! { "_id": into_id },
+            doc! { "$set": { "aliases": &aliases } },
+            None,
+        )
+        .await?;
+    into.aliases = Some(aliases);
+
+    let entries_updated = exercise_entries(db)
+        .update_many(
+            doc! { "exercise_id": from_id },
+            doc! { "$set": { "exercise_id": into_id, "exercise_label": &into.name } },
+            None,
+        )
+        .await?
+        .modified_count;
+
+    exercise_types(db)
+        .delete_one(doc! { "_id": from_id }, None)
+        .await?;
+
+    Ok(ExerciseTypeMergeResult {
+        entries_updated,
+        merged: into,
+    })
+}
+
 // Helper function to get exercise type by ID
 async fn get_exercise_type_by_id(
     db: &Database,
@@ -697,20 +1165,8 @@ async fn get_exercise_type_by_id(
 )]
 pub async fn create_exercise_type_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Json(payload): Json<ExerciseTypeInput>,
 ) -> impl axum::response::IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<ExerciseType> = db.collection("exercise_types");
 
@@ -766,21 +1222,9 @@ pub async fn create_exercise_type_mongo(
 )]
 pub async fn update_exercise_type_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Path(id): Path<String>,
     Json(payload): Json<ExerciseTypePatch>,
 ) -> impl axum::response::IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let collection: Collection<ExerciseType> = db.collection("exercise_types");
 
@@ -867,42 +1311,33 @@ pub async fn update_exercise_type_mongo(
 }
 
 #[utoipa::path(
-    get,
-    path = "/workout/exercise-types",
+    post,
+    path = "/workout/exercise-types/merge",
+    request_body = MergeExerciseTypesRequest,
     responses(
-        (status = 200, description = "List of all exercise types", body = Vec<ExerciseType>),
+        (status = 200, description = "Types merged", body = ExerciseTypeMergeResult),
+        (status = 400, description = "from_id and into_id are the same"),
         (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise type not found"),
         (status = 500, description = "Internal server error")
     ),
     security(
         ("ApiKey" = [])
     )
 )]
-pub async fn get_all_exercise_types_mongo(
+pub async fn merge_exercise_types_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
+    Json(payload): Json<MergeExerciseTypesRequest>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        ).into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<ExerciseType>("exercise_types");
-
-    match collection.find(None, None).await {
-        Ok(mut cursor) => {
-            let mut results = Vec::new();
-            while let Some(doc) = cursor.try_next().await.unwrap_or(None) {
-                results.push(doc);
-            }
-            (StatusCode::OK, Json(results)).into_response()
-        }
+    match merge_exercise_types(&db, payload.from_id, payload.into_id).await {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e @ WorkoutError::Validation(_)) => validation_error_response(e),
+        Err(WorkoutError::ExerciseTypeNotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Exercise type not found" })),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": e.to_string() })),
@@ -911,99 +1346,141 @@ pub async fn get_all_exercise_types_mongo(
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteExerciseTypeQuery {
+    #[serde(default)]
+    pub force: bool,
+}
+
 #[utoipa::path(
-    post,
-    path = "/workout/exercise-entries",
-    request_body = ExerciseEntryInput,
+    delete,
+    path = "/workout/exercise-types/{id}",
+    params(
+        ("id" = String, Path, description = "Exercise type ID"),
+        ("force" = Option<bool>, Query, description = "Delete even if entries reference the type; those entries keep their label but lose exercise_id")
+    ),
     responses(
-        (status = 201, description = "Exercise entry created successfully", body = ExerciseEntry),
-        (status = 400, description = "Bad request"),
+        (status = 200, description = "Exercise type deleted"),
+        (status = 400, description = "Invalid ObjectId"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Exercise type not found"),
+        (status = 409, description = "Entries still reference the type"),
         (status = 500, description = "Internal server error")
     ),
     security(
         ("ApiKey" = [])
     )
 )]
-pub async fn create_exercise_entry_mongo(
+pub async fn delete_exercise_type_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
-    Json(payload): Json<ExerciseEntryInput>,
-) -> impl axum::response::IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
+    Path(id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<DeleteExerciseTypeQuery>,
+) -> impl IntoResponse {
+    let object_id = match ObjectId::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid ObjectId" })),
+            )
+                .into_response();
+        }
+    };
 
     let db = state.mongo_client.database("wyat");
-    let exercise_entries_collection: Collection<ExerciseEntry> = db.collection("exercise_entries");
-    let exercise_types_collection: Collection<ExerciseType> = db.collection("exercise_types");
-
-    // Validate date_unix
-    if payload.date_unix < 946684800 || payload.date_unix > 9999999999 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "date_unix must be a 10-digit UTC seconds timestamp" })),
+    match delete_exercise_type(&db, object_id, query.force).await {
+        Ok(TypeDeleteOutcome::Deleted { orphaned_entries }) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "deleted",
+                "id": id,
+                "orphaned_entries": orphaned_entries,
+            })),
         )
-            .into_response();
-    }
-
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-    let min_date = 946684800; // 2000-01-01
-    let max_date = now + 86400; // now + 24h
-
-    if payload.date_unix < min_date || payload.date_unix > max_date {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ 
-                "error": format!("date_unix must be between 2000-01-01 and {} (now+24h)", max_date)
+            .into_response(),
+        Ok(TypeDeleteOutcome::InUse { entries }) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": format!(
+                    "{} exercise entries reference this type; pass force=true to delete it and keep them unlinked",
+                    entries
+                ),
+                "entries": entries,
             })),
         )
-            .into_response();
+            .into_response(),
+        Ok(TypeDeleteOutcome::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Exercise type not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
     }
+}
 
-    // Validate intensity if provided
-    if let Some(intensity) = payload.intensity {
-        if intensity < 1 || intensity > 5 {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "intensity must be between 1 and 5" })),
-            )
-                .into_response();
-        }
-    }
+#[utoipa::path(
+    get,
+    path = "/workout/exercise-types",
+    responses(
+        (status = 200, description = "List of all exercise types", body = Vec<ExerciseType>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn get_all_exercise_types_mongo(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let db = state.mongo_client.database("wyat");
+    let collection = db.collection::<ExerciseType>("exercise_types");
 
-    // Validate weight data
-    if payload.weight_value.is_some() && payload.weight_unit.is_none() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "weight_unit must be provided when weight_value is present" })),
+    match collection.find(None, None).await {
+        Ok(mut cursor) => {
+            let mut results = Vec::new();
+            while let Some(doc) = cursor.try_next().await.unwrap_or(None) {
+                results.push(doc);
+            }
+            (StatusCode::OK, Json(results)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
         )
-            .into_response();
+            .into_response(),
     }
+}
 
-    // Ensure at least some exercise data is provided
-    let has_gym_data = payload.sets.is_some() || payload.reps.is_some() || payload.weight_value.is_some();
-    let has_cardio_data = payload.time_seconds.is_some() || payload.distance_meters.is_some();
+#[utoipa::path(
+    post,
+    path = "/workout/exercise-entries",
+    request_body = ExerciseEntryInput,
+    responses(
+        (status = 201, description = "Exercise entry created successfully", body = ExerciseEntry),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise type not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn create_exercise_entry_mongo(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ExerciseEntryInput>,
+) -> impl axum::response::IntoResponse {
+    let db = state.mongo_client.database("wyat");
+    let exercise_entries_collection: Collection<ExerciseEntry> = db.collection("exercise_entries");
+    let exercise_types_collection: Collection<ExerciseType> = db.collection("exercise_types");
 
-    if !has_gym_data && !has_cardio_data {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ 
-                "error": "Exercise entry must have either gym data (sets/reps/weight) or cardio data (time/distance)" 
-            })),
-        )
-            .into_response();
+    if let Err(e) = validate_exercise_entry_data(&payload) {
+        return validation_error_response(e);
     }
 
     // Verify exercise_id exists and get the exercise type
@@ -1066,6 +1543,55 @@ pub async fn create_exercise_entry_mongo(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/workout/exercise-entries/bulk",
+    request_body = Vec<ExerciseEntryInput>,
+    responses(
+        (status = 201, description = "Every entry created", body = BulkEntriesResult),
+        (status = 200, description = "Some entries created; `errors` lists the failed inputs by index", body = BulkEntriesResult),
+        (status = 400, description = "Empty or oversized request, or no input was valid", body = BulkEntriesResult),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn create_exercise_entries_bulk_mongo(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<Vec<ExerciseEntryInput>>,
+) -> impl IntoResponse {
+    if payload.is_empty() || payload.len() > BULK_ENTRIES_MAX {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("Send between 1 and {} entries", BULK_ENTRIES_MAX)
+            })),
+        )
+            .into_response();
+    }
+
+    let db = state.mongo_client.database("wyat");
+    match create_exercise_entries_bulk(&db, payload).await {
+        Ok(result) => {
+            let status = if result.errors.is_empty() {
+                StatusCode::CREATED
+            } else if result.created.is_empty() {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::OK
+            };
+            (status, Json(result)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
 #[utoipa::path(
     patch,
     path = "/workout/exercise-entries/{id}",
@@ -1086,21 +1612,9 @@ pub async fn create_exercise_entry_mongo(
 )]
 pub async fn update_exercise_entry_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Path(id): Path<String>,
     Json(payload): Json<ExerciseEntryPatch>,
 ) -> impl axum::response::IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        )
-            .into_response();
-    }
-
     let db = state.mongo_client.database("wyat");
     let exercise_entries_collection: Collection<ExerciseEntry> = db.collection("exercise_entries");
     let exercise_types_collection: Collection<ExerciseType> = db.collection("exercise_types");
@@ -1138,54 +1652,17 @@ pub async fn update_exercise_entry_mongo(
         }
     };
 
-    // Validate date if provided
-    if let Some(date_unix) = payload.date_unix {
-        if date_unix < 946684800 || date_unix > 9999999999 {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "date_unix must be a 10-digit UTC seconds timestamp" })),
-            )
-                .into_response();
-        }
-
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let min_date = 946684800; // 2000-01-01
-        let max_date = now + 86400; // now + 24h
-
-        if date_unix < min_date || date_unix > max_date {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ 
-                    "error": format!("date_unix must be between 2000-01-01 and {} (now+24h)", max_date)
-                })),
-            )
-                .into_response();
-        }
-    }
-
-    // Validate intensity if provided
-    if let Some(intensity) = payload.intensity {
-        if intensity < 1 || intensity > 5 {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "intensity must be between 1 and 5" })),
-            )
-                .into_response();
-        }
-    }
-
-    // Validate weight data if provided
+    // Validate patched fields, falling back to the stored entry for weight data
     let weight_value = payload.weight_value.or(current_entry.weight_value);
     let weight_unit = payload.weight_unit.or(current_entry.weight_unit);
-    if weight_value.is_some() && weight_unit.is_none() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "weight_unit must be provided when weight_value is present" })),
-        )
-            .into_response();
+    let validation = payload
+        .date_unix
+        .map_or(Ok(()), validate_date_unix)
+        .and_then(|_| payload.intensity.map_or(Ok(()), validate_intensity))
+        .and_then(|_| validate_weight_data(weight_value, weight_unit))
+        .and_then(|_| validate_patched_entry_kind(&current_entry, &payload));
+    if let Err(e) = validation {
+        return validation_error_response(e);
     }
 
     // Determine new exercise_id and exercise_label
@@ -1334,11 +1811,164 @@ pub async fn update_exercise_entry_mongo(
     }
 }
 
+pub const ENTRIES_PAGE_DEFAULT_LIMIT: i64 = 50;
+pub const ENTRIES_PAGE_MAX_LIMIT: i64 = 200;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExerciseEntryPageQuery {
+    pub limit: Option<i64>,
+    /// Exclusive upper bound on `date_unix`; pass the previous page's `next_before`.
+    pub before_unix: Option<i64>,
+    /// With `before_unix`, resume after this entry: entries at `before_unix` with a
+    /// smaller `_id` are included. Pass the previous page's `next_before_id`.
+    pub before_id: Option<String>,
+    /// Inclusive lower bound on `date_unix`.
+    pub after_unix: Option<i64>,
+    /// `kg` or `lb`: return weights converted to this unit; stored entries are unchanged.
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExerciseEntryPage {
+    /// Newest first.
+    pub entries: Vec<ExerciseEntry>,
+    /// `before_unix` for the next page; null on the last page.
+    pub next_before: Option<i64>,
+    /// `before_id` for the next page; null on the last page.
+    pub next_before_id: Option<String>,
+}
+
+/// One page of entries, newest first, ordered by `(date_unix, _id)`.
+///
+/// The page's last entry is the cursor: `next_before` / `next_before_id` resume right
+/// after it, so entries sharing a timestamp are never skipped, however many there are.
+pub async fn list_exercise_entries_page(
+    db: &Database,
+    query: &ExerciseEntryPageQuery,
+) -> Result<ExerciseEntryPage, WorkoutError> {
+    let limit = query
+        .limit
+        .unwrap_or(ENTRIES_PAGE_DEFAULT_LIMIT)
+        .clamp(1, ENTRIES_PAGE_MAX_LIMIT);
+
+    let before_id = match query.before_id.as_deref() {
+        Some(id) => Some(
+            ObjectId::parse_str(id)
+                .map_err(|_| WorkoutError::Validation(format!("Invalid before_id: {}", id)))?,
+        ),
+        None => None,
+    };
+
+    let mut clauses = Vec::new();
+    match (query.before_unix, before_id) {
+        (Some(before), Some(id)) => clauses.push(doc! { "$or": [
+            { "date_unix": { "$lt": before } },
+            { "date_unix": before, "_id": { "$lt": id } },
+        ] }),
+        (Some(before), None) => clauses.push(doc! { "date_unix": { "$lt": before } }),
+        (None, Some(_)) => {
+            return Err(WorkoutError::Validation(
+                "before_id requires before_unix".to_string(),
+            ));
+        }
+        (None, None) => {}
+    }
+    if let Some(after) = query.after_unix {
+        clauses.push(doc! { "date_unix": { "$gte": after } });
+    }
+    let filter = if clauses.is_empty() {
+        doc! {}
+    } else {
+        doc! { "$and": clauses }
+    };
+
+    // One extra row tells us whether there is a next page and where it starts
+    let options = mongodb::options::FindOptions::builder()
+        .sort(doc! { "date_unix": -1, "_id": -1 })
+        .limit(limit + 1)
+        .build();
+    let mut entries: Vec<ExerciseEntry> = exercise_entries(db)
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+
+    let limit = limit as usize;
+    let has_more = entries.len() > limit;
+    entries.truncate(limit);
+    let cursor = entries.last().filter(|_| has_more);
+    Ok(ExerciseEntryPage {
+        next_before: cursor.map(|e| e.date_unix),
+        next_before_id: cursor.and_then(|e| e.id).map(|id| id.to_hex()),
+        entries,
+    })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/workout/exercise-entries/{id}",
+    params(
+        ("id" = String, Path, description = "Exercise entry ID")
+    ),
+    responses(
+        (status = 200, description = "Exercise entry deleted"),
+        (status = 400, description = "Invalid ObjectId"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise entry not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn delete_exercise_entry_mongo(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let object_id = match ObjectId::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid ObjectId" })),
+            )
+                .into_response();
+        }
+    };
+
+    let db = state.mongo_client.database("wyat");
+    match delete_exercise_entry(&db, object_id).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "deleted", "id": id })),
+        )
+            .into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Exercise entry not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/workout/exercise-entries",
+    params(
+        ("limit" = Option<i64>, Query, description = "Page size (default 50, max 200)"),
+        ("before_unix" = Option<i64>, Query, description = "Only entries before this unix timestamp (exclusive); use the previous page's next_before"),
+        ("before_id" = Option<String>, Query, description = "With before_unix, also include entries at before_unix with a smaller id; use the previous page's next_before_id"),
+        ("after_unix" = Option<i64>, Query, description = "Only entries at or after this unix timestamp"),
+        ("unit" = Option<String>, Query, description = "Convert weights to 'kg' or 'lb' (rounded to 2 decimals); omit to return stored units")
+    ),
     responses(
-        (status = 200, description = "List of all exercise entries", body = Vec<ExerciseEntry>),
+        (status = 200, description = "A page of exercise entries, newest first", body = ExerciseEntryPage),
+        (status = 400, description = "after_unix is not before before_unix, invalid before_id, or unit is not kg or lb"),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -1348,29 +1978,35 @@ pub async fn update_exercise_entry_mongo(
 )]
 pub async fn get_all_exercise_entries_mongo(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<ExerciseEntryPageQuery>,
 ) -> impl IntoResponse {
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
+    if let (Some(after), Some(before)) = (query.after_unix, query.before_unix)
+        && after >= before
+    {
         return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        ).into_response();
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "after_unix must be before before_unix" })),
+        )
+            .into_response();
     }
 
-    let db = state.mongo_client.database("wyat");
-    let collection = db.collection::<ExerciseEntry>("exercise_entries");
+    let unit = match query.unit.as_deref().map(WeightUnit::parse) {
+        None => None,
+        Some(Some(unit)) => Some(unit),
+        Some(None) => return invalid_unit_response(),
+    };
 
-    match collection.find(None, None).await {
-        Ok(mut cursor) => {
-            let mut results = Vec::new();
-            while let Some(doc) = cursor.try_next().await.unwrap_or(None) {
-                results.push(doc);
+    let db = state.mongo_client.database("wyat");
+    match list_exercise_entries_page(&db, &query).await {
+        Ok(mut page) => {
+            if let Some(unit) = unit {
+                page.entries
+                    .iter_mut()
+                    .for_each(|entry| present_weight_in(entry, unit));
             }
-            (StatusCode::OK, Json(results)).into_response()
+            (StatusCode::OK, Json(page)).into_response()
         }
+        Err(e @ WorkoutError::Validation(_)) => validation_error_response(e),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": e.to_string() })),
@@ -1384,11 +2020,12 @@ pub async fn get_all_exercise_entries_mongo(
     path = "/workout/exercise-entries/day/{date_unix}",
     params(
         ("date_unix" = i64, Path, description = "Unix timestamp (any time on the target day)"),
-        ("tz" = Option<String>, Query, description = "IANA timezone, e.g. 'America/New_York'. Defaults to UTC.")
+        ("tz" = Option<String>, Query, description = "IANA timezone, e.g. 'America/New_York'. Defaults to WYAT_DEFAULT_TZ, else UTC."),
+        ("unit" = Option<String>, Query, description = "Convert weights to 'kg' or 'lb' (rounded to 2 decimals); omit to return stored units")
     ),
     responses(
         (status = 200, description = "Exercise entries for the day", body = Vec<ExerciseEntry>),
-        (status = 400, description = "Invalid timestamp or timezone"),
+        (status = 400, description = "Invalid timestamp, timezone or unit"),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -1398,23 +2035,12 @@ pub async fn get_all_exercise_entries_mongo(
 )]
 pub async fn get_exercise_entries_by_day(
     State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
     Path(date_unix): Path<i64>,
     axum::extract::Query(params): axum::extract::Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
     use chrono::{DateTime, Utc, TimeZone};
     use chrono_tz::Tz;
     
-    let expected_key = std::env::var("WYAT_API_KEY").unwrap_or_default();
-    let provided_key = headers.get("x-wyat-api-key").and_then(|v| v.to_str().ok());
-
-    if provided_key != Some(expected_key.as_str()) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            "Unauthorized: missing or invalid API key",
-        ).into_response();
-    }
-
     // Validate the timestamp
     if date_unix < 946684800 || date_unix > 9999999999 {
         return (
@@ -1424,13 +2050,11 @@ pub async fn get_exercise_entries_by_day(
             .into_response();
     }
 
-    // Parse timezone (IANA timezone string, e.g., "America/New_York")
-    let tz_str = params.get("tz").map(|s| s.as_str()).unwrap_or("UTC");
-    
-    // Parse the timezone
-    let tz: Tz = match tz_str.parse() {
+    // Parse timezone (IANA timezone string, e.g., "America/New_York"),
+    // falling back to WYAT_DEFAULT_TZ
+    let tz: Tz = match crate::timezone::resolve_tz(params.get("tz").map(|s| s.as_str())) {
         Ok(tz) => tz,
-        Err(_) => {
+        Err(tz_str) => {
             eprintln!("❌ Invalid timezone: {}", tz_str);
             return (
                 StatusCode::BAD_REQUEST,
@@ -1442,6 +2066,12 @@ pub async fn get_exercise_entries_by_day(
         }
     };
 
+    let unit = match params.get("unit").map(|s| WeightUnit::parse(s)) {
+        None => None,
+        Some(Some(unit)) => Some(unit),
+        Some(None) => return invalid_unit_response(),
+    };
+
     // Convert Unix timestamp to DateTime<Utc>
     let utc_dt = match DateTime::from_timestamp(date_unix, 0) {
         Some(dt) => dt,
@@ -1475,7 +2105,7 @@ pub async fn get_exercise_entries_by_day(
     // Debug logging
     eprintln!("🌍 Timezone query debug:");
     eprintln!("  Input timestamp: {} ({})", date_unix, utc_dt.format("%Y-%m-%d %H:%M:%S UTC"));
-    eprintln!("  Requested timezone: {}", tz_str);
+    eprintln!("  Requested timezone: {}", tz);
     eprintln!("  Local date: {}", local_dt.format("%Y-%m-%d %H:%M:%S %Z"));
     eprintln!("  Local day start: {}", local_day_start.format("%Y-%m-%d %H:%M:%S %Z"));
     eprintln!("  Local day end: {}", local_day_end.format("%Y-%m-%d %H:%M:%S %Z"));
@@ -1500,7 +2130,10 @@ pub async fn get_exercise_entries_by_day(
     match collection.find(filter, None).await {
         Ok(mut cursor) => {
             let mut results = Vec::new();
-            while let Some(doc) = cursor.try_next().await.unwrap_or(None) {
+            while let Some(mut doc) = cursor.try_next().await.unwrap_or(None) {
+                if let Some(unit) = unit {
+                    present_weight_in(&mut doc, unit);
+                }
                 results.push(doc);
             }
             eprintln!("  Found {} entries", results.len());
@@ -1519,12 +2152,579 @@ pub async fn get_exercise_entries_by_day(
 
 
 
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+pub struct MuscleVolume {
+    pub volume_kg: f64,
+    pub entries: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VolumeQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/workout/volume",
+    params(
+        ("from" = Option<i64>, Query, description = "Start unix timestamp (inclusive). Defaults to 30 days ago."),
+        ("to" = Option<i64>, Query, description = "End unix timestamp (exclusive). Defaults to now.")
+    ),
+    responses(
+        (status = 200, description = "Volume (kg) and entry count keyed by muscle", body = HashMap<String, MuscleVolume>),
+        (status = 400, description = "Invalid range"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn get_workout_volume(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<VolumeQuery>,
+) -> impl IntoResponse {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let to = query.to.unwrap_or(now);
+    let from = query.from.unwrap_or(to - 30 * 86400);
+
+    if from >= to {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "'from' must be before 'to'" })),
+        )
+            .into_response();
+    }
+
+    let db = state.mongo_client.database("wyat");
+    match muscle_volume(&db, from, Some(to)).await {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+pub const FREQUENCY_DEFAULT_WEEKS: u32 = 4;
+pub const FREQUENCY_MAX_WEEKS: u32 = 52;
+
+#[derive(Debug, Deserialize)]
+pub struct FrequencyQuery {
+    pub weeks: Option<u32>,
+    /// Timezone that decides which week is current (entries use their own `tz`).
+    pub tz: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WeeklyFrequency {
+    /// ISO week, e.g. "2025-W02"
+    pub week: String,
+    /// Monday of the ISO week (YYYY-MM-DD)
+    pub week_start: String,
+    /// Distinct local days with at least one entry working the region; every region is present.
+    pub days: HashMap<Region, u32>,
+}
+
+/// Calendar day an entry was logged on, in the entry's own timezone (UTC when unset or invalid).
+fn entry_local_date(entry: &ExerciseEntry) -> Option<chrono::NaiveDate> {
+    let utc = chrono::DateTime::from_timestamp(entry.date_unix, 0)?;
+    let tz = entry
+        .tz
+        .as_deref()
+        .and_then(|name| name.parse::<chrono_tz::Tz>().ok())
+        .unwrap_or(chrono_tz::UTC);
+    Some(utc.with_timezone(&tz).date_naive())
+}
+
+fn iso_week_monday(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    use chrono::Datelike;
+    date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Training days per region for each week starting on one of `mondays` (oldest first).
+/// A day counts once per region however many entries hit it; an entry whose type spans
+/// regions counts toward each of them.
+fn weekly_region_frequency(
+    pairs: &[(ExerciseEntry, ExerciseType)],
+    mondays: &[chrono::NaiveDate],
+) -> Vec<WeeklyFrequency> {
+    use chrono::Datelike;
+    use std::collections::HashSet;
+
+    let mut days: HashMap<(chrono::NaiveDate, Region), HashSet<chrono::NaiveDate>> = HashMap::new();
+    for (entry, exercise_type) in pairs {
+        let Some(local_day) = entry_local_date(entry) else {
+            continue;
+        };
+        let monday = iso_week_monday(local_day);
+        if !mondays.contains(&monday) {
+            continue;
+        }
+        for muscle in &exercise_type.primary_muscles {
+            days.entry((monday, muscle.region()))
+                .or_default()
+                .insert(local_day);
+        }
+    }
+
+    mondays
+        .iter()
+        .map(|monday| {
+            let week = monday.iso_week();
+            let counts = [Region::UpperBody, Region::LowerBody, Region::Core]
+                .into_iter()
+                .map(|region| {
+                    let n = days.get(&(*monday, region)).map_or(0, |d| d.len() as u32);
+                    (region, n)
+                })
+                .collect();
+            WeeklyFrequency {
+                week: format!("{}-W{:02}", week.year(), week.week()),
+                week_start: monday.format("%Y-%m-%d").to_string(),
+                days: counts,
+            }
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/workout/frequency",
+    params(
+        ("weeks" = Option<u32>, Query, description = "Number of ISO weeks ending with the current one (default 4, max 52)"),
+        ("tz" = Option<String>, Query, description = "IANA timezone deciding the current week. Defaults to WYAT_DEFAULT_TZ, else UTC.")
+    ),
+    responses(
+        (status = 200, description = "Training days per region for each ISO week, oldest first", body = Vec<WeeklyFrequency>),
+        (status = 400, description = "Invalid weeks or timezone"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn get_workout_frequency(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<FrequencyQuery>,
+) -> impl IntoResponse {
+    let weeks = query.weeks.unwrap_or(FREQUENCY_DEFAULT_WEEKS);
+    if weeks == 0 || weeks > FREQUENCY_MAX_WEEKS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("weeks must be between 1 and {}", FREQUENCY_MAX_WEEKS)
+            })),
+        )
+            .into_response();
+    }
+    let tz = match crate::timezone::resolve_tz(query.tz.as_deref()) {
+        Ok(tz) => tz,
+        Err(tz_str) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": format!("Invalid timezone: {}", tz_str) })),
+            )
+                .into_response();
+        }
+    };
+
+    let now = chrono::Utc::now();
+    let this_monday = iso_week_monday(now.with_timezone(&tz).date_naive());
+    let mondays: Vec<chrono::NaiveDate> = (0..weeks as i64)
+        .rev()
+        .map(|back| this_monday - chrono::Duration::weeks(back))
+        .collect();
+
+    // Entries carry their own timezones, so widen the range by a day on each side and
+    // let the local-day bucketing drop anything outside the window
+    let since = mondays[0]
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc()
+        .timestamp()
+        - 86400;
+    let until = now.timestamp() + 86400;
+
+    let db = state.mongo_client.database("wyat");
+    match load_entries_with_types(&db, since, Some(until)).await {
+        Ok(pairs) => (
+            StatusCode::OK,
+            Json(weekly_region_frequency(&pairs, &mondays)),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Estimated one-rep max in kg using the Epley formula: weight × (1 + reps / 30).
+/// The load is normalized to a total in kg (`PerSide` counts both sides).
+/// Returns None for entries without a positive weight and rep count.
+pub fn estimated_one_rep_max_kg(
+    entry: &ExerciseEntry,
+    default_basis: Option<LoadBasis>,
+) -> Option<f64> {
+    let reps = entry.reps.filter(|r| *r > 0)? as f64;
+    let weight = weight_in_kg(entry.weight_value?, entry.weight_unit?);
+    if weight <= 0.0 {
+        return None;
+    }
+    let total = match entry.load_basis.or(default_basis) {
+        Some(LoadBasis::PerSide) => weight * 2.0,
+        Some(LoadBasis::Total) | None => weight,
+    };
+    Some(total * (1.0 + reps / 30.0))
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PersonalRecord {
+    #[schema(value_type = Option<String>)]
+    pub entry_id: Option<ObjectId>,
+    pub date_unix: i64,
+    pub estimated_1rm_kg: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExercisePersonalRecords {
+    #[schema(value_type = String)]
+    pub exercise_id: ObjectId,
+    pub exercise_name: String,
+    pub best: Option<PersonalRecord>,
+    /// Every entry that beat the previous best, oldest first.
+    pub history: Vec<PersonalRecord>,
+}
+
+/// Walk entries chronologically and keep each one that sets a new best 1RM.
+fn personal_record_history(
+    entries: &[ExerciseEntry],
+    default_basis: Option<LoadBasis>,
+) -> Vec<PersonalRecord> {
+    let mut sorted: Vec<&ExerciseEntry> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.date_unix);
+
+    let mut history: Vec<PersonalRecord> = Vec::new();
+    for entry in sorted {
+        let Some(one_rm) = estimated_one_rep_max_kg(entry, default_basis) else {
+            continue;
+        };
+        let is_pr = history
+            .last()
+            .is_none_or(|best| one_rm > best.estimated_1rm_kg);
+        if is_pr {
+            history.push(PersonalRecord {
+                entry_id: entry.id,
+                date_unix: entry.date_unix,
+                estimated_1rm_kg: one_rm,
+            });
+        }
+    }
+    history
+}
+
+#[utoipa::path(
+    get,
+    path = "/workout/exercise-types/{id}/prs",
+    params(
+        ("id" = String, Path, description = "Exercise type ObjectId")
+    ),
+    responses(
+        (status = 200, description = "Best estimated 1RM and PR history", body = ExercisePersonalRecords),
+        (status = 400, description = "Invalid ObjectId"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise type not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("ApiKey" = [])
+    )
+)]
+pub async fn get_exercise_type_prs(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let object_id = match ObjectId::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid ObjectId" })),
+            )
+                .into_response();
+        }
+    };
+
+    let db = state.mongo_client.database("wyat");
+    let exercise_type = match get_exercise_type_by_id(&db, object_id).await {
+        Ok(t) => t,
+        Err(WorkoutError::ExerciseTypeNotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Exercise type not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let entries: Vec<ExerciseEntry> = match exercise_entries(&db)
+        .find(doc! { "exercise_id": object_id }, None)
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": e.to_string() })),
+                )
+                    .into_response();
+            }
+        },
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let history = personal_record_history(&entries, exercise_type.default_load_basis);
+    let result = ExercisePersonalRecords {
+        exercise_id: object_id,
+        exercise_name: exercise_type.name,
+        best: history.last().cloned(),
+        history,
+    };
+    (StatusCode::OK, Json(result)).into_response()
+}
+
 // Test outline and example test cases
 #[cfg(test)]
 mod tests {
     use super::*;
     use mongodb::Client;
 
+    fn gym_entry(
+        sets: u16,
+        reps: u16,
+        weight: f32,
+        unit: WeightUnit,
+        basis: Option<LoadBasis>,
+    ) -> ExerciseEntry {
+        ExerciseEntry {
+            id: Some(ObjectId::new()),
+            exercise_id: Some(ObjectId::new()),
+            exercise_label: "Test".to_string(),
+            date_unix: 1609459200,
+            intensity: Some(3),
+            notes: None,
+            tz: None,
+            sets: Some(sets),
+            reps: Some(reps),
+            weight_value: Some(weight),
+            weight_unit: Some(unit),
+            load_basis: basis,
+            time_seconds: None,
+            distance_meters: None,
+        }
+    }
+
+    fn exercise_type(name: &str, muscles: Vec<Muscle>) -> ExerciseType {
+        ExerciseType {
+            id: Some(ObjectId::new()),
+            name: name.to_string(),
+            aliases: None,
+            primary_muscles: muscles,
+            guidance: None,
+            default_load_basis: None,
+        }
+    }
+
+    #[test]
+    fn test_volume_doubles_per_side_and_converts_lb() {
+        // 3 x 10 @ 20kg per side dumbbell press => 1200kg
+        let per_side = gym_entry(3, 10, 20.0, WeightUnit::Kg, Some(LoadBasis::PerSide));
+        // 2 x 5 @ 100lb total squat => 10 * 45.359237 = 453.59237kg
+        let total = gym_entry(2, 5, 100.0, WeightUnit::Lb, Some(LoadBasis::Total));
+
+        let press = exercise_type("Dumbbell Press", vec![Muscle::Chest, Muscle::Triceps]);
+        let squat = exercise_type("Squat", vec![Muscle::Quads, Muscle::Glutes]);
+        let pairs = vec![(per_side, press), (total, squat)];
+
+        let volume = tally_muscle_volume(&pairs);
+        assert!((volume[&Muscle::Chest] - 1200.0).abs() < 1e-6);
+        assert!((volume[&Muscle::Triceps] - 1200.0).abs() < 1e-6);
+        assert!((volume[&Muscle::Quads] - 453.59237).abs() < 1e-3);
+        assert!(!volume.contains_key(&Muscle::Back));
+
+        let counts = tally_muscle_counts(&pairs);
+        assert_eq!(counts[&Muscle::Chest], 1);
+        assert_eq!(counts[&Muscle::Glutes], 1);
+    }
+
+    #[test]
+    fn test_weight_conversion_rounds_to_hundredths() {
+        assert_eq!(convert_weight(100.0, WeightUnit::Lb, WeightUnit::Kg), 45.36);
+        assert_eq!(convert_weight(20.0, WeightUnit::Kg, WeightUnit::Lb), 44.09);
+        assert_eq!(convert_weight(45.36, WeightUnit::Kg, WeightUnit::Lb), 100.0);
+        // Same unit is passed through without rounding
+        let kg = WeightUnit::Kg;
+        assert_eq!(convert_weight(22.675, kg, kg), 22.675);
+
+        let mut entry = gym_entry(3, 5, 225.0, WeightUnit::Lb, None);
+        present_weight_in(&mut entry, WeightUnit::Kg);
+        assert_eq!(entry.weight_value, Some(102.06));
+        assert_eq!(entry.weight_unit, Some(WeightUnit::Kg));
+
+        let mut cardio = gym_entry(1, 1, 0.0, WeightUnit::Kg, None);
+        cardio.weight_value = None;
+        cardio.weight_unit = None;
+        present_weight_in(&mut cardio, WeightUnit::Lb);
+        assert_eq!(cardio.weight_unit, None);
+
+        assert_eq!(WeightUnit::parse("LB"), Some(WeightUnit::Lb));
+        assert_eq!(WeightUnit::parse("stone"), None);
+    }
+
+    #[test]
+    fn test_volume_ignores_cardio_entries() {
+        let mut run = gym_entry(1, 1, 1.0, WeightUnit::Kg, None);
+        run.reps = None;
+        run.weight_value = None;
+        run.time_seconds = Some(1800);
+        assert_eq!(entry_volume_kg(&run, None), None);
+    }
+
+    #[test]
+    fn test_muscle_counts_one_per_muscle_per_entry() {
+        let row = exercise_type("Barbell Row", vec![Muscle::Back, Muscle::Biceps]);
+        let curl = exercise_type("Curl", vec![Muscle::Biceps]);
+        let pairs = vec![
+            (gym_entry(3, 8, 60.0, WeightUnit::Kg, None), row.clone()),
+            (gym_entry(3, 8, 60.0, WeightUnit::Kg, None), row),
+            (gym_entry(3, 12, 15.0, WeightUnit::Kg, None), curl),
+        ];
+
+        let counts = tally_muscle_counts(&pairs);
+        assert_eq!(counts[&Muscle::Back], 2);
+        assert_eq!(counts[&Muscle::Biceps], 3);
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn test_pr_history_normalizes_units_and_basis() {
+        let mut first = gym_entry(3, 5, 100.0, WeightUnit::Kg, Some(LoadBasis::Total));
+        first.date_unix = 1_700_000_000;
+        // 200lb total at 5 reps ≈ 90.7kg: not a PR
+        let mut lighter = gym_entry(3, 5, 200.0, WeightUnit::Lb, Some(LoadBasis::Total));
+        lighter.date_unix = 1_700_100_000;
+        // 55kg per side at 5 reps = 110kg total: new PR
+        let mut per_side = gym_entry(3, 5, 55.0, WeightUnit::Kg, Some(LoadBasis::PerSide));
+        per_side.date_unix = 1_700_200_000;
+        let mut run = gym_entry(1, 1, 1.0, WeightUnit::Kg, None);
+        run.reps = None;
+        run.weight_value = None;
+        run.date_unix = 1_700_300_000;
+
+        // Out of order on purpose; history is chronological
+        let entries = vec![per_side.clone(), run, lighter, first.clone()];
+        let history = personal_record_history(&entries, None);
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].entry_id, first.id);
+        assert!((history[0].estimated_1rm_kg - 100.0 * (1.0 + 5.0 / 30.0)).abs() < 1e-9);
+        assert_eq!(history[1].entry_id, per_side.id);
+        assert!((history[1].estimated_1rm_kg - 110.0 * (1.0 + 5.0 / 30.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weekly_frequency_buckets_by_each_entry_local_day() {
+        let squat = exercise_type("Squat", vec![Muscle::Quads, Muscle::Glutes]);
+        let bench = exercise_type("Bench Press", vec![Muscle::Chest, Muscle::Triceps]);
+        let plank = exercise_type("Plank", vec![Muscle::Abdominals]);
+        let at = |date_unix: i64, tz: Option<&str>| {
+            let mut entry = gym_entry(3, 5, 100.0, WeightUnit::Kg, None);
+            entry.date_unix = date_unix;
+            entry.tz = tz.map(str::to_string);
+            entry
+        };
+
+        // 2025-01-06 04:30 UTC: Sunday night in New York (W01), Monday in UTC and Hong Kong (W02)
+        let boundary = 1_736_137_800;
+        let pairs = vec![
+            (at(boundary, Some("America/New_York")), squat.clone()),
+            (at(boundary, None), squat),
+            (at(boundary, Some("Asia/Hong_Kong")), plank),
+            // Two sessions on Friday 2025-01-03 in New York count as one day
+            (at(1_735_916_400, Some("America/New_York")), bench.clone()),
+            (at(1_735_927_200, Some("America/New_York")), bench),
+        ];
+        let mondays = [
+            chrono::NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(),
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+        ];
+
+        let weeks = weekly_region_frequency(&pairs, &mondays);
+        assert_eq!(weeks[0].week, "2025-W01");
+        assert_eq!(weeks[0].week_start, "2024-12-30");
+        assert_eq!(weeks[0].days[&Region::UpperBody], 1);
+        assert_eq!(weeks[0].days[&Region::LowerBody], 1);
+        assert_eq!(weeks[0].days[&Region::Core], 0);
+
+        assert_eq!(weeks[1].week, "2025-W02");
+        assert_eq!(weeks[1].days[&Region::UpperBody], 0);
+        assert_eq!(weeks[1].days[&Region::LowerBody], 1);
+        assert_eq!(weeks[1].days[&Region::Core], 1);
+    }
+
+    #[test]
+    fn test_expand_muscle_terms_resolves_legs_alias() {
+        let muscles = expand_muscle_terms(&["legs".to_string()]);
+        assert_eq!(muscles, vec!["glutes", "quads", "hamstrings", "calves"]);
+    }
+
+    #[test]
+    fn test_expand_muscle_terms_dedups_and_passes_literals_through() {
+        let terms = vec![
+            "Arms".to_string(),
+            "biceps".to_string(),
+            "core".to_string(),
+            "upper_body".to_string(),
+        ];
+        let muscles = expand_muscle_terms(&terms);
+        assert_eq!(
+            muscles,
+            vec![
+                "biceps",
+                "triceps",
+                "forearms",
+                "abdominals",
+                "obliques",
+                "spinal_erectors",
+                "chest",
+                "back",
+                "shoulders",
+            ]
+        );
+    }
+
     async fn setup_test_db() -> Database {
         let client = Client::with_uri_str("mongodb://localhost:27017")
             .await
@@ -1738,4 +2938,611 @@ mod tests {
         assert_eq!(entry.sets, None);
         assert_eq!(entry.weight_value, None);
     }
+
+    #[tokio::test]
+    async fn test_recent_muscle_counts_tallies_entries_across_types() {
+        let db = setup_test_db().await;
+        // Far-future window so entries from other tests are excluded
+        let since = 9_000_000_000;
+        exercise_entries(&db)
+            .delete_many(doc! { "date_unix": { "$gte": since } }, None)
+            .await
+            .unwrap();
+
+        let suffix = ObjectId::new().to_hex();
+        let row = create_exercise_type(
+            &db,
+            ExerciseTypeInput {
+                name: format!("Barbell Row {}", suffix),
+                aliases: None,
+                primary_muscles: vec![Muscle::Back, Muscle::Biceps],
+                guidance: None,
+                default_load_basis: None,
+            },
+        )
+        .await
+        .unwrap();
+        let curl = create_exercise_type(
+            &db,
+            ExerciseTypeInput {
+                name: format!("Curl {}", suffix),
+                aliases: None,
+                primary_muscles: vec![Muscle::Biceps],
+                guidance: None,
+                default_load_basis: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        for (offset, exercise_type) in [(0, &row), (60, &row), (120, &curl)] {
+            let input = ExerciseEntryInput {
+                exercise_id: exercise_type.id.unwrap(),
+                date_unix: since + offset,
+                intensity: Some(3),
+                notes: None,
+                tz: Some("UTC".to_string()),
+                sets: Some(3),
+                reps: Some(10),
+                weight_value: Some(20.0),
+                weight_unit: Some(WeightUnit::Kg),
+                load_basis: None,
+                time_seconds: None,
+                distance_meters: None,
+            };
+            create_exercise_entry(&db, input).await.unwrap();
+        }
+
+        let counts = recent_muscle_counts(&db, since).await.unwrap();
+        assert_eq!(counts.get(&Muscle::Back), Some(&2));
+        assert_eq!(counts.get(&Muscle::Biceps), Some(&3));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_muscle_expands_legs_alias() {
+        let db = setup_test_db().await;
+        let suffix = ObjectId::new().to_hex();
+
+        let mut leg_ids = Vec::new();
+        for (name, muscle) in [
+            ("Leg Extension", Muscle::Quads),
+            ("Leg Curl", Muscle::Hamstrings),
+            ("Hip Thrust", Muscle::Glutes),
+            ("Calf Raise", Muscle::Calves),
+            ("Lateral Raise", Muscle::Shoulders),
+        ] {
+            let created = create_exercise_type(
+                &db,
+                ExerciseTypeInput {
+                    name: format!("{} {}", name, suffix),
+                    aliases: None,
+                    primary_muscles: vec![muscle],
+                    guidance: None,
+                    default_load_basis: None,
+                },
+            )
+            .await
+            .unwrap();
+            if muscle.region() == Region::LowerBody {
+                leg_ids.push(created.id.unwrap());
+            }
+        }
+
+        let found = find_exercise_types_by_muscles(&db, &["legs".to_string()])
+            .await
+            .unwrap();
+        let found_ids: Vec<ObjectId> = found
+            .iter()
+            .filter(|t| t.name.ends_with(&suffix))
+            .filter_map(|t| t.id)
+            .collect();
+
+        assert_eq!(found_ids.len(), 4);
+        assert!(leg_ids.iter().all(|id| found_ids.contains(id)));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_reports_failures_and_inserts_the_rest() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_workout_{}", ObjectId::new().to_hex()));
+
+        let press = create_exercise_type(
+            &db,
+            ExerciseTypeInput {
+                name: "Dumbbell Press".to_string(),
+                aliases: None,
+                primary_muscles: vec![Muscle::Chest],
+                guidance: None,
+                default_load_basis: Some(LoadBasis::PerSide),
+            },
+        )
+        .await
+        .unwrap();
+        let run = create_exercise_type(
+            &db,
+            ExerciseTypeInput {
+                name: "Run".to_string(),
+                aliases: None,
+                primary_muscles: vec![],
+                guidance: None,
+                default_load_basis: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let set = ExerciseEntryInput {
+            exercise_id: press.id.unwrap(),
+            date_unix: 1609459200,
+            intensity: Some(3),
+            notes: None,
+            tz: None,
+            sets: Some(3),
+            reps: Some(10),
+            weight_value: Some(20.0),
+            weight_unit: Some(WeightUnit::Kg),
+            load_basis: None,
+            time_seconds: None,
+            distance_meters: None,
+        };
+        let inputs = vec![
+            set.clone(),
+            // Invalid: sets without reps
+            ExerciseEntryInput {
+                reps: None,
+                ..set.clone()
+            },
+            ExerciseEntryInput {
+                sets: None,
+                reps: None,
+                weight_value: None,
+                weight_unit: None,
+                exercise_id: run.id.unwrap(),
+                time_seconds: Some(1800),
+                ..set.clone()
+            },
+            // Unknown exercise type
+            ExerciseEntryInput {
+                exercise_id: ObjectId::new(),
+                ..set.clone()
+            },
+            ExerciseEntryInput {
+                weight_value: Some(22.5),
+                ..set
+            },
+        ];
+
+        let result = create_exercise_entries_bulk(&db, inputs).await.unwrap();
+
+        let failed: Vec<usize> = result.errors.iter().map(|e| e.index).collect();
+        assert_eq!(failed, vec![1, 3]);
+        assert!(result.errors[0].error.contains("sets and reps"));
+        assert_eq!(result.errors[1].error, "Exercise type not found");
+
+        assert_eq!(result.created.len(), 3);
+        assert!(result.created.iter().all(|e| e.id.is_some()));
+        let labels: Vec<&str> = result
+            .created
+            .iter()
+            .map(|e| e.exercise_label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Dumbbell Press", "Run", "Dumbbell Press"]);
+        assert_eq!(result.created[0].load_basis, Some(LoadBasis::PerSide));
+        assert_eq!(result.created[1].load_basis, None);
+
+        let stored = exercise_entries(&db)
+            .count_documents(None, None)
+            .await
+            .unwrap();
+        assert_eq!(stored, 3);
+
+        db.drop(None).await.unwrap();
+    }
+
+    async fn type_with_one_entry(db: &Database) -> (ObjectId, ObjectId) {
+        let squat = create_exercise_type(
+            db,
+            ExerciseTypeInput {
+                name: "Squat".to_string(),
+                aliases: None,
+                primary_muscles: vec![Muscle::Quads],
+                guidance: None,
+                default_load_basis: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut entry = gym_entry(5, 5, 100.0, WeightUnit::Kg, None);
+        entry.id = None;
+        entry.exercise_id = squat.id;
+        entry.exercise_label = "Squat".to_string();
+        let inserted = exercise_entries(db).insert_one(&entry, None).await.unwrap();
+        (
+            squat.id.unwrap(),
+            inserted.inserted_id.as_object_id().unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_deleting_referenced_type_is_blocked_without_force() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_workout_{}", ObjectId::new().to_hex()));
+        let (type_id, entry_id) = type_with_one_entry(&db).await;
+
+        let outcome = delete_exercise_type(&db, type_id, false).await.unwrap();
+        assert_eq!(outcome, TypeDeleteOutcome::InUse { entries: 1 });
+        assert!(get_exercise_type_by_id(&db, type_id).await.is_ok());
+
+        // Once the entry is gone the type can be deleted
+        assert!(delete_exercise_entry(&db, entry_id).await.unwrap());
+        assert!(!delete_exercise_entry(&db, entry_id).await.unwrap());
+        let outcome = delete_exercise_type(&db, type_id, false).await.unwrap();
+        assert_eq!(
+            outcome,
+            TypeDeleteOutcome::Deleted {
+                orphaned_entries: 0
+            }
+        );
+        assert_eq!(
+            delete_exercise_type(&db, type_id, false).await.unwrap(),
+            TypeDeleteOutcome::NotFound
+        );
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_forced_type_delete_orphans_entries_and_keeps_labels() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_workout_{}", ObjectId::new().to_hex()));
+        let (type_id, entry_id) = type_with_one_entry(&db).await;
+
+        let outcome = delete_exercise_type(&db, type_id, true).await.unwrap();
+        assert_eq!(
+            outcome,
+            TypeDeleteOutcome::Deleted {
+                orphaned_entries: 1
+            }
+        );
+        assert!(matches!(
+            get_exercise_type_by_id(&db, type_id).await,
+            Err(WorkoutError::ExerciseTypeNotFound)
+        ));
+
+        let entry = exercise_entries(&db)
+            .find_one(doc! { "_id": entry_id }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.exercise_id, None);
+        assert_eq!(entry.exercise_label, "Squat");
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_merge_types_repoints_entries_and_keeps_names_as_aliases() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_workout_{}", ObjectId::new().to_hex()));
+
+        let new_type = |name: &str, aliases: Option<Vec<&str>>| ExerciseTypeInput {
+            name: name.to_string(),
+            aliases: aliases.map(|a| a.into_iter().map(str::to_string).collect()),
+            primary_muscles: vec![Muscle::Chest],
+            guidance: None,
+            default_load_basis: None,
+        };
+        let from = new_type("Bench Press", Some(vec!["bench", "BB Bench"]));
+        let from = create_exercise_type(&db, from).await.unwrap();
+        let into = new_type("Barbell Bench Press", Some(vec!["bb bench"]));
+        let into = create_exercise_type(&db, into).await.unwrap();
+        let (from_id, into_id) = (from.id.unwrap(), into.id.unwrap());
+
+        for (exercise_id, label) in [
+            (from_id, "Bench Press"),
+            (from_id, "Bench Press"),
+            (into_id, "Barbell Bench Press"),
+        ] {
+            let mut entry = gym_entry(3, 8, 80.0, WeightUnit::Kg, None);
+            entry.id = None;
+            entry.exercise_id = Some(exercise_id);
+            entry.exercise_label = label.to_string();
+            exercise_entries(&db)
+                .insert_one(&entry, None)
+                .await
+                .unwrap();
+        }
+
+        let result = merge_exercise_types(&db, from_id, into_id).await.unwrap();
+        assert_eq!(result.entries_updated, 2);
+        assert_eq!(
+            result.merged.aliases,
+            Some(vec![
+                "bb bench".to_string(),
+                "Bench Press".to_string(),
+                "bench".to_string(),
+            ])
+        );
+
+        let entries: Vec<ExerciseEntry> = exercise_entries(&db)
+            .find(None, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.exercise_id == Some(into_id)));
+        assert!(entries.iter().all(|e| e.exercise_label == into.name));
+
+        assert!(matches!(
+            get_exercise_type_by_id(&db, from_id).await,
+            Err(WorkoutError::ExerciseTypeNotFound)
+        ));
+        assert!(matches!(
+            merge_exercise_types(&db, into_id, into_id).await,
+            Err(WorkoutError::Validation(_))
+        ));
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entry_pages_walk_newest_first_without_gaps() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_workout_{}", ObjectId::new().to_hex()));
+
+        // Seven entries, two sharing a timestamp that straddles the second page boundary
+        let stamps = [
+            1_700_000_700,
+            1_700_000_600,
+            1_700_000_500,
+            1_700_000_500,
+            1_700_000_300,
+            1_700_000_200,
+            1_700_000_100,
+        ];
+        for date_unix in stamps {
+            let mut entry = gym_entry(3, 10, 50.0, WeightUnit::Kg, None);
+            entry.id = None;
+            entry.date_unix = date_unix;
+            exercise_entries(&db)
+                .insert_one(&entry, None)
+                .await
+                .unwrap();
+        }
+
+        let mut query = ExerciseEntryPageQuery {
+            limit: Some(3),
+            ..Default::default()
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = list_exercise_entries_page(&db, &query).await.unwrap();
+            pages.push(page.entries.iter().map(|e| e.date_unix).collect::<Vec<_>>());
+            match page.next_before {
+                Some(next) => {
+                    query.before_unix = Some(next);
+                    query.before_id = page.next_before_id;
+                }
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            pages,
+            vec![
+                vec![1_700_000_700, 1_700_000_600, 1_700_000_500],
+                vec![1_700_000_500, 1_700_000_300, 1_700_000_200],
+                vec![1_700_000_100],
+            ]
+        );
+
+        // after_unix bounds the range from below
+        let bounded = list_exercise_entries_page(
+            &db,
+            &ExerciseEntryPageQuery {
+                limit: None,
+                before_unix: Some(1_700_000_600),
+                after_unix: Some(1_700_000_300),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(bounded.entries.len(), 3);
+        assert_eq!(bounded.next_before, None);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entry_pages_do_not_skip_a_timestamp_larger_than_a_page() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let db = client.database(&format!("test_workout_{}", ObjectId::new().to_hex()));
+
+        // limit + 1 entries logged at the same instant, e.g. one bulk session
+        let limit = 3;
+        for _ in 0..=limit {
+            let mut entry = gym_entry(3, 10, 50.0, WeightUnit::Kg, None);
+            entry.id = None;
+            entry.date_unix = 1_700_000_000;
+            exercise_entries(&db)
+                .insert_one(&entry, None)
+                .await
+                .unwrap();
+        }
+
+        let mut query = ExerciseEntryPageQuery {
+            limit: Some(limit),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = list_exercise_entries_page(&db, &query).await.unwrap();
+            seen.extend(page.entries.iter().map(|e| e.id.unwrap()));
+            match page.next_before {
+                Some(next) => {
+                    query.before_unix = Some(next);
+                    query.before_id = page.next_before_id;
+                }
+                None => break,
+            }
+        }
+
+        let unique: std::collections::HashSet<_> = seen.iter().collect();
+        assert_eq!(seen.len(), limit as usize + 1);
+        assert_eq!(unique.len(), seen.len());
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_entry_validator_rejects_bad_intensity_and_missing_data() {
+        let valid = ExerciseEntryInput {
+            exercise_id: ObjectId::new(),
+            date_unix: 1609459200,
+            intensity: Some(3),
+            notes: None,
+            tz: None,
+            sets: Some(3),
+            reps: Some(10),
+            weight_value: None,
+            weight_unit: None,
+            load_basis: None,
+            time_seconds: None,
+            distance_meters: None,
+        };
+        assert!(validate_exercise_entry_data(&valid).is_ok());
+
+        let bad_intensity = ExerciseEntryInput {
+            intensity: Some(6),
+            ..valid.clone()
+        };
+        assert!(matches!(
+            validate_exercise_entry_data(&bad_intensity),
+            Err(WorkoutError::Validation(msg)) if msg.contains("intensity")
+        ));
+
+        let no_data = ExerciseEntryInput {
+            sets: None,
+            reps: None,
+            ..valid.clone()
+        };
+        assert!(validate_exercise_entry_data(&no_data).is_err());
+
+        let unitless_weight = ExerciseEntryInput {
+            weight_value: Some(20.0),
+            ..valid
+        };
+        assert!(validate_exercise_entry_data(&unitless_weight).is_err());
+    }
+
+    fn rejection(input: &ExerciseEntryInput) -> String {
+        match validate_exercise_entry_data(input) {
+            Err(WorkoutError::Validation(msg)) => msg,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_entry_validator_rejects_incomplete_and_mixed_entries() {
+        let gym = ExerciseEntryInput {
+            exercise_id: ObjectId::new(),
+            date_unix: 1609459200,
+            intensity: None,
+            notes: None,
+            tz: None,
+            sets: Some(3),
+            reps: Some(10),
+            weight_value: Some(60.0),
+            weight_unit: Some(WeightUnit::Kg),
+            load_basis: None,
+            time_seconds: None,
+            distance_meters: None,
+        };
+        let cardio = ExerciseEntryInput {
+            sets: None,
+            reps: None,
+            weight_value: None,
+            weight_unit: None,
+            distance_meters: Some(5000),
+            ..gym.clone()
+        };
+        assert!(validate_exercise_entry_data(&gym).is_ok());
+        assert!(validate_exercise_entry_data(&cardio).is_ok());
+
+        let sets_only = ExerciseEntryInput {
+            reps: None,
+            ..gym.clone()
+        };
+        assert!(rejection(&sets_only).contains("sets and reps"));
+
+        let reps_only = ExerciseEntryInput {
+            sets: None,
+            ..gym.clone()
+        };
+        assert!(rejection(&reps_only).contains("sets and reps"));
+
+        let weight_only = ExerciseEntryInput {
+            sets: None,
+            reps: None,
+            ..gym.clone()
+        };
+        assert!(rejection(&weight_only).contains("sets and reps"));
+
+        let gym_with_time = ExerciseEntryInput {
+            time_seconds: Some(600),
+            ..gym.clone()
+        };
+        assert!(rejection(&gym_with_time).contains("cannot mix"));
+
+        let cardio_with_sets = ExerciseEntryInput {
+            sets: Some(1),
+            ..cardio
+        };
+        assert!(rejection(&cardio_with_sets).contains("cannot mix"));
+    }
+
+    #[test]
+    fn test_patch_cannot_turn_gym_entry_into_mixed_entry() {
+        let current = gym_entry(3, 10, 60.0, WeightUnit::Kg, None);
+        let patch = ExerciseEntryPatch {
+            exercise_id: None,
+            date_unix: None,
+            intensity: None,
+            notes: Some("felt good".to_string()),
+            tz: None,
+            sets: None,
+            reps: None,
+            weight_value: None,
+            weight_unit: None,
+            load_basis: None,
+            time_seconds: None,
+            distance_meters: None,
+        };
+        assert!(validate_patched_entry_kind(&current, &patch).is_ok());
+
+        let add_distance = ExerciseEntryPatch {
+            distance_meters: Some(400),
+            ..patch
+        };
+        assert!(matches!(
+            validate_patched_entry_kind(&current, &add_distance),
+            Err(WorkoutError::Validation(msg)) if msg.contains("cannot mix")
+        ));
+    }
 }
@@ -1,7 +1,7 @@
 "use client";
 
 import React, { useEffect, useState } from "react";
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
 
 type CycleList = {
   labels: string[];
@@ -16,7 +16,7 @@ type Period = {
   endIso: string; // ISO string in UTC
 };
 
-type Currency = "USD" | "HKD" | "BTC";
+type Currency = "USD" | "HKD" | "BTC" | "EUR" | "GBP";
 type Account = { id: string; name: string; currency: Currency };
 
 type Money = { amount: number | string; ccy: Currency };
@@ -70,8 +70,14 @@ export default function CapitalCheckPage() {
   useEffect(() => {
     setLoading(true);
     Promise.all([
-      fetch(`${API_URL}/capital/cycles`, { credentials: "include" }),
-      fetch(`${API_URL}/capital/accounts`, { credentials: "include" }),
+      fetch(`${API_URL}/capital/cycles`, {
+        headers: { "x-wyat-api-key": WYAT_API_KEY },
+        credentials: "include",
+      }),
+      fetch(`${API_URL}/capital/accounts`, {
+        headers: { "x-wyat-api-key": WYAT_API_KEY },
+        credentials: "include",
+      }),
     ])
       .then(async ([cyclesRes, accountsRes]) => {
         if (!cyclesRes.ok) throw new Error(await cyclesRes.text());
@@ -120,7 +126,11 @@ export default function CapitalCheckPage() {
                   `${API_URL}/capital/accounts/${encodeURIComponent(
                     id
                   )}/balance?label=${encodeURIComponent(label)}`,
-                  { credentials: "include", signal: controller.signal }
+                  {
+                    headers: { "x-wyat-api-key": WYAT_API_KEY },
+                    credentials: "include",
+                    signal: controller.signal,
+                  }
                 );
                 if (!res.ok) throw new Error(await res.text());
                 const data = (await res.json()) as BalanceRespSingle;
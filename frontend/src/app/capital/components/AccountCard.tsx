@@ -2,7 +2,7 @@
 
 import React, { useState, useEffect } from "react";
 import type { Account, Currency, AccountNetwork } from "@/app/capital/types";
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
 import { Balance } from "@/components/ui/Balance";
 
 interface AccountCardProps {
@@ -35,6 +35,7 @@ export function AccountCard({ account, isInGroup = false }: AccountCardProps) {
     if (shouldFetchBalance) {
       setLoadingBalance(true);
       fetch(`${API_URL}/capital/accounts/${account.id}/balance`, {
+        headers: { "x-wyat-api-key": WYAT_API_KEY },
         credentials: "include",
       })
         .then((res) => res.json())
@@ -2,7 +2,7 @@
 
 import React, { useState, useEffect } from "react";
 import type { Account, Currency } from "@/app/capital/types";
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
 import { Balance } from "@/components/ui/Balance";
 
 interface AccountGroupBalanceProps {
@@ -46,6 +46,7 @@ export function AccountGroupBalance({
     Promise.all(
       accountsToFetch.map((account) =>
         fetch(`${API_URL}/capital/accounts/${account.id}/balance`, {
+          headers: { "x-wyat-api-key": WYAT_API_KEY },
           credentials: "include",
         })
           .then((res) => res.json())
@@ -6,7 +6,7 @@ import { usePreferencesStore } from "@/stores/preferences-store";
 import { Heading } from "@/components/ui/Heading";
 import { Balance } from "@/components/ui/Balance";
 
-type Currency = "USD" | "HKD" | "BTC";
+type Currency = "USD" | "HKD" | "BTC" | "EUR" | "GBP";
 
 export default function FundsPage() {
   const {
@@ -5,7 +5,7 @@ import type {
 
 export type { FlatTransaction, BatchImportResponse };
 
-export type Currency = "USD" | "HKD" | "BTC";
+export type Currency = "USD" | "HKD" | "BTC" | "EUR" | "GBP";
 
 export interface Money {
   amount: string;
@@ -55,6 +55,13 @@ export interface Transaction {
     | "unknown";
   external_refs: Array<[string, string]>;
   legs: Leg[];
+  tags?: string[];
+  reimbursement?: ReimbursementSettlement | null;
+}
+
+export interface ReimbursementSettlement {
+  settled_by_tx: string;
+  settled_at: number;
 }
 
 export interface Envelope {
@@ -10,7 +10,7 @@ import {
   type ListDocumentsResponse,
 } from "@/stores";
 import Modal from "@/components/ui/Modal";
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
 import ExtractionModal from "./components/ExtractionModal";
 import Loader from "@/components/Loader";
 
@@ -46,6 +46,24 @@ function getBlobId(blobId: string | { $oid: string }): string {
   return typeof blobId === "string" ? blobId : blobId.$oid;
 }
 
+// Blob downloads need the API key header, so fetch the file and save it from memory
+async function downloadBlob(doc: DocumentInfo) {
+  const res = await fetch(`${BACKEND_URL}/blobs/${getBlobId(doc.blob_id)}`, {
+    headers: { "x-wyat-api-key": WYAT_API_KEY },
+    credentials: "include",
+  });
+  if (!res.ok) {
+    alert(`Download failed (${res.status})`);
+    return;
+  }
+  const url = URL.createObjectURL(await res.blob());
+  const link = document.createElement("a");
+  link.href = url;
+  link.download = `${doc.title}.pdf`;
+  link.click();
+  URL.revokeObjectURL(url);
+}
+
 function getPromptVersion(doc: DocumentInfo): string {
   const version = (doc.metadata as any)?.prompt_version;
   return version != null ? String(version) : "1";
@@ -79,7 +97,13 @@ export default function DocumentPage() {
   } | null>(null);
 
   // Memoize PDF options to prevent unnecessary reloads
-  const pdfOptions = useMemo(() => ({ withCredentials: true }), []);
+  const pdfOptions = useMemo(
+    () => ({
+      withCredentials: true,
+      httpHeaders: { "x-wyat-api-key": WYAT_API_KEY },
+    }),
+    []
+  );
 
   // Step 1 – file upload
   const [file, setFile] = useState<File | null>(null);
@@ -161,6 +185,7 @@ export default function DocumentPage() {
         headers: {
           "Content-Type": file.type || "application/pdf",
           Accept: "application/json",
+          "x-wyat-api-key": WYAT_API_KEY,
         },
         body: file,
         credentials: "include",
@@ -628,13 +653,8 @@ export default function DocumentPage() {
                             />
                           </svg>
                         </button>
-                        <a
-                          href={`${BACKEND_URL}/blobs/${getBlobId(
-                            doc.blob_id
-                          )}`}
-                          target="_blank"
-                          rel="noopener noreferrer"
-                          download={`${doc.title}.pdf`}
+                        <button
+                          onClick={() => downloadBlob(doc)}
                           className="inline-flex items-center h-7.5 w-7.5 flex justify-center items-center border border-gray-300 rounded-md text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-blue-500"
                         >
                           <svg
@@ -651,7 +671,7 @@ export default function DocumentPage() {
                               d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-4l-4 4m0 0l-4-4m4 4V4"
                             />
                           </svg>
-                        </a>
+                        </button>
                       </div>
                     </td>
                   </tr>
@@ -1,4 +1,4 @@
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
 import type {
   FlatTransaction,
   BatchImportResponse,
@@ -13,6 +13,7 @@ type ExtractImportOptions = {
   debit_tx_type?: string;
   credit_tx_type?: string;
   fallback_account_id?: string;
+  signed_amounts?: boolean;
 };
 
 export async function extractBankStatement(payload: {
@@ -37,6 +38,7 @@ export async function extractBankStatement(payload: {
     headers: {
       "Content-Type": "application/json",
       Accept: "application/json",
+      "x-wyat-api-key": WYAT_API_KEY,
     },
     credentials: "include",
     body: JSON.stringify(payload),
@@ -60,6 +62,7 @@ export async function batchImportTransactions(
       headers: {
         "Content-Type": "application/json",
         Accept: "application/json",
+        "x-wyat-api-key": WYAT_API_KEY,
       },
       credentials: "include",
       body: JSON.stringify({ transactions }),
@@ -78,6 +81,7 @@ export async function listExtractionRuns(docId: string) {
   const response = await fetch(
     `${BASE_URL}/ai/extraction-runs?doc_id=${encodeURIComponent(docId)}`,
     {
+      headers: { "x-wyat-api-key": WYAT_API_KEY },
       credentials: "include",
     }
   );
@@ -98,6 +102,7 @@ export async function listExtractionRuns(docId: string) {
 
 export async function getExtractionRun(runId: string) {
   const response = await fetch(`${BASE_URL}/ai/extraction-runs/${runId}`, {
+    headers: { "x-wyat-api-key": WYAT_API_KEY },
     credentials: "include",
   });
   if (!response.ok) {
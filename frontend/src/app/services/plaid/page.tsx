@@ -2,12 +2,12 @@
 
 import { PlaidLink } from "react-plaid-link";
 import { useEffect, useState } from "react";
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
 
 export default function PlaidPage() {
   const [linkToken, setLinkToken] = useState<string | null>(null);
   const [connectedItems, setConnectedItems] = useState<
-    Array<{ item_id: string; access_token: string }>
+    Array<{ item_id: string }>
   >([]);
   const [selectedItem, setSelectedItem] = useState<string>("");
   const [accountId, setAccountId] = useState<string>("acct.chase_checking");
@@ -22,7 +22,9 @@ export default function PlaidPage() {
 
   useEffect(() => {
     // Call your backend to create a new link token
-    fetch(`${API_URL}/plaid/link-token/create`)
+    fetch(`${API_URL}/plaid/link-token/create`, {
+      headers: { "x-wyat-api-key": WYAT_API_KEY },
+    })
       .then((res) => res.json())
       .then((data) => setLinkToken(data.link_token))
       .catch((err) => console.error("Failed to create link token:", err));
@@ -47,7 +49,10 @@ export default function PlaidPage() {
     try {
       const response = await fetch(`${API_URL}/plaid/sync-transactions`, {
         method: "POST",
-        headers: { "Content-Type": "application/json" },
+        headers: {
+          "Content-Type": "application/json",
+          "x-wyat-api-key": WYAT_API_KEY,
+        },
         body: JSON.stringify({
           item_id: selectedItem,
           account_id: accountId,
@@ -84,14 +89,17 @@ export default function PlaidPage() {
         <PlaidLink
           token={linkToken}
           onSuccess={(public_token, metadata) => {
-            fetch(`${API_URL}/plaid/exchange-public-token`, {
+            fetch(`${API_URL}/plaid/item/public-token-exchange`, {
               method: "POST",
-              headers: { "Content-Type": "application/json" },
+              headers: {
+                "Content-Type": "application/json",
+                "x-wyat-api-key": WYAT_API_KEY,
+              },
               body: JSON.stringify({ public_token }),
             })
               .then((res) => res.json())
               .then((data) => {
-                console.log("Access Token Response", data);
+                console.log("Linked Plaid item", data);
                 setConnectedItems([...connectedItems, data]);
                 setSelectedItem(data.item_id);
                 alert(`Successfully connected! Item ID: ${data.item_id}`);
@@ -2,6 +2,7 @@
 
 import { useState, useEffect } from "react";
 import { API_URL, WYAT_API_KEY } from "@/lib/config";
+import { fetchAllExerciseEntries } from "@/stores/workout-store";
 
 // Types matching the backend
 interface Muscle {
@@ -215,24 +216,7 @@ export default function WorkoutPage() {
   const loadExerciseEntries = async () => {
     try {
       setLoading(true);
-      const response = await fetch(`${API_URL}/workout/exercise-entries`, {
-        headers: {
-          "x-wyat-api-key": WYAT_API_KEY,
-        },
-      });
-
-      if (!response.ok) {
-        let errorMessage = `Failed to load exercise entries: ${response.statusText}`;
-        try {
-          const errorData = await response.json();
-          errorMessage = errorData.error || errorMessage;
-        } catch {
-          // If JSON parsing fails, use the status text
-        }
-        throw new Error(errorMessage);
-      }
-
-      const data = await response.json();
+      const data = await fetchAllExerciseEntries();
       // Convert ObjectId to string for frontend
       const processedData = data.map((entry: any) => ({
         ...entry,
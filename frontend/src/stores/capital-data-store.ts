@@ -1,6 +1,7 @@
 import { create } from "zustand";
 import { devtools } from "zustand/middleware";
 import { API_CONFIG } from "@/app/capital/config";
+import { WYAT_API_KEY } from "@/lib/config";
 
 export type WatchlistAssetKind = "stock" | "crypto";
 
@@ -15,6 +16,15 @@ export interface WatchlistAsset {
   latest_value_text?: string | null;
   change_24h_pct?: number | null;
   last_updated?: string | null;
+  offline?: boolean;
+}
+
+export interface WatchlistHistoryPoint {
+  fetch_time: string;
+  source_time?: string | null;
+  value?: number | null;
+  value_text: string;
+  unit?: string | null;
 }
 
 export interface AddWatchlistAssetPayload {
@@ -34,6 +44,11 @@ interface CapitalDataState {
   addWatchlistAsset: (payload: AddWatchlistAssetPayload) => Promise<void>;
   updateWatchlistAsset: (symbol: string, name: string) => Promise<void>;
   removeWatchlistAsset: (symbol: string) => Promise<void>;
+  fetchWatchlistHistory: (
+    symbol: string,
+    from?: number,
+    to?: number
+  ) => Promise<WatchlistHistoryPoint[]>;
   clearError: () => void;
 }
 
@@ -52,6 +67,7 @@ export const useCapitalDataStore = create<CapitalDataState>()(
       set({ loading: true, error: null });
       try {
         const response = await fetch(`${API_CONFIG.BASE_URL}${endpoint}`, {
+          headers: { "x-wyat-api-key": WYAT_API_KEY },
           credentials: "include",
         });
         if (!response.ok) {
@@ -82,6 +98,7 @@ export const useCapitalDataStore = create<CapitalDataState>()(
         const response = await fetch(url, {
           method: "POST",
           headers: {
+            "x-wyat-api-key": WYAT_API_KEY,
             "Content-Type": "application/json",
           },
           credentials: "include",
@@ -131,6 +148,7 @@ export const useCapitalDataStore = create<CapitalDataState>()(
           {
             method: "PATCH",
             headers: {
+              "x-wyat-api-key": WYAT_API_KEY,
               "Content-Type": "application/json",
             },
             credentials: "include",
@@ -175,6 +193,7 @@ export const useCapitalDataStore = create<CapitalDataState>()(
         const response = await fetch(
           `${API_CONFIG.BASE_URL}${endpoint}/${encoded}`,
           {
+            headers: { "x-wyat-api-key": WYAT_API_KEY },
             method: "DELETE",
             credentials: "include",
           }
@@ -198,5 +217,27 @@ export const useCapitalDataStore = create<CapitalDataState>()(
         throw error;
       }
     },
+
+    fetchWatchlistHistory: async (symbol: string, from?: number, to?: number) => {
+      const params = new URLSearchParams();
+      if (from !== undefined) params.set("from", String(from));
+      if (to !== undefined) params.set("to", String(to));
+      const query = params.toString() ? `?${params.toString()}` : "";
+      const response = await fetch(
+        `${API_CONFIG.BASE_URL}${endpoint}/${encodeURIComponent(
+          symbol
+        )}/history${query}`,
+        {
+          headers: { "x-wyat-api-key": WYAT_API_KEY },
+          credentials: "include",
+        }
+      );
+      if (!response.ok) {
+        throw new Error(
+          `Failed to fetch price history: ${response.statusText}`
+        );
+      }
+      return (await response.json()) as WatchlistHistoryPoint[];
+    },
   }))
 );
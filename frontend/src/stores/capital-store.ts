@@ -1,6 +1,7 @@
 import { create } from "zustand";
 import { devtools } from "zustand/middleware";
 import { API_CONFIG } from "@/app/capital/config";
+import { WYAT_API_KEY } from "@/lib/config";
 import type {
   Transaction,
   Envelope,
@@ -11,6 +12,18 @@ import type {
   Leg,
 } from "@/app/capital/types";
 
+// Pulls the message out of a `{ error, code }` API error body, falling back to raw text.
+async function readApiError(response: Response): Promise<string> {
+  const text = await response.text();
+  try {
+    const body = JSON.parse(text);
+    if (body && typeof body.error === "string") return body.error;
+  } catch {
+    // not JSON
+  }
+  return text || `HTTP error! status: ${response.status}`;
+}
+
 // AI Prompt Types
 export interface AiPrompt {
   _id: { $oid: string };
@@ -121,7 +134,7 @@ export interface PublicFund {
   discretionary_sales: boolean;
   acquisition_policy?: string | null;
   yield_policy?: string | null;
-  denominated_in: "USD" | "HKD" | "BTC";
+  denominated_in: "USD" | "HKD" | "BTC" | "EUR" | "GBP";
   balancing_policy?: any;
   multiplier_rules?: string[] | null;
   max_pct_networth: number;
@@ -258,7 +271,8 @@ export const useCapitalStore = create<CapitalState>()(
           }
 
           const response = await fetch(
-            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.TRANSACTIONS}?${params}`
+            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.TRANSACTIONS}?${params}`,
+            { headers: { "x-wyat-api-key": WYAT_API_KEY } }
           );
 
           if (!response.ok) {
@@ -282,14 +296,12 @@ export const useCapitalStore = create<CapitalState>()(
       fetchTransactionById: async (transactionId: string) => {
         try {
           const response = await fetch(
-            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.TRANSACTIONS}/${transactionId}`
+            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.TRANSACTIONS}/${transactionId}`,
+            { headers: { "x-wyat-api-key": WYAT_API_KEY } }
           );
 
           if (!response.ok) {
-            const errorText = await response.text();
-            throw new Error(
-              errorText || `HTTP error! status: ${response.status}`
-            );
+            throw new Error(await readApiError(response));
           }
 
           const transaction = await response.json();
@@ -306,7 +318,8 @@ export const useCapitalStore = create<CapitalState>()(
 
         try {
           const response = await fetch(
-            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.ENVELOPES}`
+            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.ENVELOPES}`,
+            { headers: { "x-wyat-api-key": WYAT_API_KEY } }
           );
 
           if (!response.ok) {
@@ -331,7 +344,9 @@ export const useCapitalStore = create<CapitalState>()(
           const url = `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.ACCOUNTS}`;
           console.log("Fetching from:", url);
 
-          const response = await fetch(url);
+          const response = await fetch(url, {
+            headers: { "x-wyat-api-key": WYAT_API_KEY },
+          });
           console.log("Response status:", response.status);
 
           if (!response.ok) {
@@ -360,6 +375,7 @@ export const useCapitalStore = create<CapitalState>()(
             {
               method: "POST",
               headers: {
+                "x-wyat-api-key": WYAT_API_KEY,
                 "Content-Type": "application/json",
               },
               body: JSON.stringify(account),
@@ -385,7 +401,8 @@ export const useCapitalStore = create<CapitalState>()(
       fetchCycles: async () => {
         try {
           const response = await fetch(
-            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.CYCLES}`
+            `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.CYCLES}`,
+            { headers: { "x-wyat-api-key": WYAT_API_KEY } }
           );
 
           if (!response.ok) {
@@ -412,7 +429,8 @@ export const useCapitalStore = create<CapitalState>()(
               const res = await fetch(
                 `${API_CONFIG.BASE_URL}${API_CONFIG.ENDPOINTS.ENVELOPE_USAGE(
                   envelope.id
-                )}?label=${cycle}`
+                )}?label=${cycle}`,
+                { headers: { "x-wyat-api-key": WYAT_API_KEY } }
               );
               if (res.ok) {
                 const usage: EnvelopeUsage = await res.json();
@@ -441,6 +459,7 @@ export const useCapitalStore = create<CapitalState>()(
         set({ fundsLoading: true, fundsError: null });
         try {
           const response = await fetch(`${API_CONFIG.BASE_URL}/capital/funds`, {
+            headers: { "x-wyat-api-key": WYAT_API_KEY },
             credentials: "include",
           });
           if (!response.ok) {
@@ -469,7 +488,10 @@ export const useCapitalStore = create<CapitalState>()(
             `${API_CONFIG.BASE_URL}/capital/funds/${encodeURIComponent(
               fundId
             )}/positions`,
-            { credentials: "include" }
+            {
+              headers: { "x-wyat-api-key": WYAT_API_KEY },
+              credentials: "include",
+            }
           );
           if (!response.ok) {
             throw new Error(await response.text());
@@ -508,7 +530,10 @@ export const useCapitalStore = create<CapitalState>()(
         try {
           const response = await fetch(
             `${API_CONFIG.BASE_URL}/capital/data/watchlist`,
-            { credentials: "include" }
+            {
+              headers: { "x-wyat-api-key": WYAT_API_KEY },
+              credentials: "include",
+            }
           );
 
           if (!response.ok) {
@@ -565,6 +590,7 @@ export const useCapitalStore = create<CapitalState>()(
             {
               method: "PUT",
               headers: {
+                "x-wyat-api-key": WYAT_API_KEY,
                 "Content-Type": "application/json",
               },
               body: JSON.stringify({
@@ -576,7 +602,7 @@ export const useCapitalStore = create<CapitalState>()(
           );
 
           if (!response.ok) {
-            throw new Error(`HTTP error! status: ${response.status}`);
+            throw new Error(await readApiError(response));
           }
 
           // Update the transaction in the store
@@ -623,6 +649,7 @@ export const useCapitalStore = create<CapitalState>()(
             {
               method: "PATCH",
               headers: {
+                "x-wyat-api-key": WYAT_API_KEY,
                 "Content-Type": "application/json",
               },
               body: JSON.stringify({ tx_type: txType }),
@@ -664,7 +691,10 @@ export const useCapitalStore = create<CapitalState>()(
           )}/legs`,
           {
             method: "PATCH",
-            headers: { "Content-Type": "application/json" },
+            headers: {
+              "Content-Type": "application/json",
+              "x-wyat-api-key": WYAT_API_KEY,
+            },
             credentials: "include",
             body: JSON.stringify(request),
           }
@@ -692,7 +722,10 @@ export const useCapitalStore = create<CapitalState>()(
           )}/balance`,
           {
             method: "POST",
-            headers: { "Content-Type": "application/json" },
+            headers: {
+              "Content-Type": "application/json",
+              "x-wyat-api-key": WYAT_API_KEY,
+            },
             credentials: "include",
           }
         );
@@ -719,6 +752,7 @@ export const useCapitalStore = create<CapitalState>()(
           const response = await fetch(
             `${API_CONFIG.BASE_URL}/capital/transactions/${transactionId}`,
             {
+              headers: { "x-wyat-api-key": WYAT_API_KEY },
               method: "DELETE",
             }
           );
@@ -753,6 +787,7 @@ export const useCapitalStore = create<CapitalState>()(
           {
             method: "POST",
             headers: {
+              "x-wyat-api-key": WYAT_API_KEY,
               "Content-Type": "application/json",
               Accept: "application/json",
             },
@@ -762,9 +797,9 @@ export const useCapitalStore = create<CapitalState>()(
         );
 
         if (!response.ok) {
-          const text = await response.text();
+          const message = await readApiError(response);
           throw new Error(
-            `Create transaction failed (${response.status}): ${text}`
+            `Create transaction failed (${response.status}): ${message}`
           );
         }
 
@@ -1,5 +1,6 @@
 import { create } from "zustand";
-import { API_URL } from "@/lib/config";
+import { API_URL, WYAT_API_KEY } from "@/lib/config";
+import type { Transaction } from "@/app/capital/types";
 
 const API_CONFIG = {
   BASE_URL: API_URL,
@@ -65,6 +66,10 @@ export type BatchImportResponse = {
   imported: number;
   skipped: number;
   errors: string[];
+  imported_ids: string[];
+  skipped_ids: string[];
+  sign_conflicts: string[];
+  preview: Transaction[];
 };
 
 export interface ImportRequest {
@@ -127,6 +132,7 @@ export const useDocumentStore = create<DocumentState>()((set, get) => ({
       }/capital/documents?${params.toString()}`;
       const response = await fetch(url, {
         method: "GET",
+        headers: { "x-wyat-api-key": WYAT_API_KEY },
         credentials: "include",
       });
 
@@ -153,6 +159,7 @@ export const useDocumentStore = create<DocumentState>()((set, get) => ({
         `${API_CONFIG.BASE_URL}/capital/documents/${docId}`,
         {
           method: "GET",
+          headers: { "x-wyat-api-key": WYAT_API_KEY },
           credentials: "include",
         }
       );
@@ -181,6 +188,7 @@ export const useDocumentStore = create<DocumentState>()((set, get) => ({
         headers: {
           "Content-Type": "application/json",
           Accept: "application/json",
+          "x-wyat-api-key": WYAT_API_KEY,
         },
         body: JSON.stringify({
           blob_id: request.blob_id,
@@ -217,6 +225,7 @@ export const useDocumentStore = create<DocumentState>()((set, get) => ({
           headers: {
             "Content-Type": "application/json",
             Accept: "application/json",
+            "x-wyat-api-key": WYAT_API_KEY,
           },
           body: JSON.stringify({
             blob_id: request.blob_id,
@@ -7,6 +7,9 @@ export interface JournalEntry {
   versions: { text: string; timestamp: string }[];
   timestamp: string;
   date_unix: number;
+  deleted_at?: string;
+  // Text-search relevance, only set on /journal/mongo/search results
+  score?: number;
 }
 
 export interface CreateJournalEntry {
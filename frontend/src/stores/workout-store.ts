@@ -49,6 +49,48 @@ export interface ExerciseEntryInput {
   distance_meters?: number;
 }
 
+interface ExerciseEntryPage {
+  entries: ExerciseEntry[];
+  next_before: number | null;
+  next_before_id: string | null;
+}
+
+// Fetch every exercise entry, newest first, following the page cursor
+export async function fetchAllExerciseEntries(): Promise<ExerciseEntry[]> {
+  const entries: ExerciseEntry[] = [];
+  const params = new URLSearchParams({ limit: "200" });
+
+  for (;;) {
+    const response = await fetch(
+      `${API_URL}/workout/exercise-entries?${params}`,
+      {
+        headers: {
+          "x-wyat-api-key": WYAT_API_KEY,
+        },
+      }
+    );
+
+    if (!response.ok) {
+      let errorMessage = `Failed to load exercise entries: ${response.statusText}`;
+      try {
+        const errorData = await response.json();
+        errorMessage = errorData.error || errorMessage;
+      } catch {
+        // If JSON parsing fails, use the status text
+      }
+      throw new Error(errorMessage);
+    }
+
+    const page: ExerciseEntryPage = await response.json();
+    entries.push(...page.entries);
+    if (page.next_before === null || page.next_before_id === null) {
+      return entries;
+    }
+    params.set("before_unix", String(page.next_before));
+    params.set("before_id", page.next_before_id);
+  }
+}
+
 interface WorkoutState {
   // Data
   exerciseTypes: ExerciseType[];
@@ -155,18 +197,8 @@ export const useWorkoutStore = create<WorkoutState>()(
         set({ loading: true, error: null });
 
         try {
-          const response = await fetch(`${API_URL}/workout/exercise-entries`, {
-            headers: {
-              "x-wyat-api-key": WYAT_API_KEY,
-            },
-          });
-
-          if (!response.ok) {
-            throw new Error(`HTTP error! status: ${response.status}`);
-          }
-
-          const data = await response.json();
-          set({ exerciseEntries: data, loading: false });
+          const entries = await fetchAllExerciseEntries();
+          set({ exerciseEntries: entries, loading: false });
         } catch (err) {
           set({
             error: err instanceof Error ? err.message : "Unknown error",